@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE assigned_subaddresses
+    ADD COLUMN hidden BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE assigned_subaddresses DROP COLUMN first_deposit_notified;
+ALTER TABLE assigned_subaddresses DROP COLUMN customer_tag;
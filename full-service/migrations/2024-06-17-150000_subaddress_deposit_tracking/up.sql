@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE assigned_subaddresses ADD COLUMN customer_tag TEXT;
+ALTER TABLE assigned_subaddresses
+    ADD COLUMN first_deposit_notified BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE txos ADD COLUMN memo_payload BLOB;
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE txos ADD COLUMN received_timestamp BIGINT;
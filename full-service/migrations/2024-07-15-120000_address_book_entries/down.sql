@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE assigned_subaddresses DROP COLUMN address_book_entry_id;
+DROP TABLE address_book_entries;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+CREATE TABLE address_book_entries (
+    id INTEGER NOT NULL PRIMARY KEY,
+    public_address_b58 TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL
+);
+ALTER TABLE assigned_subaddresses
+    ADD COLUMN address_book_entry_id INTEGER REFERENCES address_book_entries(id);
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE accounts ADD COLUMN default_fee BIGINT;
@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE accounts DROP COLUMN created_at;
+ALTER TABLE accounts DROP COLUMN updated_at;
+ALTER TABLE txos DROP COLUMN created_at;
+ALTER TABLE txos DROP COLUMN updated_at;
+ALTER TABLE transaction_logs DROP COLUMN created_at;
+ALTER TABLE transaction_logs DROP COLUMN updated_at;
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+ALTER TABLE accounts ADD COLUMN created_at BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE accounts ADD COLUMN updated_at BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE txos ADD COLUMN created_at BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE txos ADD COLUMN updated_at BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE transaction_logs ADD COLUMN created_at BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE transaction_logs ADD COLUMN updated_at BIGINT NOT NULL DEFAULT 0;
+
+-- Existing rows were created at some unknown point before now.
+UPDATE accounts SET created_at = CAST(strftime('%s', 'now') AS INTEGER), updated_at = CAST(strftime('%s', 'now') AS INTEGER);
+UPDATE txos SET created_at = CAST(strftime('%s', 'now') AS INTEGER), updated_at = CAST(strftime('%s', 'now') AS INTEGER);
+UPDATE transaction_logs SET created_at = CAST(strftime('%s', 'now') AS INTEGER), updated_at = CAST(strftime('%s', 'now') AS INTEGER);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX idx_transaction_logs__idempotency_key;
+ALTER TABLE transaction_logs DROP COLUMN idempotency_key;
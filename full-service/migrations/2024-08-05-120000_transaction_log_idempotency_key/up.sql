@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE transaction_logs ADD COLUMN idempotency_key TEXT;
+CREATE UNIQUE INDEX idx_transaction_logs__idempotency_key ON transaction_logs (idempotency_key);
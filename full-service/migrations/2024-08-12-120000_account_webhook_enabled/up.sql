@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE accounts ADD COLUMN webhook_enabled BOOLEAN NOT NULL DEFAULT TRUE;
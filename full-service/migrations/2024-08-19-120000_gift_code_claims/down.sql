@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE gift_codes DROP COLUMN claim_tombstone_block;
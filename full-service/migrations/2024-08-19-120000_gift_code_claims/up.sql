@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE gift_codes ADD COLUMN claim_tombstone_block BIGINT;
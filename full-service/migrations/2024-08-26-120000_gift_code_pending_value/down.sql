@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+CREATE TABLE NEW_gift_codes (
+  id INTEGER NOT NULL PRIMARY KEY,
+  gift_code_b58 VARCHAR NOT NULL,
+  value UNSIGNED BIG INT NOT NULL,
+  claim_tombstone_block BIGINT
+);
+INSERT INTO NEW_gift_codes SELECT id, gift_code_b58, COALESCE(value, 0), claim_tombstone_block FROM gift_codes;
+DROP TABLE gift_codes;
+ALTER TABLE NEW_gift_codes RENAME TO gift_codes;
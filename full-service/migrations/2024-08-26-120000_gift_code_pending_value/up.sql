@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- A gift code imported before its txo lands in the ledger has no known value yet.
+CREATE TABLE NEW_gift_codes (
+  id INTEGER NOT NULL PRIMARY KEY,
+  gift_code_b58 VARCHAR NOT NULL,
+  value BIGINT,
+  claim_tombstone_block BIGINT
+);
+INSERT INTO NEW_gift_codes SELECT id, gift_code_b58, value, claim_tombstone_block FROM gift_codes;
+DROP TABLE gift_codes;
+ALTER TABLE NEW_gift_codes RENAME TO gift_codes;
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE accounts ADD COLUMN hardware_wallet_account_index INTEGER;
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE assigned_subaddresses DROP COLUMN is_change;
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+ALTER TABLE assigned_subaddresses ADD COLUMN is_change BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Only the current change subaddress of each account is known for existing rows.
+UPDATE assigned_subaddresses SET is_change = TRUE
+WHERE subaddress_index = (
+    SELECT change_subaddress_index FROM accounts
+    WHERE accounts.id = assigned_subaddresses.account_id
+);
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE transaction_logs DROP COLUMN finalized_timestamp;
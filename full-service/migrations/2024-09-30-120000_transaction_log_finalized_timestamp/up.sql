@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE transaction_logs ADD COLUMN finalized_timestamp BIGINT;
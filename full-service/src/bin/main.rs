@@ -15,7 +15,11 @@ use mc_fog_report_resolver::FogResolver;
 use mc_full_service::{
     check_host,
     config::{APIConfig, NetworkConfig, WebhookConfig},
-    wallet::{consensus_backed_rocket, validator_backed_rocket, APIKeyState, WalletState},
+    service::sync::LedgerUpdateNotifier,
+    wallet::{
+        consensus_backed_rocket, validator_backed_rocket, APIKeyState, SyncProgressState,
+        WalletState,
+    },
     ValidatorLedgerSyncThread, WalletDb, WalletService,
 };
 use mc_ledger_sync::{LedgerSyncServiceThread, PollingNetworkState, ReqwestTransactionsFetcher};
@@ -90,7 +94,10 @@ fn rocket() -> Rocket<Build> {
             WalletDb::run_proto_conversions_if_necessary(conn);
             log::info!(logger, "Connected to database.");
 
-            Some(WalletDb::new_from_url(wallet_db_path, 10).expect("Could not access wallet db"))
+            Some(
+                WalletDb::new_from_url(wallet_db_path, config.wallet_db_connections)
+                    .expect("Could not access wallet db"),
+            )
         }
         None => None,
     };
@@ -249,7 +256,7 @@ fn consensus_backed_full_service(
         None => (None, None),
     };
 
-    let service = WalletService::new(
+    let mut service = WalletService::new(
         wallet_db,
         ledger_db,
         watcher_db,
@@ -260,11 +267,21 @@ fn consensus_backed_full_service(
         config.offline,
         config.t3_sync_config.clone(),
         webhook_config,
+        config.blocks_chunk_size,
+        // The consensus ledger sync thread does not signal appended blocks, so
+        // the account sync thread polls for them.
+        None,
         logger,
     );
+    service.tombstone_horizon = config.tombstone_horizon;
+    service.gift_code_confirmations = config.gift_code_confirmations;
+    service.unique_account_names = config.unique_account_names;
+    service.hardware_wallet_config = config.get_hardware_wallet_config();
+    let sync_progress = SyncProgressState::new(&service);
 
     consensus_backed_rocket(rocket_config, config.allowed_origin.clone())
         .manage(WalletState { service })
+        .manage(sync_progress)
         .manage(ledger_sync_service_thread)
         .manage(watcher_sync_thread)
 }
@@ -317,25 +334,16 @@ fn validator_backed_full_service(
         logger.clone(),
     )));
 
-    // Create the ledger sync thread.
-    let ledger_sync_thread = ValidatorLedgerSyncThread::new(
-        validator_uri,
-        config.peers_config.chain_id.clone(),
-        config.poll_interval,
-        ledger_db.clone(),
-        network_state.clone(),
-        logger.clone(),
-    );
-
     let fog_ingest_identity = config.get_fog_ingest_identity();
     let logger2 = logger.clone();
-    let service = WalletService::new(
+    let ledger_updates = LedgerUpdateNotifier::default();
+    let mut service = WalletService::new(
         wallet_db,
-        ledger_db,
+        ledger_db.clone(),
         None,
         conn_manager,
         network_config,
-        network_state,
+        network_state.clone(),
         Arc::new(move |fog_uris| -> Result<FogResolver, String> {
             if fog_uris.is_empty() {
                 Ok(Default::default())
@@ -359,10 +367,32 @@ fn validator_backed_full_service(
         false,
         config.t3_sync_config.clone(),
         webhook_config,
+        config.blocks_chunk_size,
+        Some(ledger_updates.clone()),
+        logger.clone(),
+    );
+
+    // Create the ledger sync thread, which wakes the wallet's sync thread
+    // whenever it appends blocks.
+    let ledger_sync_thread = ValidatorLedgerSyncThread::new(
+        validator_uri,
+        config.peers_config.chain_id.clone(),
+        config.validator_reconnect_after_failures,
+        config.poll_interval,
+        ledger_db,
+        network_state,
+        ledger_updates,
         logger,
     );
+    service.validator_sync_stats = Some(ledger_sync_thread.sync_stats());
+    service.tombstone_horizon = config.tombstone_horizon;
+    service.gift_code_confirmations = config.gift_code_confirmations;
+    service.unique_account_names = config.unique_account_names;
+    service.hardware_wallet_config = config.get_hardware_wallet_config();
+    let sync_progress = SyncProgressState::new(&service);
 
     validator_backed_rocket(rocket_config, config.allowed_origin.clone())
         .manage(WalletState { service })
+        .manage(sync_progress)
         .manage(ledger_sync_thread)
 }
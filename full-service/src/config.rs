@@ -15,6 +15,7 @@ use mc_fog_report_connection::GrpcFogReportConnection;
 use mc_fog_report_resolver::FogResolver;
 use mc_ledger_db::{Ledger, LedgerDB};
 use mc_sgx_css::Signature;
+use mc_transaction_core::constants::MAX_TOMBSTONE_BLOCKS;
 use mc_util_parse::parse_duration_in_seconds;
 use mc_util_uri::{ConnectionUri, ConsensusClientUri, FogUri};
 use mc_validator_api::ValidatorUri;
@@ -30,7 +31,7 @@ use std::{
     time::Duration,
 };
 
-use crate::service::t3_sync::T3Config;
+use crate::service::{hardware_wallet::HardwareWalletConfig, t3_sync::T3Config};
 
 /// Command line config for the Wallet API
 #[derive(Clone, Debug, Parser)]
@@ -52,6 +53,68 @@ pub struct APIConfig {
     #[clap(long, value_parser, env = "MC_WALLET_DB")]
     pub wallet_db: Option<PathBuf>,
 
+    /// Maximum number of connections to the WalletDb. SQLite only allows one
+    /// writer at a time, but readers can run alongside it.
+    #[clap(
+        long,
+        default_value = "10",
+        value_parser = clap::value_parser!(u32).range(1..),
+        env = "MC_WALLET_DB_CONNECTIONS"
+    )]
+    pub wallet_db_connections: u32,
+
+    /// Number of blocks past the current block height at which a new
+    /// transaction expires, when no tombstone block is given. Consensus rejects
+    /// transactions which expire more than MAX_TOMBSTONE_BLOCKS blocks ahead.
+    #[clap(
+        long,
+        default_value = "10",
+        value_parser = clap::value_parser!(u64).range(1..=MAX_TOMBSTONE_BLOCKS),
+        env = "MC_TOMBSTONE_HORIZON"
+    )]
+    pub tombstone_horizon: u64,
+
+    /// Number of blocks, counting the one it landed in, which must hold a gift
+    /// code's txo before the gift code is available to claim.
+    #[clap(
+        long,
+        default_value = "1",
+        value_parser = clap::value_parser!(u64).range(1..),
+        env = "MC_GIFT_CODE_CONFIRMATIONS"
+    )]
+    pub gift_code_confirmations: u64,
+
+    /// Reject creating, importing or renaming an account to a name which
+    /// another account already has.
+    #[clap(long, env = "MC_UNIQUE_ACCOUNT_NAMES")]
+    pub unique_account_names: bool,
+
+    /// Number of blocks loaded into memory and scanned at a time when syncing
+    /// an account. Lower values reduce peak memory use during long syncs.
+    #[clap(
+        long,
+        default_value = "1000",
+        value_parser = clap::value_parser!(u64).range(1..),
+        env = "MC_BLOCKS_CHUNK_SIZE"
+    )]
+    pub blocks_chunk_size: u64,
+
+    /// Index of the hardware wallet to use, in the order reported by
+    /// `get_hardware_wallets`. If not given, each connected device is tried
+    /// in turn.
+    #[clap(long, env = "MC_HARDWARE_WALLET_DEVICE_INDEX")]
+    pub hardware_wallet_device_index: Option<usize>,
+
+    /// How many seconds to wait for a transaction to be approved on the
+    /// hardware wallet before giving up.
+    #[clap(
+        long,
+        default_value = "60",
+        value_parser = clap::value_parser!(u32).range(1..),
+        env = "MC_HARDWARE_WALLET_APPROVAL_TIMEOUT"
+    )]
+    pub hardware_wallet_approval_timeout: u32,
+
     #[clap(flatten)]
     pub ledger_db_config: LedgerDbConfig,
 
@@ -76,6 +139,15 @@ pub struct APIConfig {
     #[clap(long, env = "MC_VALIDATOR")]
     pub validator: Option<ValidatorUri>,
 
+    /// How many consecutive failures to fetch blocks from the validator before
+    /// reconnecting to it.
+    #[clap(
+        long,
+        default_value = "5",
+        env = "MC_VALIDATOR_RECONNECT_AFTER_FAILURES"
+    )]
+    pub validator_reconnect_after_failures: u32,
+
     /// Path to watcher db (lmdb). When provided, watcher syncing will take
     /// place.
     #[clap(long, value_parser, env = "MC_WATCHER_DB")]
@@ -98,12 +170,24 @@ pub struct APIConfig {
     /// parameters:
     ///
     /// POST /webhook -H "Content-Type: application/json" \
-    ///     -d '{"accounts": [A,B,C]}'
+    ///     -d '{"accounts": [A,B,C], "restart": false}'
     ///
     /// The expected action to take in response to the webhook is to call
     /// the `get_txos` API endpoint for the given accounts to retrieve more
     /// details about the TXOs received.
     ///
+    /// `restart` is true on the first webhook delivered after Full Service
+    /// starts, and false afterwards. Deposits may have gone unreported while
+    /// the wallet was down, so on `restart` the client should reconcile all of
+    /// its accounts, not just the listed ones.
+    ///
+    /// The first deposit to an address assigned with a `customer_tag` is
+    /// additionally reported in a separate request:
+    ///
+    /// POST /webhook -H "Content-Type: application/json" \
+    ///     -d '{"tagged_deposits": [{"account_id": A, "customer_tag": T,
+    ///          "public_address_b58": P, "subaddress_index": I}]}'
+    ///
     /// It is also expected for the client to call get_txos on startup and
     /// periodically to ensure that no TXOs are missed.
     ///
@@ -135,6 +219,14 @@ fn load_css_file(filename: &str) -> Result<Signature, String> {
 }
 
 impl APIConfig {
+    /// Get which hardware wallet view only accounts sign and sync with.
+    pub fn get_hardware_wallet_config(&self) -> HardwareWalletConfig {
+        HardwareWalletConfig {
+            device_index: self.hardware_wallet_device_index,
+            approval_timeout_secs: self.hardware_wallet_approval_timeout,
+        }
+    }
+
     /// Get the attestation verifier used to verify fog reports when sending to
     /// fog recipients.
     pub fn get_fog_ingest_identity(&self) -> Option<TrustedIdentity> {
@@ -5,10 +5,13 @@
 use crate::{
     db::{
         assigned_subaddress::AssignedSubaddressModel,
-        models::{Account, AssignedSubaddress, NewAccount, TransactionLog, Txo},
+        models::{
+            Account, AssignedSubaddress, AuthenticatedSenderMemo, DestinationMemo, NewAccount,
+            TransactionInputTxo, TransactionLog, TransactionOutputTxo, Txo,
+        },
         transaction_log::TransactionLogModel,
         txo::TxoModel,
-        Conn, WalletDbError,
+        unix_timestamp, Conn, WalletDbError,
     },
     util::constants::{
         DEFAULT_FIRST_BLOCK_INDEX, DEFAULT_NEXT_SUBADDRESS_INDEX, LEGACY_CHANGE_SUBADDRESS_INDEX,
@@ -28,8 +31,8 @@ use mc_account_keys::{
 use mc_core::slip10::Slip10KeyGenerator;
 use mc_crypto_digestible::{Digestible, MerlinTranscript};
 use mc_crypto_keys::{RistrettoPrivate, RistrettoPublic};
-use mc_transaction_core::{get_tx_out_shared_secret, TokenId};
-use serde_derive::Serialize;
+use mc_transaction_core::{get_tx_out_shared_secret, tokens::Mob, Token, TokenId};
+use serde_derive::{Deserialize, Serialize};
 use std::fmt;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize)]
@@ -68,6 +71,22 @@ impl fmt::Display for AccountID {
     }
 }
 
+/// Every row in the wallet database belonging to an account, so that it can
+/// be moved to another wallet without re-syncing from the ledger.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AccountState {
+    pub account: Account,
+    pub assigned_subaddresses: Vec<AssignedSubaddress>,
+    /// Txos received by the account, along with any Txos referenced by its
+    /// transaction logs.
+    pub txos: Vec<Txo>,
+    pub transaction_logs: Vec<TransactionLog>,
+    pub transaction_input_txos: Vec<TransactionInputTxo>,
+    pub transaction_output_txos: Vec<TransactionOutputTxo>,
+    pub authenticated_sender_memos: Vec<AuthenticatedSenderMemo>,
+    pub destination_memos: Vec<DestinationMemo>,
+}
+
 #[rustfmt::skip]
 pub trait AccountModel {
     /// Create an account from mnemonic.
@@ -238,7 +257,7 @@ pub trait AccountModel {
     ///| `import_block_index`    | Index of the last block in local ledger database.                       |                                                                       |
     ///| `first_block_index`     | Index of the first block when this account may have received funds.     | Defaults to 0 if not provided                                         |
     ///| `next_subaddress_index` | This index represents the next subaddress to be assigned as an address. | This is useful information in case the account is imported elsewhere. |
-    ///| `managed_by_hardware_wallet` | Whether the account is managed by a hardware wallet.                 |                                                                       |
+    ///| `hardware_wallet_account_index` | The account index on the hardware wallet that manages the account. | None if the account is not managed by a hardware wallet.          |
     ///| `require_spend_subaddress` | If enabled, this mode requires all transactions to spend from a provided subaddress |                                                        |
     ///| `conn`                  | An reference to the pool connection of wallet database                  |                                                                       |
     ///
@@ -251,7 +270,7 @@ pub trait AccountModel {
         import_block_index: u64,
         first_block_index: Option<u64>,
         next_subaddress_index: Option<u64>,
-        managed_by_hardware_wallet: bool,
+        hardware_wallet_account_index: Option<u32>,
         require_spend_subaddress: bool,
         conn: Conn,
     ) -> Result<Account, WalletDbError>;
@@ -262,6 +281,7 @@ pub trait AccountModel {
         import_block_index: u64,
         first_block_index: Option<u64>,
         default_public_address: &PublicAddress,
+        hardware_wallet_account_index: u32,
         require_spend_subaddress: bool,
         conn: Conn,
     ) -> Result<Account, WalletDbError>;
@@ -284,6 +304,26 @@ pub trait AccountModel {
         limit: Option<u64>,
     ) -> Result<Vec<Account>, WalletDbError>;
 
+    /// Search accounts by name.
+    ///
+    /// # Arguments
+    ///
+    ///| Name     | Purpose                                                   | Notes                                |
+    ///|----------|-----------------------------------------------------------|--------------------------------------|
+    ///| `query`  | Text to find in the account name.                         | Case-insensitive. Empty matches all. |
+    ///| `conn`   | An reference to the pool connection of wallet database    |                                      |
+    ///| `offset` | The pagination offset. Results start at the offset index. | Optional, defaults to 0.             |
+    ///| `limit`  | Limit for the number of results.                          | Optional                             |
+    ///
+    /// # Returns:
+    /// * Vector of Accounts whose name contains the query
+    fn search_by_name(
+        query: &str,
+        conn: Conn,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<Account>, WalletDbError>;
+
     /// Get a specific account.
     ///
     /// # Arguments
@@ -350,6 +390,55 @@ pub trait AccountModel {
         conn: Conn,
     ) -> Result<(), WalletDbError>;
 
+    /// Update the account's default fee, used for MOB fees when a transaction is built without one.
+    ///
+    /// # Arguments
+    ///| Name          | Purpose                                                | Notes                                   |
+    ///|---------------|--------------------------------------------------------|-----------------------------------------|
+    ///| `default_fee` | The new default fee, in picoMOB.                       | None to fall back to the network fee.   |
+    ///| `conn`        | An reference to the pool connection of wallet database |                                         |
+    ///
+    /// # Returns:
+    /// * unit
+    fn update_default_fee(
+        &self,
+        default_fee: Option<u64>,
+        conn: Conn,
+    ) -> Result<(), WalletDbError>;
+
+    /// Update whether deposits to the account trigger the deposits webhook.
+    ///
+    /// # Arguments
+    ///| Name              | Purpose                                                | Notes |
+    ///|-------------------|--------------------------------------------------------|-------|
+    ///| `webhook_enabled` | Whether to send webhooks for this account's deposits.  |       |
+    ///| `conn`            | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns:
+    /// * unit
+    fn update_webhook_enabled(
+        &self,
+        webhook_enabled: bool,
+        conn: Conn,
+    ) -> Result<(), WalletDbError>;
+
+    /// Update the subaddress that change is sent to when a transaction does not
+    /// choose one.
+    ///
+    /// # Arguments
+    ///| Name                      | Purpose                                                | Notes                              |
+    ///|---------------------------|--------------------------------------------------------|------------------------------------|
+    ///| `change_subaddress_index` | The index of the new change subaddress.                | Must be assigned for this account. |
+    ///| `conn`                    | An reference to the pool connection of wallet database |                                    |
+    ///
+    /// # Returns:
+    /// * unit
+    fn update_change_subaddress_index(
+        &self,
+        change_subaddress_index: u64,
+        conn: Conn,
+    ) -> Result<(), WalletDbError>;
+
     /// Update the next block index in current account that needs to sync.
     ///
     /// # Arguments
@@ -367,6 +456,24 @@ pub trait AccountModel {
         conn: Conn,
     ) -> Result<(), WalletDbError>;
 
+    /// Update the block index up to which a block range sync has scanned ahead
+    /// of the account's next block index.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                  | Purpose                                                      | Notes |
+    ///|-----------------------|--------------------------------------------------------------|-------|
+    ///| `scanned_block_index` | One past the last block scanned ahead of the next block index |       |
+    ///| `conn`                | An reference to the pool connection of wallet database       |       |
+    ///
+    /// # Returns:
+    /// * unit
+    fn update_scanned_block_index(
+        &self,
+        scanned_block_index: u64,
+        conn: Conn,
+    ) -> Result<(), WalletDbError>;
+
     /// Delete the current account.
     ///
     /// # Arguments
@@ -413,7 +520,7 @@ pub trait AccountModel {
     ///
     /// # Returns:
     /// * Vector of all TokenIds
-    fn get_token_ids(self, conn: Conn) -> Result<Vec<TokenId>, WalletDbError>;
+    fn get_token_ids(&self, conn: Conn) -> Result<Vec<TokenId>, WalletDbError>;
 
     /// Get the next sequentially unassigned subaddress index for the account
     /// * reserved addresses are not included
@@ -456,6 +563,16 @@ pub trait AccountModel {
     /// * RistrettoPrivate
     fn view_private_key(&self) -> Result<RistrettoPrivate, WalletDbError>;
 
+    /// Get the fee the account has chosen for transactions built without one.
+    ///
+    /// # Arguments
+    ///| Name               | Purpose                                                     | Notes |
+    ///|--------------------|-------------------------------------------------------------|-------|
+    ///| `token_id`         | The token the fee is paid in.                               |       |
+    ///
+    /// # Returns:
+    /// * The account's default fee for MOB fees, if set, and None otherwise
+    fn default_fee_for_token(&self, token_id: TokenId) -> Option<u64>;
 
     /// Get the shared secret for the account and the tx_public_key
     ///
@@ -473,6 +590,37 @@ pub trait AccountModel {
     fn update_resyncing(&self, resyncing: bool, conn: Conn) -> Result<(), WalletDbError>;
 
     fn resync_in_progress(conn: Conn) -> Result<bool, WalletDbError>;
+
+    /// Export every row belonging to the current account.
+    ///
+    /// Txos that were received by another account are included without their
+    /// owner, subaddress, or key image, which are not part of this account's state.
+    ///
+    /// # Arguments
+    ///
+    ///| Name               | Purpose                                                     | Notes |
+    ///|--------------------|-------------------------------------------------------------|-------|
+    ///| `conn`             | An reference to the pool connection of wallet database      |       |
+    ///
+    /// # Returns:
+    /// * AccountState
+    fn export_state(&self, conn: Conn) -> Result<AccountState, WalletDbError>;
+
+    /// Import every row of an exported account.
+    ///
+    /// Txos that are already in the wallet are kept, and are only assigned to the
+    /// imported account if they have no owner.
+    ///
+    /// # Arguments
+    ///
+    ///| Name               | Purpose                                                     | Notes                                  |
+    ///|--------------------|-------------------------------------------------------------|----------------------------------------|
+    ///| `state`            | The exported rows of the account.                           | The account must not be in the wallet. |
+    ///| `conn`             | An reference to the pool connection of wallet database      |                                        |
+    ///
+    /// # Returns:
+    /// * Account
+    fn import_state(state: &AccountState, conn: Conn) -> Result<Account, WalletDbError>;
 }
 
 impl AccountModel for Account {
@@ -572,6 +720,7 @@ impl AccountModel for Account {
         let next_subaddress_index =
             next_subaddress_index.unwrap_or(DEFAULT_NEXT_SUBADDRESS_INDEX) as i64;
 
+        let now = unix_timestamp();
         let new_account = NewAccount {
             id: &account_id.to_string(),
             account_key: &mc_util_serial::encode(account_key),
@@ -585,6 +734,10 @@ impl AccountModel for Account {
             view_only: false,
             managed_by_hardware_wallet: false,
             require_spend_subaddress,
+            created_at: now,
+            updated_at: now,
+            hardware_wallet_account_index: None,
+            scanned_block_index: None,
         };
 
         diesel::insert_into(accounts::table)
@@ -668,7 +821,7 @@ impl AccountModel for Account {
         import_block_index: u64,
         first_block_index: Option<u64>,
         next_subaddress_index: Option<u64>,
-        managed_by_hardware_wallet: bool,
+        hardware_wallet_account_index: Option<u32>,
         require_spend_subaddress: bool,
         conn: Conn,
     ) -> Result<Account, WalletDbError> {
@@ -686,6 +839,7 @@ impl AccountModel for Account {
         let next_subaddress_index =
             next_subaddress_index.unwrap_or(DEFAULT_NEXT_SUBADDRESS_INDEX) as i64;
 
+        let now = unix_timestamp();
         let new_account = NewAccount {
             id: &account_id.to_string(),
             account_key: &mc_util_serial::encode(view_account_key),
@@ -697,8 +851,11 @@ impl AccountModel for Account {
             name: &name.unwrap_or_default(),
             fog_enabled: false,
             view_only: true,
-            managed_by_hardware_wallet,
+            managed_by_hardware_wallet: hardware_wallet_account_index.is_some(),
             require_spend_subaddress,
+            created_at: now,
+            updated_at: now,
+            hardware_wallet_account_index: hardware_wallet_account_index.map(|i| i as i32),
         };
 
         diesel::insert_into(accounts::table)
@@ -744,6 +901,7 @@ impl AccountModel for Account {
         import_block_index: u64,
         first_block_index: Option<u64>,
         default_public_address: &PublicAddress,
+        hardware_wallet_account_index: u32,
         require_spend_subaddress: bool,
         conn: Conn,
     ) -> Result<Account, WalletDbError> {
@@ -758,6 +916,7 @@ impl AccountModel for Account {
         let first_block_index = first_block_index.unwrap_or(DEFAULT_FIRST_BLOCK_INDEX) as i64;
         let next_block_index = first_block_index;
 
+        let now = unix_timestamp();
         let new_account = NewAccount {
             id: &account_id.to_string(),
             account_key: &mc_util_serial::encode(view_account_key),
@@ -771,6 +930,9 @@ impl AccountModel for Account {
             view_only: true,
             managed_by_hardware_wallet: true,
             require_spend_subaddress,
+            created_at: now,
+            updated_at: now,
+            hardware_wallet_account_index: Some(hardware_wallet_account_index as i32),
         };
 
         diesel::insert_into(accounts::table)
@@ -811,6 +973,35 @@ impl AccountModel for Account {
         Ok(query.load(conn)?)
     }
 
+    fn search_by_name(
+        query: &str,
+        conn: Conn,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<Account>, WalletDbError> {
+        use crate::db::schema::accounts;
+
+        // SQLite's LIKE is case-insensitive for ASCII. Escape the LIKE
+        // wildcards so the query is matched literally.
+        let pattern = format!(
+            "%{}%",
+            query
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+
+        let mut query = accounts::table
+            .filter(accounts::name.like(pattern).escape('\\'))
+            .into_boxed();
+
+        if let (Some(offset), Some(limit)) = (offset, limit) {
+            query = query.limit(limit as i64).offset(offset as i64);
+        }
+
+        Ok(query.load(conn)?)
+    }
+
     fn get(account_id: &AccountID, conn: Conn) -> Result<Account, WalletDbError> {
         use crate::db::schema::accounts;
 
@@ -844,7 +1035,10 @@ impl AccountModel for Account {
         use crate::db::schema::accounts;
 
         diesel::update(accounts::table.filter(accounts::id.eq(&self.id)))
-            .set(accounts::name.eq(new_name))
+            .set((
+                accounts::name.eq(new_name),
+                accounts::updated_at.eq(unix_timestamp()),
+            ))
             .execute(conn)?;
         Ok(())
     }
@@ -857,7 +1051,58 @@ impl AccountModel for Account {
         use crate::db::schema::accounts;
 
         diesel::update(accounts::table.filter(accounts::id.eq(&self.id)))
-            .set(accounts::require_spend_subaddress.eq(require_spend_subaddress))
+            .set((
+                accounts::require_spend_subaddress.eq(require_spend_subaddress),
+                accounts::updated_at.eq(unix_timestamp()),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    fn update_default_fee(
+        &self,
+        default_fee: Option<u64>,
+        conn: Conn,
+    ) -> Result<(), WalletDbError> {
+        use crate::db::schema::accounts;
+
+        diesel::update(accounts::table.filter(accounts::id.eq(&self.id)))
+            .set((
+                accounts::default_fee.eq(default_fee.map(|fee| fee as i64)),
+                accounts::updated_at.eq(unix_timestamp()),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    fn update_webhook_enabled(
+        &self,
+        webhook_enabled: bool,
+        conn: Conn,
+    ) -> Result<(), WalletDbError> {
+        use crate::db::schema::accounts;
+
+        diesel::update(accounts::table.filter(accounts::id.eq(&self.id)))
+            .set((
+                accounts::webhook_enabled.eq(webhook_enabled),
+                accounts::updated_at.eq(unix_timestamp()),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    fn update_change_subaddress_index(
+        &self,
+        change_subaddress_index: u64,
+        conn: Conn,
+    ) -> Result<(), WalletDbError> {
+        use crate::db::schema::accounts;
+
+        diesel::update(accounts::table.filter(accounts::id.eq(&self.id)))
+            .set((
+                accounts::change_subaddress_index.eq(Some(change_subaddress_index as i64)),
+                accounts::updated_at.eq(unix_timestamp()),
+            ))
             .execute(conn)?;
         Ok(())
     }
@@ -869,7 +1114,25 @@ impl AccountModel for Account {
     ) -> Result<(), WalletDbError> {
         use crate::db::schema::accounts;
         diesel::update(accounts::table.filter(accounts::id.eq(&self.id)))
-            .set(accounts::next_block_index.eq(next_block_index as i64))
+            .set((
+                accounts::next_block_index.eq(next_block_index as i64),
+                accounts::updated_at.eq(unix_timestamp()),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    fn update_scanned_block_index(
+        &self,
+        scanned_block_index: u64,
+        conn: Conn,
+    ) -> Result<(), WalletDbError> {
+        use crate::db::schema::accounts;
+        diesel::update(accounts::table.filter(accounts::id.eq(&self.id)))
+            .set((
+                accounts::scanned_block_index.eq(Some(scanned_block_index as i64)),
+                accounts::updated_at.eq(unix_timestamp()),
+            ))
             .execute(conn)?;
         Ok(())
     }
@@ -895,7 +1158,10 @@ impl AccountModel for Account {
     }
 
     fn change_subaddress(self, conn: Conn) -> Result<AssignedSubaddress, WalletDbError> {
-        AssignedSubaddress::get_for_account_by_index(&self.id, CHANGE_SUBADDRESS_INDEX as i64, conn)
+        let change_subaddress_index = self
+            .change_subaddress_index
+            .unwrap_or(CHANGE_SUBADDRESS_INDEX as i64);
+        AssignedSubaddress::get_for_account_by_index(&self.id, change_subaddress_index, conn)
     }
 
     fn main_subaddress(self, conn: Conn) -> Result<AssignedSubaddress, WalletDbError> {
@@ -906,7 +1172,7 @@ impl AccountModel for Account {
         )
     }
 
-    fn get_token_ids(self, conn: Conn) -> Result<Vec<TokenId>, WalletDbError> {
+    fn get_token_ids(&self, conn: Conn) -> Result<Vec<TokenId>, WalletDbError> {
         use crate::db::schema::txos;
 
         let distinct_token_ids = txos::table
@@ -957,6 +1223,13 @@ impl AccountModel for Account {
         Ok(*self.view_account_key()?.view_private_key())
     }
 
+    fn default_fee_for_token(&self, token_id: TokenId) -> Option<u64> {
+        match self.default_fee {
+            Some(default_fee) if token_id == Mob::ID => Some(default_fee as u64),
+            _ => None,
+        }
+    }
+
     fn get_shared_secret(
         &self,
         tx_public_key: &RistrettoPublic,
@@ -976,7 +1249,10 @@ impl AccountModel for Account {
         use crate::db::schema::accounts;
 
         diesel::update(accounts::table.filter(accounts::id.eq(&self.id)))
-            .set(accounts::resyncing.eq(resyncing))
+            .set((
+                accounts::resyncing.eq(resyncing),
+                accounts::updated_at.eq(unix_timestamp()),
+            ))
             .execute(conn)?;
         Ok(())
     }
@@ -989,6 +1265,162 @@ impl AccountModel for Account {
                 .get_result(conn)?,
         )
     }
+
+    fn export_state(&self, conn: Conn) -> Result<AccountState, WalletDbError> {
+        use crate::db::schema::{
+            assigned_subaddresses, authenticated_sender_memos, destination_memos,
+            transaction_input_txos, transaction_logs, transaction_output_txos, txos,
+        };
+
+        let assigned_subaddresses: Vec<AssignedSubaddress> = assigned_subaddresses::table
+            .filter(assigned_subaddresses::account_id.eq(&self.id))
+            .order(assigned_subaddresses::subaddress_index.asc())
+            .load(conn)?;
+
+        let transaction_logs: Vec<TransactionLog> = transaction_logs::table
+            .filter(transaction_logs::account_id.eq(&self.id))
+            .order(transaction_logs::id.asc())
+            .load(conn)?;
+
+        let transaction_input_txos: Vec<TransactionInputTxo> = transaction_input_txos::table
+            .inner_join(transaction_logs::table)
+            .filter(transaction_logs::account_id.eq(&self.id))
+            .select(transaction_input_txos::all_columns)
+            .load(conn)?;
+
+        let transaction_output_txos: Vec<TransactionOutputTxo> = transaction_output_txos::table
+            .inner_join(transaction_logs::table)
+            .filter(transaction_logs::account_id.eq(&self.id))
+            .select(transaction_output_txos::all_columns)
+            .load(conn)?;
+
+        let referenced_txo_ids: Vec<String> = transaction_input_txos
+            .iter()
+            .map(|input| input.txo_id.clone())
+            .chain(
+                transaction_output_txos
+                    .iter()
+                    .map(|output| output.txo_id.clone()),
+            )
+            .collect();
+
+        let mut txos: Vec<Txo> = txos::table
+            .filter(
+                txos::account_id
+                    .eq(&self.id)
+                    .or(txos::id.eq_any(&referenced_txo_ids)),
+            )
+            .order(txos::id.asc())
+            .load(conn)?;
+
+        for txo in txos.iter_mut() {
+            if txo.account_id.as_ref() != Some(&self.id) {
+                txo.account_id = None;
+                txo.subaddress_index = None;
+                txo.key_image = None;
+            }
+        }
+
+        let txo_ids: Vec<String> = txos.iter().map(|txo| txo.id.clone()).collect();
+
+        let authenticated_sender_memos: Vec<AuthenticatedSenderMemo> =
+            authenticated_sender_memos::table
+                .filter(authenticated_sender_memos::txo_id.eq_any(&txo_ids))
+                .load(conn)?;
+
+        let destination_memos: Vec<DestinationMemo> = destination_memos::table
+            .filter(destination_memos::txo_id.eq_any(&txo_ids))
+            .load(conn)?;
+
+        Ok(AccountState {
+            account: self.clone(),
+            assigned_subaddresses,
+            txos,
+            transaction_logs,
+            transaction_input_txos,
+            transaction_output_txos,
+            authenticated_sender_memos,
+            destination_memos,
+        })
+    }
+
+    fn import_state(state: &AccountState, conn: Conn) -> Result<Account, WalletDbError> {
+        use crate::db::schema::{
+            accounts, assigned_subaddresses, authenticated_sender_memos, destination_memos,
+            transaction_input_txos, transaction_logs, transaction_output_txos, txos,
+        };
+
+        let account_id = AccountID(state.account.id.clone());
+        if Account::get(&account_id, conn).is_ok() {
+            return Err(WalletDbError::AccountAlreadyExists(account_id.to_string()));
+        }
+
+        diesel::insert_into(accounts::table)
+            .values(&state.account)
+            .execute(conn)?;
+
+        for assigned_subaddress in state.assigned_subaddresses.iter() {
+            diesel::insert_into(assigned_subaddresses::table)
+                .values(assigned_subaddress)
+                .execute(conn)?;
+        }
+
+        for txo in state.txos.iter() {
+            match Txo::get(&txo.id, conn) {
+                Ok(existing_txo) => {
+                    if txo.account_id.is_some() && existing_txo.account_id.is_none() {
+                        diesel::update(&existing_txo)
+                            .set((
+                                txos::account_id.eq(&txo.account_id),
+                                txos::subaddress_index.eq(txo.subaddress_index),
+                                txos::key_image.eq(&txo.key_image),
+                                txos::received_block_index.eq(txo.received_block_index),
+                                txos::spent_block_index.eq(txo.spent_block_index),
+                                txos::updated_at.eq(unix_timestamp()),
+                            ))
+                            .execute(conn)?;
+                    }
+                }
+                Err(WalletDbError::TxoNotFound(_)) => {
+                    diesel::insert_into(txos::table).values(txo).execute(conn)?;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        for transaction_log in state.transaction_logs.iter() {
+            diesel::insert_into(transaction_logs::table)
+                .values(transaction_log)
+                .execute(conn)?;
+        }
+
+        for transaction_input_txo in state.transaction_input_txos.iter() {
+            diesel::insert_into(transaction_input_txos::table)
+                .values(transaction_input_txo)
+                .execute(conn)?;
+        }
+
+        for transaction_output_txo in state.transaction_output_txos.iter() {
+            diesel::insert_into(transaction_output_txos::table)
+                .values(transaction_output_txo)
+                .execute(conn)?;
+        }
+
+        // Memos for Txos that were already in the wallet are already present.
+        for memo in state.authenticated_sender_memos.iter() {
+            diesel::insert_or_ignore_into(authenticated_sender_memos::table)
+                .values(memo)
+                .execute(conn)?;
+        }
+
+        for memo in state.destination_memos.iter() {
+            diesel::insert_or_ignore_into(destination_memos::table)
+                .values(memo)
+                .execute(conn)?;
+        }
+
+        Account::get(&account_id, conn)
+    }
 }
 
 #[cfg(test)]
@@ -1055,6 +1487,13 @@ mod tests {
             managed_by_hardware_wallet: false,
             resyncing: false,
             require_spend_subaddress: false,
+            default_fee: None,
+            created_at: acc.created_at,
+            updated_at: acc.updated_at,
+            webhook_enabled: true,
+            change_subaddress_index: None,
+            hardware_wallet_account_index: None,
+            scanned_block_index: None,
         };
         assert_eq!(expected_account, acc);
 
@@ -1124,6 +1563,13 @@ mod tests {
             managed_by_hardware_wallet: false,
             resyncing: false,
             require_spend_subaddress: false,
+            default_fee: None,
+            created_at: acc_secondary.created_at,
+            updated_at: acc_secondary.updated_at,
+            webhook_enabled: true,
+            change_subaddress_index: None,
+            hardware_wallet_account_index: None,
+            scanned_block_index: None,
         };
         assert_eq!(expected_account_secondary, acc_secondary);
 
@@ -1140,7 +1586,9 @@ mod tests {
         )
         .unwrap();
         expected_account_secondary.name = "Alice's Secondary Account".to_string();
+        expected_account_secondary.updated_at = acc_secondary2.updated_at;
         assert_eq!(expected_account_secondary, acc_secondary2);
+        assert!(acc_secondary2.updated_at >= acc_secondary.updated_at);
 
         // Delete the secondary account
         acc_secondary
@@ -1165,6 +1613,106 @@ mod tests {
         }
     }
 
+    #[test_with_logger]
+    fn test_account_timestamps(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let root_id = RootIdentity::from_random(&mut rng);
+        let (account_id, _public_address_b58) = Account::create_from_root_entropy(
+            &root_id.root_entropy,
+            Some(0),
+            None,
+            None,
+            "Alice's Main Account",
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+
+        let account = Account::get(&account_id, conn).unwrap();
+        assert!(account.created_at > 0);
+        assert_eq!(account.updated_at, account.created_at);
+
+        account
+            .update_name("Alice's Renamed Account".to_string(), conn)
+            .unwrap();
+        let updated = Account::get(&account_id, conn).unwrap();
+        assert_eq!(updated.created_at, account.created_at);
+        assert!(updated.updated_at >= account.updated_at);
+    }
+
+    #[test_with_logger]
+    fn test_search_accounts_by_name(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let mut account_ids = Vec::new();
+        for name in ["Alice's Main Account", "Alice's Savings", "Bob", "100% Bob"] {
+            let root_id = RootIdentity::from_random(&mut rng);
+            let (account_id, _public_address_b58) = Account::create_from_root_entropy(
+                &root_id.root_entropy,
+                Some(0),
+                None,
+                None,
+                name,
+                "".to_string(),
+                "".to_string(),
+                false,
+                conn,
+            )
+            .unwrap();
+            account_ids.push(account_id.to_string());
+        }
+
+        let search = |query: &str, conn: Conn| -> HashSet<String> {
+            Account::search_by_name(query, conn, None, None)
+                .unwrap()
+                .into_iter()
+                .map(|account| account.id)
+                .collect()
+        };
+
+        // Exact match
+        assert_eq!(
+            search("Alice's Savings", conn),
+            HashSet::from_iter([account_ids[1].clone()])
+        );
+
+        // Case-insensitive substring
+        assert_eq!(
+            search("alice", conn),
+            HashSet::from_iter([account_ids[0].clone(), account_ids[1].clone()])
+        );
+        assert_eq!(
+            search("BOB", conn),
+            HashSet::from_iter([account_ids[2].clone(), account_ids[3].clone()])
+        );
+
+        // Wildcards are matched literally
+        assert_eq!(
+            search("%", conn),
+            HashSet::from_iter([account_ids[3].clone()])
+        );
+        assert!(search("_", conn).is_empty());
+
+        // No match
+        assert!(search("Carol", conn).is_empty());
+
+        // Empty query matches all
+        assert_eq!(search("", conn), HashSet::from_iter(account_ids));
+    }
+
     // Providing entropy should succeed and derive account key.
     #[test_with_logger]
     fn test_create_account_from_entropy(logger: Logger) {
@@ -1294,6 +1842,13 @@ mod tests {
             managed_by_hardware_wallet: false,
             resyncing: false,
             require_spend_subaddress: false,
+            default_fee: None,
+            created_at: acc.created_at,
+            updated_at: acc.updated_at,
+            webhook_enabled: true,
+            change_subaddress_index: None,
+            hardware_wallet_account_index: None,
+            scanned_block_index: None,
         };
         assert_eq!(expected_account, acc);
     }
@@ -1320,7 +1875,7 @@ mod tests {
                 12,
                 None,
                 None,
-                false,
+                None,
                 false,
                 conn,
             )
@@ -1354,10 +1909,56 @@ mod tests {
             managed_by_hardware_wallet: false,
             resyncing: false,
             require_spend_subaddress: false,
+            default_fee: None,
+            created_at: account.created_at,
+            updated_at: account.updated_at,
+            webhook_enabled: true,
+            change_subaddress_index: None,
+            hardware_wallet_account_index: None,
+            scanned_block_index: None,
         };
         assert_eq!(expected_account, account);
     }
 
+    #[test_with_logger]
+    fn test_import_view_only_account_from_hardware_wallet(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+
+        let view_private_key = RistrettoPrivate::from_random(&mut rng);
+        let spend_public_key = RistrettoPublic::from_random(&mut rng);
+        let view_account_key = ViewAccountKey::new(view_private_key, spend_public_key);
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let account = Account::import_view_only(
+            &view_account_key,
+            Some("Hardware Wallet Account".to_string()),
+            12,
+            None,
+            None,
+            Some(2),
+            false,
+            conn,
+        )
+        .unwrap();
+
+        // The hardware wallet account index is kept so that the account syncs
+        // and signs with the same account on the device.
+        assert!(account.view_only);
+        assert!(account.managed_by_hardware_wallet);
+        assert_eq!(account.hardware_wallet_account_index, Some(2));
+        assert_eq!(
+            Account::get(&AccountID(account.id.clone()), conn)
+                .unwrap()
+                .hardware_wallet_account_index,
+            Some(2)
+        );
+    }
+
     #[test_with_logger]
     fn test_import_view_only_from_hardware_wallet_with_fog(logger: Logger) {
         // Test Setup
@@ -1386,6 +1987,7 @@ mod tests {
                 12,
                 None,
                 &default_public_address,
+                3,
                 false,
                 conn,
             )
@@ -1416,6 +2018,13 @@ mod tests {
             managed_by_hardware_wallet: true,
             resyncing: false,
             require_spend_subaddress: false,
+            default_fee: None,
+            created_at: account.created_at,
+            updated_at: account.updated_at,
+            webhook_enabled: true,
+            change_subaddress_index: None,
+            hardware_wallet_account_index: Some(3),
+            scanned_block_index: None,
         };
 
         // Check to make sure the account in the database is correct
@@ -1448,6 +2057,11 @@ mod tests {
             subaddress_index: 0,
             comment: "Main".to_string(),
             spend_public_key: default_subaddress_spend_public_key_bytes,
+            hidden: false,
+            customer_tag: None,
+            first_deposit_notified: false,
+            address_book_entry_id: None,
+            is_change: false,
         };
 
         assert_eq!(default_subaddress, expected_default_subaddress);
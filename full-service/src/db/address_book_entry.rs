@@ -0,0 +1,248 @@
+// Copyright (c) 2020-2024 MobileCoin Inc.
+
+//! A named contact, which can be attached to the subaddresses given to that
+//! contact so that payments received from them can be labeled.
+
+use crate::{
+    db::{
+        models::{AddressBookEntry, NewAddressBookEntry},
+        Conn, WalletDbError,
+    },
+    util::b58::b58_decode_public_address,
+};
+use diesel::prelude::*;
+
+#[rustfmt::skip]
+pub trait AddressBookEntryModel {
+    /// Create an address book entry.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                 | Purpose                                                | Notes                                        |
+    ///|----------------------|--------------------------------------------------------|----------------------------------------------|
+    ///| `public_address_b58` | The b58-encoded public address of the contact.         | Must be unique among address book entries.   |
+    ///| `name`               | The name of the contact.                               |                                              |
+    ///| `conn`               | An reference to the pool connection of wallet database |                                              |
+    ///
+    /// # Returns:
+    /// * AddressBookEntry
+    fn create(
+        public_address_b58: &str,
+        name: &str,
+        conn: Conn,
+    ) -> Result<AddressBookEntry, WalletDbError>;
+
+    /// Get an address book entry.
+    ///
+    /// # Arguments
+    ///
+    ///| Name   | Purpose                                                | Notes                  |
+    ///|--------|--------------------------------------------------------|------------------------|
+    ///| `id`   | The id of the address book entry.                      | Entry must exist.      |
+    ///| `conn` | An reference to the pool connection of wallet database |                        |
+    ///
+    /// # Returns:
+    /// * AddressBookEntry
+    fn get(id: i32, conn: Conn) -> Result<AddressBookEntry, WalletDbError>;
+
+    /// Get all address book entries in this wallet.
+    ///
+    /// # Arguments
+    ///
+    ///| Name     | Purpose                                                   | Notes                    |
+    ///|----------|-----------------------------------------------------------|--------------------------|
+    ///| `conn`   | An reference to the pool connection of wallet database    |                          |
+    ///| `offset` | The pagination offset. Results start at the offset index. | Optional, defaults to 0. |
+    ///| `limit`  | Limit for the number of results.                          | Optional                 |
+    ///
+    /// # Returns:
+    /// * Vector of AddressBookEntry, ordered by id
+    fn list_all(
+        conn: Conn,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<AddressBookEntry>, WalletDbError>;
+
+    /// Update the public address and name of an address book entry.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                 | Purpose                                                | Notes |
+    ///|----------------------|--------------------------------------------------------|-------|
+    ///| `public_address_b58` | The new b58-encoded public address of the contact.     |       |
+    ///| `name`               | The new name of the contact.                           |       |
+    ///| `conn`               | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns:
+    /// * unit
+    fn update(
+        &self,
+        public_address_b58: &str,
+        name: &str,
+        conn: Conn,
+    ) -> Result<(), WalletDbError>;
+
+    /// Delete an address book entry, detaching it from any subaddresses.
+    ///
+    /// # Arguments
+    ///
+    ///| Name   | Purpose                                                | Notes |
+    ///|--------|--------------------------------------------------------|-------|
+    ///| `conn` | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns:
+    /// * unit
+    fn delete(self, conn: Conn) -> Result<(), WalletDbError>;
+}
+
+impl AddressBookEntryModel for AddressBookEntry {
+    fn create(
+        public_address_b58: &str,
+        name: &str,
+        conn: Conn,
+    ) -> Result<AddressBookEntry, WalletDbError> {
+        use crate::db::schema::address_book_entries;
+
+        b58_decode_public_address(public_address_b58)?;
+
+        let new_address_book_entry = NewAddressBookEntry {
+            public_address_b58,
+            name,
+        };
+        diesel::insert_into(address_book_entries::table)
+            .values(&new_address_book_entry)
+            .execute(conn)?;
+
+        Ok(address_book_entries::table
+            .filter(address_book_entries::public_address_b58.eq(public_address_b58))
+            .get_result(conn)?)
+    }
+
+    fn get(id: i32, conn: Conn) -> Result<AddressBookEntry, WalletDbError> {
+        use crate::db::schema::address_book_entries;
+
+        match address_book_entries::table
+            .filter(address_book_entries::id.eq(id))
+            .get_result::<AddressBookEntry>(conn)
+        {
+            Ok(a) => Ok(a),
+            // Match on NotFound to get a more informative NotFound Error
+            Err(diesel::result::Error::NotFound) => {
+                Err(WalletDbError::AddressBookEntryNotFound(id))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn list_all(
+        conn: Conn,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<AddressBookEntry>, WalletDbError> {
+        use crate::db::schema::address_book_entries;
+
+        let mut query = address_book_entries::table
+            .order(address_book_entries::id.asc())
+            .into_boxed();
+
+        if let (Some(offset), Some(limit)) = (offset, limit) {
+            query = query.offset(offset as i64).limit(limit as i64);
+        }
+
+        Ok(query.load(conn)?)
+    }
+
+    fn update(
+        &self,
+        public_address_b58: &str,
+        name: &str,
+        conn: Conn,
+    ) -> Result<(), WalletDbError> {
+        use crate::db::schema::address_book_entries;
+
+        b58_decode_public_address(public_address_b58)?;
+
+        diesel::update(address_book_entries::table.filter(address_book_entries::id.eq(self.id)))
+            .set((
+                address_book_entries::public_address_b58.eq(public_address_b58),
+                address_book_entries::name.eq(name),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    fn delete(self, conn: Conn) -> Result<(), WalletDbError> {
+        use crate::db::schema::{address_book_entries, assigned_subaddresses};
+
+        diesel::update(
+            assigned_subaddresses::table
+                .filter(assigned_subaddresses::address_book_entry_id.eq(self.id)),
+        )
+        .set(assigned_subaddresses::address_book_entry_id.eq::<Option<i32>>(None))
+        .execute(conn)?;
+
+        diesel::delete(address_book_entries::table.filter(address_book_entries::id.eq(self.id)))
+            .execute(conn)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::WalletDbTestContext, util::b58::b58_encode_public_address};
+    use mc_account_keys::AccountKey;
+    use mc_common::logger::{test_with_logger, Logger};
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::ops::DerefMut;
+
+    #[test_with_logger]
+    fn test_address_book_entry_crud(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let alice_b58 =
+            b58_encode_public_address(&AccountKey::random(&mut rng).default_subaddress()).unwrap();
+        let bob_b58 =
+            b58_encode_public_address(&AccountKey::random(&mut rng).default_subaddress()).unwrap();
+
+        let alice = AddressBookEntry::create(&alice_b58, "Alice", conn).unwrap();
+        let bob = AddressBookEntry::create(&bob_b58, "Bob", conn).unwrap();
+        assert_eq!(alice.public_address_b58, alice_b58);
+        assert_eq!(alice.name, "Alice");
+        assert_eq!(AddressBookEntry::get(alice.id, conn).unwrap(), alice);
+
+        // An address can only be in the address book once.
+        assert!(AddressBookEntry::create(&alice_b58, "Also Alice", conn).is_err());
+        assert!(AddressBookEntry::create("not an address", "Carol", conn).is_err());
+
+        assert_eq!(
+            AddressBookEntry::list_all(conn, None, None).unwrap(),
+            vec![alice.clone(), bob.clone()]
+        );
+        assert_eq!(
+            AddressBookEntry::list_all(conn, Some(1), Some(1)).unwrap(),
+            vec![bob.clone()]
+        );
+
+        alice.update(&alice_b58, "Alice Smith", conn).unwrap();
+        let updated = AddressBookEntry::get(alice.id, conn).unwrap();
+        assert_eq!(updated.name, "Alice Smith");
+        assert_eq!(updated.public_address_b58, alice_b58);
+
+        let bob_id = bob.id;
+        bob.delete(conn).unwrap();
+        match AddressBookEntry::get(bob_id, conn) {
+            Err(WalletDbError::AddressBookEntryNotFound(id)) => assert_eq!(id, bob_id),
+            result => panic!("Expected AddressBookEntryNotFound, got {result:?}"),
+        }
+        assert_eq!(
+            AddressBookEntry::list_all(conn, None, None).unwrap(),
+            vec![updated]
+        );
+    }
+}
@@ -6,14 +6,21 @@
 use crate::{
     db::{
         account::{AccountID, AccountModel},
-        models::{Account, AssignedSubaddress, NewAssignedSubaddress, Txo},
+        address_book_entry::AddressBookEntryModel,
+        models::{Account, AddressBookEntry, AssignedSubaddress, NewAssignedSubaddress, Txo},
         txo::TxoModel,
-        Conn, WalletDbError,
+        unix_timestamp, Conn, WalletDbError,
+    },
+    util::{
+        b58::{b58_decode_public_address, b58_encode_public_address},
+        constants::DEFAULT_NEXT_SUBADDRESS_INDEX,
     },
-    util::b58::{b58_decode_public_address, b58_encode_public_address},
 };
 use core::convert::TryFrom;
-use diesel::prelude::*;
+use diesel::{
+    dsl::{exists, not},
+    prelude::*,
+};
 use mc_account_keys::{AccountKey, PublicAddress, ViewAccountKey};
 use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPublic};
 use mc_ledger_db::{Ledger, LedgerDB};
@@ -157,6 +164,143 @@ pub trait AssignedSubaddressModel {
         conn: Conn,
     ) -> Result<Vec<AssignedSubaddress>, WalletDbError>;
 
+    /// List the AssignedSubaddresses for a given account that have never
+    /// received a txo.
+    ///
+    /// The main and change subaddresses are never included, including any
+    /// rotated change subaddresses, nor are subaddresses that have been hidden.
+    ///
+    /// # Arguments
+    ///
+    ///| Name             | Purpose                                                | Notes                            |
+    ///|------------------|--------------------------------------------------------|----------------------------------|
+    ///| `account_id_hex` | The account on which to perform this action.           | Account must exist in the wallet |
+    ///| `conn`           | An reference to the pool connection of wallet database |                                  |
+    ///
+    /// # Returns:
+    /// * Vector of AssignedSubaddress, ordered by subaddress index
+    fn list_unused(
+        account_id_hex: &str,
+        conn: Conn,
+    ) -> Result<Vec<AssignedSubaddress>, WalletDbError>;
+
+    /// Set whether an AssignedSubaddress is hidden.
+    ///
+    /// Subaddresses cannot be deleted, since funds may still arrive at them,
+    /// so they are hidden instead.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                 | Purpose                                                | Notes |
+    ///|----------------------|--------------------------------------------------------|-------|
+    ///| `public_address_b58` | The public address b58 string to update.               |       |
+    ///| `hidden`             | Whether the subaddress should be hidden.               |       |
+    ///| `conn`               | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns:
+    /// * unit
+    fn set_hidden(public_address_b58: &str, hidden: bool, conn: Conn) -> Result<(), WalletDbError>;
+
+    /// Tag an AssignedSubaddress with a customer, so that its first deposit
+    /// is reported to the deposits webhook.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                 | Purpose                                                | Notes |
+    ///|----------------------|--------------------------------------------------------|-------|
+    ///| `public_address_b58` | The public address b58 string to update.               |       |
+    ///| `customer_tag`       | The customer this subaddress was assigned to.          |       |
+    ///| `conn`               | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns:
+    /// * unit
+    fn set_customer_tag(
+        public_address_b58: &str,
+        customer_tag: &str,
+        conn: Conn,
+    ) -> Result<(), WalletDbError>;
+
+    /// Attach an address book entry to an AssignedSubaddress, or detach it.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                    | Purpose                                                | Notes                           |
+    ///|-------------------------|--------------------------------------------------------|---------------------------------|
+    ///| `public_address_b58`    | The public address b58 string to update.               |                                 |
+    ///| `address_book_entry_id` | The address book entry of the contact, if any.         | The entry must exist, if given. |
+    ///| `conn`                  | An reference to the pool connection of wallet database |                                 |
+    ///
+    /// # Returns:
+    /// * unit
+    fn set_address_book_entry(
+        public_address_b58: &str,
+        address_book_entry_id: Option<i32>,
+        conn: Conn,
+    ) -> Result<(), WalletDbError>;
+
+    /// List the tagged AssignedSubaddresses for a given account that have
+    /// received a txo, but whose first deposit has not yet been reported.
+    ///
+    /// # Arguments
+    ///
+    ///| Name             | Purpose                                                | Notes                            |
+    ///|------------------|--------------------------------------------------------|----------------------------------|
+    ///| `account_id_hex` | The account on which to perform this action.           | Account must exist in the wallet |
+    ///| `conn`           | An reference to the pool connection of wallet database |                                  |
+    ///
+    /// # Returns:
+    /// * Vector of AssignedSubaddress, ordered by subaddress index
+    fn list_unreported_first_deposits(
+        account_id_hex: &str,
+        conn: Conn,
+    ) -> Result<Vec<AssignedSubaddress>, WalletDbError>;
+
+    /// Mark the first deposit to an AssignedSubaddress as reported.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                 | Purpose                                                | Notes |
+    ///|----------------------|--------------------------------------------------------|-------|
+    ///| `public_address_b58` | The public address b58 string to update.               |       |
+    ///| `conn`               | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns:
+    /// * unit
+    fn set_first_deposit_notified(
+        public_address_b58: &str,
+        conn: Conn,
+    ) -> Result<(), WalletDbError>;
+
+    /// Mark an AssignedSubaddress as one the account's change is sent to.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                 | Purpose                                                | Notes |
+    ///|----------------------|--------------------------------------------------------|-------|
+    ///| `public_address_b58` | The public address b58 string to update.               |       |
+    ///| `conn`               | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns:
+    /// * unit
+    fn set_change(public_address_b58: &str, conn: Conn) -> Result<(), WalletDbError>;
+
+    /// List the indices of the subaddresses that have been rotated in to
+    /// receive change for a given account.
+    ///
+    /// # Arguments
+    ///
+    ///| Name             | Purpose                                                | Notes                            |
+    ///|------------------|--------------------------------------------------------|----------------------------------|
+    ///| `account_id_hex` | The account on which to perform this action.           | Account must exist in the wallet |
+    ///| `conn`           | An reference to the pool connection of wallet database |                                  |
+    ///
+    /// # Returns:
+    /// * Vector of subaddress indices
+    fn list_change_indices_for_account(
+        account_id_hex: &str,
+        conn: Conn,
+    ) -> Result<Vec<i64>, WalletDbError>;
+
     /// Delete all AssignedSubaddresses for a given account.
     /// 
     /// # Arguments
@@ -288,8 +432,17 @@ impl AssignedSubaddressModel for AssignedSubaddress {
             let subaddress = view_account_key.subaddress(next_subaddress_index);
 
             // Find and repair orphaned txos at this subaddress.
-            let orphaned_txos =
-                Txo::list_orphaned(Some(account_id_hex), None, None, None, None, None, conn)?;
+            let orphaned_txos = Txo::list_orphaned(
+                Some(account_id_hex),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                conn,
+            )?;
 
             for orphaned_txo in orphaned_txos.iter() {
                 let tx_out_target_key: RistrettoPublic =
@@ -306,8 +459,11 @@ impl AssignedSubaddressModel for AssignedSubaddress {
                 if txo_subaddress_spk == *subaddress.spend_public_key() {
                     // Update the account status mapping.
                     diesel::update(orphaned_txo)
-                        .set((crate::db::schema::txos::subaddress_index
-                            .eq(next_subaddress_index as i64),))
+                        .set((
+                            crate::db::schema::txos::subaddress_index
+                                .eq(next_subaddress_index as i64),
+                            crate::db::schema::txos::updated_at.eq(unix_timestamp()),
+                        ))
                         .execute(conn)?;
                 }
             }
@@ -322,8 +478,17 @@ impl AssignedSubaddressModel for AssignedSubaddress {
             let subaddress = account_key.subaddress(next_subaddress_index);
 
             // Find and repair orphaned txos at this subaddress.
-            let orphaned_txos =
-                Txo::list_orphaned(Some(account_id_hex), None, None, None, None, None, conn)?;
+            let orphaned_txos = Txo::list_orphaned(
+                Some(account_id_hex),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                conn,
+            )?;
 
             for orphaned_txo in orphaned_txos.iter() {
                 let tx_out_target_key: RistrettoPublic =
@@ -352,10 +517,11 @@ impl AssignedSubaddressModel for AssignedSubaddress {
                             ledger_db.get_tx_out_index_by_public_key(&txo_public_key)?;
                         let block_index = ledger_db.get_block_index_by_tx_out_index(txo_index)?;
                         diesel::update(orphaned_txo)
-                            .set(
+                            .set((
                                 crate::db::schema::txos::spent_block_index
                                     .eq(Some(block_index as i64)),
-                            )
+                                crate::db::schema::txos::updated_at.eq(unix_timestamp()),
+                            ))
                             .execute(conn)?;
                     }
 
@@ -367,6 +533,7 @@ impl AssignedSubaddressModel for AssignedSubaddress {
                             crate::db::schema::txos::subaddress_index
                                 .eq(next_subaddress_index as i64),
                             crate::db::schema::txos::key_image.eq(key_image_bytes),
+                            crate::db::schema::txos::updated_at.eq(unix_timestamp()),
                         ))
                         .execute(conn)?;
                 }
@@ -464,6 +631,145 @@ impl AssignedSubaddressModel for AssignedSubaddress {
         Ok(addresses_query.load(conn)?)
     }
 
+    fn list_unused(
+        account_id_hex: &str,
+        conn: Conn,
+    ) -> Result<Vec<AssignedSubaddress>, WalletDbError> {
+        use crate::db::schema::{assigned_subaddresses, txos};
+
+        // The change subaddress index is stored as a negative i64, so this also
+        // excludes it along with the main and legacy change subaddresses.
+        Ok(assigned_subaddresses::table
+            .filter(assigned_subaddresses::account_id.eq(account_id_hex))
+            .filter(
+                assigned_subaddresses::subaddress_index.ge(DEFAULT_NEXT_SUBADDRESS_INDEX as i64),
+            )
+            .filter(assigned_subaddresses::hidden.eq(false))
+            .filter(assigned_subaddresses::is_change.eq(false))
+            .filter(not(exists(
+                txos::table
+                    .filter(txos::account_id.eq(assigned_subaddresses::account_id.nullable()))
+                    .filter(
+                        txos::subaddress_index
+                            .eq(assigned_subaddresses::subaddress_index.nullable()),
+                    ),
+            )))
+            .order(assigned_subaddresses::subaddress_index.asc())
+            .load(conn)?)
+    }
+
+    fn set_hidden(public_address_b58: &str, hidden: bool, conn: Conn) -> Result<(), WalletDbError> {
+        use crate::db::schema::assigned_subaddresses;
+
+        let assigned_subaddress = AssignedSubaddress::get(public_address_b58, conn)?;
+
+        diesel::update(assigned_subaddresses::table.filter(
+            assigned_subaddresses::public_address_b58.eq(&assigned_subaddress.public_address_b58),
+        ))
+        .set(assigned_subaddresses::hidden.eq(hidden))
+        .execute(conn)?;
+        Ok(())
+    }
+
+    fn set_customer_tag(
+        public_address_b58: &str,
+        customer_tag: &str,
+        conn: Conn,
+    ) -> Result<(), WalletDbError> {
+        use crate::db::schema::assigned_subaddresses;
+
+        let assigned_subaddress = AssignedSubaddress::get(public_address_b58, conn)?;
+
+        diesel::update(assigned_subaddresses::table.filter(
+            assigned_subaddresses::public_address_b58.eq(&assigned_subaddress.public_address_b58),
+        ))
+        .set(assigned_subaddresses::customer_tag.eq(customer_tag))
+        .execute(conn)?;
+        Ok(())
+    }
+
+    fn set_address_book_entry(
+        public_address_b58: &str,
+        address_book_entry_id: Option<i32>,
+        conn: Conn,
+    ) -> Result<(), WalletDbError> {
+        use crate::db::schema::assigned_subaddresses;
+
+        let assigned_subaddress = AssignedSubaddress::get(public_address_b58, conn)?;
+        if let Some(address_book_entry_id) = address_book_entry_id {
+            AddressBookEntry::get(address_book_entry_id, conn)?;
+        }
+
+        diesel::update(assigned_subaddresses::table.filter(
+            assigned_subaddresses::public_address_b58.eq(&assigned_subaddress.public_address_b58),
+        ))
+        .set(assigned_subaddresses::address_book_entry_id.eq(address_book_entry_id))
+        .execute(conn)?;
+        Ok(())
+    }
+
+    fn list_unreported_first_deposits(
+        account_id_hex: &str,
+        conn: Conn,
+    ) -> Result<Vec<AssignedSubaddress>, WalletDbError> {
+        use crate::db::schema::{assigned_subaddresses, txos};
+
+        Ok(assigned_subaddresses::table
+            .filter(assigned_subaddresses::account_id.eq(account_id_hex))
+            .filter(assigned_subaddresses::customer_tag.is_not_null())
+            .filter(assigned_subaddresses::first_deposit_notified.eq(false))
+            .filter(exists(
+                txos::table
+                    .filter(txos::account_id.eq(assigned_subaddresses::account_id.nullable()))
+                    .filter(
+                        txos::subaddress_index
+                            .eq(assigned_subaddresses::subaddress_index.nullable()),
+                    ),
+            ))
+            .order(assigned_subaddresses::subaddress_index.asc())
+            .load(conn)?)
+    }
+
+    fn set_first_deposit_notified(
+        public_address_b58: &str,
+        conn: Conn,
+    ) -> Result<(), WalletDbError> {
+        use crate::db::schema::assigned_subaddresses;
+
+        diesel::update(
+            assigned_subaddresses::table
+                .filter(assigned_subaddresses::public_address_b58.eq(public_address_b58)),
+        )
+        .set(assigned_subaddresses::first_deposit_notified.eq(true))
+        .execute(conn)?;
+        Ok(())
+    }
+
+    fn set_change(public_address_b58: &str, conn: Conn) -> Result<(), WalletDbError> {
+        use crate::db::schema::assigned_subaddresses;
+
+        diesel::update(
+            assigned_subaddresses::table
+                .filter(assigned_subaddresses::public_address_b58.eq(public_address_b58)),
+        )
+        .set(assigned_subaddresses::is_change.eq(true))
+        .execute(conn)?;
+        Ok(())
+    }
+
+    fn list_change_indices_for_account(
+        account_id_hex: &str,
+        conn: Conn,
+    ) -> Result<Vec<i64>, WalletDbError> {
+        use crate::db::schema::assigned_subaddresses;
+
+        Ok(assigned_subaddresses::table
+            .filter(assigned_subaddresses::account_id.eq(account_id_hex))
+            .filter(assigned_subaddresses::is_change.eq(true))
+            .select(assigned_subaddresses::subaddress_index)
+            .load(conn)?)
+    }
+
     fn delete_all(account_id_hex: &str, conn: Conn) -> Result<(), WalletDbError> {
         use crate::db::schema::assigned_subaddresses;
 
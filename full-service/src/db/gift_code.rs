@@ -7,6 +7,7 @@ use crate::{
         models::{GiftCode, NewGiftCode},
         Conn, WalletDbError,
     },
+    json_rpc::json_rpc_response::{ServerErrorCode, ServerErrorCodes},
     service::gift_code::EncodedGiftCode,
 };
 use diesel::prelude::*;
@@ -18,6 +19,15 @@ pub enum GiftCodeDbError {
     GiftCodeNotFound(String),
 }
 
+impl ServerErrorCode for GiftCodeDbError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::GiftCodeNotFound(_) => Some(ServerErrorCodes::NotFound),
+            _ => None,
+        }
+    }
+}
+
 #[rustfmt::skip]
 pub trait GiftCodeModel {
     /// Create a gift code.
@@ -32,7 +42,7 @@ pub trait GiftCodeModel {
     ///| Name            | Purpose                                                | Notes                                                      |
     ///|-----------------|--------------------------------------------------------|------------------------------------------------------------|
     ///| `gift_code_b58` | The base58-encoded gift code contents.                 | Gift code includes `entropy`, `txo public key`, and `memo` |
-    ///| `value`         | The amount of MOB to send in this transaction.         |                                                            |
+    ///| `value`         | The amount of MOB to send in this transaction.         | `None` if the gift code txo is not yet in the ledger.      |
     ///| `conn`          | An reference to the pool connection of wallet database |                                                            |
     ///
     /// # Returns:
@@ -40,7 +50,7 @@ pub trait GiftCodeModel {
     #[allow(clippy::too_many_arguments)]
     fn create(
         gift_code_b58: &EncodedGiftCode,
-        value: i64,
+        value: Option<i64>,
         conn: Conn,
     ) -> Result<GiftCode, WalletDbError>;
 
@@ -60,6 +70,24 @@ pub trait GiftCodeModel {
         conn: Conn
     ) -> Result<GiftCode, WalletDbError>;
 
+    /// Get a gift code, creating it if it is not yet in the wallet.
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name            | Purpose                                                | Notes                                    |
+    ///|-----------------|--------------------------------------------------------|------------------------------------------|
+    ///| `gift_code_b58` | The base58-encoded gift code contents.                 |                                          |
+    ///| `value`         | The amount of MOB in the gift code, if known.          | Only used if the gift code is created.   |
+    ///| `conn`          | An reference to the pool connection of wallet database |                                          |
+    /// 
+    /// # Returns:
+    /// * The stored gift code.
+    fn get_or_create(
+        gift_code_b58: &EncodedGiftCode,
+        value: Option<i64>,
+        conn: Conn,
+    ) -> Result<GiftCode, WalletDbError>;
+
     /// Get all Gift Codes in this wallet.
     /// 
     /// # Arguments
@@ -89,12 +117,44 @@ pub trait GiftCodeModel {
     /// # Returns:
     /// * unit
     fn delete(self, conn: Conn) -> Result<(), WalletDbError>;
+
+    /// Record or clear the tombstone block of an in-flight claim of this gift
+    /// code.
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name              | Purpose                                                 | Notes                           |
+    ///|-------------------|---------------------------------------------------------|---------------------------------|
+    ///| `tombstone_block` | The tombstone block of the claim transaction.           | `None` clears the claim.        |
+    ///| `conn`            | An reference to the pool connection of wallet database  |                                 |
+    ///
+    /// # Returns:
+    /// * unit
+    fn set_claim_tombstone_block(
+        &self,
+        tombstone_block: Option<u64>,
+        conn: Conn,
+    ) -> Result<(), WalletDbError>;
+
+    /// Record the value of a gift code which was stored before its txo landed
+    /// in the ledger.
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name    | Purpose                                                 | Notes |
+    ///|---------|---------------------------------------------------------|-------|
+    ///| `value` | The amount of MOB in the gift code.                     |       |
+    ///| `conn`  | An reference to the pool connection of wallet database  |       |
+    ///
+    /// # Returns:
+    /// * unit
+    fn set_value(&self, value: i64, conn: Conn) -> Result<(), WalletDbError>;
 }
 
 impl GiftCodeModel for GiftCode {
     fn create(
         gift_code_b58: &EncodedGiftCode,
-        value: i64,
+        value: Option<i64>,
         conn: Conn,
     ) -> Result<GiftCode, WalletDbError> {
         use crate::db::schema::gift_codes;
@@ -128,6 +188,19 @@ impl GiftCodeModel for GiftCode {
         }
     }
 
+    fn get_or_create(
+        gift_code_b58: &EncodedGiftCode,
+        value: Option<i64>,
+        conn: Conn,
+    ) -> Result<GiftCode, WalletDbError> {
+        match GiftCode::get(gift_code_b58, conn) {
+            Err(WalletDbError::GiftCode(GiftCodeDbError::GiftCodeNotFound(_))) => {
+                GiftCode::create(gift_code_b58, value, conn)
+            }
+            result => result,
+        }
+    }
+
     fn list_all(
         conn: Conn,
         offset: Option<u64>,
@@ -150,6 +223,28 @@ impl GiftCodeModel for GiftCode {
         diesel::delete(gift_codes.filter(gift_code_b58.eq(&self.gift_code_b58))).execute(conn)?;
         Ok(())
     }
+
+    fn set_claim_tombstone_block(
+        &self,
+        tombstone_block: Option<u64>,
+        conn: Conn,
+    ) -> Result<(), WalletDbError> {
+        use crate::db::schema::gift_codes;
+
+        diesel::update(gift_codes::table.filter(gift_codes::id.eq(self.id)))
+            .set(gift_codes::claim_tombstone_block.eq(tombstone_block.map(|t| t as i64)))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    fn set_value(&self, value: i64, conn: Conn) -> Result<(), WalletDbError> {
+        use crate::db::schema::gift_codes;
+
+        diesel::update(gift_codes::table.filter(gift_codes::id.eq(self.id)))
+            .set(gift_codes::value.eq(Some(value)))
+            .execute(conn)?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -190,7 +285,7 @@ mod tests {
 
         let gift_code = GiftCode::create(
             &EncodedGiftCode("gk7CcXuK5RKNW13LvrWY156ZLjaoHaXxLedqACZsw3w6FfF6TR4TVzaAQkH5EHxaw54DnGWRJPA31PpcmvGLoArZbDRj1kBhcTusE8AVW4Mj7QT5".to_string()),
-            value as i64,
+            Some(value as i64),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -204,7 +299,8 @@ mod tests {
         let expected_gift_code = GiftCode {
             id: 1,
             gift_code_b58: gotten.gift_code_b58.clone(),
-            value: value as i64,
+            value: Some(value as i64),
+            claim_tombstone_block: None,
         };
         assert_eq!(gotten, expected_gift_code);
 
@@ -212,5 +308,46 @@ mod tests {
             GiftCode::list_all(&mut wallet_db.get_pooled_conn().unwrap(), None, None).unwrap();
         assert_eq!(all_gift_codes.len(), 1);
         assert_eq!(all_gift_codes[0], expected_gift_code);
+
+        // Getting an existing gift code does not create another
+        let existing = GiftCode::get_or_create(
+            &EncodedGiftCode(expected_gift_code.gift_code_b58.clone()),
+            Some(0),
+            &mut wallet_db.get_pooled_conn().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(existing, expected_gift_code);
+
+        existing
+            .set_claim_tombstone_block(Some(42), &mut wallet_db.get_pooled_conn().unwrap())
+            .unwrap();
+        let claimed = GiftCode::get(
+            &EncodedGiftCode(expected_gift_code.gift_code_b58.clone()),
+            &mut wallet_db.get_pooled_conn().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(claimed.claim_tombstone_block, Some(42));
+
+        let all_gift_codes =
+            GiftCode::list_all(&mut wallet_db.get_pooled_conn().unwrap(), None, None).unwrap();
+        assert_eq!(all_gift_codes.len(), 1);
+
+        // A gift code can be stored before its value is known
+        let pending = GiftCode::create(
+            &EncodedGiftCode("pending".to_string()),
+            None,
+            &mut wallet_db.get_pooled_conn().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(pending.value, None);
+        pending
+            .set_value(value as i64, &mut wallet_db.get_pooled_conn().unwrap())
+            .unwrap();
+        let landed = GiftCode::get(
+            &EncodedGiftCode("pending".to_string()),
+            &mut wallet_db.get_pooled_conn().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(landed.value, Some(value as i64));
     }
 }
@@ -4,6 +4,7 @@
 //! is stored in LMDB).
 
 pub mod account;
+pub mod address_book_entry;
 pub mod assigned_subaddress;
 pub mod authenticated_sender_memo;
 pub mod gift_code;
@@ -15,5 +16,5 @@ pub mod txo;
 mod wallet_db;
 mod wallet_db_error;
 
-pub use wallet_db::{exclusive_transaction, Conn, WalletDb};
+pub use wallet_db::{exclusive_transaction, unix_timestamp, Conn, WalletDb};
 pub use wallet_db_error::WalletDbError;
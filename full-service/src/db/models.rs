@@ -3,17 +3,17 @@
 //! DB Models
 
 use super::schema::{
-    __diesel_schema_migrations, accounts, assigned_subaddresses, authenticated_sender_memos,
-    destination_memos, gift_codes, transaction_input_txos, transaction_logs,
-    transaction_output_txos, txos,
+    __diesel_schema_migrations, accounts, address_book_entries, assigned_subaddresses,
+    authenticated_sender_memos, destination_memos, gift_codes, transaction_input_txos,
+    transaction_logs, transaction_output_txos, txos,
 };
 use mc_crypto_keys::CompressedRistrettoPublic;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 /// An Account entity.
 ///
 /// Contains the account private keys, subaddress configuration, and ...
-#[derive(Clone, Serialize, Identifiable, Queryable, PartialEq, Debug)]
+#[derive(Clone, Serialize, Deserialize, Insertable, Identifiable, Queryable, PartialEq, Debug)]
 #[diesel(primary_key(id))]
 #[diesel(table_name = accounts)]
 pub struct Account {
@@ -42,6 +42,35 @@ pub struct Account {
     pub resyncing: bool,
     /// If true, this account is only allowed to spend from subaddresses.
     pub require_spend_subaddress: bool,
+    /// The MOB fee to use for this account's transactions when none is given,
+    /// instead of the network fee.
+    pub default_fee: Option<i64>,
+    /// Unix timestamp of when this account was added to the wallet.
+    #[serde(default)]
+    pub created_at: i64,
+    /// Unix timestamp of when this account was last modified.
+    #[serde(default)]
+    pub updated_at: i64,
+    /// If true, deposits to this account trigger the deposits webhook.
+    #[serde(default = "default_webhook_enabled")]
+    pub webhook_enabled: bool,
+    /// The subaddress that change is sent to when a transaction does not
+    /// choose one, if it has been rotated away from the reserved change
+    /// subaddress.
+    #[serde(default)]
+    pub change_subaddress_index: Option<i64>,
+    /// The account index on the hardware wallet that holds this account's
+    /// spend key, if it is managed by a hardware wallet.
+    #[serde(default)]
+    pub hardware_wallet_account_index: Option<i32>,
+    /// One past the last block scanned for this account by a block range sync
+    /// ahead of its next block index, if any.
+    #[serde(default)]
+    pub scanned_block_index: Option<i64>,
+}
+
+fn default_webhook_enabled() -> bool {
+    true
 }
 
 /// A structure that can be inserted to create a new entity in the `accounts`
@@ -61,6 +90,9 @@ pub struct NewAccount<'a> {
     pub view_only: bool,
     pub managed_by_hardware_wallet: bool,
     pub require_spend_subaddress: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub hardware_wallet_account_index: Option<i32>,
 }
 
 /// A transaction output entity that either was received to an Account in this
@@ -68,7 +100,7 @@ pub struct NewAccount<'a> {
 /// output can be in one of many states with respect to multiple accounts.
 /// Managing these relationships and states is one of the main goals of
 /// the Full-Service wallet.
-#[derive(Clone, Serialize, Identifiable, Queryable, PartialEq, Debug)]
+#[derive(Clone, Serialize, Deserialize, Insertable, Identifiable, Queryable, PartialEq, Debug)]
 #[diesel(primary_key(id))]
 #[diesel(table_name = txos)]
 pub struct Txo {
@@ -96,6 +128,19 @@ pub struct Txo {
     pub shared_secret: Option<Vec<u8>>,
     pub memo_type: Option<i32>,
     pub is_synced_to_t3: bool,
+    /// Unix timestamp until which this Txo is reserved by a built transaction
+    /// that has not been submitted yet.
+    pub reserved_until: Option<i64>,
+    /// The decrypted memo payload bytes, including the two memo type bytes.
+    pub memo_payload: Option<Vec<u8>>,
+    /// Unix timestamp of the block containing this Txo, once known.
+    pub received_timestamp: Option<i64>,
+    /// Unix timestamp of when this Txo was added to the wallet.
+    #[serde(default)]
+    pub created_at: i64,
+    /// Unix timestamp of when this Txo was last modified.
+    #[serde(default)]
+    pub updated_at: i64,
 }
 
 impl Txo {
@@ -123,11 +168,24 @@ pub struct NewTxo<'a> {
     pub confirmation: Option<&'a [u8]>,
     pub shared_secret: Option<&'a [u8]>,
     pub memo_type: Option<i32>,
+    pub memo_payload: Option<&'a [u8]>,
+    pub created_at: i64,
+    pub updated_at: i64,
 }
 
 /// A subaddress given to a particular contact, for the purpose of tracking
 /// funds received from that contact.
-#[derive(Clone, Serialize, Associations, Identifiable, Queryable, PartialEq, Debug)]
+#[derive(
+    Clone,
+    Serialize,
+    Deserialize,
+    Insertable,
+    Associations,
+    Identifiable,
+    Queryable,
+    PartialEq,
+    Debug,
+)]
 #[diesel(belongs_to(Account, foreign_key = account_id))]
 #[diesel(primary_key(public_address_b58))]
 #[diesel(table_name = assigned_subaddresses)]
@@ -137,6 +195,14 @@ pub struct AssignedSubaddress {
     pub subaddress_index: i64,
     pub comment: String,
     pub spend_public_key: Vec<u8>,
+    pub hidden: bool,
+    pub customer_tag: Option<String>,
+    pub first_deposit_notified: bool,
+    /// The address book entry of the contact this subaddress was given to.
+    pub address_book_entry_id: Option<i32>,
+    /// If true, this subaddress was rotated in to receive the account's
+    /// change, and change received on it is still counted as change.
+    pub is_change: bool,
 }
 
 /// A structure that can be inserted to create a new AssignedSubaddress entity.
@@ -150,8 +216,36 @@ pub struct NewAssignedSubaddress<'a> {
     pub spend_public_key: &'a [u8],
 }
 
+/// A named contact, identified by their public address.
+#[derive(Clone, Serialize, Deserialize, Identifiable, Queryable, PartialEq, Eq, Debug)]
+#[diesel(primary_key(id))]
+#[diesel(table_name = address_book_entries)]
+pub struct AddressBookEntry {
+    pub id: i32,
+    pub public_address_b58: String,
+    pub name: String,
+}
+
+/// A structure that can be inserted to create a new AddressBookEntry entity.
+#[derive(Insertable)]
+#[diesel(table_name = address_book_entries)]
+pub struct NewAddressBookEntry<'a> {
+    pub public_address_b58: &'a str,
+    pub name: &'a str,
+}
+
 /// The status of a sent transaction OR a received transaction output.
-#[derive(Clone, Serialize, Associations, Identifiable, Queryable, PartialEq, Debug)]
+#[derive(
+    Clone,
+    Serialize,
+    Deserialize,
+    Insertable,
+    Associations,
+    Identifiable,
+    Queryable,
+    PartialEq,
+    Debug,
+)]
 #[diesel(belongs_to(Account, foreign_key = account_id))]
 #[diesel(primary_key(id))]
 #[diesel(table_name = transaction_logs)]
@@ -166,6 +260,20 @@ pub struct TransactionLog {
     pub comment: String,
     pub tx: Vec<u8>,
     pub failed: bool,
+    /// Unix timestamp of when this transaction log was added to the wallet.
+    #[serde(default)]
+    pub created_at: i64,
+    /// Unix timestamp of when this transaction log was last modified.
+    #[serde(default)]
+    pub updated_at: i64,
+    /// Client-supplied key that makes repeat submissions of this transaction
+    /// return this log instead of resubmitting.
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    /// Unix timestamp of the block this transaction was finalized in, once
+    /// known.
+    #[serde(default)]
+    pub finalized_timestamp: Option<i64>,
 }
 
 /// A structure that can be inserted to create a new TransactionLog entity.
@@ -182,9 +290,22 @@ pub struct NewTransactionLog<'a> {
     pub comment: &'a str,
     pub tx: &'a [u8],
     pub failed: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub idempotency_key: Option<&'a str>,
 }
 
-#[derive(Clone, Serialize, Associations, Identifiable, Queryable, PartialEq, Debug)]
+#[derive(
+    Clone,
+    Serialize,
+    Deserialize,
+    Insertable,
+    Associations,
+    Identifiable,
+    Queryable,
+    PartialEq,
+    Debug,
+)]
 #[diesel(belongs_to(TransactionLog, foreign_key = transaction_log_id))]
 #[diesel(belongs_to(Txo, foreign_key = txo_id))]
 #[diesel(table_name = transaction_input_txos)]
@@ -201,7 +322,17 @@ pub struct NewTransactionInputTxo<'a> {
     pub txo_id: &'a str,
 }
 
-#[derive(Clone, Serialize, Associations, Identifiable, Queryable, PartialEq, Debug)]
+#[derive(
+    Clone,
+    Serialize,
+    Deserialize,
+    Insertable,
+    Associations,
+    Identifiable,
+    Queryable,
+    PartialEq,
+    Debug,
+)]
 #[diesel(belongs_to(TransactionLog, foreign_key = transaction_log_id))]
 #[diesel(belongs_to(Txo, foreign_key = txo_id))]
 #[diesel(table_name = transaction_output_txos)]
@@ -230,17 +361,32 @@ pub struct NewTransactionOutputTxo<'a> {
 pub struct GiftCode {
     pub id: i32,
     pub gift_code_b58: String,
-    pub value: i64,
+    /// The value of the gift code, or None if it was imported before its txo
+    /// landed in the ledger.
+    pub value: Option<i64>,
+    /// The tombstone block of a claim of this gift code that is in flight.
+    pub claim_tombstone_block: Option<i64>,
 }
 
 #[derive(Insertable)]
 #[diesel(table_name = gift_codes)]
 pub struct NewGiftCode<'a> {
     pub gift_code_b58: &'a str,
-    pub value: i64,
+    pub value: Option<i64>,
 }
 
-#[derive(Clone, Serialize, Associations, Identifiable, Queryable, PartialEq, Eq, Debug)]
+#[derive(
+    Clone,
+    Serialize,
+    Deserialize,
+    Insertable,
+    Associations,
+    Identifiable,
+    Queryable,
+    PartialEq,
+    Eq,
+    Debug,
+)]
 #[diesel(belongs_to(Txo, foreign_key = txo_id))]
 #[diesel(table_name = authenticated_sender_memos)]
 #[diesel(primary_key(txo_id))]
@@ -260,7 +406,18 @@ pub struct NewAuthenticatedSenderMemo<'a> {
     pub payment_intent_id: Option<i64>,
 }
 
-#[derive(Clone, Serialize, Associations, Identifiable, Queryable, PartialEq, Eq, Debug)]
+#[derive(
+    Clone,
+    Serialize,
+    Deserialize,
+    Insertable,
+    Associations,
+    Identifiable,
+    Queryable,
+    PartialEq,
+    Eq,
+    Debug,
+)]
 #[diesel(belongs_to(Txo, foreign_key = txo_id))]
 #[diesel(table_name = destination_memos)]
 #[diesel(primary_key(txo_id))]
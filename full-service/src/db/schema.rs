@@ -15,6 +15,21 @@ diesel::table! {
         managed_by_hardware_wallet -> Bool,
         resyncing -> Bool,
         require_spend_subaddress -> Bool,
+        default_fee -> Nullable<BigInt>,
+        created_at -> BigInt,
+        updated_at -> BigInt,
+        webhook_enabled -> Bool,
+        change_subaddress_index -> Nullable<BigInt>,
+        hardware_wallet_account_index -> Nullable<Integer>,
+        scanned_block_index -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    address_book_entries (id) {
+        id -> Integer,
+        public_address_b58 -> Text,
+        name -> Text,
     }
 }
 
@@ -25,6 +40,11 @@ diesel::table! {
         subaddress_index -> BigInt,
         comment -> Text,
         spend_public_key -> Binary,
+        hidden -> Bool,
+        customer_tag -> Nullable<Text>,
+        first_deposit_notified -> Bool,
+        address_book_entry_id -> Nullable<Integer>,
+        is_change -> Bool,
     }
 }
 
@@ -53,7 +73,8 @@ diesel::table! {
     gift_codes (id) {
         id -> Integer,
         gift_code_b58 -> Text,
-        value -> BigInt,
+        value -> Nullable<BigInt>,
+        claim_tombstone_block -> Nullable<BigInt>,
     }
 }
 
@@ -76,6 +97,10 @@ diesel::table! {
         comment -> Text,
         tx -> Binary,
         failed -> Bool,
+        created_at -> BigInt,
+        updated_at -> BigInt,
+        idempotency_key -> Nullable<Text>,
+        finalized_timestamp -> Nullable<BigInt>,
     }
 }
 
@@ -105,6 +130,11 @@ diesel::table! {
         shared_secret -> Nullable<Binary>,
         memo_type -> Nullable<Integer>,
         is_synced_to_t3 -> Bool,
+        reserved_until -> Nullable<BigInt>,
+        memo_payload -> Nullable<Binary>,
+        received_timestamp -> Nullable<BigInt>,
+        created_at -> BigInt,
+        updated_at -> BigInt,
     }
 }
 
@@ -116,6 +146,7 @@ diesel::table! {
 }
 
 diesel::joinable!(assigned_subaddresses -> accounts (account_id));
+diesel::joinable!(assigned_subaddresses -> address_book_entries (address_book_entry_id));
 diesel::joinable!(authenticated_sender_memos -> txos (txo_id));
 diesel::joinable!(destination_memos -> txos (txo_id));
 diesel::joinable!(transaction_input_txos -> transaction_logs (transaction_log_id));
@@ -127,6 +158,7 @@ diesel::joinable!(txos -> accounts (account_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     accounts,
+    address_book_entries,
     assigned_subaddresses,
     authenticated_sender_memos,
     destination_memos,
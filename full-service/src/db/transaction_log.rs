@@ -16,7 +16,7 @@ use crate::{
             TransactionLog, TransactionOutputTxo, Txo,
         },
         txo::{TxoID, TxoModel},
-        Conn, WalletDbError,
+        unix_timestamp, Conn, WalletDbError,
     },
     service::models::tx_proposal::{OutputTxo, TxProposal, UnsignedTxProposal},
 };
@@ -114,10 +114,10 @@ impl fmt::Display for TxoType {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct ValueMap(pub HashMap<TokenId, u64>);
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct AssociatedTxos {
     pub inputs: Vec<Txo>,
     pub outputs: Vec<(Txo, String)>,
@@ -151,6 +151,40 @@ pub trait TransactionLogModel {
         conn: Conn
     ) -> Result<TransactionLog, WalletDbError>;
 
+    /// Get the transaction log that was submitted with a given idempotency key, if any.
+    ///
+    /// # Arguments
+    ///
+    ///| Name              | Purpose                                                     | Notes |
+    ///|-------------------|-------------------------------------------------------------|-------|
+    ///| `idempotency_key` | The client-supplied key the transaction was submitted with. |       |
+    ///| `conn`            | An reference to the pool connection of wallet database      |       |
+    ///
+    /// # Returns
+    /// * Option(TransactionLog)
+    fn get_by_idempotency_key(
+        idempotency_key: &str,
+        conn: Conn
+    ) -> Result<Option<TransactionLog>, WalletDbError>;
+
+    /// Get the transaction log that produced a given Txo as a payload or change output, if any.
+    ///
+    /// # Arguments
+    ///
+    ///| Name             | Purpose                                                | Notes                                   |
+    ///|------------------|--------------------------------------------------------|-----------------------------------------|
+    ///| `account_id_hex` | The account that built the transaction.                |                                         |
+    ///| `txo_id_hex`     | The output Txo to trace.                               |                                         |
+    ///| `conn`           | An reference to the pool connection of wallet database |                                         |
+    ///
+    /// # Returns
+    /// * Option(TransactionLog)
+    fn get_for_output_txo(
+        account_id_hex: &str,
+        txo_id_hex: &str,
+        conn: Conn
+    ) -> Result<Option<TransactionLog>, WalletDbError>;
+
     /// Get the Txos associated with a given transaction id, grouped according to their type.
     ///
     /// # Arguments
@@ -194,6 +228,23 @@ pub trait TransactionLogModel {
     /// * unit
     fn update_comment(&self, comment: String, conn: Conn) -> Result<(), WalletDbError>;
 
+    /// Set the idempotency key of a transaction log.
+    ///
+    /// # Arguments
+    /// 
+    ///| Name              | Purpose                                                | Notes                                  |
+    ///|-------------------|--------------------------------------------------------|----------------------------------------|
+    ///| `idempotency_key` | The client-supplied key to store with the transaction. | Must be unique among transaction logs. |
+    ///| `conn`            | An reference to the pool connection of wallet database |                                        |
+    ///
+    /// # Returns:
+    /// * unit
+    fn update_idempotency_key(
+        &self,
+        idempotency_key: &str,
+        conn: Conn,
+    ) -> Result<(), WalletDbError>;
+
     /// Update encoded value of the associate transaction and the tombstone_block_index to a transaction log.
     ///
     /// # Arguments
@@ -213,7 +264,8 @@ pub trait TransactionLogModel {
         conn: Conn,
     ) -> Result<(), WalletDbError>;
 
-    /// List all transaction logs and their associated Txos for a given account.
+    /// List all transaction logs and their associated Txos for a given account,
+    /// newest first by submitted block index.
     /// 
     /// # Arguments
     ///
@@ -356,6 +408,48 @@ pub trait TransactionLogModel {
         conn: Conn,
     ) -> Result<(), WalletDbError>;
 
+    /// Set the status of a transaction log to failed, releasing its input txos so they can be selected again.
+    /// 
+    /// # Arguments
+    ///
+    ///| Name   | Purpose                                                | Notes |
+    ///|--------|--------------------------------------------------------|-------|
+    ///| `conn` | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns
+    /// * unit
+    fn update_to_failed(&self, conn: Conn) -> Result<(), WalletDbError>;
+
+    /// List the block indices of finalized transaction logs whose block timestamp is not yet known.
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name   | Purpose                                                | Notes |
+    ///|--------|--------------------------------------------------------|-------|
+    ///| `conn` | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns
+    /// * Vector of block indices
+    fn list_finalized_block_indices_without_timestamp(conn: Conn) -> Result<Vec<u64>, WalletDbError>;
+
+    /// Set the block timestamp of all transaction logs finalized in a block.
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name                    | Purpose                                                | Notes                |
+    ///|-------------------------|--------------------------------------------------------|----------------------|
+    ///| `finalized_block_index` | The block the transactions were finalized in           |                      |
+    ///| `timestamp`             | The timestamp of that block                            | Seconds since epoch. |
+    ///| `conn`                  | An reference to the pool connection of wallet database |                      |
+    ///
+    /// # Returns
+    /// * unit
+    fn update_finalized_timestamp(
+        finalized_block_index: u64,
+        timestamp: u64,
+        conn: Conn,
+    ) -> Result<(), WalletDbError>;
+
     /// Retrieve the status of an associated transaction from a transaction log.
     /// 
     /// # Arguments
@@ -421,6 +515,34 @@ impl TransactionLogModel for TransactionLog {
         }
     }
 
+    fn get_by_idempotency_key(
+        idempotency_key: &str,
+        conn: Conn,
+    ) -> Result<Option<TransactionLog>, WalletDbError> {
+        use crate::db::schema::transaction_logs;
+
+        Ok(transaction_logs::table
+            .filter(transaction_logs::idempotency_key.eq(idempotency_key))
+            .get_result::<TransactionLog>(conn)
+            .optional()?)
+    }
+
+    fn get_for_output_txo(
+        account_id_hex: &str,
+        txo_id_hex: &str,
+        conn: Conn,
+    ) -> Result<Option<TransactionLog>, WalletDbError> {
+        use crate::db::schema::{transaction_logs, transaction_output_txos};
+
+        Ok(transaction_logs::table
+            .inner_join(transaction_output_txos::table)
+            .filter(transaction_logs::account_id.eq(account_id_hex))
+            .filter(transaction_output_txos::txo_id.eq(txo_id_hex))
+            .select(transaction_logs::all_columns)
+            .first::<TransactionLog>(conn)
+            .optional()?)
+    }
+
     fn get_associated_txos(&self, conn: Conn) -> Result<AssociatedTxos, WalletDbError> {
         use crate::db::schema::{transaction_input_txos, transaction_output_txos, txos};
 
@@ -465,7 +587,10 @@ impl TransactionLogModel for TransactionLog {
         use crate::db::schema::transaction_logs;
 
         diesel::update(self)
-            .set(transaction_logs::submitted_block_index.eq(Some(submitted_block_index as i64)))
+            .set((
+                transaction_logs::submitted_block_index.eq(Some(submitted_block_index as i64)),
+                transaction_logs::updated_at.eq(unix_timestamp()),
+            ))
             .execute(conn)?;
 
         Ok(())
@@ -475,7 +600,27 @@ impl TransactionLogModel for TransactionLog {
         use crate::db::schema::transaction_logs;
 
         diesel::update(self)
-            .set(transaction_logs::comment.eq(comment))
+            .set((
+                transaction_logs::comment.eq(comment),
+                transaction_logs::updated_at.eq(unix_timestamp()),
+            ))
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    fn update_idempotency_key(
+        &self,
+        idempotency_key: &str,
+        conn: Conn,
+    ) -> Result<(), WalletDbError> {
+        use crate::db::schema::transaction_logs;
+
+        diesel::update(self)
+            .set((
+                transaction_logs::idempotency_key.eq(Some(idempotency_key)),
+                transaction_logs::updated_at.eq(unix_timestamp()),
+            ))
             .execute(conn)?;
 
         Ok(())
@@ -493,6 +638,7 @@ impl TransactionLogModel for TransactionLog {
             .set((
                 transaction_logs::tx.eq(tx),
                 transaction_logs::tombstone_block_index.eq(tombstone_block_index),
+                transaction_logs::updated_at.eq(unix_timestamp()),
             ))
             .execute(conn)?;
         Ok(())
@@ -514,8 +660,12 @@ impl TransactionLogModel for TransactionLog {
             query = query.filter(transaction_logs::account_id.eq(account_id));
         }
 
-        if let (Some(o), Some(l)) = (offset, limit) {
-            query = query.offset(o as i64).limit(l as i64);
+        if let Some(limit) = limit {
+            query = query.limit(limit as i64);
+        }
+
+        if let Some(offset) = offset {
+            query = query.offset(offset as i64);
         }
 
         if let Some(min_block_index) = min_block_index {
@@ -528,8 +678,12 @@ impl TransactionLogModel for TransactionLog {
                 query.filter(transaction_logs::submitted_block_index.le(max_block_index as i64));
         }
 
+        // Newest first, with ties broken by id so that pages don't overlap.
         let transaction_logs: Vec<TransactionLog> = query
-            .order(transaction_logs::submitted_block_index.desc())
+            .order((
+                transaction_logs::submitted_block_index.desc(),
+                transaction_logs::id.desc(),
+            ))
             .load(conn)?;
 
         let results = transaction_logs
@@ -557,6 +711,8 @@ impl TransactionLogModel for TransactionLog {
         let transaction_log_id = TransactionId::try_from(unsigned_tx_proposal)
             .map_err(|e| WalletDbError::InvalidArgument(e.to_string()))?;
 
+        let now = unix_timestamp();
+
         let new_transaction_log = NewTransactionLog {
             id: &transaction_log_id.to_string(),
             account_id: &account_id.to_string(),
@@ -568,6 +724,9 @@ impl TransactionLogModel for TransactionLog {
             comment: "",
             tx: &[],
             failed: false,
+            created_at: now,
+            updated_at: now,
+            idempotency_key: None,
         };
 
         diesel::insert_into(transaction_logs::table)
@@ -630,6 +789,7 @@ impl TransactionLogModel for TransactionLog {
                 )?;
             }
             Err(WalletDbError::TransactionLogNotFound(_)) => {
+                let now = unix_timestamp();
                 let new_transaction_log = NewTransactionLog {
                     id: &transaction_log_id.to_string(),
                     account_id: account_id_hex,
@@ -641,6 +801,9 @@ impl TransactionLogModel for TransactionLog {
                     comment: &comment,
                     tx: &tx,
                     failed: false,
+                    created_at: now,
+                    updated_at: now,
+                    idempotency_key: None,
                 };
 
                 diesel::insert_into(crate::db::schema::transaction_logs::table)
@@ -695,6 +858,7 @@ impl TransactionLogModel for TransactionLog {
             }
 
             Err(WalletDbError::TransactionLogNotFound(_)) => {
+                let now = unix_timestamp();
                 let new_transaction_log = NewTransactionLog {
                     id: &transaction_log_id.to_string(),
                     account_id: account_id_hex,
@@ -706,6 +870,9 @@ impl TransactionLogModel for TransactionLog {
                     comment: &comment,
                     tx: &tx,
                     failed: false,
+                    created_at: now,
+                    updated_at: now,
+                    idempotency_key: None,
                 };
 
                 diesel::insert_into(crate::db::schema::transaction_logs::table)
@@ -795,7 +962,10 @@ impl TransactionLogModel for TransactionLog {
         diesel::update(
             transaction_logs::table.filter(transaction_logs::id.eq_any(transaction_log_ids)),
         )
-        .set((transaction_logs::finalized_block_index.eq(finalized_block_index as i64),))
+        .set((
+            transaction_logs::finalized_block_index.eq(finalized_block_index as i64),
+            transaction_logs::updated_at.eq(unix_timestamp()),
+        ))
         .execute(conn)?;
 
         Ok(())
@@ -804,18 +974,86 @@ impl TransactionLogModel for TransactionLog {
     fn update_pending_exceeding_tombstone_block_index_to_failed(
         block_index: u64,
         conn: Conn,
+    ) -> Result<(), WalletDbError> {
+        use crate::db::schema::{transaction_input_txos, transaction_logs};
+
+        let expired_log_ids: Vec<String> = transaction_logs::table
+            .filter(transaction_logs::tombstone_block_index.lt(block_index as i64))
+            .filter(transaction_logs::failed.eq(false))
+            .filter(transaction_logs::finalized_block_index.is_null())
+            .select(transaction_logs::id)
+            .load(conn)?;
+
+        diesel::update(
+            transaction_logs::table.filter(transaction_logs::id.eq_any(&expired_log_ids)),
+        )
+        .set((
+            transaction_logs::failed.eq(true),
+            transaction_logs::updated_at.eq(unix_timestamp()),
+        ))
+        .execute(conn)?;
+
+        // The inputs of the expired transactions can be selected again.
+        let input_txo_ids: Vec<String> = transaction_input_txos::table
+            .filter(transaction_input_txos::transaction_log_id.eq_any(&expired_log_ids))
+            .select(transaction_input_txos::txo_id)
+            .load(conn)?;
+        Txo::release_reservations(&input_txo_ids, conn)?;
+
+        Ok(())
+    }
+
+    fn update_to_failed(&self, conn: Conn) -> Result<(), WalletDbError> {
+        use crate::db::schema::{transaction_input_txos, transaction_logs};
+
+        diesel::update(self)
+            .set((
+                transaction_logs::failed.eq(true),
+                transaction_logs::updated_at.eq(unix_timestamp()),
+            ))
+            .execute(conn)?;
+
+        let input_txo_ids: Vec<String> = transaction_input_txos::table
+            .filter(transaction_input_txos::transaction_log_id.eq(&self.id))
+            .select(transaction_input_txos::txo_id)
+            .load(conn)?;
+        Txo::release_reservations(&input_txo_ids, conn)?;
+
+        Ok(())
+    }
+
+    fn list_finalized_block_indices_without_timestamp(
+        conn: Conn,
+    ) -> Result<Vec<u64>, WalletDbError> {
+        use crate::db::schema::transaction_logs;
+
+        let block_indices: Vec<Option<i64>> = transaction_logs::table
+            .filter(transaction_logs::finalized_block_index.is_not_null())
+            .filter(transaction_logs::finalized_timestamp.is_null())
+            .select(transaction_logs::finalized_block_index)
+            .distinct()
+            .load(conn)?;
+
+        Ok(block_indices
+            .into_iter()
+            .flatten()
+            .map(|block_index| block_index as u64)
+            .collect())
+    }
+
+    fn update_finalized_timestamp(
+        finalized_block_index: u64,
+        timestamp: u64,
+        conn: Conn,
     ) -> Result<(), WalletDbError> {
         use crate::db::schema::transaction_logs;
 
         diesel::update(
             transaction_logs::table
-                .filter(transaction_logs::tombstone_block_index.lt(block_index as i64))
-                .filter(transaction_logs::failed.eq(false))
-                .filter(transaction_logs::finalized_block_index.is_null()),
+                .filter(transaction_logs::finalized_block_index.eq(finalized_block_index as i64)),
         )
-        .set((transaction_logs::failed.eq(true),))
+        .set(transaction_logs::finalized_timestamp.eq(Some(timestamp as i64)))
         .execute(conn)?;
-
         Ok(())
     }
 
@@ -1015,6 +1253,22 @@ mod tests {
         .unwrap();
         assert_eq!(change_details.value as u64, 20 * MOB - Mob::MINIMUM_FEE);
 
+        // The change confirmation number is recorded at submission, like the
+        // payload's.
+        assert_eq!(tx_proposal.change_txos.len(), 1);
+        assert_eq!(
+            change_details.confirmation,
+            Some(mc_util_serial::encode(
+                &tx_proposal.change_txos[0].confirmation_number
+            ))
+        );
+        assert_eq!(
+            output_details.confirmation,
+            Some(mc_util_serial::encode(
+                &tx_proposal.payload_txos[0].confirmation_number
+            ))
+        );
+
         // Note, this will still be marked as not change until the txo
         // appears on the ledger and the account syncs.
         // change becomes unspent once scanned.
@@ -1166,6 +1420,74 @@ mod tests {
         assert_eq!(associated.change.len(), 1);
     }
 
+    #[async_test_with_logger]
+    async fn test_log_submitted_with_configured_fee(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let account_key = random_account_with_seed_values(
+            &wallet_db,
+            &mut ledger_db,
+            &[70 * MOB],
+            &mut rng,
+            &logger,
+        );
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let account = Account::get(&AccountID::from(&account_key), conn).unwrap();
+
+        // Pay more than the minimum fee, so the logged fee can only have come
+        // from the builder
+        let fee = 4 * Mob::MINIMUM_FEE;
+        let (recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+        builder
+            .add_recipient(recipient.clone(), 50 * MOB, Mob::ID)
+            .unwrap();
+        builder.set_fee(fee, Mob::ID).unwrap();
+        builder.set_tombstone(0).unwrap();
+        builder.select_txos(conn, None).unwrap();
+        let unsigned_tx_proposal = builder
+            .build(
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                conn,
+            )
+            .unwrap();
+        let tx_proposal = unsigned_tx_proposal.sign(&account).await.unwrap();
+
+        let tx_log = TransactionLog::log_submitted(
+            &tx_proposal,
+            ledger_db.num_blocks().unwrap(),
+            "".to_string(),
+            &AccountID::from(&account_key).to_string(),
+            conn,
+        )
+        .unwrap();
+
+        assert_eq!(tx_log.fee_value as u64, fee);
+        assert_eq!(tx_log.fee_token_id as u64, *Mob::ID);
+        assert_eq!(tx_log.fee_amount(), Amount::new(fee, Mob::ID));
+
+        // The fee is what the inputs pay beyond the outputs and change
+        let associated = tx_log.get_associated_txos(conn).unwrap();
+        let input_value: u64 = associated.inputs.iter().map(|txo| txo.value as u64).sum();
+        let output_value: u64 = associated
+            .outputs
+            .iter()
+            .chain(associated.change.iter())
+            .map(|(txo, _)| txo.value as u64)
+            .sum();
+        assert_eq!(input_value - output_value, fee);
+    }
+
     #[async_test_with_logger]
     async fn test_delete_transaction_logs_for_account(logger: Logger) {
         use crate::db::schema::{
@@ -1666,6 +1988,10 @@ mod tests {
             comment: "".to_string(),
             tx: vec![],
             failed: false,
+            created_at: tx_log.created_at,
+            updated_at: tx_log.updated_at,
+            idempotency_key: None,
+            finalized_timestamp: None,
         };
 
         assert_eq!(tx_log, expected_tx_log);
@@ -1699,6 +2025,10 @@ mod tests {
             comment: "".to_string(),
             tx: tx_bytes.clone(),
             failed: false,
+            created_at: tx_log.created_at,
+            updated_at: tx_log.updated_at,
+            idempotency_key: None,
+            finalized_timestamp: None,
         };
 
         assert_eq!(tx_log, expected_tx_log);
@@ -1726,6 +2056,10 @@ mod tests {
             comment: "".to_string(),
             tx: tx_bytes,
             failed: false,
+            created_at: tx_log.created_at,
+            updated_at: tx_log.updated_at,
+            idempotency_key: None,
+            finalized_timestamp: None,
         };
         assert_eq!(tx_log, expected_tx_log);
         assert_eq!(tx_log.value_for_token_id(Mob::ID, conn).unwrap(), 50 * MOB);
@@ -1906,6 +2240,10 @@ mod tests {
             comment: "".to_string(),
             tx: vec![],
             failed: false,
+            created_at: tx_log.created_at,
+            updated_at: tx_log.updated_at,
+            idempotency_key: None,
+            finalized_timestamp: None,
         };
 
         assert_eq!(tx_log, expected_tx_log);
@@ -1939,6 +2277,10 @@ mod tests {
             comment: "first change".to_string(),
             tx: tx_bytes.clone(),
             failed: false,
+            created_at: tx_log.created_at,
+            updated_at: tx_log.updated_at,
+            idempotency_key: None,
+            finalized_timestamp: None,
         };
 
         assert_eq!(tx_log, expected_tx_log);
@@ -1966,6 +2308,10 @@ mod tests {
             comment: "second change".to_string(),
             tx: tx_bytes,
             failed: false,
+            created_at: tx_log.created_at,
+            updated_at: tx_log.updated_at,
+            idempotency_key: None,
+            finalized_timestamp: None,
         };
 
         assert_eq!(tx_log.tx, expected_tx_log.tx);
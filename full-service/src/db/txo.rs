@@ -6,13 +6,14 @@ use diesel::{
     dsl::{count, exists, not},
     prelude::*,
 };
-use mc_account_keys::{AccountKey, PublicAddress};
+use mc_account_keys::{AccountKey, PublicAddress, ViewAccountKey};
 use mc_common::{logger::global_log, HashMap};
 use mc_crypto_digestible::{Digestible, MerlinTranscript};
-use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPublic};
+use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPrivate, RistrettoPublic};
 use mc_ledger_db::{Ledger, LedgerDB};
 use mc_transaction_core::{
     constants::MAX_INPUTS,
+    onetime_keys::{recover_onetime_private_key, recover_public_subaddress_spend_key},
     ring_signature::KeyImage,
     tx::{TxOut, TxOutMembershipProof},
     Amount, MemoPayload, TokenId,
@@ -20,8 +21,8 @@ use mc_transaction_core::{
 use mc_transaction_extra::{
     AuthenticatedSenderMemo, AuthenticatedSenderWithPaymentIntentIdMemo,
     AuthenticatedSenderWithPaymentRequestIdMemo, DestinationMemo,
-    DestinationWithPaymentIntentIdMemo, DestinationWithPaymentRequestIdMemo, MemoType,
-    RegisteredMemoType, TxOutConfirmationNumber, UnusedMemo,
+    DestinationWithPaymentIntentIdMemo, DestinationWithPaymentRequestIdMemo, MemoDecodingError,
+    MemoType, RegisteredMemoType, TxOutConfirmationNumber, UnusedMemo,
 };
 use mc_util_serial::Message;
 use std::{convert::TryFrom, fmt, str::FromStr};
@@ -36,12 +37,18 @@ use crate::{
             NewDestinationMemo, NewTransactionOutputTxo, NewTxo, TransactionOutputTxo, Txo,
         },
         transaction_log::TransactionId,
-        Conn, WalletDbError,
+        unix_timestamp, Conn, WalletDbError,
     },
     service::models::tx_proposal::OutputTxo,
     util::b58::b58_encode_public_address,
 };
 
+/// How long, in seconds, the inputs of a built transaction stay reserved
+/// before they can be selected for another transaction. Submitting the
+/// transaction marks the inputs pending, so this only needs to cover the time
+/// between building and submitting.
+pub const TXO_RESERVATION_SECONDS: i64 = 120;
+
 #[derive(Debug, PartialEq)]
 pub enum TxoStatus {
     // The txo has been created as part of build-transaction, but its associated transaction is
@@ -150,6 +157,7 @@ pub struct TxoInfo {
     pub txo: Txo,
     pub memo: TxoMemo,
     pub status: TxoStatus,
+    pub spent_in_transaction_id: Option<String>,
 }
 
 #[rustfmt::skip]
@@ -220,6 +228,8 @@ pub trait TxoModel {
     ///| `public_key`         | The per output tx public key                                                                                    |                 |
     ///| `e_fog_hint`         | The encrypted fog hint for the fog ingest server.                                                               |                 |
     ///| `shared_secret`      | A cryptographic key shared between the sender and recipient that is used to decrypt the TxOut's amount and memo |                 |
+    ///| `memo_type`          | The type bytes of the decrypted memo                                                                            |                 |
+    ///| `memo_payload`       | The decrypted memo payload bytes                                                                                |                 |
     ///| `conn`               | An reference to the pool connection of wallet database                                                          |                 |
     ///
     /// # Returns
@@ -237,6 +247,7 @@ pub trait TxoModel {
         e_fog_hint: &[u8],
         shared_secret: Option<&[u8]>,
         memo_type: Option<i32>,
+        memo_payload: Option<&[u8]>,
         conn: Conn,
     ) -> Result<(), WalletDbError>;
 
@@ -258,6 +269,63 @@ pub trait TxoModel {
         conn: Conn,
     ) -> Result<(), WalletDbError>;
 
+    /// Reserve Txos for a transaction that has been built but not yet submitted,
+    /// so that they are not selected as inputs for another transaction.
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name      | Purpose                                                | Notes                                             |
+    ///|-----------|--------------------------------------------------------|---------------------------------------------------|
+    ///| `txo_ids` | The ids of the Txos to reserve                         | Released after `TXO_RESERVATION_SECONDS` seconds. |
+    ///| `conn`    | An reference to the pool connection of wallet database |                                                   |
+    ///
+    /// # Returns
+    /// * unit
+    fn reserve(txo_ids: &[String], conn: Conn) -> Result<(), WalletDbError>;
+
+    /// Release the reservation on Txos, so that they can be selected again.
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name      | Purpose                                                | Notes |
+    ///|-----------|--------------------------------------------------------|-------|
+    ///| `txo_ids` | The ids of the Txos to release                         |       |
+    ///| `conn`    | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns
+    /// * unit
+    fn release_reservations(txo_ids: &[String], conn: Conn) -> Result<(), WalletDbError>;
+
+    /// List the block indices of received Txos whose block timestamp is not yet known.
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name   | Purpose                                                | Notes |
+    ///|--------|--------------------------------------------------------|-------|
+    ///| `conn` | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns
+    /// * Vector of block indices
+    fn list_received_block_indices_without_timestamp(conn: Conn) -> Result<Vec<u64>, WalletDbError>;
+
+    /// Set the block timestamp of all Txos received in a block.
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name                   | Purpose                                                | Notes                |
+    ///|------------------------|--------------------------------------------------------|----------------------|
+    ///| `received_block_index` | The block the Txos were received in                    |                      |
+    ///| `timestamp`            | The timestamp of that block                            | Seconds since epoch. |
+    ///| `conn`                 | An reference to the pool connection of wallet database |                      |
+    ///
+    /// # Returns
+    /// * unit
+    fn update_received_timestamp(
+        received_block_index: u64,
+        timestamp: u64,
+        conn: Conn,
+    ) -> Result<(), WalletDbError>;
+
     /// Update a Txo's key image and optionally update its status to spent
     /// 
     /// # Arguments
@@ -295,6 +363,8 @@ pub trait TxoModel {
     ///| `status`                   | The status of Txos to filter on                               | Option in `Created`, `Orphaned`, `Pending`, `Secreted`, `Spent`, `Unspent`, `Unverified` |
     ///| `min_received_block_index` | The minimum block index to query for received txos, inclusive |                                                                                          |
     ///| `max_received_block_index` | The maximum block index to query for received txos, inclusive |                                                                                          |
+    ///| `min_value`                | The minimum value of txos to query for, inclusive             |                                                                                          |
+    ///| `max_value`                | The maximum value of txos to query for, inclusive             |                                                                                          |
     ///| `offset`                   | The pagination offset. Results start at the offset index.     | Optional. Defaults to 0.                                                                 |
     ///| `limit`                    | Limit for the number of results.                              | Optional.                                                                                |
     ///| `token_id`                 | The id of a supported type of token to filter on              |                                                                                          |
@@ -302,10 +372,13 @@ pub trait TxoModel {
     /// 
     /// # Returns
     /// * Vector of TxoOut
+    #[allow(clippy::too_many_arguments)]
     fn list(
         status: Option<TxoStatus>,
         min_received_block_index: Option<u64>,
         max_received_block_index: Option<u64>,
+        min_value: Option<u64>,
+        max_value: Option<u64>,
         offset: Option<u64>,
         limit: Option<u64>,
         token_id: Option<u64>,
@@ -322,6 +395,8 @@ pub trait TxoModel {
     ///| `status`                   | The status of Txos to filter on                               | Option in `Created`, `Orphaned`, `Pending`, `Secreted`, `Spent`, `Unspent`, `Unverified` |
     ///| `min_received_block_index` | The minimum block index to query for received txos, inclusive |                                                                                          |
     ///| `max_received_block_index` | The maximum block index to query for received txos, inclusive |                                                                                          |
+    ///| `min_value`                | The minimum value of txos to query for, inclusive             |                                                                                          |
+    ///| `max_value`                | The maximum value of txos to query for, inclusive             |                                                                                          |
     ///| `offset`                   | The pagination offset. Results start at the offset index.     | Optional. Defaults to 0.                                                                 |
     ///| `limit`                    | Limit for the number of results.                              | Optional.                                                                                |
     ///| `token_id`                 | The id of a supported type of token to filter on              |                                                                                          |
@@ -335,6 +410,8 @@ pub trait TxoModel {
         status: Option<TxoStatus>,
         min_received_block_index: Option<u64>,
         max_received_block_index: Option<u64>,
+        min_value: Option<u64>,
+        max_value: Option<u64>,
         offset: Option<u64>,
         limit: Option<u64>,
         token_id: Option<u64>,
@@ -351,6 +428,8 @@ pub trait TxoModel {
     ///| `status`                   | The status of Txos to filter on                               | Option in `Created`, `Orphaned`, `Pending`, `Secreted`, `Spent`, `Unspent`, `Unverified` |
     ///| `min_received_block_index` | The minimum block index to query for received txos, inclusive |                                                                                          |
     ///| `max_received_block_index` | The maximum block index to query for received txos, inclusive |                                                                                          |
+    ///| `min_value`                | The minimum value of txos to query for, inclusive             |                                                                                          |
+    ///| `max_value`                | The maximum value of txos to query for, inclusive             |                                                                                          |
     ///| `offset`                   | The pagination offset. Results start at the offset index.     | Optional. Defaults to 0.                                                                 |
     ///| `limit`                    | Limit for the number of results.                              | Optional.                                                                                |
     ///| `token_id`                 | The id of a supported type of token to filter on              |                                                                                          |
@@ -364,6 +443,8 @@ pub trait TxoModel {
         status: Option<TxoStatus>,
         min_received_block_index: Option<u64>,
         max_received_block_index: Option<u64>,
+        min_value: Option<u64>,
+        max_value: Option<u64>,
         offset: Option<u64>,
         limit: Option<u64>,
         token_id: Option<u64>,
@@ -399,6 +480,8 @@ pub trait TxoModel {
     ///| `token_id`                 | The id of a supported type of token to filter on              |                                                                                          |
     ///| `min_received_block_index` | The minimum block index to query for received txos, inclusive |                                                                                          |
     ///| `max_received_block_index` | The maximum block index to query for received txos, inclusive |                                                                                          |
+    ///| `min_value`                | The minimum value of txos to query for, inclusive             |                                                                                          |
+    ///| `max_value`                | The maximum value of txos to query for, inclusive             |                                                                                          |
     ///| `offset`                   | The pagination offset. Results start at the offset index.     | Optional. Defaults to 0.                                                                 |
     ///| `limit`                    | Limit for the number of results.                              | Optional.                                                                                |
     ///| `conn`                     | An reference to the pool connection of wallet database        |                                                                                          |
@@ -412,6 +495,8 @@ pub trait TxoModel {
         token_id: Option<u64>,
         min_received_block_index: Option<u64>,
         max_received_block_index: Option<u64>,
+        min_value: Option<u64>,
+        max_value: Option<u64>,
         offset: Option<u64>,
         limit: Option<u64>,
         conn: Conn,
@@ -428,6 +513,8 @@ pub trait TxoModel {
     ///| `token_id`                 | The id of a supported type of token to filter on              |                                      |
     ///| `min_received_block_index` | The minimum block index to query for received txos, inclusive |                                      |
     ///| `max_received_block_index` | The maximum block index to query for received txos, inclusive |                                      |
+    ///| `min_value`                | The minimum value of txos to query for, inclusive             |                                      |
+    ///| `max_value`                | The maximum value of txos to query for, inclusive             |                                      |
     ///| `offset`                   | The pagination offset. Results start at the offset index.     | Optional. Defaults to 0.             |
     ///| `limit`                    | Limit for the number of results.                              | Optional.                            |
     ///| `conn`                     | An reference to the pool connection of wallet database        |                                      |
@@ -441,6 +528,8 @@ pub trait TxoModel {
         token_id: Option<u64>,
         min_received_block_index: Option<u64>,
         max_received_block_index: Option<u64>,
+        min_value: Option<u64>,
+        max_value: Option<u64>,
         offset: Option<u64>,
         limit: Option<u64>,
         conn: Conn,
@@ -456,17 +545,22 @@ pub trait TxoModel {
     ///| `token_id`                 | The id of a supported type of token to filter on              |                                      |
     ///| `min_received_block_index` | The minimum block index to query for received txos, inclusive |                                      |
     ///| `max_received_block_index` | The maximum block index to query for received txos, inclusive |                                      |
+    ///| `min_value`                | The minimum value of txos to query for, inclusive             |                                      |
+    ///| `max_value`                | The maximum value of txos to query for, inclusive             |                                      |
     ///| `offset`                   | The pagination offset. Results start at the offset index.     | Optional. Defaults to 0.             |
     ///| `limit`                    | Limit for the number of results.                              | Optional.                            |
     ///| `conn`                     | An reference to the pool connection of wallet database        |                                      |
     /// 
     /// # Returns
     /// * Vector of TxoOut
+    #[allow(clippy::too_many_arguments)]
     fn list_orphaned(
         account_id_hex: Option<&str>,
         token_id: Option<u64>,
         min_received_block_index: Option<u64>,
         max_received_block_index: Option<u64>,
+        min_value: Option<u64>,
+        max_value: Option<u64>,
         offset: Option<u64>,
         limit: Option<u64>,
         conn: Conn,
@@ -483,6 +577,8 @@ pub trait TxoModel {
     ///| `token_id`                 | The id of a supported type of token to filter on              |                                      |
     ///| `min_received_block_index` | The minimum block index to query for received txos, inclusive |                                      |
     ///| `max_received_block_index` | The maximum block index to query for received txos, inclusive |                                      |
+    ///| `min_value`                | The minimum value of txos to query for, inclusive             |                                      |
+    ///| `max_value`                | The maximum value of txos to query for, inclusive             |                                      |
     ///| `offset`                   | The pagination offset. Results start at the offset index.     | Optional. Defaults to 0.             |
     ///| `limit`                    | Limit for the number of results.                              | Optional.                            |
     ///| `conn`                     | An reference to the pool connection of wallet database        |                                      |
@@ -496,6 +592,8 @@ pub trait TxoModel {
         token_id: Option<u64>,
         min_received_block_index: Option<u64>,
         max_received_block_index: Option<u64>,
+        min_value: Option<u64>,
+        max_value: Option<u64>,
         offset: Option<u64>,
         limit: Option<u64>,
         conn: Conn,
@@ -512,6 +610,8 @@ pub trait TxoModel {
     ///| `token_id`                 | The id of a supported type of token to filter on              |                                      |
     ///| `min_received_block_index` | The minimum block index to query for received txos, inclusive |                                      |
     ///| `max_received_block_index` | The maximum block index to query for received txos, inclusive |                                      |
+    ///| `min_value`                | The minimum value of txos to query for, inclusive             |                                      |
+    ///| `max_value`                | The maximum value of txos to query for, inclusive             |                                      |
     ///| `offset`                   | The pagination offset. Results start at the offset index.     | Optional. Defaults to 0.             |
     ///| `limit`                    | Limit for the number of results.                              | Optional.                            |
     ///| `conn`                     | An reference to the pool connection of wallet database        |                                      |
@@ -525,6 +625,8 @@ pub trait TxoModel {
         token_id: Option<u64>,
         min_received_block_index: Option<u64>,
         max_received_block_index: Option<u64>,
+        min_value: Option<u64>,
+        max_value: Option<u64>,
         offset: Option<u64>,
         limit: Option<u64>,
         conn: Conn,
@@ -635,6 +737,7 @@ pub trait TxoModel {
     ///| `target_value`        | The value used to filter spendable Txos on its value       |                                     |
     ///| `max_spendable_value` | The upper limit for the spendable TxOut value to filter on |                                     |
     ///| `assigned_subaddress_b58`  | The subaddress where the spendable Txos can be sourced from |                                      |
+    ///| `exclude_txo_ids`     | Txos which must not be selected                            |                                     |
     ///| `token_id`            | The id of a supported type of token to filter on           |                                     |
     ///| `default_token_fee`   | The default transaction fee in Mob network                 |                                     |
     ///| `conn`                | An reference to the pool connection of wallet database     |                                     |
@@ -646,6 +749,7 @@ pub trait TxoModel {
         target_value: u128,
         max_spendable_value: Option<u64>,
         assigned_subaddress_b58: Option<&str>,
+        exclude_txo_ids: &[String],
         token_id: u64,
         default_token_fee: u64,
         conn: Conn,
@@ -684,6 +788,22 @@ pub trait TxoModel {
     /// * unit
     fn scrub_account(account_id_hex: &str, conn: Conn) -> Result<(), WalletDbError>;
 
+    /// Get the highest block index at which any Txo of an account was received or spent.
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name             | Purpose                                                | Notes |
+    ///|------------------|--------------------------------------------------------|-------|
+    ///| `account_id_hex` | The account id whose Txos to look at                   |       |
+    ///| `conn`           | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns
+    /// * The highest block index, or None if the account has no Txos in the ledger
+    fn max_block_index_for_account(
+        account_id_hex: &str,
+        conn: Conn,
+    ) -> Result<Option<u64>, WalletDbError>;
+
     /// Delete txos which are not referenced by any account or transaction.
     /// 
     /// # Arguments
@@ -696,6 +816,59 @@ pub trait TxoModel {
     /// * unit
     fn delete_unreferenced(conn: Conn) -> Result<(), WalletDbError>;
 
+    /// Re-attribute orphaned Txos for an account to any assigned subaddress that now matches
+    /// them, deriving the key image and spent block index where the account is able to.
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name             | Purpose                                                   | Notes                               |
+    ///|------------------|-----------------------------------------------------------|-------------------------------------|
+    ///| `account_id_hex` | The account id whose orphaned Txos should be repaired     | Account must exist in the database. |
+    ///| `ledger_db`      | A reference to the instance of the whole ledger database. | Used to find the spent block index. |
+    ///| `conn`           | An reference to the pool connection of wallet database    |                                     |
+    ///
+    /// # Returns
+    /// * Vector of the ids of the Txos that were re-attributed
+    fn reattribute_orphaned(
+        account_id_hex: &str,
+        ledger_db: &LedgerDB,
+        conn: Conn,
+    ) -> Result<Vec<String>, WalletDbError>;
+
+    /// Delete the Txos minted by an account's transactions that never landed and never can,
+    /// because one of their inputs has since been spent by another transaction. The
+    /// transaction logs themselves are kept.
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name             | Purpose                                                | Notes                               |
+    ///|------------------|--------------------------------------------------------|-------------------------------------|
+    ///| `account_id_hex` | The account id whose transactions should be checked    | Account must exist in the database. |
+    ///| `conn`           | An reference to the pool connection of wallet database |                                     |
+    ///
+    /// # Returns
+    /// * Vector of the ids of the Txos that were deleted
+    fn delete_superseded_outputs(
+        account_id_hex: &str,
+        conn: Conn,
+    ) -> Result<Vec<String>, WalletDbError>;
+
+    /// Recover the spend public key of the subaddress this Txo was sent to, whether or not that
+    /// subaddress has been assigned yet.
+    ///
+    /// # Arguments
+    ///
+    ///| Name               | Purpose                                        | Notes |
+    ///|--------------------|------------------------------------------------|-------|
+    ///| `view_private_key` | The view private key of the receiving account. |       |
+    ///
+    /// # Returns
+    /// * RistrettoPublic
+    fn recover_subaddress_spend_public_key(
+        &self,
+        view_private_key: &RistrettoPrivate,
+    ) -> Result<RistrettoPublic, WalletDbError>;
+
     /// Get status for current TxOut
     /// 
     /// # Arguments
@@ -711,6 +884,19 @@ pub trait TxoModel {
     /// Get memo for current TxOut
     fn memo(&self, conn: Conn) -> Result<TxoMemo, WalletDbError>;
 
+    /// Get the id of the transaction log that spent this Txo, if it has been
+    /// spent by a transaction this wallet built.
+    ///
+    /// # Arguments
+    /// 
+    ///| Name   | Purpose                                                | Notes |
+    ///|--------|--------------------------------------------------------|-------|
+    ///| `conn` | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns
+    /// * Option(transaction_log_id)
+    fn spent_in_transaction_log_id(&self, conn: Conn) -> Result<Option<String>, WalletDbError>;
+
     /// Get the membership proof from ledger DB for current TxOut
     /// 
     /// # Arguments
@@ -775,6 +961,7 @@ impl TxoModel for Txo {
             None => UnusedMemo.into(),
         };
         let memo_type = Some(two_bytes_to_i32(*memo_payload.get_memo_type()));
+        let memo_payload_bytes = memo_payload_to_bytes(&memo_payload);
 
         // Ensure that the TXO is added to the database.
         match Txo::get(&txo_id.to_string(), conn) {
@@ -792,6 +979,7 @@ impl TxoModel for Txo {
                     &mc_util_serial::encode(&txo.e_fog_hint),
                     Some(&shared_secret_vec),
                     memo_type,
+                    Some(&memo_payload_bytes),
                     conn,
                 )?;
             }
@@ -799,6 +987,7 @@ impl TxoModel for Txo {
             // If we don't already have this TXO, create a new entry
             Err(WalletDbError::TxoNotFound(_)) => {
                 let key_image_bytes = key_image.map(|k| mc_util_serial::encode(&k));
+                let now = unix_timestamp();
                 let new_txo = NewTxo {
                     id: &txo_id.to_string(),
                     value: amount.value as i64,
@@ -814,6 +1003,9 @@ impl TxoModel for Txo {
                     account_id: Some(account_id_hex.to_string()),
                     shared_secret: Some(&shared_secret_vec),
                     memo_type,
+                    memo_payload: Some(&memo_payload_bytes),
+                    created_at: now,
+                    updated_at: now,
                 };
 
                 diesel::insert_into(crate::db::schema::txos::table)
@@ -859,7 +1051,9 @@ impl TxoModel for Txo {
         let shared_secret_bytes = output_txo
             .shared_secret
             .map(|shared_secret| shared_secret.to_bytes().to_vec());
+        let memo_payload_bytes = memo_payload.as_ref().map(memo_payload_to_bytes);
 
+        let now = unix_timestamp();
         let new_txo = NewTxo {
             id: &txo_id.to_string(),
             account_id: None,
@@ -875,6 +1069,9 @@ impl TxoModel for Txo {
             confirmation: Some(&encoded_confirmation),
             shared_secret: shared_secret_bytes.as_deref(),
             memo_type,
+            memo_payload: memo_payload_bytes.as_deref(),
+            created_at: now,
+            updated_at: now,
         };
 
         diesel::insert_into(txos::table)
@@ -914,6 +1111,7 @@ impl TxoModel for Txo {
         e_fog_hint: &[u8],
         shared_secret: Option<&[u8]>,
         memo_type: Option<i32>,
+        memo_payload: Option<&[u8]>,
         conn: Conn,
     ) -> Result<(), WalletDbError> {
         use crate::db::schema::txos;
@@ -933,6 +1131,8 @@ impl TxoModel for Txo {
                 txos::e_fog_hint.eq(e_fog_hint),
                 txos::shared_secret.eq(shared_secret),
                 txos::memo_type.eq(memo_type),
+                txos::memo_payload.eq(memo_payload),
+                txos::updated_at.eq(unix_timestamp()),
             ))
             .execute(conn)?;
         Ok(())
@@ -946,11 +1146,75 @@ impl TxoModel for Txo {
         use crate::db::schema::txos;
 
         diesel::update(txos::table.filter(txos::id.eq(txo_id_hex)))
-            .set((txos::spent_block_index.eq(Some(spent_block_index as i64)),))
+            .set((
+                txos::spent_block_index.eq(Some(spent_block_index as i64)),
+                txos::updated_at.eq(unix_timestamp()),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    fn reserve(txo_ids: &[String], conn: Conn) -> Result<(), WalletDbError> {
+        use crate::db::schema::txos;
+
+        diesel::update(txos::table.filter(txos::id.eq_any(txo_ids)))
+            .set((
+                txos::reserved_until.eq(Some(unix_timestamp() + TXO_RESERVATION_SECONDS)),
+                txos::updated_at.eq(unix_timestamp()),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    fn release_reservations(txo_ids: &[String], conn: Conn) -> Result<(), WalletDbError> {
+        use crate::db::schema::txos;
+
+        diesel::update(txos::table.filter(txos::id.eq_any(txo_ids)))
+            .set((
+                txos::reserved_until.eq(None::<i64>),
+                txos::updated_at.eq(unix_timestamp()),
+            ))
             .execute(conn)?;
         Ok(())
     }
 
+    fn list_received_block_indices_without_timestamp(
+        conn: Conn,
+    ) -> Result<Vec<u64>, WalletDbError> {
+        use crate::db::schema::txos;
+
+        let block_indices: Vec<Option<i64>> = txos::table
+            .filter(txos::received_block_index.is_not_null())
+            .filter(txos::received_timestamp.is_null())
+            .select(txos::received_block_index)
+            .distinct()
+            .load(conn)?;
+
+        Ok(block_indices
+            .into_iter()
+            .flatten()
+            .map(|block_index| block_index as u64)
+            .collect())
+    }
+
+    fn update_received_timestamp(
+        received_block_index: u64,
+        timestamp: u64,
+        conn: Conn,
+    ) -> Result<(), WalletDbError> {
+        use crate::db::schema::txos;
+
+        diesel::update(
+            txos::table.filter(txos::received_block_index.eq(received_block_index as i64)),
+        )
+        .set((
+            txos::received_timestamp.eq(Some(timestamp as i64)),
+            txos::updated_at.eq(unix_timestamp()),
+        ))
+        .execute(conn)?;
+        Ok(())
+    }
+
     fn update_key_image(
         txo_id_hex: &str,
         key_image: &KeyImage,
@@ -965,6 +1229,7 @@ impl TxoModel for Txo {
             .set((
                 txos::key_image.eq(Some(encoded_key_image)),
                 txos::spent_block_index.eq(spent_block_index.map(|i| i as i64)),
+                txos::updated_at.eq(unix_timestamp()),
             ))
             .execute(conn)?;
 
@@ -975,7 +1240,10 @@ impl TxoModel for Txo {
         use crate::db::schema::txos;
 
         diesel::update(self)
-            .set(txos::is_synced_to_t3.eq(is_synced))
+            .set((
+                txos::is_synced_to_t3.eq(is_synced),
+                txos::updated_at.eq(unix_timestamp()),
+            ))
             .execute(conn)?;
 
         Ok(())
@@ -1014,6 +1282,8 @@ impl TxoModel for Txo {
         status: Option<TxoStatus>,
         min_received_block_index: Option<u64>,
         max_received_block_index: Option<u64>,
+        min_value: Option<u64>,
+        max_value: Option<u64>,
         offset: Option<u64>,
         limit: Option<u64>,
         token_id: Option<u64>,
@@ -1030,6 +1300,8 @@ impl TxoModel for Txo {
                         token_id,
                         min_received_block_index,
                         max_received_block_index,
+                        min_value,
+                        max_value,
                         offset,
                         limit,
                         conn,
@@ -1042,6 +1314,8 @@ impl TxoModel for Txo {
                         token_id,
                         min_received_block_index,
                         max_received_block_index,
+                        min_value,
+                        max_value,
                         offset,
                         limit,
                         conn,
@@ -1054,6 +1328,8 @@ impl TxoModel for Txo {
                         token_id,
                         min_received_block_index,
                         max_received_block_index,
+                        min_value,
+                        max_value,
                         offset,
                         limit,
                         conn,
@@ -1066,6 +1342,8 @@ impl TxoModel for Txo {
                         token_id,
                         min_received_block_index,
                         max_received_block_index,
+                        min_value,
+                        max_value,
                         offset,
                         limit,
                         conn,
@@ -1077,6 +1355,8 @@ impl TxoModel for Txo {
                         token_id,
                         min_received_block_index,
                         max_received_block_index,
+                        min_value,
+                        max_value,
                         offset,
                         limit,
                         conn,
@@ -1109,6 +1389,15 @@ impl TxoModel for Txo {
             query = query.filter(txos::received_block_index.le(max_received_block_index as i64));
         }
 
+        if min_value.is_some() || max_value.is_some() {
+            let [low, high] = stored_value_ranges(min_value, max_value);
+            query = query.filter(
+                txos::value
+                    .between(low.0, low.1)
+                    .or(txos::value.between(high.0, high.1)),
+            );
+        }
+
         Ok(query.order(txos::received_block_index.desc()).load(conn)?)
     }
 
@@ -1117,6 +1406,8 @@ impl TxoModel for Txo {
         status: Option<TxoStatus>,
         min_received_block_index: Option<u64>,
         max_received_block_index: Option<u64>,
+        min_value: Option<u64>,
+        max_value: Option<u64>,
         offset: Option<u64>,
         limit: Option<u64>,
         token_id: Option<u64>,
@@ -1133,6 +1424,8 @@ impl TxoModel for Txo {
                         token_id,
                         min_received_block_index,
                         max_received_block_index,
+                        min_value,
+                        max_value,
                         offset,
                         limit,
                         conn,
@@ -1145,6 +1438,8 @@ impl TxoModel for Txo {
                         token_id,
                         min_received_block_index,
                         max_received_block_index,
+                        min_value,
+                        max_value,
                         offset,
                         limit,
                         conn,
@@ -1157,6 +1452,8 @@ impl TxoModel for Txo {
                         token_id,
                         min_received_block_index,
                         max_received_block_index,
+                        min_value,
+                        max_value,
                         offset,
                         limit,
                         conn,
@@ -1169,6 +1466,8 @@ impl TxoModel for Txo {
                         token_id,
                         min_received_block_index,
                         max_received_block_index,
+                        min_value,
+                        max_value,
                         offset,
                         limit,
                         conn,
@@ -1180,6 +1479,8 @@ impl TxoModel for Txo {
                         token_id,
                         min_received_block_index,
                         max_received_block_index,
+                        min_value,
+                        max_value,
                         offset,
                         limit,
                         conn,
@@ -1214,6 +1515,15 @@ impl TxoModel for Txo {
             query = query.filter(txos::received_block_index.le(max_received_block_index as i64));
         }
 
+        if min_value.is_some() || max_value.is_some() {
+            let [low, high] = stored_value_ranges(min_value, max_value);
+            query = query.filter(
+                txos::value
+                    .between(low.0, low.1)
+                    .or(txos::value.between(high.0, high.1)),
+            );
+        }
+
         Ok(query.order(txos::received_block_index.desc()).load(conn)?)
     }
 
@@ -1222,6 +1532,8 @@ impl TxoModel for Txo {
         status: Option<TxoStatus>,
         min_received_block_index: Option<u64>,
         max_received_block_index: Option<u64>,
+        min_value: Option<u64>,
+        max_value: Option<u64>,
         offset: Option<u64>,
         limit: Option<u64>,
         token_id: Option<u64>,
@@ -1238,6 +1550,8 @@ impl TxoModel for Txo {
                         token_id,
                         min_received_block_index,
                         max_received_block_index,
+                        min_value,
+                        max_value,
                         offset,
                         limit,
                         conn,
@@ -1250,6 +1564,8 @@ impl TxoModel for Txo {
                         token_id,
                         min_received_block_index,
                         max_received_block_index,
+                        min_value,
+                        max_value,
                         offset,
                         limit,
                         conn,
@@ -1262,6 +1578,8 @@ impl TxoModel for Txo {
                         token_id,
                         min_received_block_index,
                         max_received_block_index,
+                        min_value,
+                        max_value,
                         offset,
                         limit,
                         conn,
@@ -1274,6 +1592,8 @@ impl TxoModel for Txo {
                         token_id,
                         min_received_block_index,
                         max_received_block_index,
+                        min_value,
+                        max_value,
                         offset,
                         limit,
                         conn,
@@ -1311,6 +1631,15 @@ impl TxoModel for Txo {
             query = query.filter(txos::received_block_index.le(max_received_block_index as i64));
         }
 
+        if min_value.is_some() || max_value.is_some() {
+            let [low, high] = stored_value_ranges(min_value, max_value);
+            query = query.filter(
+                txos::value
+                    .between(low.0, low.1)
+                    .or(txos::value.between(high.0, high.1)),
+            );
+        }
+
         Ok(query.order(txos::received_block_index.desc()).load(conn)?)
     }
 
@@ -1320,6 +1649,8 @@ impl TxoModel for Txo {
         token_id: Option<u64>,
         min_received_block_index: Option<u64>,
         max_received_block_index: Option<u64>,
+        min_value: Option<u64>,
+        max_value: Option<u64>,
         offset: Option<u64>,
         limit: Option<u64>,
         conn: Conn,
@@ -1387,6 +1718,15 @@ impl TxoModel for Txo {
             query = query.filter(txos::received_block_index.le(max_received_block_index as i64));
         }
 
+        if min_value.is_some() || max_value.is_some() {
+            let [low, high] = stored_value_ranges(min_value, max_value);
+            query = query.filter(
+                txos::value
+                    .between(low.0, low.1)
+                    .or(txos::value.between(high.0, high.1)),
+            );
+        }
+
         Ok(query
             .select(txos::all_columns)
             .distinct()
@@ -1400,6 +1740,8 @@ impl TxoModel for Txo {
         token_id: Option<u64>,
         min_received_block_index: Option<u64>,
         max_received_block_index: Option<u64>,
+        min_value: Option<u64>,
+        max_value: Option<u64>,
         offset: Option<u64>,
         limit: Option<u64>,
         conn: Conn,
@@ -1455,6 +1797,15 @@ impl TxoModel for Txo {
             query = query.filter(txos::received_block_index.le(max_received_block_index as i64));
         }
 
+        if min_value.is_some() || max_value.is_some() {
+            let [low, high] = stored_value_ranges(min_value, max_value);
+            query = query.filter(
+                txos::value
+                    .between(low.0, low.1)
+                    .or(txos::value.between(high.0, high.1)),
+            );
+        }
+
         Ok(query
             .distinct()
             .order(txos::received_block_index.desc())
@@ -1595,6 +1946,8 @@ impl TxoModel for Txo {
         token_id: Option<u64>,
         min_received_block_index: Option<u64>,
         max_received_block_index: Option<u64>,
+        min_value: Option<u64>,
+        max_value: Option<u64>,
         offset: Option<u64>,
         limit: Option<u64>,
         conn: Conn,
@@ -1632,6 +1985,15 @@ impl TxoModel for Txo {
             query = query.filter(txos::received_block_index.le(max_received_block_index as i64));
         }
 
+        if min_value.is_some() || max_value.is_some() {
+            let [low, high] = stored_value_ranges(min_value, max_value);
+            query = query.filter(
+                txos::value
+                    .between(low.0, low.1)
+                    .or(txos::value.between(high.0, high.1)),
+            );
+        }
+
         Ok(query.order(txos::received_block_index.desc()).load(conn)?)
     }
 
@@ -1640,6 +2002,8 @@ impl TxoModel for Txo {
         token_id: Option<u64>,
         min_received_block_index: Option<u64>,
         max_received_block_index: Option<u64>,
+        min_value: Option<u64>,
+        max_value: Option<u64>,
         offset: Option<u64>,
         limit: Option<u64>,
         conn: Conn,
@@ -1672,6 +2036,15 @@ impl TxoModel for Txo {
             query = query.filter(txos::received_block_index.le(max_received_block_index as i64));
         }
 
+        if min_value.is_some() || max_value.is_some() {
+            let [low, high] = stored_value_ranges(min_value, max_value);
+            query = query.filter(
+                txos::value
+                    .between(low.0, low.1)
+                    .or(txos::value.between(high.0, high.1)),
+            );
+        }
+
         Ok(query.order(txos::received_block_index.desc()).load(conn)?)
     }
 
@@ -1681,6 +2054,8 @@ impl TxoModel for Txo {
         token_id: Option<u64>,
         min_received_block_index: Option<u64>,
         max_received_block_index: Option<u64>,
+        min_value: Option<u64>,
+        max_value: Option<u64>,
         offset: Option<u64>,
         limit: Option<u64>,
         conn: Conn,
@@ -1731,6 +2106,15 @@ impl TxoModel for Txo {
             query = query.filter(txos::received_block_index.le(max_received_block_index as i64));
         }
 
+        if min_value.is_some() || max_value.is_some() {
+            let [low, high] = stored_value_ranges(min_value, max_value);
+            query = query.filter(
+                txos::value
+                    .between(low.0, low.1)
+                    .or(txos::value.between(high.0, high.1)),
+            );
+        }
+
         Ok(query
             .select(txos::all_columns)
             .distinct()
@@ -1789,74 +2173,16 @@ impl TxoModel for Txo {
         default_token_fee: u64,
         conn: Conn,
     ) -> Result<SpendableTxosResult, WalletDbError> {
-        use crate::db::schema::{transaction_input_txos, transaction_logs, txos};
-
-        let mut query = txos::table
-            .into_boxed()
-            .left_join(transaction_input_txos::table)
-            .left_join(
-                transaction_logs::table
-                    .on(transaction_logs::id.eq(transaction_input_txos::transaction_log_id)),
-            );
-
-        query = query
-            .filter(transaction_logs::id.is_null())
-            .or_filter(transaction_logs::failed.eq(true))
-            .or_filter(
-                transaction_logs::id
-                    .is_not_null()
-                    .and(transaction_logs::submitted_block_index.is_null()),
-            );
-
-        query = query
-            .filter(txos::received_block_index.is_not_null())
-            .filter(txos::spent_block_index.is_null())
-            .filter(txos::subaddress_index.is_not_null())
-            .filter(txos::token_id.eq(token_id as i64));
-
-        if let Some(subaddress_b58) = assigned_subaddress_b58 {
-            let subaddress = AssignedSubaddress::get(subaddress_b58, conn)?;
-            query = query
-                .filter(txos::subaddress_index.eq(subaddress.subaddress_index))
-                .filter(txos::account_id.eq(subaddress.account_id));
-        }
-
-        if let Some(account_id_hex) = account_id_hex {
-            query = query.filter(txos::account_id.eq(account_id_hex));
-        }
-
-        let mut spendable_txos = query
-            .select(txos::all_columns)
-            .distinct()
-            .load(conn)?
-            .drain(..)
-            .filter(|txo: &Txo| txo.value as u64 <= max_spendable_value.unwrap_or(u64::MAX))
-            .collect::<Vec<Txo>>();
-
-        spendable_txos.sort_by(|a: &Txo, b: &Txo| (b.value as u64).cmp(&(a.value as u64)));
-
-        // The maximum spendable is limited by the maximal number of inputs we can use.
-        // Since the txos are sorted by decreasing value, this is the maximum
-        // value we can possibly spend in one transaction.
-        // Note, u128::Max = 340_282_366_920_938_463_463_374_607_431_768_211_455, which
-        // is far beyond the total number of pMOB in the MobileCoin system
-        // (250_000_000_000_000_000_000)
-        let mut max_spendable_in_wallet: u128 = spendable_txos
-            .iter()
-            .take(MAX_INPUTS as usize)
-            .map(|utxo: &Txo| (utxo.value as u64) as u128)
-            .sum();
-
-        if max_spendable_in_wallet > default_token_fee as u128 {
-            max_spendable_in_wallet -= default_token_fee as u128;
-        } else {
-            max_spendable_in_wallet = 0;
-        }
-
-        Ok(SpendableTxosResult {
-            spendable_txos,
-            max_spendable_in_wallet,
-        })
+        list_spendable_txos(
+            account_id_hex,
+            max_spendable_value,
+            assigned_subaddress_b58,
+            &[],
+            token_id,
+            default_token_fee,
+            true,
+            conn,
+        )
     }
 
     fn select_spendable_txos_for_value(
@@ -1864,6 +2190,7 @@ impl TxoModel for Txo {
         target_value: u128,
         max_spendable_value: Option<u64>,
         assigned_subaddress_b58: Option<&str>,
+        exclude_txo_ids: &[String],
         token_id: u64,
         default_token_fee: u64,
         conn: Conn,
@@ -1871,12 +2198,14 @@ impl TxoModel for Txo {
         let SpendableTxosResult {
             mut spendable_txos,
             max_spendable_in_wallet,
-        } = Txo::list_spendable(
+        } = list_spendable_txos(
             Some(account_id_hex),
             max_spendable_value,
             assigned_subaddress_b58,
+            exclude_txo_ids,
             token_id,
             default_token_fee,
+            false,
             conn,
         )?;
 
@@ -1894,7 +2223,20 @@ impl TxoModel for Txo {
                 .sum();
 
             if total_unspent_value_in_wallet >= target_value + default_token_fee as u128 {
-                return Err(WalletDbError::InsufficientFundsFragmentedTxos);
+                // Count the inputs needed, taking the largest Txos first.
+                let mut needed_inputs = 0;
+                let mut needed_total: u128 = 0;
+                for utxo in spendable_txos.iter() {
+                    if needed_total >= target_value {
+                        break;
+                    }
+                    needed_total += (utxo.value as u64) as u128;
+                    needed_inputs += 1;
+                }
+                return Err(WalletDbError::InsufficientFundsFragmentedTxos(
+                    needed_inputs,
+                    MAX_INPUTS,
+                ));
             } else {
                 return Err(WalletDbError::InsufficientFundsUnderMaxSpendable(format!(
                     "Max spendable value in wallet: {max_spendable_in_wallet:?}, but target value: {target_value:?}"
@@ -1967,12 +2309,35 @@ impl TxoModel for Txo {
         let txos_received_by_account = txos::table.filter(txos::account_id.eq(account_id_hex));
 
         diesel::update(txos_received_by_account)
-            .set(txos::account_id.eq::<Option<String>>(None))
+            .set((
+                txos::account_id.eq::<Option<String>>(None),
+                txos::updated_at.eq(unix_timestamp()),
+            ))
             .execute(conn)?;
 
         Ok(())
     }
 
+    fn max_block_index_for_account(
+        account_id_hex: &str,
+        conn: Conn,
+    ) -> Result<Option<u64>, WalletDbError> {
+        use crate::db::schema::txos;
+
+        let max_received_block_index: Option<i64> = txos::table
+            .filter(txos::account_id.eq(account_id_hex))
+            .select(diesel::dsl::max(txos::received_block_index))
+            .first(conn)?;
+        let max_spent_block_index: Option<i64> = txos::table
+            .filter(txos::account_id.eq(account_id_hex))
+            .select(diesel::dsl::max(txos::spent_block_index))
+            .first(conn)?;
+
+        Ok(max_received_block_index
+            .max(max_spent_block_index)
+            .map(|index| index as u64))
+    }
+
     fn delete_unreferenced(conn: Conn) -> Result<(), WalletDbError> {
         use crate::db::schema::{
             authenticated_sender_memos, destination_memos, transaction_input_txos,
@@ -2010,34 +2375,180 @@ impl TxoModel for Txo {
         Ok(())
     }
 
-    fn status(&self, conn: Conn) -> Result<TxoStatus, WalletDbError> {
-        use crate::db::schema::{
-            transaction_input_txos, transaction_logs, transaction_output_txos, txos,
+    fn reattribute_orphaned(
+        account_id_hex: &str,
+        ledger_db: &LedgerDB,
+        conn: Conn,
+    ) -> Result<Vec<String>, WalletDbError> {
+        use crate::db::schema::txos;
+
+        let account = Account::get(&AccountID(account_id_hex.to_string()), conn)?;
+        let (view_private_key, account_key) = if account.view_only {
+            let view_account_key: ViewAccountKey = mc_util_serial::decode(&account.account_key)?;
+            (view_account_key.view_private_key().clone(), None)
+        } else {
+            let account_key: AccountKey = mc_util_serial::decode(&account.account_key)?;
+            (account_key.view_private_key().clone(), Some(account_key))
         };
 
-        if self.spent_block_index.is_some() {
-            return Ok(TxoStatus::Spent);
-        }
+        let orphaned_txos = Txo::list_orphaned(
+            Some(account_id_hex),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            conn,
+        )?;
 
-        let num_pending_logs: i64 = transaction_logs::table
-            .inner_join(transaction_input_txos::table)
-            .inner_join(transaction_output_txos::table)
-            .filter(
-                transaction_input_txos::txo_id
-                    .eq(&self.id)
-                    .or(transaction_output_txos::txo_id.eq(&self.id)),
-            )
-            .filter(transaction_logs::finalized_block_index.is_null())
-            .filter(transaction_logs::submitted_block_index.is_not_null())
-            .filter(transaction_logs::failed.eq(false))
-            .select(count(transaction_logs::id))
-            .first(conn)?;
+        let mut reattributed_txo_ids = Vec::new();
+        for orphaned_txo in orphaned_txos.iter() {
+            let tx_public_key: RistrettoPublic = mc_util_serial::decode(&orphaned_txo.public_key)?;
+            let txo_subaddress_spk =
+                orphaned_txo.recover_subaddress_spend_public_key(&view_private_key)?;
 
-        if num_pending_logs > 0 {
-            return Ok(TxoStatus::Pending);
+            let subaddress_index = match AssignedSubaddress::find_by_subaddress_spend_public_key(
+                &txo_subaddress_spk,
+                conn,
+            ) {
+                Ok((index, subaddress_account_id)) if subaddress_account_id == account_id_hex => {
+                    index
+                }
+                Ok(_) | Err(WalletDbError::AssignedSubaddressNotFound(_)) => continue,
+                Err(e) => return Err(e),
+            };
+
+            match &account_key {
+                Some(account_key) => {
+                    let onetime_private_key = recover_onetime_private_key(
+                        &tx_public_key,
+                        account_key.view_private_key(),
+                        &account_key.subaddress_spend_private(subaddress_index as u64),
+                    );
+                    let key_image = KeyImage::from(&onetime_private_key);
+                    let spent_block_index = ledger_db.check_key_image(&key_image)?;
+
+                    diesel::update(orphaned_txo)
+                        .set((
+                            txos::subaddress_index.eq(subaddress_index),
+                            txos::key_image.eq(mc_util_serial::encode(&key_image)),
+                            txos::spent_block_index.eq(spent_block_index.map(|i| i as i64)),
+                            txos::updated_at.eq(unix_timestamp()),
+                        ))
+                        .execute(conn)?;
+                }
+                None => {
+                    diesel::update(orphaned_txo)
+                        .set((
+                            txos::subaddress_index.eq(subaddress_index),
+                            txos::updated_at.eq(unix_timestamp()),
+                        ))
+                        .execute(conn)?;
+                }
+            }
+
+            reattributed_txo_ids.push(orphaned_txo.id.clone());
         }
 
-        let num_secreted_logs: i64 = transaction_logs::table
+        Ok(reattributed_txo_ids)
+    }
+
+    fn delete_superseded_outputs(
+        account_id_hex: &str,
+        conn: Conn,
+    ) -> Result<Vec<String>, WalletDbError> {
+        use crate::db::schema::{
+            authenticated_sender_memos, destination_memos, transaction_input_txos,
+            transaction_logs, transaction_output_txos, txos,
+        };
+
+        // A transaction which failed or was never submitted can no longer land once
+        // one of its inputs is spent, so the received and change Txos of whatever
+        // transaction spent that input are the ones that count.
+        let superseded_log_ids: Vec<String> = transaction_logs::table
+            .inner_join(transaction_input_txos::table.inner_join(txos::table))
+            .filter(transaction_logs::account_id.eq(account_id_hex))
+            .filter(transaction_logs::finalized_block_index.is_null())
+            .filter(
+                transaction_logs::failed
+                    .eq(true)
+                    .or(transaction_logs::submitted_block_index.is_null()),
+            )
+            .filter(txos::spent_block_index.is_not_null())
+            .select(transaction_logs::id)
+            .distinct()
+            .load(conn)?;
+
+        let superseded_txo_ids: Vec<String> = transaction_output_txos::table
+            .inner_join(txos::table)
+            .filter(transaction_output_txos::transaction_log_id.eq_any(&superseded_log_ids))
+            .filter(txos::account_id.is_null())
+            .select(txos::id)
+            .load(conn)?;
+
+        diesel::delete(
+            authenticated_sender_memos::table
+                .filter(authenticated_sender_memos::txo_id.eq_any(&superseded_txo_ids)),
+        )
+        .execute(conn)?;
+        diesel::delete(
+            destination_memos::table.filter(destination_memos::txo_id.eq_any(&superseded_txo_ids)),
+        )
+        .execute(conn)?;
+        diesel::delete(
+            transaction_output_txos::table
+                .filter(transaction_output_txos::txo_id.eq_any(&superseded_txo_ids)),
+        )
+        .execute(conn)?;
+        diesel::delete(txos::table.filter(txos::id.eq_any(&superseded_txo_ids))).execute(conn)?;
+
+        Ok(superseded_txo_ids)
+    }
+
+    fn recover_subaddress_spend_public_key(
+        &self,
+        view_private_key: &RistrettoPrivate,
+    ) -> Result<RistrettoPublic, WalletDbError> {
+        let tx_out_target_key: RistrettoPublic = mc_util_serial::decode(&self.target_key)?;
+        let tx_public_key: RistrettoPublic = mc_util_serial::decode(&self.public_key)?;
+
+        Ok(recover_public_subaddress_spend_key(
+            view_private_key,
+            &tx_out_target_key,
+            &tx_public_key,
+        ))
+    }
+
+    fn status(&self, conn: Conn) -> Result<TxoStatus, WalletDbError> {
+        use crate::db::schema::{
+            transaction_input_txos, transaction_logs, transaction_output_txos, txos,
+        };
+
+        if self.spent_block_index.is_some() {
+            return Ok(TxoStatus::Spent);
+        }
+
+        let num_pending_logs: i64 = transaction_logs::table
+            .inner_join(transaction_input_txos::table)
+            .inner_join(transaction_output_txos::table)
+            .filter(
+                transaction_input_txos::txo_id
+                    .eq(&self.id)
+                    .or(transaction_output_txos::txo_id.eq(&self.id)),
+            )
+            .filter(transaction_logs::finalized_block_index.is_null())
+            .filter(transaction_logs::submitted_block_index.is_not_null())
+            .filter(transaction_logs::failed.eq(false))
+            .select(count(transaction_logs::id))
+            .first(conn)?;
+
+        if num_pending_logs > 0 {
+            return Ok(TxoStatus::Pending);
+        }
+
+        let num_secreted_logs: i64 = transaction_logs::table
             .left_join(transaction_output_txos::table)
             .left_join(txos::table.on(transaction_output_txos::txo_id.eq(txos::id)))
             .filter(transaction_output_txos::txo_id.eq(&self.id))
@@ -2114,6 +2625,25 @@ impl TxoModel for Txo {
         )
     }
 
+    fn spent_in_transaction_log_id(&self, conn: Conn) -> Result<Option<String>, WalletDbError> {
+        use crate::db::schema::{transaction_input_txos, transaction_logs};
+
+        if self.spent_block_index.is_none() {
+            return Ok(None);
+        }
+
+        // A txo may be an input to several transaction logs if earlier attempts
+        // failed, so prefer the one that was finalized.
+        Ok(transaction_input_txos::table
+            .inner_join(transaction_logs::table)
+            .filter(transaction_input_txos::txo_id.eq(&self.id))
+            .filter(transaction_logs::failed.eq(false))
+            .order(transaction_logs::finalized_block_index.desc())
+            .select(transaction_logs::id)
+            .first::<String>(conn)
+            .optional()?)
+    }
+
     fn membership_proof(
         &self,
         ledger_db: &LedgerDB,
@@ -2237,6 +2767,34 @@ fn add_destination_memo_to_database(
     Ok(())
 }
 
+/// Txo values are stored as i64, so values above i64::MAX wrap around to
+/// negative numbers. The values from `min_value` to `max_value` therefore cover
+/// up to two ranges of stored values: values up to i64::MAX, stored as they
+/// are, and values above it, stored as negative numbers. A range with nothing
+/// in it is returned as (0, -1).
+fn stored_value_ranges(min_value: Option<u64>, max_value: Option<u64>) -> [(i64, i64); 2] {
+    const EMPTY_RANGE: (i64, i64) = (0, -1);
+    let min_value = min_value.unwrap_or(0);
+    let max_value = max_value.unwrap_or(u64::MAX);
+    let i64_max = i64::MAX as u64;
+
+    if min_value > max_value {
+        return [EMPTY_RANGE, EMPTY_RANGE];
+    }
+
+    let low = if min_value <= i64_max {
+        (min_value as i64, max_value.min(i64_max) as i64)
+    } else {
+        EMPTY_RANGE
+    };
+    let high = if max_value > i64_max {
+        (min_value.max(i64_max + 1) as i64, max_value as i64)
+    } else {
+        EMPTY_RANGE
+    };
+    [low, high]
+}
+
 fn i32_to_two_bytes(value: i32) -> [u8; 2] {
     [(value >> 8) as u8, (value & 0xFF) as u8]
 }
@@ -2245,6 +2803,14 @@ fn two_bytes_to_i32(bytes: [u8; 2]) -> i32 {
     ((bytes[0] as i32) << 8) | (bytes[1] as i32)
 }
 
+fn memo_payload_to_bytes(memo_payload: &MemoPayload) -> Vec<u8> {
+    [
+        &memo_payload.get_memo_type()[..],
+        &memo_payload.get_memo_data()[..],
+    ]
+    .concat()
+}
+
 fn add_memo_to_database(
     txo_id: &str,
     memo_payload: &MemoPayload,
@@ -2308,11 +2874,109 @@ fn add_memo_to_database(
             Some(memo.get_payment_intent_id() as i64),
             conn,
         ),
-        Ok(_) => Ok(()),
-        Err(e) => Err(e.into()),
+        // Memos without a table of their own, such as unauthenticated sender memos, and
+        // memos of an unknown type are kept only as the raw payload on the txo.
+        Ok(_) | Err(MemoDecodingError::UnknownMemoType(_)) => Ok(()),
     }
 }
 
+/// List the spendable Txos, optionally leaving out those reserved by a
+/// transaction that has been built but not yet submitted.
+fn list_spendable_txos(
+    account_id_hex: Option<&str>,
+    max_spendable_value: Option<u64>,
+    assigned_subaddress_b58: Option<&str>,
+    exclude_txo_ids: &[String],
+    token_id: u64,
+    default_token_fee: u64,
+    include_reserved: bool,
+    conn: Conn,
+) -> Result<SpendableTxosResult, WalletDbError> {
+    use crate::db::schema::{transaction_input_txos, transaction_logs, txos};
+
+    let mut query = txos::table
+        .into_boxed()
+        .left_join(transaction_input_txos::table)
+        .left_join(
+            transaction_logs::table
+                .on(transaction_logs::id.eq(transaction_input_txos::transaction_log_id)),
+        );
+
+    query = query
+        .filter(transaction_logs::id.is_null())
+        .or_filter(transaction_logs::failed.eq(true))
+        .or_filter(
+            transaction_logs::id
+                .is_not_null()
+                .and(transaction_logs::submitted_block_index.is_null()),
+        );
+
+    query = query
+        .filter(txos::received_block_index.is_not_null())
+        .filter(txos::spent_block_index.is_null())
+        .filter(txos::subaddress_index.is_not_null())
+        .filter(txos::token_id.eq(token_id as i64));
+
+    if let Some(subaddress_b58) = assigned_subaddress_b58 {
+        let subaddress = AssignedSubaddress::get(subaddress_b58, conn)?;
+        query = query
+            .filter(txos::subaddress_index.eq(subaddress.subaddress_index))
+            .filter(txos::account_id.eq(subaddress.account_id));
+    }
+
+    if let Some(account_id_hex) = account_id_hex {
+        query = query.filter(txos::account_id.eq(account_id_hex));
+    }
+
+    if !exclude_txo_ids.is_empty() {
+        query = query.filter(txos::id.ne_all(exclude_txo_ids));
+    }
+
+    if !include_reserved {
+        query = query.filter(
+            txos::reserved_until
+                .is_null()
+                .or(txos::reserved_until.le(unix_timestamp())),
+        );
+    }
+
+    let mut spendable_txos = query
+        .select(txos::all_columns)
+        .distinct()
+        .load(conn)?
+        .drain(..)
+        .filter(|txo: &Txo| txo.value as u64 <= max_spendable_value.unwrap_or(u64::MAX))
+        .collect::<Vec<Txo>>();
+
+    spendable_txos.sort_by(|a: &Txo, b: &Txo| (b.value as u64).cmp(&(a.value as u64)));
+
+    // The maximum spendable is limited by the maximal number of inputs we can use.
+    // Since the txos are sorted by decreasing value, this is the maximum
+    // value we can possibly spend in one transaction. Reserved txos are left
+    // out, since input selection will not use them.
+    // Note, u128::Max = 340_282_366_920_938_463_463_374_607_431_768_211_455, which
+    // is far beyond the total number of pMOB in the MobileCoin system
+    // (250_000_000_000_000_000_000)
+    let now = unix_timestamp();
+    let mut max_spendable_in_wallet: u128 = spendable_txos
+        .iter()
+        .filter(|txo: &&Txo| txo.reserved_until.map_or(true, |until| until <= now))
+        .take(MAX_INPUTS as usize)
+        .map(|utxo: &Txo| (utxo.value as u64) as u128)
+        .sum();
+
+    if max_spendable_in_wallet > default_token_fee as u128 {
+        max_spendable_in_wallet -= default_token_fee as u128;
+    } else {
+        max_spendable_in_wallet = 0;
+    }
+
+    Ok(SpendableTxosResult {
+        spendable_txos,
+        max_spendable_in_wallet,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
@@ -2516,6 +3180,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             Some(0),
             conn,
         )
@@ -2542,6 +3208,13 @@ mod tests {
             shared_secret: Some(shared_secret.encode_to_vec()),
             memo_type: Some(0),
             is_synced_to_t3: false,
+            reserved_until: None,
+            memo_payload: Some(memo_payload_to_bytes(
+                &for_alice_txo.decrypt_memo(&shared_secret),
+            )),
+            received_timestamp: None,
+            created_at: txos[0].created_at,
+            updated_at: txos[0].updated_at,
         };
 
         assert_eq!(expected_txo, txos[0]);
@@ -2555,6 +3228,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             conn,
         )
         .unwrap();
@@ -2655,6 +3330,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             Some(0),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
@@ -2669,6 +3346,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             Some(0),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
@@ -2683,6 +3362,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             Some(0),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
@@ -2703,6 +3384,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -2722,6 +3405,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -2740,6 +3425,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -2782,6 +3469,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -2794,6 +3483,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             Some(0),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
@@ -2883,6 +3574,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             Some(0),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
@@ -3148,6 +3841,7 @@ mod tests {
             300 * MOB as u128,
             None,
             None,
+            &[],
             0,
             Mob::MINIMUM_FEE,
             &mut wallet_db.get_pooled_conn().unwrap(),
@@ -3162,6 +3856,7 @@ mod tests {
             (300 * MOB + Mob::MINIMUM_FEE) as u128,
             None,
             None,
+            &[],
             0,
             Mob::MINIMUM_FEE,
             &mut wallet_db.get_pooled_conn().unwrap(),
@@ -3179,6 +3874,7 @@ mod tests {
             (300 * MOB + Mob::MINIMUM_FEE) as u128,
             Some(200 * MOB),
             None,
+            &[],
             0,
             Mob::MINIMUM_FEE,
             &mut wallet_db.get_pooled_conn().unwrap(),
@@ -3197,6 +3893,7 @@ mod tests {
             16800 * MOB as u128,
             None,
             None,
+            &[],
             0,
             Mob::MINIMUM_FEE,
             &mut wallet_db.get_pooled_conn().unwrap(),
@@ -3319,6 +4016,7 @@ mod tests {
                 42 * MOB as u128,
                 None,
                 Some(subaddress),
+                &[],
                 0,
                 Mob::MINIMUM_FEE,
                 conn,
@@ -3335,6 +4033,7 @@ mod tests {
             (100 * MOB + Mob::MINIMUM_FEE) as u128,
             None,
             Some(&alice_public_address_b58),
+            &[],
             0,
             Mob::MINIMUM_FEE,
             conn,
@@ -3388,6 +4087,7 @@ mod tests {
             16800 * MOB as u128,
             None,
             None,
+            &[],
             0,
             Mob::MINIMUM_FEE,
             &mut wallet_db.get_pooled_conn().unwrap(),
@@ -3399,6 +4099,7 @@ mod tests {
             16800 * MOB as u128,
             Some(100 * MOB),
             None,
+            &[],
             0,
             Mob::MINIMUM_FEE,
             &mut wallet_db.get_pooled_conn().unwrap(),
@@ -3454,12 +4155,16 @@ mod tests {
             1800 * MOB as u128,
             None,
             None,
+            &[],
             0,
             Mob::MINIMUM_FEE,
             &mut wallet_db.get_pooled_conn().unwrap(),
         );
         match res {
-            Err(WalletDbError::InsufficientFundsFragmentedTxos) => {}
+            Err(WalletDbError::InsufficientFundsFragmentedTxos(needed, max)) => {
+                assert_eq!(needed, 18);
+                assert_eq!(max, MAX_INPUTS);
+            }
             Ok(_) => panic!("Should error with InsufficientFundsFragmentedTxos"),
             Err(e) => panic!(
                 "Should error with InsufficientFundsFragmentedTxos but got {:?}",
@@ -3514,6 +4219,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -3651,6 +4358,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             Some(0),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
@@ -3673,6 +4382,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             Some(0),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
@@ -3821,6 +4532,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             Some(0),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
@@ -3839,6 +4552,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             Some(0),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
@@ -3911,6 +4626,93 @@ mod tests {
         );
     }
 
+    #[test_with_logger]
+    fn test_list_spendable_max_spendable_excludes_reserved(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let root_id = RootIdentity::from_random(&mut rng);
+        let account_key = AccountKey::from(&root_id);
+        let (account_id, _address) = Account::create_from_root_entropy(
+            &root_id.root_entropy,
+            Some(0),
+            None,
+            None,
+            "",
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+
+        let mut txo_ids = Vec::new();
+        for i in 1..=3 {
+            let (txo_id, _txo, _key_image) = create_test_received_txo(
+                &account_key,
+                i,
+                Amount::new(i * 100 * MOB, Mob::ID),
+                i,
+                &mut rng,
+                &wallet_db,
+            );
+            txo_ids.push(txo_id);
+        }
+
+        // Reserve the largest txo, as a built transaction would.
+        Txo::reserve(&txo_ids[2..], conn).unwrap();
+
+        // The reserved txo is still listed, but cannot count towards the
+        // maximum spendable, since input selection will not use it.
+        let SpendableTxosResult {
+            spendable_txos,
+            max_spendable_in_wallet,
+        } = Txo::list_spendable(
+            Some(&account_id.to_string()),
+            None,
+            None,
+            0,
+            Mob::MINIMUM_FEE,
+            conn,
+        )
+        .unwrap();
+        assert_eq!(spendable_txos.len(), 3);
+        assert_eq!(max_spendable_in_wallet as u64, 300 * MOB - Mob::MINIMUM_FEE);
+
+        // The maximum spendable can be selected.
+        let selected = Txo::select_spendable_txos_for_value(
+            &account_id.to_string(),
+            max_spendable_in_wallet + Mob::MINIMUM_FEE as u128,
+            None,
+            None,
+            &[],
+            0,
+            Mob::MINIMUM_FEE,
+            conn,
+        )
+        .unwrap();
+        assert_eq!(selected.len(), 2);
+
+        Txo::release_reservations(&txo_ids[2..], conn).unwrap();
+        let SpendableTxosResult {
+            max_spendable_in_wallet,
+            ..
+        } = Txo::list_spendable(
+            Some(&account_id.to_string()),
+            None,
+            None,
+            0,
+            Mob::MINIMUM_FEE,
+            conn,
+        )
+        .unwrap();
+        assert_eq!(max_spendable_in_wallet as u64, 600 * MOB - Mob::MINIMUM_FEE);
+    }
+
     #[test_with_logger]
     fn test_list_spendable_less_than_min_fee(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
@@ -4054,6 +4856,246 @@ mod tests {
         );
     }
 
+    #[test_with_logger]
+    fn test_list_for_account_value_filters(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let root_id = RootIdentity::from_random(&mut rng);
+        let account_key = AccountKey::from(&root_id);
+        let (account_id, _address) = Account::create_from_root_entropy(
+            &root_id.root_entropy,
+            Some(0),
+            None,
+            None,
+            "",
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+        let account_id_hex = account_id.to_string();
+
+        // Txos worth 1 to 5 MOB, received in blocks 1 to 5. The 3 MOB txo is spent.
+        let mut txo_ids = Vec::new();
+        for i in 1..=5 {
+            let (txo_id, _txo, _key_image) = create_test_received_txo(
+                &account_key,
+                0,
+                Amount::new(i * MOB, Mob::ID),
+                i,
+                &mut rng,
+                &wallet_db,
+            );
+            txo_ids.push(txo_id);
+        }
+        Txo::update_spent_block_index(&txo_ids[2], 6, conn).unwrap();
+
+        let values = |status: Option<TxoStatus>,
+                      min_value: Option<u64>,
+                      max_value: Option<u64>,
+                      offset: Option<u64>,
+                      limit: Option<u64>,
+                      conn: Conn|
+         -> Vec<u64> {
+            Txo::list_for_account(
+                &account_id_hex,
+                status,
+                None,
+                None,
+                min_value,
+                max_value,
+                offset,
+                limit,
+                None,
+                conn,
+            )
+            .unwrap()
+            .iter()
+            .map(|txo| txo.value as u64 / MOB)
+            .collect()
+        };
+
+        // Value range only.
+        assert_eq!(
+            values(None, None, None, None, None, conn),
+            vec![5, 4, 3, 2, 1]
+        );
+        assert_eq!(
+            values(None, Some(2 * MOB), None, None, None, conn),
+            vec![5, 4, 3, 2]
+        );
+        assert_eq!(
+            values(None, None, Some(3 * MOB), None, None, conn),
+            vec![3, 2, 1]
+        );
+        assert_eq!(
+            values(None, Some(2 * MOB), Some(4 * MOB), None, None, conn),
+            vec![4, 3, 2]
+        );
+        assert!(values(None, Some(6 * MOB), None, None, None, conn).is_empty());
+
+        // Value range combined with status.
+        assert_eq!(
+            values(
+                Some(TxoStatus::Unspent),
+                Some(2 * MOB),
+                Some(4 * MOB),
+                None,
+                None,
+                conn
+            ),
+            vec![4, 2]
+        );
+        assert_eq!(
+            values(
+                Some(TxoStatus::Spent),
+                Some(2 * MOB),
+                Some(4 * MOB),
+                None,
+                None,
+                conn
+            ),
+            vec![3]
+        );
+        assert!(values(
+            Some(TxoStatus::Spent),
+            Some(4 * MOB),
+            None,
+            None,
+            None,
+            conn
+        )
+        .is_empty());
+
+        // Value range combined with pagination.
+        assert_eq!(
+            values(None, Some(2 * MOB), None, Some(0), Some(2), conn),
+            vec![5, 4]
+        );
+        assert_eq!(
+            values(None, Some(2 * MOB), None, Some(2), Some(2), conn),
+            vec![3, 2]
+        );
+        assert_eq!(
+            values(
+                Some(TxoStatus::Unspent),
+                Some(2 * MOB),
+                None,
+                Some(1),
+                Some(2),
+                conn
+            ),
+            vec![4, 2]
+        );
+
+        // The same filters apply when listing across all accounts.
+        let all: Vec<u64> = Txo::list(
+            Some(TxoStatus::Unspent),
+            None,
+            None,
+            Some(2 * MOB),
+            Some(4 * MOB),
+            None,
+            None,
+            None,
+            conn,
+        )
+        .unwrap()
+        .iter()
+        .map(|txo| txo.value as u64 / MOB)
+        .collect();
+        assert_eq!(all, vec![4, 2]);
+
+        // Bounds past i64::MAX are not wrapped to negative values.
+        assert_eq!(
+            values(None, None, Some(u64::MAX), None, None, conn),
+            vec![5, 4, 3, 2, 1]
+        );
+        assert!(values(None, Some(i64::MAX as u64 + 1), None, None, None, conn).is_empty());
+    }
+
+    #[test_with_logger]
+    fn test_list_for_account_value_filters_above_i64_max(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let root_id = RootIdentity::from_random(&mut rng);
+        let account_key = AccountKey::from(&root_id);
+        let (account_id, _address) = Account::create_from_root_entropy(
+            &root_id.root_entropy,
+            Some(0),
+            None,
+            None,
+            "",
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+        let account_id_hex = account_id.to_string();
+
+        // A small txo, and a txo whose value is stored as a negative number.
+        let small_value = 5;
+        let large_value = i64::MAX as u64 + 10;
+        for (i, value) in [small_value, large_value].into_iter().enumerate() {
+            create_test_received_txo(
+                &account_key,
+                0,
+                Amount::new(value, Mob::ID),
+                i as u64 + 1,
+                &mut rng,
+                &wallet_db,
+            );
+        }
+
+        let values = |min_value: Option<u64>, max_value: Option<u64>, conn: Conn| -> Vec<u64> {
+            let mut values: Vec<u64> = Txo::list_for_account(
+                &account_id_hex,
+                None,
+                None,
+                None,
+                min_value,
+                max_value,
+                None,
+                None,
+                None,
+                conn,
+            )
+            .unwrap()
+            .iter()
+            .map(|txo| txo.value as u64)
+            .collect();
+            values.sort();
+            values
+        };
+
+        assert_eq!(values(Some(1), None, conn), vec![small_value, large_value]);
+        assert_eq!(values(None, Some(100), conn), vec![small_value]);
+        assert_eq!(values(None, Some(i64::MAX as u64), conn), vec![small_value]);
+        assert_eq!(values(Some(i64::MAX as u64), None, conn), vec![large_value]);
+        assert_eq!(
+            values(Some(i64::MAX as u64 + 1), Some(u64::MAX), conn),
+            vec![large_value]
+        );
+        assert!(values(Some(i64::MAX as u64 + 11), None, conn).is_empty());
+        assert_eq!(
+            values(Some(1), Some(large_value), conn),
+            vec![small_value, large_value]
+        );
+        assert!(values(Some(100), Some(1), conn).is_empty());
+    }
+
     #[test_with_logger]
     fn test_unspent_txo_query(logger: Logger) {
         // make sure it only includes txos with key image and subaddress
@@ -4103,6 +5145,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             conn,
         )
         .unwrap();
@@ -4128,6 +5172,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             conn,
         )
         .unwrap();
@@ -4153,6 +5199,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             conn,
         )
         .unwrap();
@@ -4259,6 +5307,7 @@ mod tests {
             target_value,
             None,
             None,
+            &[],
             0,
             Mob::MINIMUM_FEE,
             &mut wallet_db.get_pooled_conn().unwrap(),
@@ -4278,6 +5327,7 @@ mod tests {
             201 * MOB as u128,
             None,
             None,
+            &[],
             0,
             Mob::MINIMUM_FEE,
             &mut wallet_db.get_pooled_conn().unwrap(),
@@ -4297,6 +5347,7 @@ mod tests {
             3,
             None,
             None,
+            &[],
             0,
             Mob::MINIMUM_FEE,
             &mut wallet_db.get_pooled_conn().unwrap(),
@@ -4314,6 +5365,7 @@ mod tests {
             500 * MOB as u128,
             None,
             None,
+            &[],
             0,
             Mob::MINIMUM_FEE,
             &mut wallet_db.get_pooled_conn().unwrap(),
@@ -4332,6 +5384,7 @@ mod tests {
             12400000000,
             None,
             None,
+            &[],
             0,
             Mob::MINIMUM_FEE,
             &mut wallet_db.get_pooled_conn().unwrap(),
@@ -4496,6 +5549,37 @@ mod tests {
         }
     }
 
+    #[test_with_logger]
+    fn test_add_memo_to_database_unknown_memo_type(logger: Logger) {
+        use crate::db::schema::{authenticated_sender_memos, destination_memos};
+        use diesel::dsl::count;
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        // A memo of a type this wallet does not know is not an error, and is
+        // kept only as the raw payload.
+        let memo_payload = MemoPayload::new([0xff, 0xff], [7u8; 64]);
+        add_memo_to_database("txo_id", &memo_payload, conn).unwrap();
+
+        assert_eq!(
+            authenticated_sender_memos::table
+                .select(count(authenticated_sender_memos::txo_id))
+                .first::<i64>(conn)
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            destination_memos::table
+                .select(count(destination_memos::txo_id))
+                .first::<i64>(conn)
+                .unwrap(),
+            0
+        );
+    }
+
     #[test_with_logger]
     fn test_get_memos_for_t3_sync_get_correct_txos(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
@@ -4785,7 +5869,7 @@ mod tests {
         .unwrap();
 
         // now let's verify that there are 6 txos and 6 memos in the database
-        let txos = Txo::list(None, None, None, None, None, None, conn).unwrap();
+        let txos = Txo::list(None, None, None, None, None, None, None, None, conn).unwrap();
         let memos = crate::db::models::AuthenticatedSenderMemo::list(conn).unwrap();
         assert_eq!(txos.len(), 6);
         assert_eq!(memos.len(), 6);
@@ -4796,7 +5880,7 @@ mod tests {
 
         // now let's check to make sure that there are 3 txos and 3 memos left
         // in the database and that they are the expected ones
-        let txos = Txo::list(None, None, None, None, None, None, conn).unwrap();
+        let txos = Txo::list(None, None, None, None, None, None, None, None, conn).unwrap();
         let memos = crate::db::models::AuthenticatedSenderMemo::list(conn).unwrap();
         assert_eq!(txos.len(), 3);
         assert_eq!(memos.len(), 3);
@@ -14,7 +14,11 @@ use diesel::{
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use mc_common::logger::global_log;
 use mc_crypto_keys::RistrettoPublic;
-use std::{env, thread::sleep, time::Duration};
+use std::{
+    env,
+    thread::sleep,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations/");
 
@@ -266,3 +270,113 @@ where
 
 const BASE_DELAY_MS: u32 = 10;
 const NUM_RETRIES: u32 = 5;
+
+/// The current time as unix seconds, or 0 if the system clock is before the
+/// epoch.
+pub fn unix_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{
+            account::AccountModel,
+            address_book_entry::AddressBookEntryModel,
+            models::{Account, AddressBookEntry},
+        },
+        test_utils::WalletDbTestContext,
+        util::b58::b58_encode_public_address,
+    };
+    use mc_account_keys::AccountKey;
+    use mc_common::logger::{test_with_logger, Logger};
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::{
+        ops::DerefMut,
+        sync::{mpsc, Arc, Barrier},
+        thread,
+    };
+
+    #[test_with_logger]
+    fn test_concurrent_reads(logger: Logger) {
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+        let journal_mode = diesel::dsl::sql::<sql_types::Text>("PRAGMA journal_mode;")
+            .get_result::<String>(conn)
+            .unwrap();
+        assert_eq!(journal_mode, "wal");
+        drop(pooled_conn);
+
+        // Each reader waits inside its read transaction until all of them have
+        // started one, so this only completes if the reads run concurrently.
+        let num_readers = 4;
+        let barrier = Arc::new(Barrier::new(num_readers));
+        let handles: Vec<_> = (0..num_readers)
+            .map(|_| {
+                let wallet_db = wallet_db.clone();
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+                    let conn = pooled_conn.deref_mut();
+                    conn.transaction::<_, WalletDbError, _>(|conn| {
+                        let accounts = Account::list_all(conn, None, None)?;
+                        barrier.wait();
+                        Ok(accounts.len())
+                    })
+                    .unwrap()
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 0);
+        }
+    }
+
+    #[test_with_logger]
+    fn test_read_during_write_transaction(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+
+        let public_address_b58 =
+            b58_encode_public_address(&AccountKey::random(&mut rng).default_subaddress()).unwrap();
+
+        // Hold a write transaction open until the read has finished.
+        let (write_started_tx, write_started_rx) = mpsc::channel();
+        let (read_finished_tx, read_finished_rx) = mpsc::channel::<()>();
+        let writer_db = wallet_db.clone();
+        let writer = thread::spawn(move || {
+            let mut pooled_conn = writer_db.get_pooled_conn().unwrap();
+            let conn = pooled_conn.deref_mut();
+            conn.exclusive_transaction::<_, WalletDbError, _>(|conn| {
+                AddressBookEntry::create(&public_address_b58, "Alice", conn)?;
+                write_started_tx.send(()).unwrap();
+                read_finished_rx.recv().unwrap();
+                Ok(())
+            })
+            .unwrap();
+        });
+
+        write_started_rx.recv().unwrap();
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+        // The read does not wait for the writer, and does not see its uncommitted
+        // changes.
+        let entries = AddressBookEntry::list_all(conn, None, None).unwrap();
+        assert!(entries.is_empty());
+        read_finished_tx.send(()).unwrap();
+        writer.join().unwrap();
+
+        let entries = AddressBookEntry::list_all(conn, None, None).unwrap();
+        assert_eq!(entries.len(), 1);
+    }
+}
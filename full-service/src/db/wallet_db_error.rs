@@ -1,6 +1,10 @@
 // Copyright (c) 2020-2021 MobileCoin Inc.
 
-use crate::{db::gift_code::GiftCodeDbError, util::b58::B58Error};
+use crate::{
+    db::gift_code::GiftCodeDbError,
+    json_rpc::json_rpc_response::{ServerErrorCode, ServerErrorCodes},
+    util::b58::B58Error,
+};
 use base64::DecodeSliceError;
 use mc_transaction_extra::MemoDecodingError;
 use reqwest;
@@ -59,10 +63,10 @@ pub enum WalletDbError {
     /// No unspent Txos of token id {0} in wallet
     NoSpendableTxos(String),
 
-    /** Txos are too fragmented to construct a transaction with MAX_INPUTS.
-     * Please combine txos.
+    /** Txos are too fragmented: the value needs {0} inputs, but a
+     * transaction can have at most {1}. Please combine txos.
      */
-    InsufficientFundsFragmentedTxos,
+    InsufficientFundsFragmentedTxos(usize, u64),
 
     /// Insufficient Funds: {0}
     InsufficientFunds(String),
@@ -91,6 +95,9 @@ pub enum WalletDbError {
     /// AssignedSubaddress Not Found: {0}
     AssignedSubaddressNotFound(String),
 
+    /// AddressBookEntry Not Found: {0}
+    AddressBookEntryNotFound(i32),
+
     /// Txo Not Found: {0}
     TxoNotFound(String),
 
@@ -261,3 +268,26 @@ impl From<MemoDecodingError> for WalletDbError {
         Self::MemoDecoding(src)
     }
 }
+
+impl ServerErrorCode for WalletDbError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::InsufficientFunds(_)
+            | Self::InsufficientFundsUnderMaxSpendable(_)
+            | Self::InsufficientFundsFragmentedTxos(..) => {
+                Some(ServerErrorCodes::InsufficientFunds)
+            }
+            Self::AccountNotFound(_)
+            | Self::AssignedSubaddressNotFound(_)
+            | Self::AddressBookEntryNotFound(_)
+            | Self::TxoNotFound(_)
+            | Self::TransactionLogNotFound(_)
+            | Self::AccountTxoStatusNotFound(_) => Some(ServerErrorCodes::NotFound),
+            Self::B58Decode => Some(ServerErrorCodes::B58Decode),
+            Self::GiftCode(e) => e.code(),
+            Self::B58(e) => e.code(),
+            Self::Diesel(diesel::result::Error::NotFound) => Some(ServerErrorCodes::NotFound),
+            _ => None,
+        }
+    }
+}
@@ -4,6 +4,7 @@
 
 use crate::{
     db::WalletDbError,
+    json_rpc::json_rpc_response::{ServerErrorCode, ServerErrorCodes},
     service::{
         account::AccountServiceError, balance::BalanceServiceError,
         confirmation_number::ConfirmationServiceError, gift_code::GiftCodeServiceError,
@@ -66,6 +67,9 @@ pub enum WalletServiceError {
 
     /// Error with the Payment service: {0}
     PaymentRequestService(PaymentRequestServiceError),
+
+    /// Background threads did not stop within the shutdown timeout: {0}
+    ShutdownTimeout(String),
 }
 
 impl From<WalletDbError> for WalletServiceError {
@@ -164,6 +168,25 @@ impl From<diesel::result::Error> for WalletServiceError {
     }
 }
 
+impl ServerErrorCode for WalletServiceError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::Database(e) => e.code(),
+            Self::TransactionService(e) => e.code(),
+            Self::BalanceService(e) => e.code(),
+            Self::LedgerService(e) => e.code(),
+            Self::TxoService(e) => e.code(),
+            Self::ConfirmationService(e) => e.code(),
+            Self::TransactionLogService(e) => e.code(),
+            Self::GiftCodeService(e) => e.code(),
+            Self::AccountService(e) => e.code(),
+            Self::PaymentRequestService(e) => e.code(),
+            Self::Diesel(diesel::result::Error::NotFound) => Some(ServerErrorCodes::NotFound),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Display, Debug)]
 pub enum T3SyncError {
     /// WalletDb: {0}
@@ -206,6 +229,15 @@ impl From<mc_util_serial::DecodeError> for T3SyncError {
     }
 }
 
+impl ServerErrorCode for T3SyncError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::WalletDb(e) => e.code(),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Display, Debug)]
 pub enum SyncError {
     /// Could not find account
@@ -231,6 +263,15 @@ pub enum SyncError {
 
     /// Error with the B58 Util: {0}
     B58(B58Error),
+
+    /// Error with WatcherDB: {0}
+    WatcherDB(mc_watcher::error::WatcherDBError),
+}
+
+impl From<mc_watcher::error::WatcherDBError> for SyncError {
+    fn from(src: mc_watcher::error::WatcherDBError) -> Self {
+        Self::WatcherDB(src)
+    }
 }
 
 impl From<WalletDbError> for SyncError {
@@ -275,6 +316,18 @@ impl From<B58Error> for SyncError {
     }
 }
 
+impl ServerErrorCode for SyncError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::AccountNotFound => Some(ServerErrorCodes::NotFound),
+            Self::Database(e) => e.code(),
+            Self::B58(e) => e.code(),
+            Self::Diesel(diesel::result::Error::NotFound) => Some(ServerErrorCodes::NotFound),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Display, Debug)]
 #[allow(clippy::large_enum_variant)]
 pub enum WalletTransactionBuilderError {
@@ -332,6 +385,9 @@ pub enum WalletTransactionBuilderError {
      */
     TombstoneNotSet,
 
+    /// Tombstone block {0} has already passed, the ledger has {1} blocks
+    TombstoneInPast(u64, u64),
+
     /// Fee must be at least MINIMUM_FEE: {0}
     InsufficientFee(String),
 
@@ -353,6 +409,9 @@ pub enum WalletTransactionBuilderError {
     /// Transaction is missing inputs for outputs with token id {0}
     MissingInputsForTokenId(String),
 
+    /// No inputs of token id {0} to cover the outputs and fee in that token
+    InsufficientFundsForToken(String),
+
     /// Error decoding the hex string: {0}
     FromHexError(hex::FromHexError),
 
@@ -439,3 +498,17 @@ impl From<mc_transaction_core::TxOutConversionError> for WalletTransactionBuilde
         Self::TxOutConversion(src)
     }
 }
+
+impl ServerErrorCode for WalletTransactionBuilderError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::InsufficientFunds(_) | Self::InsufficientFundsForToken(_) => {
+                Some(ServerErrorCodes::InsufficientFunds)
+            }
+            Self::WalletDb(e) => e.code(),
+            Self::B58(e) => e.code(),
+            Self::Diesel(diesel::result::Error::NotFound) => Some(ServerErrorCodes::NotFound),
+            _ => None,
+        }
+    }
+}
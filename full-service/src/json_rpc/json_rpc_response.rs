@@ -3,8 +3,22 @@
 //! JSON-RPC Responses from the Wallet API.
 //!
 //! API v2
+use crate::{
+    db::{gift_code::GiftCodeDbError, WalletDbError},
+    error::{SyncError, T3SyncError, WalletServiceError, WalletTransactionBuilderError},
+    service::{
+        account::AccountServiceError, address::AddressServiceError, balance::BalanceServiceError,
+        confirmation_number::ConfirmationServiceError, gift_code::GiftCodeServiceError,
+        hardware_wallet::HardwareWalletServiceError, ledger::LedgerServiceError,
+        memo::MemoServiceError, payment_request::PaymentRequestServiceError,
+        receipt::ReceiptServiceError, transaction::TransactionServiceError,
+        transaction_log::TransactionLogServiceError, txo::TxoServiceError,
+    },
+    util::b58::B58Error,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::any::Any;
 use strum::Display;
 
 pub trait JsonCommandResponse {}
@@ -75,17 +89,110 @@ pub enum JsonRPCErrorCodes {
 
     /// Internal Error.
     InternalError = -32603,
-    /* Server error.
-     * ServerError(i32), // FIXME: WalletServiceError -> i32 between 32000 and 32099 */
+}
+
+/// Server error codes, in the range reserved by JSON RPC 2.0 for
+/// implementation-defined server errors (-32000 to -32099).
+///
+/// These codes are stable, so clients can match on them instead of parsing
+/// the error details.
+#[derive(Deserialize, Serialize, Debug, Display, Clone, Copy, PartialEq, Eq)]
+pub enum ServerErrorCodes {
+    /// The account does not hold enough funds for the request.
+    InsufficientFunds = -32001,
+
+    /// A requested object does not exist.
+    NotFound = -32002,
+
+    /// A b58 encoded value could not be decoded.
+    B58Decode = -32003,
+}
+
+/// Errors which map to a stable server error code.
+pub trait ServerErrorCode {
+    /// The server error code for this error, or None if it should be reported
+    /// as an internal error.
+    fn code(&self) -> Option<ServerErrorCodes>;
+}
+
+/// The server error code of `e`, if it is one of the wallet's error types.
+fn server_error_code(e: &dyn Any) -> Option<ServerErrorCodes> {
+    fn code_of<E: ServerErrorCode + 'static>(e: &dyn Any) -> Option<Option<ServerErrorCodes>> {
+        e.downcast_ref::<E>().map(E::code)
+    }
+
+    code_of::<WalletServiceError>(e)
+        .or_else(|| code_of::<AccountServiceError>(e))
+        .or_else(|| code_of::<AddressServiceError>(e))
+        .or_else(|| code_of::<BalanceServiceError>(e))
+        .or_else(|| code_of::<ConfirmationServiceError>(e))
+        .or_else(|| code_of::<GiftCodeServiceError>(e))
+        .or_else(|| code_of::<HardwareWalletServiceError>(e))
+        .or_else(|| code_of::<LedgerServiceError>(e))
+        .or_else(|| code_of::<MemoServiceError>(e))
+        .or_else(|| code_of::<PaymentRequestServiceError>(e))
+        .or_else(|| code_of::<ReceiptServiceError>(e))
+        .or_else(|| code_of::<TransactionServiceError>(e))
+        .or_else(|| code_of::<TransactionLogServiceError>(e))
+        .or_else(|| code_of::<TxoServiceError>(e))
+        .or_else(|| code_of::<WalletTransactionBuilderError>(e))
+        .or_else(|| code_of::<SyncError>(e))
+        .or_else(|| code_of::<T3SyncError>(e))
+        .or_else(|| code_of::<WalletDbError>(e))
+        .or_else(|| code_of::<GiftCodeDbError>(e))
+        .or_else(|| code_of::<B58Error>(e))
+        .flatten()
+}
+
+/// The names of the nested enum variants in a debug-formatted error, outermost
+/// first.
+///
+/// For example, `TransactionBuilder(WalletDb(InsufficientFunds("...")))`
+/// yields `["TransactionBuilder", "WalletDb", "InsufficientFunds"]`.
+pub fn error_chain(debug: &str) -> Vec<String> {
+    let mut chain = Vec::new();
+    let mut rest = debug;
+    loop {
+        let name_len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let name = &rest[..name_len];
+        if !name.starts_with(|c: char| c.is_ascii_uppercase()) {
+            break;
+        }
+        chain.push(name.to_string());
+        match rest[name_len..].strip_prefix('(') {
+            Some(inner) => rest = inner,
+            None => break,
+        }
+    }
+    chain
 }
 
 /// Helper method to format displaydoc errors in JSON RPC 2.0 format.
-pub fn format_error<T: std::fmt::Display + std::fmt::Debug>(e: T) -> JsonRPCError {
-    let data: serde_json::Value =
-        json!({"server_error": format!("{e:?}"), "details": e.to_string()});
+///
+/// Errors which implement [ServerErrorCode] get their stable server error
+/// code; all others are reported as internal errors. The `error_chain` in the
+/// data lists the variant names of the error so that clients do not need to
+/// parse `server_error`.
+pub fn format_error<T: std::fmt::Display + std::fmt::Debug + 'static>(e: T) -> JsonRPCError {
+    let server_error = format!("{e:?}");
+    let error_chain = error_chain(&server_error);
+    let (code, message) = match server_error_code(&e) {
+        Some(server_error_code) => (server_error_code as i32, server_error_code.to_string()),
+        None => (
+            JsonRPCErrorCodes::InternalError as i32,
+            JsonRPCErrorCodes::InternalError.to_string(),
+        ),
+    };
+    let data: serde_json::Value = json!({
+        "server_error": server_error,
+        "details": e.to_string(),
+        "error_chain": error_chain,
+    });
     JsonRPCError::error {
-        code: JsonRPCErrorCodes::InternalError as i32,
-        message: JsonRPCErrorCodes::InternalError.to_string(),
+        code,
+        message,
         data,
     }
 }
@@ -111,3 +218,76 @@ pub fn format_invalid_params_error<T: std::fmt::Display + std::fmt::Debug>(e: T)
         data,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_chain() {
+        assert_eq!(
+            error_chain("TransactionBuilder(WalletDb(InsufficientFunds(\"x\")))"),
+            vec!["TransactionBuilder", "WalletDb", "InsufficientFunds"]
+        );
+        assert_eq!(error_chain("Diesel(NotFound)"), vec!["Diesel", "NotFound"]);
+        assert_eq!(error_chain("\"a plain string\""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_format_error_insufficient_funds() {
+        let error = WalletTransactionBuilderError::WalletDb(WalletDbError::InsufficientFunds(
+            "not enough".to_string(),
+        ));
+        let JsonRPCError::error {
+            code,
+            message,
+            data,
+        } = format_error(error);
+        assert_eq!(code, -32001);
+        assert_eq!(message, "InsufficientFunds");
+        assert_eq!(
+            data["error_chain"],
+            json!(["WalletDb", "InsufficientFunds"])
+        );
+    }
+
+    #[test]
+    fn test_format_error_not_found() {
+        let error = AddressServiceError::Database(WalletDbError::AccountNotFound(
+            "some account".to_string(),
+        ));
+        let JsonRPCError::error { code, data, .. } = format_error(error);
+        assert_eq!(code, -32002);
+        assert_eq!(data["error_chain"], json!(["Database", "AccountNotFound"]));
+    }
+
+    #[test]
+    fn test_format_error_b58_decode() {
+        let error = AddressServiceError::B58(B58Error::NotPublicAddress);
+        let JsonRPCError::error { code, message, .. } = format_error(error);
+        assert_eq!(code, -32003);
+        assert_eq!(message, "B58Decode");
+    }
+
+    #[test]
+    fn test_format_error_uses_variant_codes() {
+        // A variant named like a classified error is still an internal error
+        // unless its code says otherwise.
+        let JsonRPCError::error { code, data, .. } =
+            format_error(TransactionServiceError::NodeNotFound);
+        assert_eq!(code, JsonRPCErrorCodes::InternalError as i32);
+        assert_eq!(data["error_chain"], json!(["NodeNotFound"]));
+    }
+
+    #[test]
+    fn test_format_error_unclassified() {
+        let JsonRPCError::error {
+            code,
+            message,
+            data,
+        } = format_error("something went wrong".to_string());
+        assert_eq!(code, JsonRPCErrorCodes::InternalError as i32);
+        assert_eq!(message, "InternalError");
+        assert_eq!(data["error_chain"], json!([]));
+    }
+}
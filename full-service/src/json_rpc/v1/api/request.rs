@@ -5,6 +5,7 @@
 use crate::json_rpc::{
     json_rpc_request::JsonRPCRequest,
     v1::models::{receiver_receipt::ReceiverReceipt, tx_proposal::TxProposal},
+    v2::models::tx_proposal::TxProposal as CompactTxProposal,
 };
 use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
@@ -81,6 +82,7 @@ pub enum JsonCommandRequest {
         tombstone_block: Option<String>,
         max_spendable_value: Option<String>,
         log_tx_proposal: Option<bool>,
+        compact: Option<bool>,
     },
     check_b58_type {
         b58_code: String,
@@ -199,8 +201,16 @@ pub enum JsonCommandRequest {
         fog_report_id: Option<String>, // Deprecated
         fog_authority_spki: Option<String>,
     },
+    import_gift_code {
+        gift_code_b58: String,
+    },
+    poll_gift_code_status {
+        gift_code_b58: String,
+        timeout_seconds: String,
+    },
     remove_account {
         account_id: String,
+        force: Option<bool>,
     },
     remove_gift_code {
         gift_code_b58: String,
@@ -211,7 +221,8 @@ pub enum JsonCommandRequest {
         tx_proposal: TxProposal,
     },
     submit_transaction {
-        tx_proposal: TxProposal,
+        tx_proposal: Option<TxProposal>,
+        compact_tx_proposal: Option<CompactTxProposal>,
         comment: Option<String>,
         account_id: Option<String>,
     },
@@ -22,6 +22,7 @@ use crate::{
             txo::Txo,
             wallet_status::WalletStatus,
         },
+        v2::models::tx_proposal::TxProposal as CompactTxProposal,
     },
     service::{gift_code::GiftCodeStatus, receipt::ReceiptTransactionStatus},
     util::b58::PrintableWrapperType,
@@ -53,7 +54,10 @@ pub enum JsonCommandResponse {
         transaction_log_id: String,
     },
     build_transaction {
-        tx_proposal: TxProposal,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tx_proposal: Option<TxProposal>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        compact_tx_proposal: Option<CompactTxProposal>,
         transaction_log_id: String,
     },
     check_b58_type {
@@ -164,6 +168,14 @@ pub enum JsonCommandResponse {
     import_account_from_legacy_root_entropy {
         account: Account,
     },
+    import_gift_code {
+        gift_code: GiftCode,
+    },
+    poll_gift_code_status {
+        gift_code_status: GiftCodeStatus,
+        gift_code_value: Option<i64>,
+        gift_code_memo: String,
+    },
     remove_account {
         removed: bool,
     },
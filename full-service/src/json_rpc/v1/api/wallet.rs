@@ -28,7 +28,7 @@ use crate::{
                 wallet_status::WalletStatus,
             },
         },
-        v2::models::amount::Amount,
+        v2::models::{amount::Amount, tx_proposal::TxProposal as CompactTxProposal},
         wallet::{ApiKeyGuard, WalletState},
     },
     service::{
@@ -58,7 +58,7 @@ use mc_mobilecoind_json::data_types::{JsonTx, JsonTxOut};
 use mc_transaction_core::{tokens::Mob, Amount as CoreAmount, Token};
 use rocket::{self, serde::json::Json};
 use serde_json::Map;
-use std::{collections::HashMap, convert::TryFrom, iter::FromIterator};
+use std::{collections::HashMap, convert::TryFrom, iter::FromIterator, time::Duration};
 
 pub async fn generic_wallet_api<T, FPR>(
     _api_key_guard: ApiKeyGuard,
@@ -172,6 +172,7 @@ where
                     &account_id,
                     &addresses_and_amounts,
                     input_txo_ids.as_ref(),
+                    None,
                     fee,
                     Some(Mob::ID.to_string()),
                     tombstone_block,
@@ -182,6 +183,7 @@ where
                     },
                     None,
                     None, // Note: Not including spend_subaddress in V1 API
+                    None,
                 )
                 .await
                 .map_err(format_error)?;
@@ -203,7 +205,7 @@ where
             tombstone_block,
             max_spendable_value,
         } => {
-            let (tx_proposal, gift_code_b58) = service
+            let (tx_proposal, gift_code_b58, _gift_code) = service
                 .build_gift_code(
                     &AccountID(account_id),
                     value_pmob.parse::<u64>().map_err(format_error)?,
@@ -266,6 +268,7 @@ where
             tombstone_block,
             max_spendable_value,
             log_tx_proposal: _,
+            compact,
         } => {
             // The user can specify either a single address and a single value,
             // or a list of addresses and values.
@@ -292,6 +295,7 @@ where
                     &account_id,
                     &addresses_and_amounts,
                     input_txo_ids.as_ref(),
+                    None,
                     fee,
                     Some(Mob::ID.to_string()),
                     tombstone_block,
@@ -301,12 +305,30 @@ where
                     },
                     None,
                     None, // Note: not including spend_subaddress in V1 API
+                    None,
                 )
                 .await
                 .map_err(format_error)?;
 
+            // The compact form carries the Tx as a protobuf hex string, which is
+            // much smaller than the verbose JSON encoding of the Tx. It is the
+            // same form accepted by the v2 API. The verbose form stays the
+            // default so that existing v1 clients keep working.
+            let (verbose_tx_proposal, compact_tx_proposal) = if compact.unwrap_or(false) {
+                (
+                    None,
+                    Some(CompactTxProposal::try_from(&tx_proposal).map_err(format_error)?),
+                )
+            } else {
+                (
+                    Some(TxProposal::try_from(&tx_proposal).map_err(format_error)?),
+                    None,
+                )
+            };
+
             JsonCommandResponse::build_transaction {
-                tx_proposal: TxProposal::try_from(&tx_proposal).map_err(format_error)?,
+                tx_proposal: verbose_tx_proposal,
+                compact_tx_proposal,
                 transaction_log_id: TransactionId::try_from(&tx_proposal)
                     .map_err(format_error)?
                     .to_string(),
@@ -557,6 +579,8 @@ where
                     Some(block_index),
                     None,
                     None,
+                    None,
+                    None,
                 )
                 .map_err(format_error)?;
 
@@ -613,7 +637,18 @@ where
             let mut transaction_log_map: Map<String, serde_json::Value> = Map::new();
 
             let received_txos = service
-                .list_txos(None, None, None, Some(*Mob::ID), None, None, None, None)
+                .list_txos(
+                    None,
+                    None,
+                    None,
+                    Some(*Mob::ID),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
                 .map_err(format_error)?;
 
             let received_tx_logs: Vec<TransactionLog> = received_txos
@@ -667,6 +702,8 @@ where
                     None,
                     None,
                     None,
+                    None,
+                    None,
                 )
                 .map_err(format_error)?;
             let txo_map: Map<String, serde_json::Value> = Map::from_iter(
@@ -840,6 +877,8 @@ where
                     None,
                     None,
                     None,
+                    None,
+                    None,
                 )
                 .map_err(format_error)?;
 
@@ -934,6 +973,8 @@ where
                     Some(*Mob::ID),
                     None,
                     None,
+                    None,
+                    None,
                     Some(o),
                     Some(l),
                 )
@@ -1066,11 +1107,38 @@ where
                 account: account_json,
             }
         }
-        JsonCommandRequest::remove_account { account_id } => JsonCommandResponse::remove_account {
-            removed: service
-                .remove_account(&AccountID(account_id))
-                .map_err(format_error)?,
-        },
+        JsonCommandRequest::import_gift_code { gift_code_b58 } => {
+            JsonCommandResponse::import_gift_code {
+                gift_code: GiftCode::from(
+                    &service
+                        .import_gift_code(&EncodedGiftCode(gift_code_b58))
+                        .map_err(format_error)?,
+                ),
+            }
+        }
+        JsonCommandRequest::poll_gift_code_status {
+            gift_code_b58,
+            timeout_seconds,
+        } => {
+            let timeout =
+                Duration::from_secs(timeout_seconds.parse::<u64>().map_err(format_error)?);
+            let (status, value, memo) = service
+                .poll_gift_code_status(&EncodedGiftCode(gift_code_b58), timeout)
+                .await
+                .map_err(format_error)?;
+            JsonCommandResponse::poll_gift_code_status {
+                gift_code_status: status,
+                gift_code_value: value,
+                gift_code_memo: memo,
+            }
+        }
+        JsonCommandRequest::remove_account { account_id, force } => {
+            JsonCommandResponse::remove_account {
+                removed: service
+                    .remove_account(&AccountID(account_id), force.unwrap_or(false))
+                    .map_err(format_error)?,
+            }
+        }
         JsonCommandRequest::remove_gift_code { gift_code_b58 } => {
             JsonCommandResponse::remove_gift_code {
                 removed: service
@@ -1097,16 +1165,27 @@ where
         }
         JsonCommandRequest::submit_transaction {
             tx_proposal,
+            compact_tx_proposal,
             comment,
             account_id,
         } => {
+            let tx_proposal = match (tx_proposal, compact_tx_proposal) {
+                (Some(tx_proposal), None) => {
+                    service::models::tx_proposal::TxProposal::try_from(&tx_proposal)
+                        .map_err(format_error)?
+                }
+                (None, Some(compact_tx_proposal)) => {
+                    service::models::tx_proposal::TxProposal::try_from(&compact_tx_proposal)
+                        .map_err(format_error)?
+                }
+                _ => {
+                    return Err(format_error(
+                        "Exactly one of tx_proposal or compact_tx_proposal must be provided",
+                    ))
+                }
+            };
             let result = service
-                .submit_transaction(
-                    &service::models::tx_proposal::TxProposal::try_from(&tx_proposal)
-                        .map_err(format_error)?,
-                    comment,
-                    account_id,
-                )
+                .submit_transaction(&tx_proposal, comment, account_id, None)
                 .map_err(format_error)?
                 .map(|(tx_log, associated_txos, _value_map)| {
                     TransactionLog::new(&tx_log, &associated_txos)
@@ -441,7 +441,8 @@ mod e2e_account {
         let res = dispatch(&client, body, &logger);
         let error = res.get("error").unwrap();
         let code = error.get("code").unwrap();
-        assert_eq!(code, -32603);
+        assert_eq!(code, -32002);
+        assert_eq!(error.get("message").unwrap(), "NotFound");
 
         // Create a subaddress
         let body = json!({
@@ -37,7 +37,7 @@ mod e2e_account {
                 "mnemonic": "sheriff odor square mistake huge skate mouse shoot purity weapon proof stuff correct concert blanket neck own shift clay mistake air viable stick group",
                 "key_derivation_version": "2",
                 "name": "Alice Main Account",
-                "first_block_index": "200",
+                "first_block_index": "10",
             }
         });
         let res = dispatch(&client, body, &logger);
@@ -86,7 +86,7 @@ mod e2e_account {
             "params": {
                 "entropy": entropy,
                 "name": "Alice Main Account",
-                "first_block_index": "200",
+                "first_block_index": "10",
             }
         });
         let res = dispatch(&client, body, &logger);
@@ -132,6 +132,20 @@ mod e2e_transaction {
         let memo = res["result"]["gift_code_memo"].as_str().unwrap();
         assert_eq!(memo, "Happy Birthday!");
 
+        // Polling an available gift code returns without waiting
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "poll_gift_code_status",
+            "params": {
+                "gift_code_b58": gift_code_b58,
+                "timeout_seconds": "30",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let status = res["result"]["gift_code_status"].as_str().unwrap();
+        assert_eq!(status, "GiftCodeAvailable");
+
         // Add Bob's account to our wallet
         let body = json!({
             "jsonrpc": "2.0",
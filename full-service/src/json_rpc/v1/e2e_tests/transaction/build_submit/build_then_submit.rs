@@ -80,11 +80,12 @@ mod e2e_transaction {
             json!({
                 "method": "build_transaction",
                 "error": json!({
-                    "code": -32603,
-                    "message": "InternalError",
+                    "code": -32001,
+                    "message": "InsufficientFunds",
                     "data": json!({
                         "server_error": format!("TransactionBuilder(WalletDb(InsufficientFundsUnderMaxSpendable(\"Max spendable value in wallet: 0, but target value: {}\")))", 42 + Mob::MINIMUM_FEE),
                         "details": format!("Error building transaction: Wallet DB Error: Insufficient funds from Txos under max_spendable_value: Max spendable value in wallet: 0, but target value: {}", 42 + Mob::MINIMUM_FEE),
+                        "error_chain": ["TransactionBuilder", "WalletDb", "InsufficientFundsUnderMaxSpendable"],
                     })
                 }),
                 "jsonrpc": "2.0",
@@ -390,4 +391,75 @@ mod e2e_transaction {
             .unwrap();
         assert_eq!(transaction_log_map.len(), 5);
     }
+
+    #[test_with_logger]
+    fn test_build_then_submit_compact_transaction(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, mut ledger_db, db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        // Add an account
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_account",
+            "params": {
+                "name": "Alice Main Account",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let account_obj = result.get("account").unwrap();
+        let account_id = account_obj.get("account_id").unwrap().as_str().unwrap();
+        let b58_public_address = account_obj.get("main_address").unwrap().as_str().unwrap();
+        let public_address = b58_decode_public_address(b58_public_address).unwrap();
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![public_address],
+            100000000000000, // 100.0 MOB
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            &db_ctx.get_db_instance(logger.clone()),
+            &AccountID(account_id.to_string()),
+            &logger,
+        );
+
+        // With the compact flag, only the compact tx proposal is returned
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "build_transaction",
+            "params": {
+                "account_id": account_id,
+                "compact": true,
+                "recipient_public_address": b58_public_address,
+                "value_pmob": "42000000000000", // 42.0 MOB
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        assert!(result.get("tx_proposal").is_none());
+        let compact_tx_proposal = result.get("compact_tx_proposal").unwrap();
+
+        // The compact tx proposal can be submitted as is
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "submit_transaction",
+            "params": {
+                "compact_tx_proposal": compact_tx_proposal,
+                "account_id": account_id,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        assert!(result
+            .get("transaction_log")
+            .unwrap()
+            .get("transaction_log_id")
+            .is_some());
+    }
 }
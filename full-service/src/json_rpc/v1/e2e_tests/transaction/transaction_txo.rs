@@ -154,13 +154,15 @@ mod e2e_transaction {
             3
         );
 
-        // Remove account 1.
+        // Remove account 1. It was never synced after its transaction landed, so
+        // the transaction is still pending from its point of view.
         let body = json!({
             "jsonrpc": "2.0",
             "id": 2,
             "method": "remove_account",
             "params": {
                 "account_id": account_id_1,
+                "force": true,
             }
         });
         let res = dispatch(&client, body, &logger);
@@ -29,8 +29,9 @@ pub struct Balance {
     pub account_block_height: String,
 
     /// Whether the account is synced with the network_block_height. Balances
-    /// may not appear correct if the account is still syncing.
-    pub is_synced: bool,
+    /// may not appear correct if the account is still syncing. Null if the
+    /// network block height is unknown, such as when running offline.
+    pub is_synced: Option<bool>,
 
     /// Unspent pico MOB for this account at the current account_block_height.
     /// If the account is syncing, this value may change.
@@ -77,7 +78,9 @@ impl Balance {
             network_block_height: network_status.network_block_height.to_string(),
             local_block_height: network_status.local_block_height.to_string(),
             account_block_height: account_block_height.to_string(),
-            is_synced: account_block_height == network_status.network_block_height,
+            is_synced: network_status
+                .is_synced
+                .map(|_| account_block_height == network_status.network_block_height),
             unspent_pmob: (balance.unspent + balance.unverified).to_string().into(),
             max_spendable_pmob: balance.max_spendable.to_string().into(),
             pending_pmob: balance.pending.to_string().into(),
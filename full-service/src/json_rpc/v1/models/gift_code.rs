@@ -24,7 +24,8 @@ pub struct GiftCode {
     /// The entropy mnemonic for the account in this gift code.
     pub bip39_entropy: String,
 
-    /// The amount of MOB contained in the gift code account.
+    /// The amount of MOB contained in the gift code account. Empty if the gift
+    /// code was imported before it landed in the ledger.
     pub value_pmob: String,
 
     /// A memo associated with this gift code.
@@ -46,7 +47,7 @@ impl From<&DecodedGiftCode> for GiftCode {
                 .as_ref()
                 .map(hex::encode)
                 .unwrap_or_default(),
-            value_pmob: src.value.to_string(),
+            value_pmob: src.value.map(|value| value.to_string()).unwrap_or_default(),
             memo: src.memo.clone(),
         }
     }
@@ -265,6 +265,7 @@ mod tests {
             txo: txo_details,
             status: txo_status,
             memo: txo_memo,
+            spent_in_transaction_id: None,
         });
         assert_eq!(json_txo.value_pmob, "15625000000000000000");
     }
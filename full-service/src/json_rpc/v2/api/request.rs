@@ -5,8 +5,8 @@
 use crate::json_rpc::{
     json_rpc_request::JsonRPCRequest,
     v2::models::{
-        account_key::FogInfo, amount::Amount, receiver_receipt::ReceiverReceipt,
-        tx_proposal::TxProposal,
+        account::ImportAccountParams, account_key::FogInfo, amount::Amount,
+        receiver_receipt::ReceiverReceipt, tx_proposal::TxProposal,
     },
 };
 
@@ -39,9 +39,20 @@ impl TryFrom<&JsonRPCRequest> for JsonCommandRequest {
 #[serde(tag = "method", content = "params")]
 #[allow(non_camel_case_types)]
 pub enum JsonCommandRequest {
+    abort_transaction {
+        transaction_log_id: String,
+        #[serde(default = "bool::default")] // default is false
+        force: bool,
+    },
     assign_address_for_account {
         account_id: String,
         metadata: Option<String>,
+        customer_tag: Option<String>,
+    },
+    assign_address_for_contact {
+        account_id: String,
+        address_book_entry_id: String,
+        metadata: Option<String>,
     },
     build_and_submit_transaction {
         account_id: String,
@@ -49,6 +60,7 @@ pub enum JsonCommandRequest {
         recipient_public_address: Option<String>,
         amount: Option<Amount>,
         input_txo_ids: Option<Vec<String>>,
+        exclude_txo_ids: Option<Vec<String>>,
         fee_value: Option<String>,
         fee_token_id: Option<String>,
         tombstone_block: Option<String>,
@@ -58,6 +70,7 @@ pub enum JsonCommandRequest {
         sender_memo_credential_subaddress_index: Option<String>,
         payment_request_id: Option<String>,
         spend_subaddress: Option<String>,
+        change_subaddress_index: Option<String>,
     },
     build_burn_transaction {
         account_id: String,
@@ -77,6 +90,7 @@ pub enum JsonCommandRequest {
         recipient_public_address: Option<String>,
         amount: Option<Amount>,
         input_txo_ids: Option<Vec<String>>,
+        exclude_txo_ids: Option<Vec<String>>,
         fee_value: Option<String>,
         fee_token_id: Option<String>,
         tombstone_block: Option<String>,
@@ -85,6 +99,7 @@ pub enum JsonCommandRequest {
         sender_memo_credential_subaddress_index: Option<String>,
         payment_request_id: Option<String>,
         spend_subaddress: Option<String>,
+        change_subaddress_index: Option<String>,
     },
     build_unsigned_burn_transaction {
         account_id: String,
@@ -104,12 +119,14 @@ pub enum JsonCommandRequest {
         recipient_public_address: Option<String>,
         amount: Option<Amount>,
         input_txo_ids: Option<Vec<String>>,
+        exclude_txo_ids: Option<Vec<String>>,
         fee_value: Option<String>,
         fee_token_id: Option<String>,
         tombstone_block: Option<String>,
         max_spendable_value: Option<String>,
         block_version: Option<String>,
         spend_subaddress: Option<String>,
+        change_subaddress_index: Option<String>,
     },
     check_b58_type {
         b58_code: String,
@@ -124,6 +141,10 @@ pub enum JsonCommandRequest {
         #[serde(default = "bool::default")] // default is false
         require_spend_subaddress: bool,
     },
+    create_address_book_entry {
+        public_address_b58: String,
+        name: String,
+    },
     create_payment_request {
         account_id: String,
         subaddress_index: Option<i64>,
@@ -142,13 +163,24 @@ pub enum JsonCommandRequest {
     export_account_secrets {
         account_id: String,
     },
+    export_transaction_log {
+        account_id: String,
+    },
     get_account_status {
         account_id: String,
+        structured_amounts: Option<bool>,
     },
     get_accounts {
         offset: Option<u64>,
         limit: Option<u64>,
     },
+    get_address_book_entries {
+        offset: Option<u64>,
+        limit: Option<u64>,
+    },
+    get_address_book_entry {
+        address_book_entry_id: String,
+    },
     get_address_details {
         address: String,
     },
@@ -169,6 +201,7 @@ pub enum JsonCommandRequest {
     },
     get_balance {
         account_id: String,
+        structured_amounts: Option<bool>,
     },
     get_block {
         block_index: Option<String>,
@@ -184,16 +217,25 @@ pub enum JsonCommandRequest {
     get_confirmations {
         transaction_log_id: String,
     },
+    get_fee_estimate {
+        account_id: String,
+        amount: Amount,
+        input_txo_ids: Option<Vec<String>>,
+    },
+    get_hardware_wallets,
+    get_health,
     get_mc_protocol_transaction {
         transaction_log_id: String,
     },
     get_mc_protocol_txo {
         txo_id: String,
     },
+    get_network_fees,
     get_network_status,
     get_token_metadata,
     get_transaction_log {
         transaction_log_id: String,
+        structured_amounts: Option<bool>,
     },
     get_transaction_logs {
         account_id: Option<String>,
@@ -201,15 +243,25 @@ pub enum JsonCommandRequest {
         max_block_index: Option<String>,
         offset: Option<u64>,
         limit: Option<u64>,
+        structured_amounts: Option<bool>,
     },
     get_txo_block_index {
         public_key: String,
     },
+    get_txo_membership_proof {
+        account_id: String,
+        txo_id: String,
+    },
     get_txo_membership_proofs {
         outputs: Vec<JsonTxOut>,
     },
+    get_txo_provenance {
+        account_id: String,
+        txo_id: String,
+    },
     get_txo {
         txo_id: String,
+        structured_amounts: Option<bool>,
     },
     get_txos {
         account_id: Option<String>,
@@ -218,8 +270,14 @@ pub enum JsonCommandRequest {
         token_id: Option<String>,
         min_received_block_index: Option<String>,
         max_received_block_index: Option<String>,
+        min_value: Option<String>,
+        max_value: Option<String>,
         offset: Option<u64>,
         limit: Option<u64>,
+        structured_amounts: Option<bool>,
+    },
+    get_unused_addresses_for_account {
+        account_id: String,
     },
     get_wallet_status,
     import_account_from_legacy_root_entropy {
@@ -240,6 +298,9 @@ pub enum JsonCommandRequest {
         #[serde(default = "bool::default")] // default is false
         require_spend_subaddress: bool,
     },
+    import_accounts {
+        accounts: Vec<ImportAccountParams>,
+    },
     import_view_only_account {
         view_private_key: String,
         spend_public_key: String,
@@ -255,20 +316,50 @@ pub enum JsonCommandRequest {
         fog_info: Option<FogInfo>,
         #[serde(default = "bool::default")] // default is false
         require_spend_subaddress: bool,
+        account_index: Option<u32>,
     },
     remove_account {
         account_id: String,
+        #[serde(default = "bool::default")] // default is false
+        force: bool,
+    },
+    remove_address_book_entry {
+        address_book_entry_id: String,
     },
     resync_account {
         account_id: String,
     },
+    rotate_change_subaddress {
+        account_id: String,
+    },
     sample_mixins {
         num_mixins: u64,
         excluded_outputs: Vec<JsonTxOut>,
     },
+    search_accounts {
+        query: String,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    },
     search_ledger {
         query: String,
     },
+    set_account_default_fee {
+        account_id: String,
+        default_fee: Option<String>,
+    },
+    set_account_webhook {
+        account_id: String,
+        enabled: bool,
+    },
+    set_address_book_entry {
+        address: String,
+        address_book_entry_id: Option<String>,
+    },
+    set_address_hidden {
+        address: String,
+        hidden: bool,
+    },
     set_require_spend_subaddress {
         account_id: String,
         require_spend_subaddress: bool,
@@ -277,6 +368,12 @@ pub enum JsonCommandRequest {
         tx_proposal: TxProposal,
         comment: Option<String>,
         account_id: Option<String>,
+        idempotency_key: Option<String>,
+    },
+    sync_account_block_range {
+        account_id: String,
+        start_block_index: String,
+        end_block_index: String,
     },
     sync_view_only_account {
         account_id: String,
@@ -286,15 +383,26 @@ pub enum JsonCommandRequest {
         account_id: String,
         name: String,
     },
+    update_address_book_entry {
+        address_book_entry_id: String,
+        public_address_b58: String,
+        name: String,
+    },
     validate_confirmation {
         account_id: String,
         txo_id: String,
         confirmation: String,
     },
+    validate_public_address {
+        address: String,
+    },
     validate_sender_memo {
         txo_id: String,
         sender_address: String,
     },
+    validate_transaction {
+        tx_proposal: TxProposal,
+    },
     verify_address {
         address: String,
     },
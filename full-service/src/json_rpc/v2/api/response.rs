@@ -9,12 +9,15 @@ use crate::{
         json_rpc_request::JsonRPCRequest,
         json_rpc_response::JsonCommandResponse as JsonCommandResponseTrait,
         v2::models::{
-            account::{Account, AccountMap},
+            account::{Account, AccountMap, ImportAccountResult},
             account_secrets::AccountSecrets,
             address::{Address, AddressMap},
-            balance::BalanceMap,
+            address_book_entry::AddressBookEntry,
+            amount::Amount,
+            balance::{BalanceAmounts, BalanceMap},
             block::{Block, BlockContents},
             confirmation_number::Confirmation,
+            health::Health,
             ledger::LedgerSearchResult,
             network_status::NetworkStatus,
             public_address::PublicAddress,
@@ -33,7 +36,7 @@ use mc_mobilecoind_json::data_types::{JsonTx, JsonTxOut, JsonTxOutMembershipProo
 use mc_transaction_signer::types::TxoSyncReq;
 use serde::{Deserialize, Serialize};
 use serde_json::Map;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Responses from the Full Service Wallet.
 #[derive(Deserialize, Serialize, Debug)]
@@ -41,9 +44,15 @@ use std::collections::HashMap;
 #[allow(non_camel_case_types)]
 #[allow(clippy::large_enum_variant)]
 pub enum JsonCommandResponse {
+    abort_transaction {
+        transaction_log: TransactionLog,
+    },
     assign_address_for_account {
         address: Address,
     },
+    assign_address_for_contact {
+        address: Address,
+    },
     build_and_submit_transaction {
         transaction_log: TransactionLog,
         tx_proposal: TxProposal,
@@ -75,6 +84,9 @@ pub enum JsonCommandResponse {
     create_account {
         account: Account,
     },
+    create_address_book_entry {
+        address_book_entry: AddressBookEntry,
+    },
     create_payment_request {
         payment_request_b58: String,
     },
@@ -90,16 +102,27 @@ pub enum JsonCommandResponse {
     export_account_secrets {
         account_secrets: AccountSecrets,
     },
+    export_transaction_log {
+        csv: String,
+    },
     get_account_status {
         account: Account,
         network_block_height: String,
         local_block_height: String,
         balance_per_token: BalanceMap,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        balance_amounts_per_token: Option<BTreeMap<String, BalanceAmounts>>,
     },
     get_accounts {
         account_ids: Vec<String>,
         account_map: AccountMap,
     },
+    get_address_book_entries {
+        address_book_entries: Vec<AddressBookEntry>,
+    },
+    get_address_book_entry {
+        address_book_entry: AddressBookEntry,
+    },
     get_address_details {
         details: PublicAddress,
         address_hash: String,
@@ -140,12 +163,27 @@ pub enum JsonCommandResponse {
     get_confirmations {
         confirmations: Vec<Confirmation>,
     },
+    get_fee_estimate {
+        fee: Amount,
+        num_inputs: String,
+        num_outputs: String,
+        sufficient_funds: bool,
+    },
+    get_hardware_wallets {
+        hardware_wallets: Vec<String>,
+    },
+    get_health {
+        health: Health,
+    },
     get_mc_protocol_transaction {
         transaction: JsonTx,
     },
     get_mc_protocol_txo {
         txo: JsonTxOut,
     },
+    get_network_fees {
+        fees: BTreeMap<String, String>,
+    },
     get_network_status {
         network_status: NetworkStatus,
     },
@@ -166,20 +204,33 @@ pub enum JsonCommandResponse {
     get_txo_block_index {
         block_index: String,
     },
+    get_txo_provenance {
+        transaction_log: Option<TransactionLog>,
+    },
     get_txos {
         txo_ids: Vec<String>,
         txo_map: Map<String, serde_json::Value>,
     },
+    get_txo_membership_proof {
+        membership_proof: JsonTxOutMembershipProof,
+    },
     get_txo_membership_proofs {
         outputs: Vec<JsonTxOut>,
         membership_proofs: Vec<JsonTxOutMembershipProof>,
     },
+    get_unused_addresses_for_account {
+        public_addresses: Vec<String>,
+        address_map: AddressMap,
+    },
     get_wallet_status {
         wallet_status: WalletStatus,
     },
     import_account {
         account: Account,
     },
+    import_accounts {
+        results: Vec<ImportAccountResult>,
+    },
     import_account_from_legacy_root_entropy {
         account: Account,
     },
@@ -192,30 +243,69 @@ pub enum JsonCommandResponse {
     remove_account {
         removed: bool,
     },
+    remove_address_book_entry {
+        removed: bool,
+    },
     resync_account,
+    rotate_change_subaddress {
+        address: Address,
+    },
     sample_mixins {
         mixins: Vec<JsonTxOut>,
         membership_proofs: Vec<JsonTxOutMembershipProof>,
     },
+    search_accounts {
+        account_ids: Vec<String>,
+        account_map: AccountMap,
+    },
     search_ledger {
         results: Vec<LedgerSearchResult>,
     },
+    set_account_default_fee {
+        account: Account,
+    },
+    set_account_webhook {
+        account: Account,
+    },
+    set_address_book_entry {
+        address: Address,
+    },
+    set_address_hidden {
+        address: Address,
+    },
     set_require_spend_subaddress {
         account: Account,
     },
     submit_transaction {
         transaction_log: Option<TransactionLog>,
     },
+    sync_account_block_range {
+        num_txos_received: String,
+    },
     sync_view_only_account,
     update_account_name {
         account: Account,
     },
+    update_address_book_entry {
+        address_book_entry: AddressBookEntry,
+    },
     validate_confirmation {
         validated: bool,
     },
+    validate_public_address {
+        valid: bool,
+        fog_enabled: bool,
+        view_public_key_fingerprint: Option<String>,
+        spend_public_key_fingerprint: Option<String>,
+        error: Option<String>,
+    },
     validate_sender_memo {
         validated: bool,
     },
+    validate_transaction {
+        valid: bool,
+        problems: Vec<String>,
+    },
     verify_address {
         verified: bool,
         address_hash: Option<String>,
@@ -9,7 +9,7 @@ use crate::{
             request::JsonCommandRequest, response::JsonCommandResponse, wallet::wallet_api_inner,
         },
     },
-    service::{t3_sync::T3Config, WalletService},
+    service::{sync::DEFAULT_BLOCKS_CHUNK_SIZE, t3_sync::T3Config, WalletService},
     test_utils::{
         get_resolver_factory, get_test_ledger, setup_peer_manager_and_network_state,
         WalletDbTestContext,
@@ -181,6 +181,8 @@ pub fn create_test_setup(
         false,
         T3Config::default(),
         webhook_config,
+        DEFAULT_BLOCKS_CHUNK_SIZE,
+        None,
         logger,
     );
 
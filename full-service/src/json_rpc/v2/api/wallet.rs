@@ -13,12 +13,15 @@ use crate::{
         v2::{
             api::{request::JsonCommandRequest, response::JsonCommandResponse},
             models::{
-                account::{Account, AccountMap},
+                account::{Account, AccountMap, ImportAccountResult},
                 account_secrets::AccountSecrets,
                 address::{Address, AddressMap},
-                balance::{Balance, BalanceMap},
+                address_book_entry::AddressBookEntry,
+                amount::Amount as AmountJSON,
+                balance::{Balance, BalanceAmounts, BalanceMap},
                 block::{Block, BlockContents},
                 confirmation_number::Confirmation,
+                health::Health,
                 network_status::NetworkStatus,
                 public_address::PublicAddress,
                 receiver_receipt::ReceiverReceipt,
@@ -32,11 +35,13 @@ use crate::{
     },
     service::{
         self,
-        account::AccountService,
+        account::{AccountService, ImportAccountRequest},
         address::AddressService,
+        address_book::AddressBookService,
         balance::BalanceService,
         confirmation_number::ConfirmationService,
-        hardware_wallet::sync_txos,
+        hardware_wallet::{hardware_account_index, list_hardware_wallets, sync_txos},
+        health::HealthService,
         ledger::LedgerService,
         memo::MemoService,
         models::tx_proposal::TxProposal,
@@ -79,6 +84,15 @@ pub const RECENT_BLOCKS_DEFAULT_LIMIT: usize = 10;
 /// Maximal amount of blocks we can return in a single request
 pub const MAX_BLOCKS_PER_REQUEST: usize = 100;
 
+/// Number of leading bytes of a public key shown in its fingerprint
+pub const PUBLIC_KEY_FINGERPRINT_LEN: usize = 4;
+
+/// A short, human comparable fingerprint of a public key: the hex encoding of
+/// its leading bytes.
+fn public_key_fingerprint(public_key_bytes: &[u8]) -> String {
+    hex::encode(&public_key_bytes[..PUBLIC_KEY_FINGERPRINT_LEN])
+}
+
 pub async fn generic_wallet_api<T, FPR>(
     _api_key_guard: ApiKeyGuard,
     state: &rocket::State<WalletState<T, FPR>>,
@@ -123,6 +137,37 @@ where
     Ok(Json(response))
 }
 
+/// The JSON Account for each of the given accounts, keyed by account id.
+fn account_map<T, FPR>(
+    service: &WalletService<T, FPR>,
+    accounts: &[crate::db::models::Account],
+) -> Result<AccountMap, JsonRPCError>
+where
+    T: BlockchainConnection + UserTxConnection + 'static,
+    FPR: FogPubkeyResolver + Send + Sync + 'static,
+{
+    Ok(AccountMap(
+        accounts
+            .iter()
+            .map(|a| {
+                let next_subaddress_index = service
+                    .get_next_subaddress_index_for_account(&AccountID(a.id.clone()))
+                    .map_err(format_error)?;
+                let main_public_address: mc_account_keys::PublicAddress = (&service
+                    .get_address_for_account(&a.id.clone().into(), DEFAULT_SUBADDRESS_INDEX as i64)
+                    .map_err(format_error)?)
+                    .try_into()
+                    .map_err(format_error)?;
+                Ok((
+                    a.id.to_string(),
+                    Account::new(a, &main_public_address, next_subaddress_index)
+                        .map_err(format_error)?,
+                ))
+            })
+            .collect::<Result<_, _>>()?,
+    ))
+}
+
 /// The Wallet API inner method, which handles switching on the method enum.
 ///
 /// Note that this is structured this way so that the routes can be defined to
@@ -148,22 +193,65 @@ where
     }
 
     let response = match command {
+        JsonCommandRequest::abort_transaction {
+            transaction_log_id,
+            force,
+        } => {
+            let (transaction_log, associated_txos, value_map) = service
+                .abort_transaction(&transaction_log_id, force)
+                .map_err(format_error)?;
+            JsonCommandResponse::abort_transaction {
+                transaction_log: TransactionLog::new(
+                    &transaction_log,
+                    &associated_txos,
+                    &value_map,
+                ),
+            }
+        }
         JsonCommandRequest::assign_address_for_account {
             account_id,
             metadata,
-        } => JsonCommandResponse::assign_address_for_account {
-            address: Address::from(
-                &service
-                    .assign_address_for_account(&AccountID(account_id), metadata.as_deref())
-                    .map_err(format_error)?,
-            ),
-        },
+            customer_tag,
+        } => {
+            let account_id = AccountID(account_id);
+            let assigned_subaddress = match customer_tag {
+                Some(customer_tag) => service.assign_tracked_address_for_account(
+                    &account_id,
+                    &customer_tag,
+                    metadata.as_deref(),
+                ),
+                None => service.assign_address_for_account(&account_id, metadata.as_deref()),
+            }
+            .map_err(format_error)?;
+            JsonCommandResponse::assign_address_for_account {
+                address: Address::from(&assigned_subaddress),
+            }
+        }
+        JsonCommandRequest::assign_address_for_contact {
+            account_id,
+            address_book_entry_id,
+            metadata,
+        } => {
+            let address_book_entry_id =
+                address_book_entry_id.parse::<i32>().map_err(format_error)?;
+            let assigned_subaddress = service
+                .assign_address_for_contact(
+                    &AccountID(account_id),
+                    address_book_entry_id,
+                    metadata.as_deref(),
+                )
+                .map_err(format_error)?;
+            JsonCommandResponse::assign_address_for_contact {
+                address: Address::from(&assigned_subaddress),
+            }
+        }
         JsonCommandRequest::build_and_submit_transaction {
             account_id,
             addresses_and_amounts,
             recipient_public_address,
             amount,
             input_txo_ids,
+            exclude_txo_ids,
             fee_value,
             fee_token_id,
             tombstone_block,
@@ -173,6 +261,7 @@ where
             sender_memo_credential_subaddress_index,
             payment_request_id,
             spend_subaddress,
+            change_subaddress_index,
         } => {
             // The user can specify a list of addresses and values,
             // or a single address and a single value.
@@ -212,6 +301,7 @@ where
                     &account_id,
                     &addresses_and_amounts,
                     input_txo_ids.as_ref(),
+                    exclude_txo_ids.as_ref(),
                     fee_value,
                     fee_token_id,
                     tombstone_block,
@@ -220,6 +310,7 @@ where
                     transaction_memo,
                     block_version,
                     spend_subaddress,
+                    change_subaddress_index,
                 )
                 .await
                 .map_err(format_error)?;
@@ -273,6 +364,7 @@ where
                         amount,
                     )],
                     input_txo_ids.as_ref(),
+                    None,
                     fee_value,
                     fee_token_id,
                     tombstone_block,
@@ -280,6 +372,7 @@ where
                     TransactionMemo::BurnRedemption(memo_data),
                     block_version,
                     spend_subaddress,
+                    None,
                 )
                 .await
                 .map_err(format_error)?;
@@ -297,6 +390,7 @@ where
             recipient_public_address,
             amount,
             input_txo_ids,
+            exclude_txo_ids,
             fee_value,
             fee_token_id,
             tombstone_block,
@@ -305,6 +399,7 @@ where
             sender_memo_credential_subaddress_index,
             payment_request_id,
             spend_subaddress,
+            change_subaddress_index,
         } => {
             // The user can specify a list of addresses and values,
             // or a single address and a single value.
@@ -344,6 +439,7 @@ where
                     &account_id,
                     &addresses_and_amounts,
                     input_txo_ids.as_ref(),
+                    exclude_txo_ids.as_ref(),
                     fee_value,
                     fee_token_id,
                     tombstone_block,
@@ -351,6 +447,7 @@ where
                     transaction_memo,
                     block_version,
                     spend_subaddress,
+                    change_subaddress_index,
                 )
                 .await
                 .map_err(format_error)?;
@@ -402,6 +499,7 @@ where
                         amount,
                     )],
                     input_txo_ids.as_ref(),
+                    None,
                     fee_value,
                     fee_token_id,
                     tombstone_block,
@@ -409,6 +507,7 @@ where
                     TransactionMemo::BurnRedemption(memo_data),
                     block_version,
                     spend_subaddress,
+                    None,
                 )
                 .map_err(format_error)?)
                 .try_into()
@@ -428,9 +527,11 @@ where
             tombstone_block,
             addresses_and_amounts,
             input_txo_ids,
+            exclude_txo_ids,
             max_spendable_value,
             block_version,
             spend_subaddress,
+            change_subaddress_index,
         } => {
             let mut addresses_and_amounts = addresses_and_amounts.unwrap_or_default();
             if let (Some(address), Some(amount)) = (recipient_public_address, amount) {
@@ -450,6 +551,7 @@ where
                     &account_id,
                     &addresses_and_amounts,
                     input_txo_ids.as_ref(),
+                    exclude_txo_ids.as_ref(),
                     fee_value,
                     fee_token_id,
                     tombstone_block,
@@ -457,6 +559,7 @@ where
                     TransactionMemo::Empty,
                     block_version,
                     spend_subaddress,
+                    change_subaddress_index,
                 )
                 .map_err(format_error)?)
                 .try_into()
@@ -542,6 +645,17 @@ where
 
             JsonCommandResponse::create_account { account }
         }
+        JsonCommandRequest::create_address_book_entry {
+            public_address_b58,
+            name,
+        } => {
+            let address_book_entry = service
+                .create_address_book_entry(&public_address_b58, &name)
+                .map_err(format_error)?;
+            JsonCommandResponse::create_address_book_entry {
+                address_book_entry: AddressBookEntry::from(&address_book_entry),
+            }
+        }
         JsonCommandRequest::create_payment_request {
             account_id,
             subaddress_index,
@@ -590,6 +704,8 @@ where
                     None,
                     None,
                     None,
+                    None,
+                    None,
                 )
                 .map_err(format_error)?;
 
@@ -626,8 +742,21 @@ where
                 account_secrets: AccountSecrets::try_from(&account).map_err(format_error)?,
             }
         }
-        JsonCommandRequest::get_account_status { account_id }
-        | JsonCommandRequest::get_balance { account_id } => {
+        JsonCommandRequest::export_transaction_log { account_id } => {
+            JsonCommandResponse::export_transaction_log {
+                csv: service
+                    .export_transactions_csv(&AccountID(account_id))
+                    .map_err(format_error)?,
+            }
+        }
+        JsonCommandRequest::get_account_status {
+            account_id,
+            structured_amounts,
+        }
+        | JsonCommandRequest::get_balance {
+            account_id,
+            structured_amounts,
+        } => {
             let account = service
                 .get_account(&AccountID(account_id.clone()))
                 .map_err(format_error)?;
@@ -660,43 +789,26 @@ where
                     .map(|(k, v)| (k.to_string(), Balance::from(v)))
                     .collect(),
             );
+            let balance_amounts = structured_amounts.unwrap_or(false).then(|| {
+                balance
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), BalanceAmounts::new(v, *k)))
+                    .collect()
+            });
 
             JsonCommandResponse::get_account_status {
                 account,
                 network_block_height: network_status.network_block_height.to_string(),
                 local_block_height: network_status.local_block_height.to_string(),
                 balance_per_token: balance_formatted,
+                balance_amounts_per_token: balance_amounts,
             }
         }
         JsonCommandRequest::get_accounts { offset, limit } => {
             let accounts = service.list_accounts(offset, limit).map_err(format_error)?;
-            let account_map = AccountMap(
-                accounts
-                    .iter()
-                    .map(|a| {
-                        let next_subaddress_index = service
-                            .get_next_subaddress_index_for_account(&AccountID(a.id.clone()))
-                            .map_err(format_error)?;
-                        let main_public_address: mc_account_keys::PublicAddress = (&service
-                            .get_address_for_account(
-                                &a.id.clone().into(),
-                                DEFAULT_SUBADDRESS_INDEX as i64,
-                            )
-                            .map_err(format_error)?)
-                            .try_into()
-                            .map_err(format_error)?;
-                        Ok((
-                            a.id.to_string(),
-                            Account::new(a, &main_public_address, next_subaddress_index)
-                                .map_err(format_error)?,
-                        ))
-                    })
-                    .collect::<Result<_, _>>()?,
-            );
-
             JsonCommandResponse::get_accounts {
                 account_ids: accounts.iter().map(|a| a.id.clone()).collect(),
-                account_map,
+                account_map: account_map(service, &accounts)?,
             }
         }
         JsonCommandRequest::get_address { public_address_b58 } => {
@@ -707,6 +819,29 @@ where
                 address: Address::from(&assigned_address),
             }
         }
+        JsonCommandRequest::get_address_book_entries { offset, limit } => {
+            let address_book_entries = service
+                .list_address_book_entries(offset, limit)
+                .map_err(format_error)?;
+            JsonCommandResponse::get_address_book_entries {
+                address_book_entries: address_book_entries
+                    .iter()
+                    .map(AddressBookEntry::from)
+                    .collect(),
+            }
+        }
+        JsonCommandRequest::get_address_book_entry {
+            address_book_entry_id,
+        } => {
+            let address_book_entry_id =
+                address_book_entry_id.parse::<i32>().map_err(format_error)?;
+            let address_book_entry = service
+                .get_address_book_entry(address_book_entry_id)
+                .map_err(format_error)?;
+            JsonCommandResponse::get_address_book_entry {
+                address_book_entry: AddressBookEntry::from(&address_book_entry),
+            }
+        }
         JsonCommandRequest::get_address_details { address } => {
             let address = service.verify_address(&address).map_err(format_error)?;
 
@@ -908,6 +1043,27 @@ where
                     .collect(),
             }
         }
+        JsonCommandRequest::get_fee_estimate {
+            account_id,
+            amount,
+            input_txo_ids,
+        } => {
+            let estimate = service
+                .estimate_fee(&account_id, &amount, input_txo_ids.as_ref())
+                .map_err(format_error)?;
+            JsonCommandResponse::get_fee_estimate {
+                fee: AmountJSON::from(&estimate.fee),
+                num_inputs: estimate.num_inputs.to_string(),
+                num_outputs: estimate.num_outputs.to_string(),
+                sufficient_funds: estimate.sufficient_funds,
+            }
+        }
+        JsonCommandRequest::get_hardware_wallets => JsonCommandResponse::get_hardware_wallets {
+            hardware_wallets: list_hardware_wallets().await.map_err(format_error)?,
+        },
+        JsonCommandRequest::get_health => JsonCommandResponse::get_health {
+            health: Health::from(&service.get_health()),
+        },
         JsonCommandRequest::get_mc_protocol_transaction { transaction_log_id } => {
             let tx = service
                 .get_transaction_object(&transaction_log_id)
@@ -924,6 +1080,14 @@ where
             let json_txo = JsonTxOut::from(&proto_txo);
             JsonCommandResponse::get_mc_protocol_txo { txo: json_txo }
         }
+        JsonCommandRequest::get_network_fees => JsonCommandResponse::get_network_fees {
+            fees: service
+                .get_network_fees()
+                .map_err(format_error)?
+                .iter()
+                .map(|(token_id, fee)| (token_id.to_string(), fee.to_string()))
+                .collect(),
+        },
         JsonCommandRequest::get_network_status => JsonCommandResponse::get_network_status {
             network_status: NetworkStatus::try_from(
                 &service.get_network_status().map_err(format_error)?,
@@ -937,17 +1101,19 @@ where
                 metadata: metadata_info.metadata,
             }
         }
-        JsonCommandRequest::get_transaction_log { transaction_log_id } => {
+        JsonCommandRequest::get_transaction_log {
+            transaction_log_id,
+            structured_amounts,
+        } => {
             let (transaction_log, associated_txos, value_map) = service
                 .get_transaction_log(&transaction_log_id)
                 .map_err(format_error)?;
-            JsonCommandResponse::get_transaction_log {
-                transaction_log: TransactionLog::new(
-                    &transaction_log,
-                    &associated_txos,
-                    &value_map,
-                ),
+            let mut transaction_log =
+                TransactionLog::new(&transaction_log, &associated_txos, &value_map);
+            if structured_amounts.unwrap_or(false) {
+                transaction_log = transaction_log.with_amounts();
             }
+            JsonCommandResponse::get_transaction_log { transaction_log }
         }
         JsonCommandRequest::get_transaction_logs {
             account_id,
@@ -955,6 +1121,7 @@ where
             max_block_index,
             offset,
             limit,
+            structured_amounts,
         } => {
             let min_block_index = min_block_index
                 .map(|i| i.parse::<u64>())
@@ -974,9 +1141,13 @@ where
                 transaction_logs_and_txos
                     .iter()
                     .map(|(t, a, v)| {
+                        let mut transaction_log = TransactionLog::new(t, a, v);
+                        if structured_amounts.unwrap_or(false) {
+                            transaction_log = transaction_log.with_amounts();
+                        }
                         (
                             t.id.clone(),
-                            serde_json::to_value(TransactionLog::new(t, a, v))
+                            serde_json::to_value(transaction_log)
                                 .expect("Could not get json value"),
                         )
                     })
@@ -991,11 +1162,16 @@ where
                 transaction_log_map,
             }
         }
-        JsonCommandRequest::get_txo { txo_id } => {
+        JsonCommandRequest::get_txo {
+            txo_id,
+            structured_amounts,
+        } => {
             let txo_info = service.get_txo(&TxoID(txo_id)).map_err(format_error)?;
-            JsonCommandResponse::get_txo {
-                txo: (&txo_info).into(),
+            let mut txo = Txo::from(&txo_info);
+            if structured_amounts.unwrap_or(false) {
+                txo = txo.with_amount();
             }
+            JsonCommandResponse::get_txo { txo }
         }
         JsonCommandRequest::get_txo_block_index { public_key } => {
             let public_key_bytes = hex::decode(public_key).map_err(format_error)?;
@@ -1017,8 +1193,11 @@ where
             token_id,
             min_received_block_index,
             max_received_block_index,
+            min_value,
+            max_value,
             offset,
             limit,
+            structured_amounts,
         } => {
             let status = match status {
                 Some(s) => Some(TxoStatus::from_str(&s).map_err(format_error)?),
@@ -1040,6 +1219,16 @@ where
                 None => None,
             };
 
+            let min_value = min_value
+                .map(|v| v.parse::<u64>())
+                .transpose()
+                .map_err(format_error)?;
+
+            let max_value = max_value
+                .map(|v| v.parse::<u64>())
+                .transpose()
+                .map_err(format_error)?;
+
             let txos_and_statuses = service
                 .list_txos(
                     account_id,
@@ -1048,6 +1237,8 @@ where
                     token_id,
                     min_received_block_index,
                     max_received_block_index,
+                    min_value,
+                    max_value,
                     offset,
                     limit,
                 )
@@ -1057,10 +1248,13 @@ where
                 txos_and_statuses
                     .iter()
                     .map(|txo_info| {
+                        let mut txo = Txo::from(txo_info);
+                        if structured_amounts.unwrap_or(false) {
+                            txo = txo.with_amount();
+                        }
                         (
                             txo_info.txo.id.clone(),
-                            serde_json::to_value(Txo::from(txo_info))
-                                .expect("Could not get json value"),
+                            serde_json::to_value(txo).expect("Could not get json value"),
                         )
                     })
                     .collect::<Vec<(String, serde_json::Value)>>(),
@@ -1074,6 +1268,26 @@ where
                 txo_map,
             }
         }
+        JsonCommandRequest::get_txo_provenance { account_id, txo_id } => {
+            let provenance = service
+                .get_txo_provenance(&AccountID(account_id), &TxoID(txo_id))
+                .map_err(format_error)?;
+            JsonCommandResponse::get_txo_provenance {
+                transaction_log: provenance.map(|(transaction_log, associated_txos, value_map)| {
+                    TransactionLog::new(&transaction_log, &associated_txos, &value_map)
+                }),
+            }
+        }
+        JsonCommandRequest::get_txo_membership_proof { account_id, txo_id } => {
+            let proof = service
+                .get_txo_membership_proof(&AccountID(account_id), &TxoID(txo_id))
+                .map_err(format_error)?;
+            let proof: mc_api::external::TxOutMembershipProof =
+                (&proof).try_into().map_err(format_error)?;
+            JsonCommandResponse::get_txo_membership_proof {
+                membership_proof: JsonTxOutMembershipProof::from(&proof),
+            }
+        }
         JsonCommandRequest::get_txo_membership_proofs { outputs } => {
             let public_keys = outputs
                 .clone()
@@ -1108,6 +1322,26 @@ where
                 membership_proofs,
             }
         }
+        JsonCommandRequest::get_unused_addresses_for_account { account_id } => {
+            let addresses = service
+                .list_unused_subaddresses(&AccountID(account_id))
+                .map_err(format_error)?;
+
+            let address_map = AddressMap(
+                addresses
+                    .iter()
+                    .map(|a| (a.public_address_b58.clone(), Address::from(a)))
+                    .collect(),
+            );
+
+            JsonCommandResponse::get_unused_addresses_for_account {
+                public_addresses: addresses
+                    .iter()
+                    .map(|a| a.public_address_b58.clone())
+                    .collect(),
+                address_map,
+            }
+        }
         JsonCommandRequest::get_wallet_status => JsonCommandResponse::get_wallet_status {
             wallet_status: WalletStatus::try_from(
                 &service.get_wallet_status().map_err(format_error)?,
@@ -1163,6 +1397,61 @@ where
 
             JsonCommandResponse::import_account { account }
         }
+        JsonCommandRequest::import_accounts { accounts } => {
+            // Entries with malformed params are reported in their result, the same
+            // as entries which fail to import, so that the rest are still imported.
+            let requests = accounts
+                .into_iter()
+                .map(ImportAccountRequest::try_from)
+                .collect::<Vec<_>>();
+            let mut imported = service
+                .import_accounts(requests.iter().filter_map(|r| r.clone().ok()).collect())
+                .into_iter();
+
+            let mut results = Vec::new();
+            for request in requests {
+                let imported_account = match request {
+                    Ok(_) => imported
+                        .next()
+                        .ok_or_else(|| format_error("Missing result for imported account"))?
+                        .map_err(|e| e.to_string()),
+                    Err(e) => Err(e),
+                };
+
+                let result = match imported_account {
+                    Ok(account) => {
+                        let next_subaddress_index = service
+                            .get_next_subaddress_index_for_account(&AccountID(account.id.clone()))
+                            .map_err(format_error)?;
+
+                        let main_public_address: mc_account_keys::PublicAddress = (&service
+                            .get_address_for_account(
+                                &account.id.clone().into(),
+                                DEFAULT_SUBADDRESS_INDEX as i64,
+                            )
+                            .map_err(format_error)?)
+                            .try_into()
+                            .map_err(format_error)?;
+
+                        let account =
+                            Account::new(&account, &main_public_address, next_subaddress_index)
+                                .map_err(format_error)?;
+
+                        ImportAccountResult {
+                            account: Some(account),
+                            error: None,
+                        }
+                    }
+                    Err(e) => ImportAccountResult {
+                        account: None,
+                        error: Some(e),
+                    },
+                };
+                results.push(result);
+            }
+
+            JsonCommandResponse::import_accounts { results }
+        }
         JsonCommandRequest::import_account_from_legacy_root_entropy {
             entropy,
             name,
@@ -1272,6 +1561,7 @@ where
             first_block_index,
             fog_info,
             require_spend_subaddress,
+            account_index,
         } => {
             let fb = first_block_index
                 .map(|fb| fb.parse::<u64>())
@@ -1284,6 +1574,7 @@ where
                     fb,
                     fog_info,
                     require_spend_subaddress,
+                    account_index,
                 )
                 .await
                 .map_err(format_error)?;
@@ -1304,11 +1595,24 @@ where
 
             JsonCommandResponse::import_view_only_account_from_hardware_wallet { account }
         }
-        JsonCommandRequest::remove_account { account_id } => JsonCommandResponse::remove_account {
-            removed: service
-                .remove_account(&AccountID(account_id))
-                .map_err(format_error)?,
-        },
+        JsonCommandRequest::remove_account { account_id, force } => {
+            JsonCommandResponse::remove_account {
+                removed: service
+                    .remove_account(&AccountID(account_id), force)
+                    .map_err(format_error)?,
+            }
+        }
+        JsonCommandRequest::remove_address_book_entry {
+            address_book_entry_id,
+        } => {
+            let address_book_entry_id =
+                address_book_entry_id.parse::<i32>().map_err(format_error)?;
+            JsonCommandResponse::remove_address_book_entry {
+                removed: service
+                    .remove_address_book_entry(address_book_entry_id)
+                    .map_err(format_error)?,
+            }
+        }
         JsonCommandRequest::resync_account { account_id } => {
             service
                 .resync_account(&AccountID(account_id))
@@ -1316,6 +1620,14 @@ where
 
             JsonCommandResponse::resync_account
         }
+        JsonCommandRequest::rotate_change_subaddress { account_id } => {
+            let assigned_subaddress = service
+                .rotate_change_subaddress(&AccountID(account_id))
+                .map_err(format_error)?;
+            JsonCommandResponse::rotate_change_subaddress {
+                address: Address::from(&assigned_subaddress),
+            }
+        }
         JsonCommandRequest::sample_mixins {
             num_mixins,
             excluded_outputs,
@@ -1363,12 +1675,96 @@ where
                 membership_proofs,
             }
         }
+        JsonCommandRequest::search_accounts {
+            query,
+            offset,
+            limit,
+        } => {
+            let accounts = service
+                .search_accounts(&query, offset, limit)
+                .map_err(format_error)?;
+            JsonCommandResponse::search_accounts {
+                account_ids: accounts.iter().map(|a| a.id.clone()).collect(),
+                account_map: account_map(service, &accounts)?,
+            }
+        }
         JsonCommandRequest::search_ledger { query } => {
             let results = service.search_ledger(&query).map_err(format_error)?;
             JsonCommandResponse::search_ledger {
                 results: results.iter().map(Into::into).collect(),
             }
         }
+        JsonCommandRequest::set_account_default_fee {
+            account_id,
+            default_fee,
+        } => {
+            let default_fee = match default_fee {
+                Some(fee) => Some(fee.parse::<u64>().map_err(format_error)?),
+                None => None,
+            };
+            let account = service
+                .set_account_default_fee(&AccountID(account_id), default_fee)
+                .map_err(format_error)?;
+            let next_subaddress_index = service
+                .get_next_subaddress_index_for_account(&AccountID(account.id.clone()))
+                .map_err(format_error)?;
+            let main_public_address: mc_account_keys::PublicAddress = (&service
+                .get_address_for_account(
+                    &account.id.clone().into(),
+                    DEFAULT_SUBADDRESS_INDEX as i64,
+                )
+                .map_err(format_error)?)
+                .try_into()
+                .map_err(format_error)?;
+            let account = Account::new(&account, &main_public_address, next_subaddress_index)
+                .map_err(format_error)?;
+            JsonCommandResponse::set_account_default_fee { account }
+        }
+        JsonCommandRequest::set_account_webhook {
+            account_id,
+            enabled,
+        } => {
+            let account = service
+                .set_account_webhook(&AccountID(account_id), enabled)
+                .map_err(format_error)?;
+            let next_subaddress_index = service
+                .get_next_subaddress_index_for_account(&AccountID(account.id.clone()))
+                .map_err(format_error)?;
+            let main_public_address: mc_account_keys::PublicAddress = (&service
+                .get_address_for_account(
+                    &account.id.clone().into(),
+                    DEFAULT_SUBADDRESS_INDEX as i64,
+                )
+                .map_err(format_error)?)
+                .try_into()
+                .map_err(format_error)?;
+            let account = Account::new(&account, &main_public_address, next_subaddress_index)
+                .map_err(format_error)?;
+            JsonCommandResponse::set_account_webhook { account }
+        }
+        JsonCommandRequest::set_address_book_entry {
+            address,
+            address_book_entry_id,
+        } => {
+            let address_book_entry_id = address_book_entry_id
+                .map(|id| id.parse::<i32>())
+                .transpose()
+                .map_err(format_error)?;
+            let assigned_subaddress = service
+                .set_address_book_entry(&address, address_book_entry_id)
+                .map_err(format_error)?;
+            JsonCommandResponse::set_address_book_entry {
+                address: Address::from(&assigned_subaddress),
+            }
+        }
+        JsonCommandRequest::set_address_hidden { address, hidden } => {
+            let assigned_subaddress = service
+                .set_address_hidden(&address, hidden)
+                .map_err(format_error)?;
+            JsonCommandResponse::set_address_hidden {
+                address: Address::from(&assigned_subaddress),
+            }
+        }
         JsonCommandRequest::set_require_spend_subaddress {
             account_id,
             require_spend_subaddress,
@@ -1396,10 +1792,11 @@ where
             tx_proposal,
             comment,
             account_id,
+            idempotency_key,
         } => {
             let tx_proposal = TxProposal::try_from(&tx_proposal).map_err(format_error)?;
             let result: Option<TransactionLog> = service
-                .submit_transaction(&tx_proposal, comment, account_id)
+                .submit_transaction(&tx_proposal, comment, account_id, idempotency_key)
                 .map_err(format_error)?
                 .map(|(transaction_log, associated_txos, value_map)| {
                     TransactionLog::new(&transaction_log, &associated_txos, &value_map)
@@ -1408,6 +1805,23 @@ where
                 transaction_log: result,
             }
         }
+        JsonCommandRequest::sync_account_block_range {
+            account_id,
+            start_block_index,
+            end_block_index,
+        } => {
+            let num_txos_received = service
+                .sync_account_block_range(
+                    &AccountID(account_id),
+                    start_block_index.parse::<u64>().map_err(format_error)?,
+                    end_block_index.parse::<u64>().map_err(format_error)?,
+                )
+                .map_err(format_error)?;
+
+            JsonCommandResponse::sync_account_block_range {
+                num_txos_received: num_txos_received.to_string(),
+            }
+        }
         JsonCommandRequest::sync_view_only_account {
             account_id,
             synced_txos,
@@ -1430,6 +1844,8 @@ where
                             None,
                             None,
                             None,
+                            None,
+                            None,
                         )
                         .map_err(format_error)?;
 
@@ -1449,9 +1865,14 @@ where
                         })
                         .collect::<Result<Vec<_>, JsonRPCError>>()?;
 
-                    sync_txos(unsynced_txos, &view_account_keys)
-                        .await
-                        .map_err(format_error)?
+                    sync_txos(
+                        unsynced_txos,
+                        &view_account_keys,
+                        hardware_account_index(&account),
+                        &service.hardware_wallet_config,
+                    )
+                    .await
+                    .map_err(format_error)?
                 }
             };
 
@@ -1481,6 +1902,20 @@ where
                 .map_err(format_error)?;
             JsonCommandResponse::update_account_name { account }
         }
+        JsonCommandRequest::update_address_book_entry {
+            address_book_entry_id,
+            public_address_b58,
+            name,
+        } => {
+            let address_book_entry_id =
+                address_book_entry_id.parse::<i32>().map_err(format_error)?;
+            let address_book_entry = service
+                .update_address_book_entry(address_book_entry_id, &public_address_b58, &name)
+                .map_err(format_error)?;
+            JsonCommandResponse::update_address_book_entry {
+                address_book_entry: AddressBookEntry::from(&address_book_entry),
+            }
+        }
         JsonCommandRequest::validate_confirmation {
             account_id,
             txo_id,
@@ -1491,6 +1926,28 @@ where
                 .map_err(format_error)?;
             JsonCommandResponse::validate_confirmation { validated: result }
         }
+        JsonCommandRequest::validate_public_address { address } => {
+            match service.verify_address(&address) {
+                Ok(public_address) => JsonCommandResponse::validate_public_address {
+                    valid: true,
+                    fog_enabled: public_address.fog_report_url().is_some(),
+                    view_public_key_fingerprint: Some(public_key_fingerprint(
+                        &public_address.view_public_key().to_bytes(),
+                    )),
+                    spend_public_key_fingerprint: Some(public_key_fingerprint(
+                        &public_address.spend_public_key().to_bytes(),
+                    )),
+                    error: None,
+                },
+                Err(e) => JsonCommandResponse::validate_public_address {
+                    valid: false,
+                    fog_enabled: false,
+                    view_public_key_fingerprint: None,
+                    spend_public_key_fingerprint: None,
+                    error: Some(e.to_string()),
+                },
+            }
+        }
         JsonCommandRequest::validate_sender_memo {
             txo_id,
             sender_address,
@@ -1500,6 +1957,16 @@ where
                 .map_err(format_error)?;
             JsonCommandResponse::validate_sender_memo { validated: result }
         }
+        JsonCommandRequest::validate_transaction { tx_proposal } => {
+            let tx_proposal = TxProposal::try_from(&tx_proposal).map_err(format_error)?;
+            let problems = service
+                .validate_transaction(&tx_proposal)
+                .map_err(format_error)?;
+            JsonCommandResponse::validate_transaction {
+                valid: problems.is_empty(),
+                problems: problems.iter().map(|p| p.to_string()).collect(),
+            }
+        }
         JsonCommandRequest::verify_address { address } => match service.verify_address(&address) {
             Ok(public_address) => JsonCommandResponse::verify_address {
                 verified: true,
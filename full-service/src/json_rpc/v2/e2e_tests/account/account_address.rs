@@ -11,9 +11,10 @@ mod e2e_account {
             models::public_address::PublicAddress,
         },
         test_utils::{add_block_to_ledger_db, manually_sync_account},
-        util::b58::b58_decode_public_address,
+        util::b58::{b58_decode_public_address, b58_encode_public_address},
     };
 
+    use mc_account_keys::AccountKey;
     use mc_common::logger::{test_with_logger, Logger};
     use mc_ledger_db::Ledger;
     use mc_rand::rand_core::RngCore;
@@ -444,7 +445,8 @@ mod e2e_account {
         let res = dispatch(&client, body, &logger);
         let error = res.get("error").unwrap();
         let code = error.get("code").unwrap();
-        assert_eq!(code, -32603);
+        assert_eq!(code, -32002);
+        assert_eq!(error.get("message").unwrap(), "NotFound");
 
         // Create a subaddress
         let body = json!({
@@ -518,6 +520,78 @@ mod e2e_account {
         assert!(result);
     }
 
+    #[test_with_logger]
+    fn test_validate_public_address(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, _ledger_db, _db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        // Invalid b58 is reported without an error response
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "validate_public_address",
+            "params": {
+                "address": "NOTVALIDB58",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = &res["result"];
+        assert!(!result["valid"].as_bool().unwrap());
+        assert!(result["view_public_key_fingerprint"].is_null());
+        assert!(result["error"].as_str().is_some());
+
+        // An address without fog
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_account",
+            "params": {
+                "name": "Alice Main Account",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let b58_public_address = res["result"]["account"]["main_address"].as_str().unwrap();
+        let public_address = b58_decode_public_address(b58_public_address).unwrap();
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "validate_public_address",
+            "params": {
+                "address": b58_public_address,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = &res["result"];
+        assert!(result["valid"].as_bool().unwrap());
+        assert!(!result["fog_enabled"].as_bool().unwrap());
+        assert_eq!(
+            result["view_public_key_fingerprint"],
+            hex::encode(&public_address.view_public_key().to_bytes()[..4])
+        );
+        assert_eq!(
+            result["spend_public_key_fingerprint"],
+            hex::encode(&public_address.spend_public_key().to_bytes()[..4])
+        );
+        assert!(result["error"].is_null());
+
+        // A fog enabled address
+        let fog_public_address = AccountKey::random_with_fog(&mut rng).default_subaddress();
+        let fog_b58_public_address = b58_encode_public_address(&fog_public_address).unwrap();
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "validate_public_address",
+            "params": {
+                "address": fog_b58_public_address,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = &res["result"];
+        assert!(result["valid"].as_bool().unwrap());
+        assert!(result["fog_enabled"].as_bool().unwrap());
+    }
+
     #[test_with_logger]
     fn test_get_address_details(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
@@ -71,6 +71,42 @@ mod e2e_account {
         let max_spendable = balance_mob["max_spendable"].as_str().unwrap();
         assert_eq!(unspent, (42 * MOB).to_string());
         assert_eq!(max_spendable, (42 * MOB - Mob::MINIMUM_FEE).to_string());
+        assert!(result.get("balance_amounts_per_token").is_none());
+
+        // Amounts can also be given with their token ids
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_account_status",
+            "params": {
+                "account_id": account_id,
+                "structured_amounts": true,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let balance_amounts_mob = &result["balance_amounts_per_token"][Mob::ID.to_string()];
+        assert_eq!(
+            balance_amounts_mob["unspent"],
+            json!({"value": (42 * MOB).to_string(), "token_id": Mob::ID.to_string()})
+        );
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_txos",
+            "params": {
+                "account_id": account_id,
+                "structured_amounts": true,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let txo_id = result["txo_ids"][0].as_str().unwrap();
+        assert_eq!(
+            result["txo_map"][txo_id]["amount"],
+            json!({"value": (42 * MOB).to_string(), "token_id": Mob::ID.to_string()})
+        );
     }
 
     #[test_with_logger]
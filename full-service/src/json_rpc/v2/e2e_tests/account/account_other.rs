@@ -37,7 +37,7 @@ mod e2e_account {
                 "mnemonic": "sheriff odor square mistake huge skate mouse shoot purity weapon proof stuff correct concert blanket neck own shift clay mistake air viable stick group",
                 "key_derivation_version": "2",
                 "name": "Alice Main Account",
-                "first_block_index": "200",
+                "first_block_index": "10",
             }
         });
         let res = dispatch(&client, body, &logger);
@@ -73,6 +73,48 @@ mod e2e_account {
         );
     }
 
+    #[test_with_logger]
+    fn test_create_account_does_not_return_secrets(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, _ledger_db, _db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_account",
+            "params": {
+                "name": "Alice Main Account",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let account_obj = result.get("account").unwrap();
+        let account_id = account_obj.get("id").unwrap().as_str().unwrap();
+
+        // Neither the response nor the account carries any secret material
+        for key in ["entropy", "mnemonic", "account_key", "account_secrets"] {
+            assert!(result.get(key).is_none());
+            assert!(account_obj.get(key).is_none());
+        }
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "export_account_secrets",
+            "params": {
+                "account_id": account_id,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let secrets = result.get("account_secrets").unwrap();
+
+        assert_eq!(secrets["account_id"], serde_json::json!(account_id));
+        assert_eq!(secrets["key_derivation_version"], serde_json::json!("2"));
+        let phrase = secrets["mnemonic"].as_str().unwrap();
+        assert!(Mnemonic::from_phrase(phrase, Language::English).is_ok());
+    }
+
     #[test_with_logger]
     fn test_export_legacy_account_secrets(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
@@ -86,7 +128,7 @@ mod e2e_account {
             "params": {
                 "entropy": entropy,
                 "name": "Alice Main Account",
-                "first_block_index": "200",
+                "first_block_index": "10",
             }
         });
         let res = dispatch(&client, body, &logger);
@@ -175,4 +217,67 @@ mod e2e_account {
         assert_eq!(unspent, (42 * MOB).to_string());
         let _account = result.get("account").unwrap();
     }
+
+    #[test_with_logger]
+    fn test_search_accounts(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, _ledger_db, _db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        let mut account_ids = Vec::new();
+        for name in ["Alice Main Account", "Alice Savings", "Bob"] {
+            let body = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "create_account",
+                "params": {
+                    "name": name,
+                }
+            });
+            let res = dispatch(&client, body, &logger);
+            account_ids.push(res["result"]["account"]["id"].as_str().unwrap().to_string());
+        }
+
+        let search = |query: &str| {
+            let body = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "search_accounts",
+                "params": {
+                    "query": query,
+                }
+            });
+            let res = dispatch(&client, body, &logger);
+            let result = res.get("result").unwrap().clone();
+            let mut ids: Vec<String> = result["account_ids"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|id| id.as_str().unwrap().to_string())
+                .collect();
+            ids.sort();
+            for id in &ids {
+                assert_eq!(result["account_map"][id]["id"], id.as_str());
+            }
+            ids
+        };
+        let sorted = |mut ids: Vec<String>| {
+            ids.sort();
+            ids
+        };
+
+        // Exact match
+        assert_eq!(search("Bob"), vec![account_ids[2].clone()]);
+
+        // Case-insensitive substring
+        assert_eq!(
+            search("alice"),
+            sorted(vec![account_ids[0].clone(), account_ids[1].clone()])
+        );
+
+        // No match
+        assert!(search("Carol").is_empty());
+
+        // Empty query matches all
+        assert_eq!(search(""), sorted(account_ids.clone()));
+    }
 }
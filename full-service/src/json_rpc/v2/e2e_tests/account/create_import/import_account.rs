@@ -32,7 +32,7 @@ mod e2e_account {
                 "mnemonic": "sheriff odor square mistake huge skate mouse shoot purity weapon proof stuff correct concert blanket neck own shift clay mistake air viable stick group",
                 "key_derivation_version": "2",
                 "name": "Alice Main Account",
-                "first_block_index": "200",
+                "first_block_index": "10",
             }
         });
         let res = dispatch(&client, body, &logger);
@@ -48,7 +48,7 @@ mod e2e_account {
 
         assert_eq!(
             *account_obj.get("first_block_index").unwrap(),
-            serde_json::json!("200")
+            serde_json::json!("10")
         );
         assert_eq!(account_obj.get("next_subaddress_index").unwrap(), "2");
         assert_eq!(account_obj.get("fog_enabled").unwrap(), false);
@@ -66,7 +66,7 @@ mod e2e_account {
             "params": {
                 "entropy": "c593274dc6f6eb94242e34ae5f0ab16bc3085d45d49d9e18b8a8c6f057e6b56b",
                 "name": "Alice Main Account",
-                "first_block_index": "200",
+                "first_block_index": "10",
             }
         });
         let res = dispatch(&client, body, &logger);
@@ -83,12 +83,74 @@ mod e2e_account {
         );
         assert_eq!(
             *account_obj.get("first_block_index").unwrap(),
-            serde_json::json!("200")
+            serde_json::json!("10")
         );
         assert_eq!(account_obj.get("next_subaddress_index").unwrap(), "2");
         assert_eq!(account_obj.get("fog_enabled").unwrap(), false);
     }
 
+    #[test_with_logger]
+    fn test_e2e_import_accounts(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, _ledger_db, _db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "import_accounts",
+            "params": {
+                "accounts": [
+                    {
+                        "mnemonic": "sheriff odor square mistake huge skate mouse shoot purity weapon proof stuff correct concert blanket neck own shift clay mistake air viable stick group",
+                        "name": "Alice Main Account",
+                        "first_block_index": "10",
+                    },
+                    {
+                        "entropy": "not hex",
+                        "name": "Bad Account",
+                    },
+                    {
+                        "entropy": "c593274dc6f6eb94242e34ae5f0ab16bc3085d45d49d9e18b8a8c6f057e6b56b",
+                        "name": "Alice Legacy Account",
+                    },
+                ],
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let results = result.get("results").unwrap().as_array().unwrap();
+        assert_eq!(results.len(), 3);
+
+        let account_obj = results[0].get("account").unwrap();
+        assert_eq!(
+            account_obj.get("id").unwrap().as_str().unwrap(),
+            "7872edf0d4094643213aabc92aa0d07379cfb58eda0722b21a44868f22f75b4e"
+        );
+        assert_eq!(account_obj.get("first_block_index").unwrap(), "10");
+        assert!(results[0].get("error").unwrap().is_null());
+
+        // The invalid entropy fails on its own without aborting the batch.
+        assert!(results[1].get("account").unwrap().is_null());
+        assert!(results[1].get("error").unwrap().is_string());
+
+        let account_obj = results[2].get("account").unwrap();
+        assert_eq!(
+            account_obj.get("id").unwrap().as_str().unwrap(),
+            "f9957a9d050ef8dff9d8ef6f66daa608081e631b2d918988311613343827b779"
+        );
+        assert_eq!(account_obj.get("first_block_index").unwrap(), "0");
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_accounts",
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let account_ids = result.get("account_ids").unwrap().as_array().unwrap();
+        assert_eq!(account_ids.len(), 2);
+    }
+
     #[test_with_logger]
     fn test_e2e_import_account_fog(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
@@ -103,7 +165,7 @@ mod e2e_account {
                 "mnemonic": "sheriff odor square mistake huge skate mouse shoot purity weapon proof stuff correct concert blanket neck own shift clay mistake air viable stick group",
                 "key_derivation_version": "2",
                 "name": "Alice Main Account",
-                "first_block_index": "200",
+                "first_block_index": "10",
                 "fog_info": {
                     "report_url": "fog://fog-report.example.com",
                     "report_id": "",
@@ -138,7 +200,7 @@ mod e2e_account {
             "params": {
                 "entropy": "c593274dc6f6eb94242e34ae5f0ab16bc3085d45d49d9e18b8a8c6f057e6b56b",
                 "name": "Alice Main Account",
-                "first_block_index": "200",
+                "first_block_index": "10",
                 "fog_info": {
                     "report_url": "fog://fog-report.example.com",
                     "report_id": "",
@@ -174,7 +236,7 @@ mod e2e_account {
             "params": {
                 "entropy": "c593274dc6f6eb94242e34ae5f0ab16bc3085d45d49d9e18b8a8c6f057e6b56b",
                 "name": "Alice Main Account",
-                "first_block_index": "200",
+                "first_block_index": "10",
             }
         });
         let res = dispatch(&client, body, &logger);
@@ -211,7 +273,7 @@ mod e2e_account {
             "params": {
                 "entropy": "c593274dc6f6eb94242e34ae5f0ab16bc3085d45d49d9e18b8a8c6f057e6b56b",
                 "name": "Alice Main Account",
-                "first_block_index": "200",
+                "first_block_index": "10",
             }
         });
         let res = dispatch(&client, body, &logger);
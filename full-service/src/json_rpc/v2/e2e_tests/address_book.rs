@@ -0,0 +1,270 @@
+// Copyright (c) 2020-2024 MobileCoin Inc.
+
+//! End-to-end tests for the Full Service Wallet API.
+
+#[cfg(test)]
+mod e2e_address_book {
+    use crate::{
+        json_rpc::v2::api::test_utils::{dispatch, setup},
+        util::b58::b58_encode_public_address,
+    };
+
+    use mc_account_keys::AccountKey;
+    use mc_common::logger::{test_with_logger, Logger};
+
+    use rand::{rngs::StdRng, SeedableRng};
+    use serde_json::json;
+
+    #[test_with_logger]
+    fn test_address_book_entry_crud(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, _ledger_db, _db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        let alice_b58 =
+            b58_encode_public_address(&AccountKey::random(&mut rng).default_subaddress()).unwrap();
+        let bob_b58 =
+            b58_encode_public_address(&AccountKey::random(&mut rng).default_subaddress()).unwrap();
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_address_book_entry",
+            "params": {
+                "public_address_b58": alice_b58,
+                "name": "Alice",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let alice = result.get("address_book_entry").unwrap().clone();
+        let alice_id = alice.get("id").unwrap().as_str().unwrap();
+        assert_eq!(alice["public_address_b58"], alice_b58);
+        assert_eq!(alice["name"], "Alice");
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_address_book_entry",
+            "params": {
+                "public_address_b58": bob_b58,
+                "name": "Bob",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let bob_id = result["address_book_entry"]["id"].as_str().unwrap();
+
+        // Addresses that cannot be decoded are rejected.
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_address_book_entry",
+            "params": {
+                "public_address_b58": "not an address",
+                "name": "Carol",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        assert!(res.get("error").is_some());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_address_book_entry",
+            "params": {
+                "address_book_entry_id": alice_id,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        assert_eq!(result["address_book_entry"], alice);
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "update_address_book_entry",
+            "params": {
+                "address_book_entry_id": bob_id,
+                "public_address_b58": bob_b58,
+                "name": "Robert",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        assert_eq!(result["address_book_entry"]["id"], bob_id);
+        assert_eq!(result["address_book_entry"]["name"], "Robert");
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_address_book_entries",
+            "params": {}
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let entries = result["address_book_entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0], alice);
+        assert_eq!(entries[1]["name"], "Robert");
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "remove_address_book_entry",
+            "params": {
+                "address_book_entry_id": alice_id,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        assert_eq!(result["removed"], true);
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_address_book_entry",
+            "params": {
+                "address_book_entry_id": alice_id,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        assert!(res.get("error").is_some());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_address_book_entries",
+            "params": {}
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let entries = result["address_book_entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["id"], bob_id);
+    }
+
+    #[test_with_logger]
+    fn test_assign_address_for_contact(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, _ledger_db, _db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_account",
+            "params": {
+                "name": "Alice Main Account",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let account_id = result["account"]["id"].as_str().unwrap();
+
+        let contact_b58 =
+            b58_encode_public_address(&AccountKey::random(&mut rng).default_subaddress()).unwrap();
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_address_book_entry",
+            "params": {
+                "public_address_b58": contact_b58,
+                "name": "Bob",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let contact_id = result["address_book_entry"]["id"].as_str().unwrap();
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "assign_address_for_contact",
+            "params": {
+                "account_id": account_id,
+                "address_book_entry_id": contact_id,
+                "metadata": "From Bob",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let address = result.get("address").unwrap();
+        assert_eq!(address["address_book_entry_id"], contact_id);
+        assert_eq!(address["metadata"], "From Bob");
+
+        // Unknown entries are rejected.
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "assign_address_for_contact",
+            "params": {
+                "account_id": account_id,
+                "address_book_entry_id": "1000",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        assert!(res.get("error").is_some());
+
+        // An address assigned without a contact can be attached to one later,
+        // and detached again.
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "assign_address_for_account",
+            "params": {
+                "account_id": account_id,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let untagged_b58 = result["address"]["public_address_b58"].as_str().unwrap();
+        assert!(result["address"]["address_book_entry_id"].is_null());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "set_address_book_entry",
+            "params": {
+                "address": untagged_b58,
+                "address_book_entry_id": contact_id,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        assert_eq!(result["address"]["address_book_entry_id"], contact_id);
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "set_address_book_entry",
+            "params": {
+                "address": untagged_b58,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        assert!(result["address"]["address_book_entry_id"].is_null());
+
+        // Removing the contact detaches it from its addresses.
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "remove_address_book_entry",
+            "params": {
+                "address_book_entry_id": contact_id,
+            }
+        });
+        dispatch(&client, body, &logger);
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_address",
+            "params": {
+                "public_address_b58": address["public_address_b58"],
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        assert!(result["address"]["address_book_entry_id"].is_null());
+    }
+}
@@ -1,4 +1,5 @@
 mod account;
+mod address_book;
 mod other;
 mod transaction;
 mod webhook;
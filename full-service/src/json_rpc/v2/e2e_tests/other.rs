@@ -66,6 +66,103 @@ mod e2e_misc {
         assert!(balance_mob.is_none());
     }
 
+    #[test_with_logger]
+    fn test_response_method_and_result(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, _ledger_db, _db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_account",
+            "params": {
+                "name": "Alice Main Account",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let account_id = res["result"]["account"]["id"].as_str().unwrap().to_string();
+
+        // Each response echoes the method and carries the typed result fields
+        // for that method.
+        let cases = vec![
+            ("create_account", Some(json!({ "name": "Bob" })), "account"),
+            ("get_accounts", Some(json!({})), "account_ids"),
+            ("get_wallet_status", None, "wallet_status"),
+            ("get_network_status", None, "network_status"),
+            ("get_network_fees", None, "fees"),
+            (
+                "get_account_status",
+                Some(json!({ "account_id": account_id })),
+                "account",
+            ),
+            (
+                "get_balance",
+                Some(json!({ "account_id": account_id })),
+                "balance_per_token",
+            ),
+            (
+                "get_txos",
+                Some(json!({ "account_id": account_id })),
+                "txo_ids",
+            ),
+            (
+                "get_transaction_logs",
+                Some(json!({ "account_id": account_id })),
+                "transaction_log_ids",
+            ),
+        ];
+        for (method, params, result_field) in cases {
+            let mut body = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+            });
+            if let Some(params) = params {
+                body["params"] = params;
+            }
+            let res = dispatch(&client, body, &logger);
+            assert_eq!(res["method"], method);
+            assert!(res.get("error").is_none(), "{method} failed: {res}");
+            assert!(
+                res["result"].get(result_field).is_some(),
+                "{method} result is missing {result_field}: {res}"
+            );
+        }
+    }
+
+    #[test_with_logger]
+    fn test_response_echoes_request_id(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, _ledger_db, _db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 42,
+            "method": "get_wallet_status",
+        });
+        let res = dispatch(&client, body, &logger);
+        assert_eq!(res["id"], 42);
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": "request-7",
+            "method": "get_network_status",
+        });
+        let res = dispatch(&client, body, &logger);
+        assert_eq!(res["id"], "request-7");
+
+        // The id is echoed on errors too.
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 43,
+            "method": "get_account_status",
+            "params": {},
+        });
+        let res = dispatch(&client, body, &logger);
+        assert!(res.get("error").is_some());
+        assert_eq!(res["id"], 43);
+    }
+
     #[test_with_logger]
     fn test_request_with_correct_api_key(logger: Logger) {
         let api_key = "mobilecats";
@@ -126,6 +223,14 @@ mod e2e_misc {
         assert_eq!(status.get("network_block_height").unwrap(), "12");
         assert_eq!(status.get("local_block_height").unwrap(), "12");
         assert_eq!(status.get("local_num_txos").unwrap(), "60");
+        assert_eq!(status.get("is_synced").unwrap(), true);
+        let peer_block_heights = status
+            .get("peer_block_heights")
+            .unwrap()
+            .as_object()
+            .unwrap();
+        assert_eq!(peer_block_heights.len(), 2);
+        assert!(peer_block_heights.values().all(|height| height == "12"));
         assert_eq!(
             status.get("block_version").unwrap(),
             &BlockVersion::MAX.to_string()
@@ -138,6 +243,25 @@ mod e2e_misc {
         );
     }
 
+    #[test_with_logger]
+    fn test_get_network_fees(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, _ledger_db, _db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_network_fees"
+        });
+        let res = dispatch(&client, body, &logger);
+        let fees = res["result"]["fees"].as_object().unwrap();
+        assert_eq!(
+            fees.get(&Mob::ID.to_string()).unwrap().as_str().unwrap(),
+            &Mob::MINIMUM_FEE.to_string()
+        );
+        assert_eq!(fees.get("1").unwrap().as_str().unwrap(), "1024");
+    }
+
     #[test_with_logger]
     fn test_get_txo_block_index(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
@@ -95,11 +95,12 @@ mod e2e_transaction {
             json!({
                 "method": "build_transaction",
                 "error": json!({
-                    "code": -32603,
-                    "message": "InternalError",
+                    "code": -32001,
+                    "message": "InsufficientFunds",
                     "data": json!({
                         "server_error": format!("TransactionBuilder(WalletDb(InsufficientFundsUnderMaxSpendable(\"Max spendable value in wallet: 0, but target value: {}\")))", 42 + Mob::MINIMUM_FEE),
                         "details": format!("Error building transaction: Wallet DB Error: Insufficient funds from Txos under max_spendable_value: Max spendable value in wallet: 0, but target value: {}", 42 + Mob::MINIMUM_FEE),
+                        "error_chain": ["TransactionBuilder", "WalletDb", "InsufficientFundsUnderMaxSpendable"],
                     })
                 }),
                 "jsonrpc": "2.0",
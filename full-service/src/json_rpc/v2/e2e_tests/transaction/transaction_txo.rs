@@ -161,13 +161,15 @@ mod e2e_transaction {
             3
         );
 
-        // Remove account 1.
+        // Remove account 1. It was never synced after its transaction landed, so
+        // the transaction is still pending from its point of view.
         let body = json!({
             "jsonrpc": "2.0",
             "id": 2,
             "method": "remove_account",
             "params": {
                 "account_id": account_id_1,
+                "force": true,
             }
         });
         let res = dispatch(&client, body, &logger);
@@ -698,4 +700,94 @@ mod e2e_transaction {
 
         assert_eq!(txo.memo, expected_memo);
     }
+
+    #[test_with_logger]
+    fn test_received_txo_surfaces_destination_memo(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, mut ledger_db, db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        // Add an account
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_account",
+            "params": {
+                "name": "Alice Main Account",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let account_obj = result.get("account").unwrap();
+        let account_id = account_obj.get("id").unwrap().as_str().unwrap();
+        let b58_public_address = account_obj.get("main_address").unwrap().as_str().unwrap();
+        let public_address = b58_decode_public_address(b58_public_address).unwrap();
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![public_address],
+            100_000_000_000_000, // 100.0 MOB
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        manually_sync_account(
+            &ledger_db,
+            &db_ctx.get_db_instance(logger.clone()),
+            &AccountID(account_id.to_string()),
+            &logger,
+        );
+
+        // Send to ourselves with a payment request id, so the change carries a
+        // destination memo with that id
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "build_and_submit_transaction",
+            "params": {
+                "account_id": account_id,
+                "recipient_public_address": b58_public_address,
+                "amount": { "value": "42000000000000", "token_id": "0" }, // 42.0 MOB
+                "payment_request_id": "1234",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let transaction_log: TransactionLogJSON =
+            serde_json::from_value(result.get("transaction_log").unwrap().clone()).unwrap();
+        let tx_proposal = result.get("tx_proposal").unwrap();
+        let json_tx_proposal: TxProposalJSON = serde_json::from_value(tx_proposal.clone()).unwrap();
+        let payments_tx_proposal = TxProposal::try_from(&json_tx_proposal).unwrap();
+
+        // Receive the change back into the account
+        add_block_with_tx(&mut ledger_db, payments_tx_proposal.tx, &mut rng);
+        manually_sync_account(
+            &ledger_db,
+            &db_ctx.get_db_instance(logger.clone()),
+            &AccountID(account_id.to_string()),
+            &logger,
+        );
+
+        let change_txo = transaction_log.change_txos[0].clone();
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_txo",
+            "params": {
+                "txo_id": change_txo.txo_id,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let txo: TxoJSON = serde_json::from_value(result.get("txo").unwrap().clone()).unwrap();
+
+        assert_eq!(txo.received_block_index, Some("13".to_string()));
+        match txo.memo {
+            Memo::Destination(memo) => {
+                assert_eq!(memo.payment_request_id, Some("1234".to_string()))
+            }
+            _ => panic!("expected a destination memo"),
+        }
+        assert_eq!(txo.payment_request_id, Some("1234".to_string()));
+        assert_eq!(txo.sender_address_hash, None);
+    }
 }
@@ -97,7 +97,7 @@ mod e2e_webhook {
         // syncing
         let webhook_mock =
             server.mock(|when, then| {
-                when.method(POST).path("/received_txos").body(
+                when.method(POST).path("/received_txos").json_body_partial(
                     json!(
                         {
                             "accounts": [account_id]
@@ -143,4 +143,116 @@ mod e2e_webhook {
         // the race between the sync thread and the ledger adding blocks
         assert!(webhook_mock.hits() >= 1);
     }
+
+    #[test_with_logger]
+    fn test_tagged_deposit_webhook(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let server = MockServer::start();
+        let webhook_url = Url::parse(&server.url("/received_txos")).unwrap();
+        let webhook_config = WebhookConfig {
+            url: webhook_url,
+            poll_interval: Duration::from_millis(10),
+        };
+
+        let (client, mut ledger_db, db_ctx, _network_state) =
+            setup_with_webhook(&mut rng, webhook_config, logger.clone());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_account",
+            "params": {
+                "name": "Merchant Account",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let account_obj = res.get("result").unwrap().get("account").unwrap();
+        let account_id = account_obj.get("id").unwrap().as_str().unwrap();
+
+        // Assign a subaddress to a customer, which tracks its deposits.
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "assign_address_for_account",
+            "params": {
+                "account_id": account_id,
+                "metadata": "For customer 42",
+                "customer_tag": "customer-42",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let address = res.get("result").unwrap().get("address").unwrap();
+        assert_eq!(
+            address.get("customer_tag").unwrap().as_str().unwrap(),
+            "customer-42"
+        );
+        let address_b58 = address.get("public_address_b58").unwrap().as_str().unwrap();
+        let subaddress_index = address.get("subaddress_index").unwrap().as_str().unwrap();
+        let public_address = b58_decode_public_address(address_b58).unwrap();
+
+        let accounts_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/received_txos")
+                .json_body_partial(json!({ "accounts": [account_id] }).to_string());
+            then.status(200);
+        });
+        let tagged_deposit_mock = server.mock(|when, then| {
+            when.method(POST).path("/received_txos").json_body(json!({
+                "tagged_deposits": [
+                    {
+                        "account_id": account_id,
+                        "public_address_b58": address_b58,
+                        "subaddress_index": subaddress_index,
+                        "customer_tag": "customer-42",
+                    }
+                ]
+            }));
+            then.status(200);
+        });
+
+        // Deposit to the tagged subaddress.
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![public_address.clone()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        // Wait for the account to sync and the webhook thread to fire.
+        let wallet_db = &db_ctx.get_db_instance(logger.clone());
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+        for _ in 0..500 {
+            let account = Account::get(&AccountID(account_id.to_string()), conn).unwrap();
+            if account.next_block_index as u64 >= ledger_db.num_blocks().unwrap()
+                && tagged_deposit_mock.hits() > 0
+            {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert!(accounts_mock.hits() >= 1);
+        assert_eq!(tagged_deposit_mock.hits(), 1);
+
+        // Only the first deposit is reported.
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![public_address],
+            10 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        let hits_before = accounts_mock.hits();
+        for _ in 0..500 {
+            if accounts_mock.hits() > hits_before {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(tagged_deposit_mock.hits(), 1);
+    }
 }
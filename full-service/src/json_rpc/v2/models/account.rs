@@ -2,10 +2,15 @@
 
 //! API definition for the Account object.
 
-use crate::{db, util::b58::b58_encode_public_address};
+use crate::{
+    db,
+    json_rpc::v2::models::account_key::FogInfo,
+    service::account::{ImportAccountRequest, ImportAccountSecret},
+    util::b58::b58_encode_public_address,
+};
 use mc_account_keys::PublicAddress;
 use serde_derive::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, convert::TryFrom};
 
 #[derive(Deserialize, Serialize, Default, Debug, Clone)]
 pub struct AccountMap(pub BTreeMap<String, Account>);
@@ -64,6 +69,26 @@ pub struct Account {
     /// specified when building a transaction in order to keep subaddress
     /// balances correct.
     pub require_spend_subaddress: bool,
+
+    /// The MOB fee used for this account's transactions when none is given,
+    /// if set.
+    pub default_fee: Option<String>,
+
+    /// Time at which this account was added to the wallet, in seconds since
+    /// the epoch.
+    pub created_at: String,
+
+    /// Time at which this account was last modified, in seconds since the
+    /// epoch.
+    pub updated_at: String,
+
+    /// A flag that indicates whether deposits to this account trigger the
+    /// deposits webhook.
+    pub webhook_enabled: bool,
+
+    /// The subaddress index that change is sent to by default, if it has been
+    /// rotated away from the reserved change subaddress.
+    pub change_subaddress_index: Option<String>,
 }
 
 impl Account {
@@ -88,6 +113,83 @@ impl Account {
             view_only: src.view_only,
             managed_by_hardware_wallet: src.managed_by_hardware_wallet,
             require_spend_subaddress: src.require_spend_subaddress,
+            default_fee: src.default_fee.map(|fee| (fee as u64).to_string()),
+            created_at: (src.created_at as u64).to_string(),
+            updated_at: (src.updated_at as u64).to_string(),
+            webhook_enabled: src.webhook_enabled,
+            change_subaddress_index: src.change_subaddress_index.map(|i| (i as u64).to_string()),
+        })
+    }
+}
+
+/// An account to import with `import_accounts`. Exactly one of `mnemonic` and
+/// `entropy` must be provided.
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct ImportAccountParams {
+    /// The secret BIP39 mnemonic phrase of the account.
+    pub mnemonic: Option<String>,
+
+    /// The secret legacy root entropy of the account, hex-encoded.
+    pub entropy: Option<String>,
+
+    /// Display name for the account.
+    pub name: Option<String>,
+
+    /// Index of the first block when this account may have received funds.
+    pub first_block_index: Option<String>,
+
+    /// The next known unused subaddress index for the account.
+    pub next_subaddress_index: Option<String>,
+
+    /// Fog info for the account, if it is fog enabled.
+    pub fog_info: Option<FogInfo>,
+
+    /// Whether the account requires a spend_subaddress when building a
+    /// transaction.
+    #[serde(default = "bool::default")] // default is false
+    pub require_spend_subaddress: bool,
+}
+
+impl TryFrom<ImportAccountParams> for ImportAccountRequest {
+    type Error = String;
+
+    fn try_from(src: ImportAccountParams) -> Result<ImportAccountRequest, String> {
+        let secret = match (src.mnemonic, src.entropy) {
+            (Some(mnemonic), None) => ImportAccountSecret::Mnemonic(mnemonic),
+            (None, Some(entropy)) => ImportAccountSecret::LegacyRootEntropy(entropy),
+            _ => return Err("Exactly one of mnemonic and entropy must be provided".to_string()),
+        };
+        let first_block_index = src
+            .first_block_index
+            .map(|fb| fb.parse::<u64>())
+            .transpose()
+            .map_err(|e| format!("Could not parse first_block_index: {e}"))?;
+        let next_subaddress_index = src
+            .next_subaddress_index
+            .map(|ns| ns.parse::<u64>())
+            .transpose()
+            .map_err(|e| format!("Could not parse next_subaddress_index: {e}"))?;
+        let fog_info = src.fog_info.unwrap_or_default();
+
+        Ok(ImportAccountRequest {
+            secret,
+            name: src.name,
+            first_block_index,
+            next_subaddress_index,
+            fog_report_url: fog_info.report_url,
+            fog_authority_spki: fog_info.authority_spki,
+            require_spend_subaddress: src.require_spend_subaddress,
         })
     }
 }
+
+/// The outcome of importing a single account with `import_accounts`. Exactly
+/// one of `account` and `error` is set.
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct ImportAccountResult {
+    /// The imported account, if the import succeeded.
+    pub account: Option<Account>,
+
+    /// Why the import failed, if it did.
+    pub error: Option<String>,
+}
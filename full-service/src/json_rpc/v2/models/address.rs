@@ -32,6 +32,15 @@ pub struct Address {
 
     /// The index of this address in the subaddress space for the account.
     pub subaddress_index: String,
+
+    /// Whether this address has been hidden from the list of unused addresses.
+    pub hidden: bool,
+
+    /// The customer this address was assigned to, if its deposits are tracked.
+    pub customer_tag: Option<String>,
+
+    /// The address book entry of the contact this address was given to.
+    pub address_book_entry_id: Option<String>,
 }
 
 impl From<&AssignedSubaddress> for Address {
@@ -41,6 +50,9 @@ impl From<&AssignedSubaddress> for Address {
             account_id: src.account_id.clone(),
             metadata: src.comment.clone(),
             subaddress_index: (src.subaddress_index as u64).to_string(),
+            hidden: src.hidden,
+            customer_tag: src.customer_tag.clone(),
+            address_book_entry_id: src.address_book_entry_id.map(|id| id.to_string()),
         }
     }
 }
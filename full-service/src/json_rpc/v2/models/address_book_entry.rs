@@ -0,0 +1,32 @@
+// Copyright (c) 2020-2024 MobileCoin Inc.
+
+//! API definition for the AddressBookEntry object.
+
+use crate::db;
+use serde_derive::{Deserialize, Serialize};
+
+/// A named contact in the wallet's address book.
+///
+/// Addresses assigned to a contact refer to its entry, so that payments
+/// received at them can be labeled with the contact.
+#[derive(Deserialize, Serialize, Default, Debug, Clone, PartialEq)]
+pub struct AddressBookEntry {
+    /// The unique identifier for the address book entry.
+    pub id: String,
+
+    /// A b58 encoding of the contact's public address.
+    pub public_address_b58: String,
+
+    /// The name of the contact.
+    pub name: String,
+}
+
+impl From<&db::models::AddressBookEntry> for AddressBookEntry {
+    fn from(src: &db::models::AddressBookEntry) -> AddressBookEntry {
+        AddressBookEntry {
+            id: src.id.to_string(),
+            public_address_b58: src.public_address_b58.clone(),
+            name: src.name.clone(),
+        }
+    }
+}
@@ -21,6 +21,12 @@ pub struct Amount {
 
 impl Amount {
     pub fn new(value: u64, token_id: TokenId) -> Self {
+        Self::from_u128(value as u128, token_id)
+    }
+
+    /// An amount whose value may not fit in a u64, such as a balance summed
+    /// over many txos.
+    pub fn from_u128(value: u128, token_id: TokenId) -> Self {
         Self {
             value: Secret::new(value.to_string()),
             token_id: Secret::new(token_id.to_string()),
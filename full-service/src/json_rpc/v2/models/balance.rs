@@ -4,8 +4,9 @@
 
 use std::collections::BTreeMap;
 
-use crate::service;
+use crate::{json_rpc::v2::models::amount::Amount, service};
 
+use mc_transaction_core::TokenId;
 use redact::{expose_secret, Secret};
 use serde_derive::{Deserialize, Serialize};
 
@@ -30,6 +31,16 @@ pub struct Balance {
     #[serde(serialize_with = "expose_secret")]
     pub unspent: Secret<String>,
 
+    /// The part of the unspent pico MOB held on the subaddresses reserved for
+    /// change, which comes back from the account's own transactions.
+    #[serde(serialize_with = "expose_secret")]
+    pub change_unspent: Secret<String>,
+
+    /// The part of the unspent pico MOB held on every other subaddress, which
+    /// was received from others.
+    #[serde(serialize_with = "expose_secret")]
+    pub received_unspent: Secret<String>,
+
     /// Pending, out-going pico MOB. The pending value will clear once the
     /// ledger processes the outgoing txos. The available_pmob will reflect the
     /// change.
@@ -59,6 +70,8 @@ impl From<&service::balance::Balance> for Balance {
             max_spendable: src.max_spendable.to_string().into(),
             unverified: src.unverified.to_string().into(),
             unspent: src.unspent.to_string().into(),
+            change_unspent: src.change_unspent.to_string().into(),
+            received_unspent: src.received_unspent.to_string().into(),
             pending: src.pending.to_string().into(),
             spent: src.spent.to_string().into(),
             secreted: src.secreted.to_string().into(),
@@ -66,3 +79,68 @@ impl From<&service::balance::Balance> for Balance {
         }
     }
 }
+
+/// A balance with each value given as an amount of its token, returned when
+/// structured amounts are requested.
+#[derive(Deserialize, Serialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct BalanceAmounts {
+    pub max_spendable: Amount,
+    pub unverified: Amount,
+    pub unspent: Amount,
+    pub change_unspent: Amount,
+    pub received_unspent: Amount,
+    pub pending: Amount,
+    pub spent: Amount,
+    pub secreted: Amount,
+    pub orphaned: Amount,
+}
+
+impl BalanceAmounts {
+    pub fn new(src: &service::balance::Balance, token_id: TokenId) -> Self {
+        Self {
+            max_spendable: Amount::from_u128(src.max_spendable, token_id),
+            unverified: Amount::from_u128(src.unverified, token_id),
+            unspent: Amount::from_u128(src.unspent, token_id),
+            change_unspent: Amount::from_u128(src.change_unspent, token_id),
+            received_unspent: Amount::from_u128(src.received_unspent, token_id),
+            pending: Amount::from_u128(src.pending, token_id),
+            spent: Amount::from_u128(src.spent, token_id),
+            secreted: Amount::from_u128(src.secreted, token_id),
+            orphaned: Amount::from_u128(src.orphaned, token_id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_balance_amounts_round_trip_above_u64_max() {
+        let unspent = u64::MAX as u128 * 3;
+        let balance = service::balance::Balance {
+            unspent,
+            received_unspent: unspent,
+            ..Default::default()
+        };
+
+        let balance_amounts = BalanceAmounts::new(&balance, TokenId::from(1));
+        let json = serde_json::to_value(&balance_amounts).unwrap();
+        assert_eq!(
+            json["unspent"],
+            serde_json::json!({"value": unspent.to_string(), "token_id": "1"})
+        );
+
+        let round_tripped: BalanceAmounts = serde_json::from_value(json).unwrap();
+        assert_eq!(round_tripped, balance_amounts);
+        assert_eq!(
+            round_tripped
+                .unspent
+                .value
+                .expose_secret()
+                .parse::<u128>()
+                .unwrap(),
+            unspent
+        );
+    }
+}
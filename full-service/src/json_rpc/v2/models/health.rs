@@ -0,0 +1,44 @@
+// Copyright (c) 2020-2024 MobileCoin Inc.
+
+//! API definition for the Health object.
+
+use crate::service;
+use serde_derive::{Deserialize, Serialize};
+
+/// Whether the wallet is ready to serve requests, and why not if it isn't.
+#[derive(Deserialize, Serialize, Default, Debug, Clone, PartialEq)]
+pub struct Health {
+    /// Whether every check passed.
+    pub ready: bool,
+
+    /// Whether a trivial query against the wallet database succeeded. Absent
+    /// if the wallet runs without a database.
+    pub wallet_db_reachable: Option<bool>,
+
+    /// The number of blocks in the local ledger, if it could be read.
+    pub local_block_height: Option<String>,
+
+    /// Whether the account sync thread is running. Absent if the wallet runs
+    /// without a database.
+    pub sync_thread_running: Option<bool>,
+
+    /// The number of consensus nodes or validators the wallet is configured
+    /// to talk to.
+    pub num_peers: String,
+
+    /// Why the wallet is not ready, empty if it is.
+    pub problems: Vec<String>,
+}
+
+impl From<&service::health::Health> for Health {
+    fn from(src: &service::health::Health) -> Health {
+        Health {
+            ready: src.ready(),
+            wallet_db_reachable: src.wallet_db_reachable,
+            local_block_height: src.local_block_height.map(|height| height.to_string()),
+            sync_thread_running: src.sync_thread_running,
+            num_peers: src.num_peers.to_string(),
+            problems: src.problems.clone(),
+        }
+    }
+}
@@ -2,16 +2,19 @@ pub mod account;
 pub mod account_key;
 pub mod account_secrets;
 pub mod address;
+pub mod address_book_entry;
 pub mod amount;
 pub mod balance;
 pub mod block;
 pub mod confirmation_number;
+pub mod health;
 pub mod ledger;
 pub mod masked_amount;
 pub mod memo;
 pub mod network_status;
 pub mod public_address;
 pub mod receiver_receipt;
+pub mod sync_progress;
 pub mod transaction_log;
 pub mod tx_proposal;
 pub mod txo;
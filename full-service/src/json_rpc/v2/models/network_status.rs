@@ -31,6 +31,17 @@ pub struct NetworkStatus {
 
     /// How we're connecting to the network
     pub network_info: NetworkConfig,
+
+    /// The block count last reported by each consensus node, keyed by the
+    /// node's responder id.
+    #[serde(default)]
+    pub peer_block_heights: BTreeMap<String, String>,
+
+    /// Whether the local block count has caught up with the network block
+    /// count. Null if the network block count is unknown, such as when running
+    /// offline.
+    #[serde(default)]
+    pub is_synced: Option<bool>,
 }
 
 impl TryFrom<&service::balance::NetworkStatus> for NetworkStatus {
@@ -49,6 +60,12 @@ impl TryFrom<&service::balance::NetworkStatus> for NetworkStatus {
             block_version: src.block_version.to_string(),
             max_tombstone_blocks: constants::MAX_TOMBSTONE_BLOCKS.to_string(),
             network_info: src.network_info.clone(),
+            peer_block_heights: src
+                .peer_block_heights
+                .iter()
+                .map(|(responder_id, height)| (responder_id.clone(), height.to_string()))
+                .collect(),
+            is_synced: src.is_synced,
         })
     }
 }
@@ -0,0 +1,29 @@
+// Copyright (c) 2020-2024 MobileCoin Inc.
+
+//! API definition for the SyncProgress object.
+
+use crate::service;
+use serde_derive::{Deserialize, Serialize};
+
+/// How far an account has synced, sent after each chunk of blocks is scanned.
+#[derive(Deserialize, Serialize, Default, Debug, Clone, PartialEq)]
+pub struct SyncProgress {
+    /// The account being synced.
+    pub account_id: String,
+
+    /// The number of blocks scanned for this account so far.
+    pub blocks_synced: String,
+
+    /// The number of blocks in the local ledger.
+    pub total_blocks: String,
+}
+
+impl From<&service::sync::SyncProgress> for SyncProgress {
+    fn from(src: &service::sync::SyncProgress) -> SyncProgress {
+        SyncProgress {
+            account_id: src.account_id.to_string(),
+            blocks_synced: src.blocks_synced.to_string(),
+            total_blocks: src.total_blocks.to_string(),
+        }
+    }
+}
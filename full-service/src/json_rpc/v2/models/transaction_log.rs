@@ -36,6 +36,11 @@ pub struct TransactionLog {
 
     pub value_map: HashMap<String, String>,
 
+    /// The values in `value_map` as amounts, ordered by token id, only given
+    /// when structured amounts are requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_amounts: Option<Vec<Amount>>,
+
     pub fee_amount: Amount,
 
     /// The block index of the highest block on the network at the time the
@@ -47,6 +52,11 @@ pub struct TransactionLog {
     ///  The scanned block block index in which this transaction occurred.
     pub finalized_block_index: Option<String>,
 
+    /// Timestamp of the block in which this transaction occurred, in seconds
+    /// since the epoch. Only known when a watcher db is configured, and once
+    /// the watcher has synced that block.
+    pub finalized_time: Option<String>,
+
     /// String representing the transaction log status. On "sent", valid
     /// statuses are "built", "pending", "succeeded", "failed".  On "received",
     /// the status is "succeeded".
@@ -59,6 +69,14 @@ pub struct TransactionLog {
 
     /// An arbitrary string attached to the object.
     pub comment: String,
+
+    /// Time at which this transaction log was added to the wallet, in seconds
+    /// since the epoch.
+    pub created_at: String,
+
+    /// Time at which this transaction log was last modified, in seconds since
+    /// the epoch.
+    pub updated_at: String,
 }
 
 impl TransactionLog {
@@ -85,6 +103,9 @@ impl TransactionLog {
             finalized_block_index: transaction_log
                 .finalized_block_index
                 .map(|b| (b as u64).to_string()),
+            finalized_time: transaction_log
+                .finalized_timestamp
+                .map(|t| (t as u64).to_string()),
             status: transaction_log.status().to_string(),
             input_txos: associated_txos.inputs.iter().map(InputTxo::new).collect(),
             output_txos: associated_txos
@@ -98,11 +119,29 @@ impl TransactionLog {
                 .map(|(txo, recipient)| OutputTxo::new(txo, recipient.to_string()))
                 .collect(),
             value_map: values,
+            value_amounts: None,
             fee_amount: Amount::from(&transaction_log.fee_amount()),
             sent_time: None,
             comment: transaction_log.comment.clone(),
+            created_at: (transaction_log.created_at as u64).to_string(),
+            updated_at: (transaction_log.updated_at as u64).to_string(),
         }
     }
+
+    /// Also give the values in the value map as amounts.
+    pub fn with_amounts(mut self) -> Self {
+        let mut value_amounts: Vec<Amount> = self
+            .value_map
+            .iter()
+            .map(|(token_id, value)| Amount {
+                value: value.clone().into(),
+                token_id: token_id.clone().into(),
+            })
+            .collect();
+        value_amounts.sort_by_key(|amount| amount.token_id.expose_secret().parse::<u64>().ok());
+        self.value_amounts = Some(value_amounts);
+        self
+    }
 }
 
 #[derive(Deserialize, Serialize, Default, Debug, Clone)]
@@ -194,3 +194,16 @@ impl TryFrom<&crate::service::models::tx_proposal::TxProposal> for TxProposal {
         })
     }
 }
+
+/// Upgrade a proposal from the v1 API. The v1 form carries no change outputs or
+/// shared secrets, so neither does the result.
+impl TryFrom<&crate::json_rpc::v1::models::tx_proposal::TxProposal> for TxProposal {
+    type Error = String;
+
+    fn try_from(
+        src: &crate::json_rpc::v1::models::tx_proposal::TxProposal,
+    ) -> Result<Self, String> {
+        let tx_proposal = crate::service::models::tx_proposal::TxProposal::try_from(src)?;
+        TxProposal::try_from(&tx_proposal)
+    }
+}
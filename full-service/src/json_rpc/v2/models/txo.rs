@@ -2,7 +2,10 @@
 
 //! API definition for the Txo object.
 
-use crate::{db::txo::TxoInfo, json_rpc::v2::models::memo::Memo};
+use crate::{
+    db::txo::{TxoInfo, TxoMemo},
+    json_rpc::v2::models::{amount::Amount, memo::Memo},
+};
 use redact::{expose_secret, Secret};
 use serde_derive::{Deserialize, Serialize};
 
@@ -24,13 +27,28 @@ pub struct Txo {
     #[serde(serialize_with = "expose_secret")]
     pub token_id: Secret<String>,
 
+    /// The txo's value and token id as an amount, only given when structured
+    /// amounts are requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<Amount>,
+
     /// Block index in which the txo was received by an account.
     pub received_block_index: Option<String>,
 
+    /// Timestamp of the block in which the txo was received, in seconds since
+    /// the epoch. Only known when a watcher db is configured, and once the
+    /// watcher has synced that block.
+    pub received_time: Option<String>,
+
     /// Block index in which the txo was spent by an account.
     #[serde(serialize_with = "expose_secret")]
     pub spent_block_index: Secret<Option<String>>,
 
+    /// The id of the transaction log that spent this txo, if it was spent by a
+    /// transaction built in this wallet.
+    #[serde(default)]
+    pub spent_in_transaction_id: Option<String>,
+
     /// The account_id for the account which has received this TXO. This account
     /// has spend authority.
     pub account_id: Option<String>,
@@ -68,6 +86,20 @@ pub struct Txo {
     #[serde(serialize_with = "expose_secret")]
     pub shared_secret: Secret<Option<String>>,
     pub memo: Memo,
+
+    /// The short hash of the sender's address, from an authenticated sender
+    /// memo.
+    pub sender_address_hash: Option<String>,
+
+    /// The payment request id carried by the memo, if any.
+    pub payment_request_id: Option<String>,
+
+    /// Time at which this txo was added to the wallet, in seconds since the
+    /// epoch.
+    pub created_at: String,
+
+    /// Time at which this txo was last modified, in seconds since the epoch.
+    pub updated_at: String,
 }
 
 impl From<&TxoInfo> for Txo {
@@ -76,15 +108,21 @@ impl From<&TxoInfo> for Txo {
             id: txo_info.txo.id.clone(),
             value: (txo_info.txo.value as u64).to_string().into(),
             token_id: (txo_info.txo.token_id as u64).to_string().into(),
+            amount: None,
             received_block_index: txo_info
                 .txo
                 .received_block_index
                 .map(|x| (x as u64).to_string()),
+            received_time: txo_info
+                .txo
+                .received_timestamp
+                .map(|x| (x as u64).to_string()),
             spent_block_index: txo_info
                 .txo
                 .spent_block_index
                 .map(|x| (x as u64).to_string())
                 .into(),
+            spent_in_transaction_id: txo_info.spent_in_transaction_id.clone(),
             account_id: txo_info.txo.account_id.clone(),
             status: txo_info.status.to_string().into(),
             target_key: hex::encode(&txo_info.txo.target_key),
@@ -98,10 +136,33 @@ impl From<&TxoInfo> for Txo {
             confirmation: txo_info.txo.confirmation.as_ref().map(hex::encode).into(),
             shared_secret: txo_info.txo.shared_secret.as_ref().map(hex::encode).into(),
             memo: (&txo_info.memo).into(),
+            sender_address_hash: match &txo_info.memo {
+                TxoMemo::AuthenticatedSender(memo) => Some(memo.sender_address_hash.clone()),
+                _ => None,
+            },
+            payment_request_id: match &txo_info.memo {
+                TxoMemo::AuthenticatedSender(memo) => memo.payment_request_id,
+                TxoMemo::Destination(memo) => memo.payment_request_id,
+                TxoMemo::Unused => None,
+            }
+            .map(|id| id.to_string()),
+            created_at: (txo_info.txo.created_at as u64).to_string(),
+            updated_at: (txo_info.txo.updated_at as u64).to_string(),
         }
     }
 }
 
+impl Txo {
+    /// Also give the value and token id as an amount.
+    pub fn with_amount(mut self) -> Self {
+        self.amount = Some(Amount {
+            value: self.value.clone(),
+            token_id: self.token_id.clone(),
+        });
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -159,6 +220,7 @@ mod tests {
             txo,
             status,
             memo: memo.clone(),
+            spent_in_transaction_id: None,
         };
 
         assert_eq!(txo_info.txo.value as u64, 15_625_000 * MOB);
@@ -27,6 +27,18 @@ pub struct WalletStatus {
     pub min_synced_block_index: String,
 
     pub balance_per_token: BTreeMap<String, Balance>,
+
+    /// The number of blocks synced from the validator since startup, when
+    /// backed by a validator.
+    pub validator_blocks_synced: Option<String>,
+
+    /// The index of the last block synced from the validator, if any.
+    pub validator_last_synced_block_index: Option<String>,
+
+    /// Whether the validator served blocks that conflict with the local
+    /// ledger. Ledger syncing halts until this is resolved by an operator.
+    #[serde(default)]
+    pub reorg_detected: bool,
 }
 
 impl TryFrom<&service::balance::WalletStatus> for WalletStatus {
@@ -43,6 +55,11 @@ impl TryFrom<&service::balance::WalletStatus> for WalletStatus {
                 .iter()
                 .map(|(k, v)| (k.to_string(), Balance::from(v)))
                 .collect(),
+            validator_blocks_synced: src.validator_blocks_synced.map(|n| n.to_string()),
+            validator_last_synced_block_index: src
+                .validator_last_synced_block_index
+                .map(|index| index.to_string()),
+            reorg_detected: src.reorg_detected,
         })
     }
 }
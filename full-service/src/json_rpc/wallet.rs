@@ -11,14 +11,18 @@ use crate::{
             response::JsonCommandResponse as JsonCommandResponse_v1,
             wallet::generic_wallet_api as generic_wallet_api_v1,
         },
-        v2::api::{
-            request::help_str as help_str_v2,
-            response::JsonCommandResponse as JsonCommandResponse_v2,
-            wallet::generic_wallet_api as generic_wallet_api_v2,
+        v2::{
+            api::{
+                request::help_str as help_str_v2,
+                response::JsonCommandResponse as JsonCommandResponse_v2,
+                wallet::generic_wallet_api as generic_wallet_api_v2,
+            },
+            models::{health::Health, sync_progress::SyncProgress},
         },
     },
-    service::WalletService,
+    service::{health::HealthService, WalletService},
 };
+use mc_common::logger::log;
 use mc_connection::{
     BlockchainConnection, HardcodedCredentialsProvider, ThickClient, UserTxConnection,
 };
@@ -27,16 +31,23 @@ use mc_fog_report_validation::FogPubkeyResolver;
 use mc_validator_connection::ValidatorConnection;
 use rocket::{
     self,
-    fairing::{Fairing, Info, Kind},
+    fairing::{AdHoc, Fairing, Info, Kind},
     get,
     http::{Header, Status},
     outcome::Outcome,
     post,
     request::FromRequest,
+    response::stream::{Event, EventStream},
     routes,
     serde::json::Json,
-    Request, Response, State,
+    tokio::{
+        select,
+        sync::broadcast::{self, error::RecvError},
+        task,
+    },
+    Request, Response, Shutdown, State,
 };
+use std::time::Duration;
 
 /// State managed by rocket.
 pub struct WalletState<
@@ -47,8 +58,36 @@ pub struct WalletState<
     pub service: WalletService<T, FPR>,
 }
 
+/// Sync progress events, streamed to clients of the sync progress endpoint.
+pub struct SyncProgressState(pub broadcast::Sender<SyncProgress>);
+
+impl SyncProgressState {
+    /// Forward the sync progress of the given service to every subscriber.
+    pub fn new<T, FPR>(service: &WalletService<T, FPR>) -> Self
+    where
+        T: BlockchainConnection + UserTxConnection + 'static,
+        FPR: FogPubkeyResolver + Send + Sync + 'static,
+    {
+        let (sender, _) = broadcast::channel(SYNC_PROGRESS_CHANNEL_CAPACITY);
+        let listener_sender = sender.clone();
+        service.add_sync_progress_listener(move |progress| {
+            // Sending only fails when nobody is subscribed.
+            let _ = listener_sender.send(SyncProgress::from(progress));
+        });
+        Self(sender)
+    }
+}
+
+/// How many sync progress events are buffered for a slow subscriber before it
+/// starts skipping events.
+pub const SYNC_PROGRESS_CHANNEL_CAPACITY: usize = 1024;
+
 pub const API_KEY_HEADER: &str = "X-API-KEY";
 
+/// How long to wait for each wallet service thread to stop when the server
+/// shuts down.
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
 pub struct APIKeyState(pub String);
 
 /// Ensures check for a pre-shared symmetric API key for the JsonRPC loop on the
@@ -124,6 +163,36 @@ fn health() -> Result<(), ()> {
     Ok(())
 }
 
+/// Whether the wallet's database, ledger, sync thread and peers are usable.
+/// Responds with 503 Service Unavailable, listing the problems, if not.
+fn generic_ready<T, FPR>(state: &WalletState<T, FPR>) -> (Status, Json<Health>)
+where
+    T: BlockchainConnection + UserTxConnection + 'static,
+    FPR: FogPubkeyResolver + Send + Sync + 'static,
+{
+    let health = state.service.get_health();
+    let status = if health.ready() {
+        Status::Ok
+    } else {
+        Status::ServiceUnavailable
+    };
+    (status, Json(Health::from(&health)))
+}
+
+#[get("/ready")]
+fn consensus_backed_ready(
+    state: &rocket::State<WalletState<ThickClient<HardcodedCredentialsProvider>, FogResolver>>,
+) -> (Status, Json<Health>) {
+    generic_ready(state)
+}
+
+#[get("/ready")]
+fn validator_backed_ready(
+    state: &rocket::State<WalletState<ValidatorConnection, FogResolver>>,
+) -> (Status, Json<Health>) {
+    generic_ready(state)
+}
+
 #[get("/wallet")]
 fn wallet_help_v1() -> Result<String, String> {
     Ok(help_str_v1())
@@ -171,6 +240,31 @@ async fn validator_backed_wallet_api_v2(
 ) -> Result<Json<JsonRPCResponse<JsonCommandResponse_v2>>, String> {
     generic_wallet_api_v2(_api_key_guard, state, command).await
 }
+
+/// Streams the progress of account syncing as Server-Sent Events, one event per
+/// chunk of blocks synced for an account.
+#[get("/wallet/v2/sync_progress")]
+fn sync_progress(
+    _api_key_guard: ApiKeyGuard,
+    state: &rocket::State<SyncProgressState>,
+    mut shutdown: Shutdown,
+) -> EventStream![] {
+    let mut receiver = state.0.subscribe();
+    EventStream! {
+        loop {
+            let progress = select! {
+                progress = receiver.recv() => match progress {
+                    Ok(progress) => progress,
+                    Err(RecvError::Closed) => break,
+                    Err(RecvError::Lagged(_)) => continue,
+                },
+                _ = &mut shutdown => break,
+            };
+            yield Event::json(&progress);
+        }
+    }
+}
+
 /// Needed to preflight OPTIONS queries for CORS.
 /// Catches all OPTION requests in order to get the CORS related Fairing
 /// triggered.
@@ -179,6 +273,38 @@ fn all_options() {
     /* Intentionally left empty */
 }
 
+/// Stops the wallet service threads when the server shuts down, so pending
+/// webhooks are delivered before the process exits.
+fn wallet_service_shutdown<T, FPR>() -> AdHoc
+where
+    T: BlockchainConnection + UserTxConnection + 'static,
+    FPR: FogPubkeyResolver + Send + Sync + 'static,
+    WalletState<T, FPR>: Send + Sync,
+{
+    AdHoc::on_shutdown("Wallet Service Shutdown", |rocket| {
+        Box::pin(async move {
+            if let Some(state) = rocket.state::<WalletState<T, FPR>>() {
+                // Joining the threads blocks, so keep it off the async executor.
+                let threads = state.service.begin_shutdown();
+                let result = task::spawn_blocking(move || threads.stop(SHUTDOWN_TIMEOUT)).await;
+                match result {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => log::error!(
+                        state.service.logger,
+                        "Failed stopping wallet service: {}",
+                        e
+                    ),
+                    Err(e) => log::error!(
+                        state.service.logger,
+                        "Wallet service shutdown task failed: {}",
+                        e
+                    ),
+                }
+            }
+        })
+    })
+}
+
 /// Returns an instance of a Rocket server.
 pub fn consensus_backed_rocket(
     rocket_config: rocket::Config,
@@ -192,6 +318,11 @@ pub fn consensus_backed_rocket(
         });
     }
 
+    consensus_rocket = consensus_rocket.attach(wallet_service_shutdown::<
+        ThickClient<HardcodedCredentialsProvider>,
+        FogResolver,
+    >());
+
     consensus_rocket.mount(
         "/",
         routes![
@@ -199,7 +330,9 @@ pub fn consensus_backed_rocket(
             consensus_backed_wallet_api_v2,
             wallet_help_v1,
             wallet_help_v2,
+            sync_progress,
             health,
+            consensus_backed_ready,
             all_options
         ],
     )
@@ -217,6 +350,9 @@ pub fn validator_backed_rocket(
         });
     }
 
+    validator_rocket =
+        validator_rocket.attach(wallet_service_shutdown::<ValidatorConnection, FogResolver>());
+
     validator_rocket.mount(
         "/",
         routes![
@@ -224,7 +360,9 @@ pub fn validator_backed_rocket(
             validator_backed_wallet_api_v2,
             wallet_help_v1,
             wallet_help_v2,
+            sync_progress,
             health,
+            validator_backed_ready,
             all_options
         ],
     )
@@ -6,21 +6,26 @@ use std::ops::DerefMut;
 
 use crate::{
     db::{
-        account::{AccountID, AccountModel},
+        account::{AccountID, AccountModel, AccountState},
+        assigned_subaddress::AssignedSubaddressModel,
         exclusive_transaction,
-        models::{Account, Txo},
+        models::{Account, AssignedSubaddress, Txo},
         txo::TxoModel,
-        WalletDbError,
+        Conn, WalletDbError,
     },
+    error::SyncError,
     json_rpc::{
         json_rpc_request::JsonRPCRequest,
+        json_rpc_response::{ServerErrorCode, ServerErrorCodes},
         v2::{api::request::JsonCommandRequest, models::account_key::FogInfo},
     },
     service::{
         hardware_wallet::{
             get_view_only_account_keys, get_view_only_subaddress_keys, HardwareWalletServiceError,
+            DEFAULT_HARDWARE_ACCOUNT_INDEX,
         },
         ledger::{LedgerService, LedgerServiceError},
+        sync::{decode_amount, sync_account_block_range},
         WalletService,
     },
 };
@@ -41,7 +46,8 @@ use mc_core::{
 use mc_crypto_keys::RistrettoPublic;
 use mc_fog_report_validation::FogPubkeyResolver;
 use mc_fog_sig_authority::Signer;
-use mc_ledger_db::Ledger;
+use mc_ledger_db::{Ledger, LedgerDB};
+use mc_transaction_core::{tokens::Mob, Amount, Token, TokenId};
 use mc_transaction_signer::types::TxoSynced;
 
 #[derive(Display, Debug)]
@@ -87,6 +93,26 @@ pub enum AccountServiceError {
 
     /// Error with the HardwareWalletService: {0}
     HardwareWalletService(HardwareWalletServiceError),
+
+    /// Invalid account state: {0}
+    InvalidAccountState(String),
+
+    /// Account {0} has pending transactions; remove it with force to do so
+    /// anyway
+    AccountHasPendingTransactions(AccountID),
+
+    /// An account named {0} already exists
+    DuplicateAccountName(String),
+
+    /// First block index {0} is past the end of the local ledger, which has {1}
+    /// blocks
+    FirstBlockIndexBeyondLedger(u64, u64),
+
+    /// Default fee {0} is below the network fee {1}
+    DefaultFeeBelowNetworkFee(u64, u64),
+
+    /// Error syncing the account: {0}
+    Sync(SyncError),
 }
 
 impl From<WalletDbError> for AccountServiceError {
@@ -149,6 +175,47 @@ impl From<HardwareWalletServiceError> for AccountServiceError {
     }
 }
 
+impl From<SyncError> for AccountServiceError {
+    fn from(src: SyncError) -> Self {
+        Self::Sync(src)
+    }
+}
+
+impl ServerErrorCode for AccountServiceError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::Database(e) => e.code(),
+            Self::LedgerService(e) => e.code(),
+            Self::HardwareWalletService(e) => e.code(),
+            Self::Sync(e) => e.code(),
+            Self::Diesel(diesel::result::Error::NotFound) => Some(ServerErrorCodes::NotFound),
+            _ => None,
+        }
+    }
+}
+
+/// The secret from which an account in a bulk import is recovered.
+#[derive(Clone)]
+pub enum ImportAccountSecret {
+    /// A BIP39 english mnemonic phrase.
+    Mnemonic(String),
+
+    /// A hex-encoded legacy root entropy.
+    LegacyRootEntropy(String),
+}
+
+/// A single account to import with `AccountService::import_accounts`.
+#[derive(Clone)]
+pub struct ImportAccountRequest {
+    pub secret: ImportAccountSecret,
+    pub name: Option<String>,
+    pub first_block_index: Option<u64>,
+    pub next_subaddress_index: Option<u64>,
+    pub fog_report_url: String,
+    pub fog_authority_spki: String,
+    pub require_spend_subaddress: bool,
+}
+
 /// AccountService trait defining the ways in which the wallet can interact with and manage
 #[rustfmt::skip]
 #[async_trait]
@@ -224,6 +291,24 @@ pub trait AccountService {
         require_spend_subaddress: bool,
     ) -> Result<Account, AccountServiceError>;
 
+    /// Import several existing accounts to the wallet in a single call.
+    ///
+    /// Each account is imported on its own, so an invalid mnemonic or entropy
+    /// only fails that account and the rest of the batch is still imported.
+    ///
+    /// # Arguments
+    ///
+    ///| Name       | Purpose                 | Notes                                                                                 |
+    ///|------------|-------------------------|---------------------------------------------------------------------------------------|
+    ///| `requests` | The accounts to import. | Each is imported as by `import_account` or `import_account_from_legacy_root_entropy`. |
+    ///
+    /// # Returns:
+    /// * One result per request, in the same order as the requests
+    fn import_accounts(
+        &self,
+        requests: Vec<ImportAccountRequest>,
+    ) -> Vec<Result<Account, AccountServiceError>>;
+
     /// Import an existing account to the wallet using the mnemonic.
     ///
     /// # Arguments
@@ -246,12 +331,25 @@ pub trait AccountService {
         require_spend_subaddress: bool,
     ) -> Result<Account, AccountServiceError>;
 
+    /// Import the view keys of an account held on a hardware wallet.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                       | Purpose                                                | Notes                                                   |
+    ///|----------------------------|--------------------------------------------------------|---------------------------------------------------------|
+    ///| `name`                     | A label for this account.                              | A label can have duplicates, but it is not recommended. |
+    ///| `first_block_index`        | The block from which to start scanning the ledger.     |                                                         |
+    ///| `fog_info`                 | The fog report url and authority of the account.       | Optional.                                               |
+    ///| `require_spend_subaddress` | If enabled, transactions must spend from a subaddress. |                                                         |
+    ///| `account_index`            | The account index on the hardware wallet.              | Defaults to 0. Used again when syncing and signing.     |
+    ///
     async fn import_view_only_account_from_hardware_wallet(
         &self,
         name: Option<String>,
         first_block_index: Option<u64>,
         fog_info: Option<FogInfo>,
         require_spend_subaddress: bool,
+        account_index: Option<u32>,
     ) -> Result<Account, AccountServiceError>;
 
     /// Re-create sync request for a view only account
@@ -295,6 +393,23 @@ pub trait AccountService {
         limit: Option<u64>,
     ) -> Result<Vec<Account>, AccountServiceError>;
 
+    /// Search the accounts in a given wallet by name.
+    ///
+    /// # Arguments
+    ///
+    ///| Name     | Purpose                                                    | Notes                               |
+    ///|----------|------------------------------------------------------------|-------------------------------------|
+    ///| `query`  | Text to find in the account name.                          | Case-insensitive. Empty matches all |
+    ///| `offset` | The pagination offset. Results start at the offset index.  | Optional, defaults to 0.            |
+    ///| `limit`  | Limit for the number of results.                           | Optional                            |
+    ///
+    fn search_accounts(
+        &self,
+        query: &str,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<Account>, AccountServiceError>;
+
     /// Get the current status of a given account. The account status includes both the account object and the balance object.
     ///
     /// # Arguments
@@ -351,6 +466,54 @@ pub trait AccountService {
         require_spend_subaddress: bool,
     ) -> Result<Account, AccountServiceError>;
 
+    /// Set the default fee for an account, used for MOB fees when a transaction is built without
+    /// one. An explicit fee always takes precedence.
+    ///
+    /// # Arguments
+    ///
+    ///| Name          | Purpose                                      | Notes                                 |
+    ///|---------------|----------------------------------------------|---------------------------------------|
+    ///| `account_id`  | The account on which to perform this action. | Account must exist in the wallet.     |
+    ///| `default_fee` | The default fee, in picoMOB.                 | None to fall back to the network fee. |
+    ///|               |                                              | Must not be below the network fee.    |
+    ///
+    fn set_account_default_fee(
+        &self,
+        account_id: &AccountID,
+        default_fee: Option<u64>,
+    ) -> Result<Account, AccountServiceError>;
+
+    /// Set whether deposits to an account trigger the deposits webhook. Accounts are enabled by
+    /// default.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                      | Notes                             |
+    ///|--------------|----------------------------------------------|-----------------------------------|
+    ///| `account_id` | The account on which to perform this action. | Account must exist in the wallet. |
+    ///| `enabled`    | Whether to send webhooks for this account.   |                                   |
+    ///
+    fn set_account_webhook(
+        &self,
+        account_id: &AccountID,
+        enabled: bool,
+    ) -> Result<Account, AccountServiceError>;
+
+    /// Assign a new subaddress to an account and send the change of its future transactions
+    /// there, unless they choose another. Earlier change subaddresses stay assigned, so change
+    /// already sent to them is still received.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                      | Notes                                                        |
+    ///|--------------|----------------------------------------------|--------------------------------------------------------------|
+    ///| `account_id` | The account on which to perform this action. | Account must exist in the wallet and must not be view only. |
+    ///
+    fn rotate_change_subaddress(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<AssignedSubaddress, AccountServiceError>;
+
     /// complete a sync request for a view only account
     ///
     /// # Arguments
@@ -366,21 +529,76 @@ pub trait AccountService {
         synced_txos: Vec<TxoSynced>,
     ) -> Result<(), AccountServiceError>;
 
+    /// Scan a range of blocks for an account ahead of its full sync, recording the txos it
+    /// received and spent in them without moving its next block index.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                | Purpose                                      | Notes                               |
+    ///|---------------------|----------------------------------------------|-------------------------------------|
+    ///| `account_id`        | The account on which to perform this action. | Account must exist in the wallet.   |
+    ///| `start_block_index` | The first block to scan.                     |                                     |
+    ///| `end_block_index`   | The block to stop scanning before.           | Blocks past the ledger are skipped. |
+    ///
+    /// # Returns
+    /// * The number of txos received in the range
+    fn sync_account_block_range(
+        &self,
+        account_id: &AccountID,
+        start_block_index: u64,
+        end_block_index: u64,
+    ) -> Result<usize, AccountServiceError>;
+
     /// Remove an account from the wallet.
     ///
+    /// An account with pending transactions is not removed unless `force` is set, since once
+    /// it is gone there is no way to tell whether those transactions landed.
+    ///
     /// # Arguments
     ///
-    ///| Name         | Purpose                                      | Notes                             |
-    ///|--------------|----------------------------------------------|-----------------------------------|
-    ///| `account_id` | The account on which to perform this action. | Account must exist in the wallet. |
-    ///| `name`       | The new name for this account.               |                                   |
+    ///| Name         | Purpose                                                 | Notes                             |
+    ///|--------------|---------------------------------------------------------|-----------------------------------|
+    ///| `account_id` | The account on which to perform this action.            | Account must exist in the wallet. |
+    ///| `force`      | Remove the account even if it has pending transactions. |                                   |
     ///
     fn remove_account(
         &self, 
-        account_id: &AccountID
+        account_id: &AccountID,
+        force: bool,
     ) -> Result<bool, AccountServiceError>;
 
     fn resync_in_progress(&self) -> Result<bool, AccountServiceError>;
+
+    /// Export an account's keys, subaddresses, txos, transaction logs and sync position,
+    /// so that it can be moved to another wallet without re-syncing.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                      | Notes                             |
+    ///|--------------|----------------------------------------------|-----------------------------------|
+    ///| `account_id` | The account on which to perform this action. | Account must exist in the wallet. |
+    ///
+    fn export_account_state(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<AccountState, AccountServiceError>;
+
+    /// Import an account exported with `export_account_state`.
+    ///
+    /// The state is checked against the local ledger before it is imported: the ledger
+    /// must have reached the account's sync position, and every received txo must match
+    /// the ledger.
+    ///
+    /// # Arguments
+    ///
+    ///| Name    | Purpose                           | Notes                                 |
+    ///|---------|-----------------------------------|---------------------------------------|
+    ///| `state` | The exported state of the account | Account must not exist in the wallet. |
+    ///
+    fn import_account_state(
+        &self,
+        state: &AccountState,
+    ) -> Result<Account, AccountServiceError>;
 }
 
 #[async_trait]
@@ -423,13 +641,15 @@ where
         let mut pooled_conn = self.get_pooled_conn()?;
         let conn = pooled_conn.deref_mut();
 
+        let name = name.unwrap_or_default();
         exclusive_transaction(conn, |conn| {
+            self.check_account_name_available(Some(name.as_str()), None, conn)?;
             let (account_id, _public_address_b58) = Account::create_from_mnemonic(
                 &mnemonic,
                 Some(first_block_index),
                 Some(import_block_index),
                 None,
-                &name.unwrap_or_default(),
+                &name,
                 fog_report_url,
                 fog_authority_spki,
                 require_spend_subaddress,
@@ -457,15 +677,17 @@ where
             first_block_index,
         );
 
-        // Get mnemonic from phrase
-        let mnemonic = match Mnemonic::from_phrase(&mnemonic_phrase, Language::English) {
-            Ok(m) => m,
-            Err(_) => {
-                return Err(AccountServiceError::InvalidMnemonic(
-                    mnemonic_phrase.to_string(),
-                ))
-            }
-        };
+        // Get mnemonic from phrase. The phrase is normalized to single spaces so that
+        // copy-pasted exports with stray whitespace still parse. We report the reason
+        // the phrase was rejected (unknown word, bad checksum, wrong length) rather
+        // than echoing the secret phrase back in the error.
+        let normalized_phrase = mnemonic_phrase
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mnemonic = Mnemonic::from_phrase(&normalized_phrase, Language::English)
+            .map_err(|e| AccountServiceError::InvalidMnemonic(e.to_string()))?;
+        self.check_first_block_index(first_block_index)?;
 
         // We record the local highest block index because that is the earliest we could
         // start scanning.
@@ -474,6 +696,7 @@ where
         let mut pooled_conn = self.get_pooled_conn()?;
         let conn = pooled_conn.deref_mut();
         exclusive_transaction(conn, |conn| {
+            self.check_account_name_available(name.as_deref(), None, conn)?;
             Ok(Account::import(
                 &mnemonic,
                 name,
@@ -507,6 +730,7 @@ where
         // Get account key from entropy
         let mut entropy_bytes = [0u8; 32];
         hex::decode_to_slice(entropy, &mut entropy_bytes)?;
+        self.check_first_block_index(first_block_index)?;
 
         // We record the local highest block index because that is the earliest we could
         // start scanning.
@@ -515,6 +739,7 @@ where
         let mut pooled_conn = self.get_pooled_conn()?;
         let conn = pooled_conn.deref_mut();
         exclusive_transaction(conn, |conn| {
+            self.check_account_name_available(name.as_deref(), None, conn)?;
             Ok(Account::import_legacy(
                 &RootEntropy::from(&entropy_bytes),
                 name,
@@ -529,6 +754,36 @@ where
         })
     }
 
+    fn import_accounts(
+        &self,
+        requests: Vec<ImportAccountRequest>,
+    ) -> Vec<Result<Account, AccountServiceError>> {
+        requests
+            .into_iter()
+            .map(|request| match request.secret {
+                ImportAccountSecret::Mnemonic(mnemonic_phrase) => self.import_account(
+                    mnemonic_phrase,
+                    request.name,
+                    request.first_block_index,
+                    request.next_subaddress_index,
+                    request.fog_report_url,
+                    request.fog_authority_spki,
+                    request.require_spend_subaddress,
+                ),
+                ImportAccountSecret::LegacyRootEntropy(entropy) => self
+                    .import_account_from_legacy_root_entropy(
+                        entropy,
+                        request.name,
+                        request.first_block_index,
+                        request.next_subaddress_index,
+                        request.fog_report_url,
+                        request.fog_authority_spki,
+                        request.require_spend_subaddress,
+                    ),
+            })
+            .collect()
+    }
+
     fn import_view_only_account(
         &self,
         view_private_key: &RootViewPrivate,
@@ -544,6 +799,7 @@ where
             name,
             first_block_index,
         );
+        self.check_first_block_index(first_block_index)?;
 
         let mut pooled_conn = self.get_pooled_conn()?;
         let conn = pooled_conn.deref_mut();
@@ -553,13 +809,14 @@ where
             ViewAccountKey::new(*view_private_key.as_ref(), *spend_public_key.as_ref());
 
         exclusive_transaction(conn, |conn| {
+            self.check_account_name_available(name.as_deref(), None, conn)?;
             Ok(Account::import_view_only(
                 &view_account_key,
                 name,
                 import_block_index,
                 first_block_index,
                 next_subaddress_index,
-                false,
+                None,
                 require_spend_subaddress,
                 conn,
             )?)
@@ -572,8 +829,12 @@ where
         first_block_index: Option<u64>,
         fog_info: Option<FogInfo>,
         require_spend_subaddress: bool,
+        account_index: Option<u32>,
     ) -> Result<Account, AccountServiceError> {
-        let view_account = get_view_only_account_keys().await?;
+        self.check_first_block_index(first_block_index)?;
+        let account_index = account_index.unwrap_or(DEFAULT_HARDWARE_ACCOUNT_INDEX);
+        let view_account =
+            get_view_only_account_keys(account_index, &self.hardware_wallet_config).await?;
 
         let view_account_keys = ViewAccountKey::new(
             *view_account.view_private_key().as_ref(),
@@ -588,8 +849,12 @@ where
             Some(fog_info) => {
                 let fog_authority_spki =
                     general_purpose::STANDARD.decode(fog_info.authority_spki)?;
-                let default_subaddress_keys =
-                    get_view_only_subaddress_keys(DEFAULT_SUBADDRESS_INDEX).await?;
+                let default_subaddress_keys = get_view_only_subaddress_keys(
+                    account_index,
+                    DEFAULT_SUBADDRESS_INDEX,
+                    &self.hardware_wallet_config,
+                )
+                .await?;
 
                 let default_public_address = get_public_fog_address(
                     &default_subaddress_keys,
@@ -597,25 +862,28 @@ where
                     &fog_authority_spki,
                 );
                 exclusive_transaction(conn, |conn| {
+                    self.check_account_name_available(name.as_deref(), None, conn)?;
                     Ok(Account::import_view_only_from_hardware_wallet_with_fog(
                         &view_account_keys,
                         name,
                         import_block_index,
                         first_block_index,
                         &default_public_address,
+                        account_index,
                         require_spend_subaddress,
                         conn,
                     )?)
                 })
             }
             None => exclusive_transaction(conn, |conn| {
+                self.check_account_name_available(name.as_deref(), None, conn)?;
                 Ok(Account::import_view_only(
                     &view_account_keys,
                     name,
                     import_block_index,
                     first_block_index,
                     None,
-                    true,
+                    Some(account_index),
                     false,
                     conn,
                 )?)
@@ -684,6 +952,17 @@ where
         Ok(Account::list_all(conn, offset, limit)?)
     }
 
+    fn search_accounts(
+        &self,
+        query: &str,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<Account>, AccountServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        Ok(Account::search_by_name(query, conn, offset, limit)?)
+    }
+
     fn get_account(&self, account_id: &AccountID) -> Result<Account, AccountServiceError> {
         let mut pooled_conn = self.get_pooled_conn()?;
         let conn = pooled_conn.deref_mut();
@@ -707,8 +986,11 @@ where
     ) -> Result<Account, AccountServiceError> {
         let mut pooled_conn = self.get_pooled_conn()?;
         let conn = pooled_conn.deref_mut();
-        Account::get(account_id, conn)?.update_name(name, conn)?;
-        Ok(Account::get(account_id, conn)?)
+        exclusive_transaction(conn, |conn| {
+            self.check_account_name_available(Some(name.as_str()), Some(account_id), conn)?;
+            Account::get(account_id, conn)?.update_name(name, conn)?;
+            Ok(Account::get(account_id, conn)?)
+        })
     }
 
     fn update_require_spend_subaddress(
@@ -723,6 +1005,63 @@ where
         Ok(Account::get(account_id, conn)?)
     }
 
+    fn set_account_default_fee(
+        &self,
+        account_id: &AccountID,
+        default_fee: Option<u64>,
+    ) -> Result<Account, AccountServiceError> {
+        if let Some(default_fee) = default_fee {
+            let network_fee = self.get_network_fee(Mob::ID)?;
+            if default_fee < network_fee {
+                return Err(AccountServiceError::DefaultFeeBelowNetworkFee(
+                    default_fee,
+                    network_fee,
+                ));
+            }
+        }
+
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        Account::get(account_id, conn)?.update_default_fee(default_fee, conn)?;
+        Ok(Account::get(account_id, conn)?)
+    }
+
+    fn set_account_webhook(
+        &self,
+        account_id: &AccountID,
+        enabled: bool,
+    ) -> Result<Account, AccountServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        Account::get(account_id, conn)?.update_webhook_enabled(enabled, conn)?;
+        Ok(Account::get(account_id, conn)?)
+    }
+
+    fn rotate_change_subaddress(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<AssignedSubaddress, AccountServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        exclusive_transaction(conn, |conn| {
+            let account = Account::get(account_id, conn)?;
+            if account.view_only {
+                return Err(AccountServiceError::AccountIsViewOnly(account_id.clone()));
+            }
+
+            let (public_address_b58, subaddress_index) =
+                AssignedSubaddress::create_next_for_account(
+                    &account.id,
+                    "Change",
+                    &self.ledger_db,
+                    conn,
+                )?;
+            AssignedSubaddress::set_change(&public_address_b58, conn)?;
+            account.update_change_subaddress_index(subaddress_index as u64, conn)?;
+            Ok(AssignedSubaddress::get(&public_address_b58, conn)?)
+        })
+    }
+
     fn sync_account(
         &self,
         account_id: &AccountID,
@@ -752,13 +1091,54 @@ where
         Ok(())
     }
 
-    fn remove_account(&self, account_id: &AccountID) -> Result<bool, AccountServiceError> {
+    fn sync_account_block_range(
+        &self,
+        account_id: &AccountID,
+        start_block_index: u64,
+        end_block_index: u64,
+    ) -> Result<usize, AccountServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        Ok(sync_account_block_range(
+            &self.ledger_db,
+            conn,
+            &account_id.to_string(),
+            start_block_index,
+            end_block_index,
+            &self.logger,
+        )?)
+    }
+
+    fn remove_account(
+        &self,
+        account_id: &AccountID,
+        force: bool,
+    ) -> Result<bool, AccountServiceError> {
         log::info!(self.logger, "Deleting account {}", account_id,);
         let mut pooled_conn = self.get_pooled_conn()?;
         let conn = pooled_conn.deref_mut();
 
         exclusive_transaction(conn, |conn| {
             let account = Account::get(account_id, conn)?;
+            if !force {
+                let pending_txos = Txo::list_pending(
+                    Some(&account.id),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    conn,
+                )?;
+                if !pending_txos.is_empty() {
+                    return Err(AccountServiceError::AccountHasPendingTransactions(
+                        account_id.clone(),
+                    ));
+                }
+            }
             account.delete(conn)?;
             Ok(true)
         })
@@ -774,6 +1154,163 @@ where
         let conn = pooled_conn.deref_mut();
         Ok(Account::resync_in_progress(conn)?)
     }
+
+    fn export_account_state(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<AccountState, AccountServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+
+        exclusive_transaction(conn, |conn| {
+            let account = Account::get(account_id, conn)?;
+            Ok(account.export_state(conn)?)
+        })
+    }
+
+    fn import_account_state(&self, state: &AccountState) -> Result<Account, AccountServiceError> {
+        validate_account_state(state, &self.ledger_db)?;
+
+        log::info!(
+            self.logger,
+            "Importing account state for {}",
+            state.account.id
+        );
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+
+        exclusive_transaction(conn, |conn| Ok(Account::import_state(state, conn)?))
+    }
+}
+
+impl<T, FPR> WalletService<T, FPR>
+where
+    T: BlockchainConnection + UserTxConnection + 'static,
+    FPR: FogPubkeyResolver + Send + Sync + 'static,
+{
+    /// When account names must be unique, fail if an account other than
+    /// `account_id` already has this name. Unnamed accounts are not checked.
+    fn check_account_name_available(
+        &self,
+        name: Option<&str>,
+        account_id: Option<&AccountID>,
+        conn: Conn,
+    ) -> Result<(), AccountServiceError> {
+        let name = match name {
+            Some(name) if self.unique_account_names && !name.is_empty() => name,
+            _ => return Ok(()),
+        };
+        let taken = Account::list_all(conn, None, None)?.iter().any(|account| {
+            account.name == name && account_id.map_or(true, |id| id.to_string() != account.id)
+        });
+        if taken {
+            return Err(AccountServiceError::DuplicateAccountName(name.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Fail if an imported account would start scanning past the end of the
+    /// local ledger, since it could not sync until the ledger caught up.
+    fn check_first_block_index(
+        &self,
+        first_block_index: Option<u64>,
+    ) -> Result<(), AccountServiceError> {
+        let num_blocks = self.ledger_db.num_blocks()?;
+        if let Some(first_block_index) = first_block_index {
+            if first_block_index > num_blocks {
+                return Err(AccountServiceError::FirstBlockIndexBeyondLedger(
+                    first_block_index,
+                    num_blocks,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check that an exported account state is internally consistent, and
+/// consistent with the local ledger.
+fn validate_account_state(
+    state: &AccountState,
+    ledger_db: &LedgerDB,
+) -> Result<(), AccountServiceError> {
+    let account = &state.account;
+
+    let view_account_key = if account.view_only {
+        mc_util_serial::decode::<ViewAccountKey>(&account.account_key)?
+    } else {
+        ViewAccountKey::from(&mc_util_serial::decode::<AccountKey>(&account.account_key)?)
+    };
+    if AccountID::from(&view_account_key).to_string() != account.id {
+        return Err(AccountServiceError::InvalidAccountState(
+            "account id does not match the account key".to_string(),
+        ));
+    }
+
+    let num_blocks = ledger_db.num_blocks()?;
+    if account.next_block_index as u64 > num_blocks {
+        return Err(AccountServiceError::InvalidAccountState(format!(
+            "account is synced to block {}, but the ledger only has {} blocks",
+            account.next_block_index, num_blocks
+        )));
+    }
+
+    if state
+        .assigned_subaddresses
+        .iter()
+        .any(|subaddress| subaddress.account_id != account.id)
+        || state
+            .transaction_logs
+            .iter()
+            .any(|transaction_log| transaction_log.account_id != account.id)
+    {
+        return Err(AccountServiceError::InvalidAccountState(
+            "rows belong to another account".to_string(),
+        ));
+    }
+
+    // Every txo the account received must be in the ledger, with the value the
+    // account recorded for it.
+    for txo in state.txos.iter() {
+        if txo.account_id.is_none() {
+            continue;
+        }
+        if txo.account_id.as_ref() != Some(&account.id) {
+            return Err(AccountServiceError::InvalidAccountState(format!(
+                "txo {} belongs to another account",
+                txo.id
+            )));
+        }
+
+        let received_block_index = match txo.received_block_index {
+            Some(received_block_index) => received_block_index,
+            None => continue,
+        };
+        if received_block_index >= account.next_block_index {
+            return Err(AccountServiceError::InvalidAccountState(format!(
+                "txo {} was received after the account's sync position",
+                txo.id
+            )));
+        }
+
+        let public_key = txo.public_key()?;
+        let tx_out_index = ledger_db.get_tx_out_index_by_public_key(&public_key)?;
+        let tx_out = ledger_db.get_tx_out_by_index(tx_out_index)?;
+        let amount = decode_amount(&tx_out, view_account_key.view_private_key());
+        if amount
+            != Some(Amount::new(
+                txo.value as u64,
+                TokenId::from(txo.token_id as u64),
+            ))
+        {
+            return Err(AccountServiceError::InvalidAccountState(format!(
+                "txo {} does not match the ledger",
+                txo.id
+            )));
+        }
+    }
+
+    Ok(())
 }
 
 fn get_public_fog_address(
@@ -808,12 +1345,18 @@ mod tests {
     use super::*;
     use crate::{
         db::{models::Txo, txo::TxoModel},
-        service::address::AddressService,
+        json_rpc::v2::models::{account_secrets::AccountSecrets, amount::Amount as AmountJSON},
+        service::{
+            address::AddressService,
+            balance::BalanceService,
+            transaction::{TransactionMemo, TransactionService},
+        },
         test_utils::{
-            add_block_to_ledger_db, create_test_received_txo, generate_n_blocks_on_ledger,
-            get_empty_test_ledger, get_test_ledger, manually_sync_account, setup_wallet_service,
-            setup_wallet_service_offline, MOB,
+            add_block_to_ledger_db, add_block_with_tx, create_test_received_txo,
+            generate_n_blocks_on_ledger, get_empty_test_ledger, get_test_ledger,
+            manually_sync_account, setup_wallet_service, setup_wallet_service_offline, MOB,
         },
+        util::b58::b58_encode_public_address,
     };
     use mc_account_keys::{AccountKey, PublicAddress, RootIdentity, ViewAccountKey};
     use mc_common::logger::{async_test_with_logger, test_with_logger, Logger};
@@ -1134,48 +1677,354 @@ mod tests {
     }
 
     #[test_with_logger]
-    fn test_remove_account_from_txo(logger: Logger) {
+    fn test_export_mnemonic_and_reimport_account(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
 
         let known_recipients: Vec<PublicAddress> = Vec::new();
         let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
 
         let service = setup_wallet_service(ledger_db, None, logger);
-        let wallet_db = &service.wallet_db.as_ref().unwrap();
 
-        // Create an account.
+        // Create an account and export its mnemonic.
         let account = service
             .create_account(Some("A".to_string()), "".to_string(), "".to_string(), false)
             .unwrap();
+        assert_eq!(account.key_derivation_version, 2);
 
-        // Add a transaction, with transaction status.
-        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+        let secrets = AccountSecrets::try_from(&account).unwrap();
+        let mnemonic_phrase = secrets.mnemonic.expose_secret().clone().unwrap();
+        assert_eq!(mnemonic_phrase.split_whitespace().count(), 24);
 
-        create_test_received_txo(
-            &account_key,
-            0,
-            Amount::new(100 * MOB, Mob::ID),
-            13_u64,
-            &mut rng,
-            wallet_db,
-        );
+        // Remove the account, then import it back from the exported mnemonic.
+        let account_id = AccountID(account.id.clone());
+        assert!(service.remove_account(&account_id, false).unwrap());
+        assert!(service.get_account(&account_id).is_err());
 
-        let txos = Txo::list_for_account(
-            &account.id,
-            None,
-            None,
+        let imported = service
+            .import_account(
+                mnemonic_phrase,
+                Some("A".to_string()),
+                None,
+                None,
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        assert_eq!(imported.id, account.id);
+        assert_eq!(imported.key_derivation_version, 2);
+        assert_eq!(imported.account_key, account.account_key);
+        assert_eq!(imported.entropy, account.entropy);
+
+        // A phrase with an invalid word is rejected without echoing the phrase back.
+        let bad_phrase = "notaword ".repeat(24);
+        match service.import_account(
+            bad_phrase.clone(),
             None,
             None,
             None,
-            Some(0),
-            wallet_db.get_pooled_conn().unwrap().deref_mut(),
-        )
-        .unwrap();
-        assert_eq!(txos.len(), 1);
-
+            "".to_string(),
+            "".to_string(),
+            false,
+        ) {
+            Err(AccountServiceError::InvalidMnemonic(reason)) => {
+                assert!(!reason.contains("notaword notaword"))
+            }
+            Err(e) => panic!("Unexpected error: {e:?}"),
+            Ok(_) => panic!("Should not import an account from an invalid mnemonic"),
+        }
+    }
+
+    #[test_with_logger]
+    fn test_import_account_with_next_subaddress_index(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let account = service
+            .create_account(Some("A".to_string()), "".to_string(), "".to_string(), false)
+            .unwrap();
+        let secrets = AccountSecrets::try_from(&account).unwrap();
+        let mnemonic_phrase = secrets.mnemonic.expose_secret().clone().unwrap();
+        let account_id = AccountID(account.id.clone());
+        assert!(service.remove_account(&account_id, false).unwrap());
+
+        // Deposits were made to subaddresses assigned before the account was
+        // removed, and one past any that were assigned
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &[account_key.subaddress(7), account_key.subaddress(12)],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        let imported = service
+            .import_account(
+                mnemonic_phrase,
+                Some("A".to_string()),
+                Some(0),
+                Some(10),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        assert_eq!(imported.id, account.id);
+
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &account_id,
+            &logger,
+        );
+
+        let mut pooled_conn = service.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+        assert_eq!(imported.next_subaddress_index(conn).unwrap(), 10);
+
+        let txos = Txo::list_for_account(
+            &account_id.to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            conn,
+        )
+        .unwrap();
+        let subaddress_indices: Vec<Option<i64>> =
+            txos.iter().map(|txo| txo.subaddress_index).collect();
+
+        // The deposit to subaddress 7 is recognized, while the one to
+        // subaddress 12 is orphaned until that subaddress is assigned
+        assert!(subaddress_indices.contains(&Some(7)));
+        assert!(subaddress_indices.contains(&None));
+        assert!(!subaddress_indices.contains(&Some(12)));
+    }
+
+    #[test_with_logger]
+    fn test_import_account_first_block_index(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db, None, logger);
+
+        // A new account starts scanning at the current tip of the ledger.
+        let created = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        assert_eq!(created.first_block_index, 12);
+
+        // An imported account scans from the origin block unless told otherwise.
+        let mnemonic = Mnemonic::new(MnemonicType::Words24, Language::English);
+        let imported = service
+            .import_account(
+                mnemonic.phrase().to_string(),
+                None,
+                None,
+                None,
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        assert_eq!(imported.first_block_index, 0);
+        assert_eq!(imported.next_block_index, 0);
+
+        // Starting past the end of the local ledger is rejected.
+        let mnemonic = Mnemonic::new(MnemonicType::Words24, Language::English);
+        match service.import_account(
+            mnemonic.phrase().to_string(),
+            None,
+            Some(13),
+            None,
+            "".to_string(),
+            "".to_string(),
+            false,
+        ) {
+            Err(AccountServiceError::FirstBlockIndexBeyondLedger(13, 12)) => {}
+            result => panic!("Expected FirstBlockIndexBeyondLedger, got {result:?}"),
+        }
+
+        let view_account_key = ViewAccountKey::from(&AccountKey::random(&mut rng));
+        match service.import_view_only_account(
+            &(*view_account_key.view_private_key()).into(),
+            &(*view_account_key.spend_public_key()).into(),
+            None,
+            Some(200),
+            None,
+            false,
+        ) {
+            Err(AccountServiceError::FirstBlockIndexBeyondLedger(200, 12)) => {}
+            result => panic!("Expected FirstBlockIndexBeyondLedger, got {result:?}"),
+        }
+
+        // Starting at the next block to be appended is allowed.
+        let imported = service
+            .import_account(
+                mnemonic.phrase().to_string(),
+                None,
+                Some(12),
+                None,
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        assert_eq!(imported.first_block_index, 12);
+    }
+
+    #[test_with_logger]
+    fn test_export_and_import_account_state(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        let wallet_db = &service.wallet_db.as_ref().unwrap();
+
+        // Create an account and fund its default and an assigned subaddress.
+        let account = service
+            .create_account(Some("A".to_string()), "".to_string(), "".to_string(), false)
+            .unwrap();
+        let account_id = AccountID(account.id.clone());
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+        let assigned = service
+            .assign_address_for_account(&account_id, Some("deposits"))
+            .unwrap();
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![
+                account_key.default_subaddress(),
+                account_key.subaddress(assigned.subaddress_index as u64),
+            ],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(&ledger_db, wallet_db, &account_id, &service.logger);
+
+        let balance = service.get_balance_for_account(&account_id).unwrap();
+        let account = service.get_account(&account_id).unwrap();
+        let state = service.export_account_state(&account_id).unwrap();
+        assert_eq!(state.txos.len(), 2);
+
+        // Import the state into a second wallet on the same ledger, without syncing.
+        let other_service = setup_wallet_service(ledger_db.clone(), None, logger);
+        let imported = other_service.import_account_state(&state).unwrap();
+        assert_eq!(imported, account);
+        assert_eq!(
+            other_service.get_balance_for_account(&account_id).unwrap(),
+            balance
+        );
+        assert!(!other_service.resync_in_progress().unwrap());
+
+        let addresses = other_service
+            .get_addresses(Some(account_id.to_string()), None, None)
+            .unwrap();
+        assert!(addresses
+            .iter()
+            .any(|address| address.public_address_b58 == assigned.public_address_b58));
+
+        // Importing the same account twice is rejected.
+        assert!(other_service.import_account_state(&state).is_err());
+    }
+
+    #[test_with_logger]
+    fn test_import_account_state_rejects_tampered_txo(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        let wallet_db = &service.wallet_db.as_ref().unwrap();
+
+        let account = service
+            .create_account(Some("A".to_string()), "".to_string(), "".to_string(), false)
+            .unwrap();
+        let account_id = AccountID(account.id.clone());
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(&ledger_db, wallet_db, &account_id, &service.logger);
+
+        // Inflate the value of the received txo.
+        let mut state = service.export_account_state(&account_id).unwrap();
+        state.txos[0].value += 1;
+
+        let other_service = setup_wallet_service(ledger_db.clone(), None, logger);
+        match other_service.import_account_state(&state) {
+            Err(AccountServiceError::InvalidAccountState(_)) => {}
+            Err(e) => panic!("Unexpected error: {e:?}"),
+            Ok(_) => panic!("Should not import a tampered account state"),
+        }
+        assert!(other_service.get_account(&account_id).is_err());
+    }
+
+    #[test_with_logger]
+    fn test_remove_account_from_txo(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db, None, logger);
+        let wallet_db = &service.wallet_db.as_ref().unwrap();
+
+        // Create an account.
+        let account = service
+            .create_account(Some("A".to_string()), "".to_string(), "".to_string(), false)
+            .unwrap();
+
+        // Add a transaction, with transaction status.
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+
+        create_test_received_txo(
+            &account_key,
+            0,
+            Amount::new(100 * MOB, Mob::ID),
+            13_u64,
+            &mut rng,
+            wallet_db,
+        );
+
+        let txos = Txo::list_for_account(
+            &account.id,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(0),
+            wallet_db.get_pooled_conn().unwrap().deref_mut(),
+        )
+        .unwrap();
+        assert_eq!(txos.len(), 1);
+
         // Delete the account. The transaction status referring to it is also cleared.
         let account_id = AccountID(account.id.clone());
-        let result = service.remove_account(&account_id);
+        let result = service.remove_account(&account_id, false);
         assert!(result.is_ok());
 
         let txos = Txo::list_for_account(
@@ -1185,6 +2034,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             Some(0),
             wallet_db.get_pooled_conn().unwrap().deref_mut(),
         )
@@ -1192,6 +2043,199 @@ mod tests {
         assert_eq!(txos.len(), 0);
     }
 
+    #[async_test_with_logger]
+    async fn test_remove_account_leaves_no_rows(logger: Logger) {
+        use crate::db::{
+            schema::{
+                accounts, assigned_subaddresses, authenticated_sender_memos, destination_memos,
+                transaction_input_txos, transaction_logs, transaction_output_txos, txos,
+            },
+            Conn,
+        };
+        use diesel::prelude::*;
+
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        let wallet_db = service.wallet_db.as_ref().unwrap();
+
+        let account = service
+            .create_account(Some("A".to_string()), "".to_string(), "".to_string(), false)
+            .unwrap();
+        let account_id = AccountID(account.id.clone());
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+
+        // Receive funds at the main subaddress and at an assigned subaddress
+        let address = service
+            .assign_address_for_account(&account_id, Some("Deposits"))
+            .unwrap();
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![
+                account_key.default_subaddress(),
+                account_key.subaddress(address.subaddress_index as u64),
+            ],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(&ledger_db, wallet_db, &account_id, &logger);
+
+        // Send some of it away, and sync the change back in
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        let (_transaction_log, _associated_txos, _value_map, tx_proposal) = service
+            .build_sign_and_submit_transaction(
+                &account.id,
+                &[(
+                    b58_encode_public_address(&recipient).unwrap(),
+                    AmountJSON::new(42 * MOB, Mob::ID),
+                )],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        add_block_with_tx(&mut ledger_db, tx_proposal.tx, &mut rng);
+        manually_sync_account(&ledger_db, wallet_db, &account_id, &logger);
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+        let count_rows = |conn: Conn| {
+            [
+                accounts::table.count().get_result::<i64>(conn).unwrap(),
+                assigned_subaddresses::table
+                    .count()
+                    .get_result::<i64>(conn)
+                    .unwrap(),
+                authenticated_sender_memos::table
+                    .count()
+                    .get_result::<i64>(conn)
+                    .unwrap(),
+                destination_memos::table
+                    .count()
+                    .get_result::<i64>(conn)
+                    .unwrap(),
+                transaction_input_txos::table
+                    .count()
+                    .get_result::<i64>(conn)
+                    .unwrap(),
+                transaction_logs::table
+                    .count()
+                    .get_result::<i64>(conn)
+                    .unwrap(),
+                transaction_output_txos::table
+                    .count()
+                    .get_result::<i64>(conn)
+                    .unwrap(),
+                txos::table.count().get_result::<i64>(conn).unwrap(),
+            ]
+        };
+        // Memo rows depend on what the outputs carry, so only check the others
+        let counts = count_rows(conn);
+        for table in [0, 1, 4, 5, 6, 7] {
+            assert!(counts[table] > 0);
+        }
+
+        assert!(service.remove_account(&account_id, false).unwrap());
+        assert_eq!(count_rows(conn), [0; 8]);
+    }
+
+    #[async_test_with_logger]
+    async fn test_remove_account_with_pending_transaction(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        let wallet_db = service.wallet_db.as_ref().unwrap();
+
+        let mut accounts = Vec::new();
+        for name in ["A", "B"] {
+            let account = service
+                .create_account(
+                    Some(name.to_string()),
+                    "".to_string(),
+                    "".to_string(),
+                    false,
+                )
+                .unwrap();
+            let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+            add_block_to_ledger_db(
+                &mut ledger_db,
+                &vec![account_key.default_subaddress()],
+                100 * MOB,
+                &[KeyImage::from(rng.next_u64())],
+                &mut rng,
+            );
+            accounts.push(AccountID(account.id));
+        }
+
+        // Each account sends a transaction that is still pending
+        let mut txs = Vec::new();
+        for account_id in accounts.iter() {
+            manually_sync_account(&ledger_db, wallet_db, account_id, &logger);
+            let recipient = AccountKey::random(&mut rng).default_subaddress();
+            let (_transaction_log, _associated_txos, _value_map, tx_proposal) = service
+                .build_sign_and_submit_transaction(
+                    &account_id.0,
+                    &[(
+                        b58_encode_public_address(&recipient).unwrap(),
+                        AmountJSON::new(42 * MOB, Mob::ID),
+                    )],
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    TransactionMemo::RTH {
+                        subaddress_index: None,
+                    },
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap();
+            txs.push(tx_proposal.tx);
+
+            match service.remove_account(account_id, false) {
+                Err(AccountServiceError::AccountHasPendingTransactions(id)) => {
+                    assert_eq!(&id, account_id)
+                }
+                Ok(_) => panic!("Should not remove an account with a pending transaction"),
+                Err(e) => panic!("Unexpected error: {e:?}"),
+            }
+            assert!(service.get_account(account_id).is_ok());
+        }
+
+        // Once A's transaction lands, A can be removed
+        add_block_with_tx(&mut ledger_db, txs.remove(0), &mut rng);
+        manually_sync_account(&ledger_db, wallet_db, &accounts[0], &logger);
+        assert!(service.remove_account(&accounts[0], false).unwrap());
+        assert!(service.get_account(&accounts[0]).is_err());
+
+        // B's transaction is still pending, but it can be removed with force
+        assert!(service.remove_account(&accounts[1], true).unwrap());
+        assert!(service.get_account(&accounts[1]).is_err());
+    }
+
     #[test_with_logger]
     fn test_create_account_offline(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
@@ -1226,6 +2270,118 @@ mod tests {
         assert_eq!(account.import_block_index, Some(12));
     }
 
+    #[test_with_logger]
+    fn test_unique_account_names(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        // Duplicate names are allowed by default.
+        let service = setup_wallet_service_offline(ledger_db.clone(), logger.clone());
+        for _ in 0..2 {
+            service
+                .create_account(
+                    Some("Alice".to_string()),
+                    "".to_string(),
+                    "".to_string(),
+                    false,
+                )
+                .unwrap();
+        }
+
+        let mut service = setup_wallet_service_offline(ledger_db, logger);
+        service.unique_account_names = true;
+        let alice = service
+            .create_account(
+                Some("Alice".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let result = service.create_account(
+            Some("Alice".to_string()),
+            "".to_string(),
+            "".to_string(),
+            false,
+        );
+        match result {
+            Err(AccountServiceError::DuplicateAccountName(name)) => assert_eq!(name, "Alice"),
+            result => panic!("Expected DuplicateAccountName, got {result:?}"),
+        }
+
+        let mnemonic = Mnemonic::new(MnemonicType::Words24, Language::English);
+        match service.import_account(
+            mnemonic.phrase().to_string(),
+            Some("Alice".to_string()),
+            None,
+            None,
+            "".to_string(),
+            "".to_string(),
+            false,
+        ) {
+            Err(AccountServiceError::DuplicateAccountName(_)) => {}
+            result => panic!("Expected DuplicateAccountName, got {result:?}"),
+        }
+
+        // Renaming to a taken name fails, but an account may keep its own name.
+        let bob = service
+            .create_account(
+                Some("Bob".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        match service.update_account_name(&AccountID(bob.id), "Alice".to_string()) {
+            Err(AccountServiceError::DuplicateAccountName(_)) => {}
+            result => panic!("Expected DuplicateAccountName, got {result:?}"),
+        }
+        service
+            .update_account_name(&AccountID(alice.id), "Alice".to_string())
+            .unwrap();
+
+        // Unnamed accounts are not checked.
+        for _ in 0..2 {
+            service
+                .create_account(None, "".to_string(), "".to_string(), false)
+                .unwrap();
+        }
+    }
+
+    #[test_with_logger]
+    fn test_set_account_default_fee(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+        let service = setup_wallet_service(ledger_db, None, logger);
+
+        let account = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        let account_id = AccountID(account.id);
+
+        let account = service
+            .set_account_default_fee(&account_id, Some(2 * Mob::MINIMUM_FEE))
+            .unwrap();
+        assert_eq!(account.default_fee, Some(2 * Mob::MINIMUM_FEE as i64));
+
+        // A default fee below the network fee would make every transaction
+        // built without a fee fail, so it is rejected and the old one kept.
+        match service.set_account_default_fee(&account_id, Some(Mob::MINIMUM_FEE - 1)) {
+            Err(AccountServiceError::DefaultFeeBelowNetworkFee(fee, network_fee)) => {
+                assert_eq!(fee, Mob::MINIMUM_FEE - 1);
+                assert_eq!(network_fee, Mob::MINIMUM_FEE);
+            }
+            result => panic!("Expected DefaultFeeBelowNetworkFee, got {result:?}"),
+        }
+        let account = service.get_account(&account_id).unwrap();
+        assert_eq!(account.default_fee, Some(2 * Mob::MINIMUM_FEE as i64));
+
+        let account = service.set_account_default_fee(&account_id, None).unwrap();
+        assert_eq!(account.default_fee, None);
+    }
+
     #[test_with_logger]
     fn test_create_account_offline_no_ledger(logger: Logger) {
         let ledger_db = get_empty_test_ledger();
@@ -1310,6 +2466,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             wallet_db.get_pooled_conn().unwrap().deref_mut(),
         )
         .unwrap();
@@ -1325,6 +2483,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             wallet_db.get_pooled_conn().unwrap().deref_mut(),
         )
         .unwrap();
@@ -1358,6 +2518,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             wallet_db.get_pooled_conn().unwrap().deref_mut(),
         )
         .unwrap();
@@ -1371,6 +2533,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             wallet_db.get_pooled_conn().unwrap().deref_mut(),
         )
         .unwrap();
@@ -1385,6 +2549,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             wallet_db.get_pooled_conn().unwrap().deref_mut(),
         )
         .unwrap();
@@ -1403,6 +2569,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             wallet_db.get_pooled_conn().unwrap().deref_mut(),
         )
         .unwrap();
@@ -1431,6 +2599,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             wallet_db.get_pooled_conn().unwrap().deref_mut(),
         )
         .unwrap();
@@ -1444,6 +2614,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             wallet_db.get_pooled_conn().unwrap().deref_mut(),
         )
         .unwrap();
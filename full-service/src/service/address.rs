@@ -9,6 +9,7 @@ use crate::{
         account::AccountID, assigned_subaddress::AssignedSubaddressModel, exclusive_transaction,
         models::AssignedSubaddress, WalletDbError,
     },
+    json_rpc::json_rpc_response::{ServerErrorCode, ServerErrorCodes},
     service::WalletService,
     util::b58::{b58_decode_public_address, B58Error},
 };
@@ -50,6 +51,17 @@ impl From<B58Error> for AddressServiceError {
     }
 }
 
+impl ServerErrorCode for AddressServiceError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::Database(e) => e.code(),
+            Self::B58(e) => e.code(),
+            Self::Diesel(diesel::result::Error::NotFound) => Some(ServerErrorCodes::NotFound),
+            _ => None,
+        }
+    }
+}
+
 /// Trait defining the ways in which the wallet can interact with and manage
 /// addresses.
 #[rustfmt::skip]
@@ -70,6 +82,45 @@ pub trait AddressService {
         // FIXME: FS-32 - add "sync from block"
     ) -> Result<AssignedSubaddress, AddressServiceError>;
 
+    /// Creates a new address tagged with a customer, for deposit tracking.
+    ///
+    /// The first deposit to a tagged address is reported to the deposits
+    /// webhook, along with its customer tag. Balances for the address can be
+    /// retrieved with `get_balance_for_address`.
+    ///
+    /// # Arguments
+    ///
+    ///| Name           | Purpose                                         | Notes                                 |
+    ///|----------------|-------------------------------------------------|---------------------------------------|
+    ///| `account_id`   | The account on which to perform this action.    | The account must exist in the wallet. |
+    ///| `customer_tag` | The customer to whom this address was assigned. |                                       |
+    ///| `metadata`     | The metadata for this address.                  | String; can contain stringified JSON. |
+    ///
+    fn assign_tracked_address_for_account(
+        &self,
+        account_id: &AccountID,
+        customer_tag: &str,
+        metadata: Option<&str>,
+    ) -> Result<AssignedSubaddress, AddressServiceError>;
+
+    /// Creates a new address for a contact in the address book, so that
+    /// payments received at it can be labeled with that contact.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                    | Purpose                                               | Notes                                 |
+    ///|-------------------------|-------------------------------------------------------|---------------------------------------|
+    ///| `account_id`            | The account on which to perform this action.          | The account must exist in the wallet. |
+    ///| `address_book_entry_id` | The address book entry of the contact.                | The entry must exist in the wallet.   |
+    ///| `metadata`              | The metadata for this address.                        | String; can contain stringified JSON. |
+    ///
+    fn assign_address_for_contact(
+        &self,
+        account_id: &AccountID,
+        address_book_entry_id: i32,
+        metadata: Option<&str>,
+    ) -> Result<AssignedSubaddress, AddressServiceError>;
+
     /// Get an assigned subaddress, if it exists.
     ///
     /// # Arguments
@@ -115,6 +166,55 @@ pub trait AddressService {
         limit: Option<u64>,
     ) -> Result<Vec<AssignedSubaddress>, AddressServiceError>;
 
+    /// Gets the addresses for an account that have never received a txo.
+    ///
+    /// The main and change addresses are never included, nor are addresses
+    /// that have been hidden with `set_address_hidden`.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                      | Notes                                 |
+    ///|--------------|----------------------------------------------|---------------------------------------|
+    ///| `account_id` | The account on which to perform this action. | The account must exist in the wallet. |
+    ///
+    fn list_unused_subaddresses(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<Vec<AssignedSubaddress>, AddressServiceError>;
+
+    /// Hides or unhides an assigned address.
+    ///
+    /// Addresses cannot be deleted, since funds could still arrive at them.
+    ///
+    /// # Arguments
+    ///
+    ///| Name          | Purpose                                             | Notes                                        |
+    ///|---------------|-----------------------------------------------------|----------------------------------------------|
+    ///| `address_b58` | The b58 subaddress on which to perform this action. | The address must have already been assigned. |
+    ///| `hidden`      | Whether the address should be hidden.               |                                              |
+    ///
+    fn set_address_hidden(
+        &self,
+        address_b58: &str,
+        hidden: bool,
+    ) -> Result<AssignedSubaddress, AddressServiceError>;
+
+    /// Attaches an assigned address to a contact in the address book, or
+    /// detaches it from its contact.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                    | Purpose                                             | Notes                                        |
+    ///|-------------------------|-----------------------------------------------------|----------------------------------------------|
+    ///| `address_b58`           | The b58 subaddress on which to perform this action. | The address must have already been assigned. |
+    ///| `address_book_entry_id` | The address book entry of the contact.              | None detaches the address from its contact.  |
+    ///
+    fn set_address_book_entry(
+        &self,
+        address_b58: &str,
+        address_book_entry_id: Option<i32>,
+    ) -> Result<AssignedSubaddress, AddressServiceError>;
+
     /// Verifies whether an address can be decoded from b58.
     ///
     /// # Arguments
@@ -153,6 +253,52 @@ where
         })
     }
 
+    fn assign_tracked_address_for_account(
+        &self,
+        account_id: &AccountID,
+        customer_tag: &str,
+        metadata: Option<&str>,
+    ) -> Result<AssignedSubaddress, AddressServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        exclusive_transaction(conn, |conn| {
+            let (public_address_b58, _subaddress_index) =
+                AssignedSubaddress::create_next_for_account(
+                    &account_id.to_string(),
+                    metadata.unwrap_or(""),
+                    &self.ledger_db,
+                    conn,
+                )?;
+            AssignedSubaddress::set_customer_tag(&public_address_b58, customer_tag, conn)?;
+            Ok(AssignedSubaddress::get(&public_address_b58, conn)?)
+        })
+    }
+
+    fn assign_address_for_contact(
+        &self,
+        account_id: &AccountID,
+        address_book_entry_id: i32,
+        metadata: Option<&str>,
+    ) -> Result<AssignedSubaddress, AddressServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        exclusive_transaction(conn, |conn| {
+            let (public_address_b58, _subaddress_index) =
+                AssignedSubaddress::create_next_for_account(
+                    &account_id.to_string(),
+                    metadata.unwrap_or(""),
+                    &self.ledger_db,
+                    conn,
+                )?;
+            AssignedSubaddress::set_address_book_entry(
+                &public_address_b58,
+                Some(address_book_entry_id),
+                conn,
+            )?;
+            Ok(AssignedSubaddress::get(&public_address_b58, conn)?)
+        })
+    }
+
     fn get_address(&self, address_b58: &str) -> Result<AssignedSubaddress, AddressServiceError> {
         let mut pooled_conn = self.get_pooled_conn()?;
         let conn = pooled_conn.deref_mut();
@@ -186,6 +332,44 @@ where
         )?)
     }
 
+    fn list_unused_subaddresses(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<Vec<AssignedSubaddress>, AddressServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        Ok(AssignedSubaddress::list_unused(
+            &account_id.to_string(),
+            conn,
+        )?)
+    }
+
+    fn set_address_hidden(
+        &self,
+        address_b58: &str,
+        hidden: bool,
+    ) -> Result<AssignedSubaddress, AddressServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        exclusive_transaction(conn, |conn| {
+            AssignedSubaddress::set_hidden(address_b58, hidden, conn)?;
+            Ok(AssignedSubaddress::get(address_b58, conn)?)
+        })
+    }
+
+    fn set_address_book_entry(
+        &self,
+        address_b58: &str,
+        address_book_entry_id: Option<i32>,
+    ) -> Result<AssignedSubaddress, AddressServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        exclusive_transaction(conn, |conn| {
+            AssignedSubaddress::set_address_book_entry(address_b58, address_book_entry_id, conn)?;
+            Ok(AssignedSubaddress::get(address_b58, conn)?)
+        })
+    }
+
     fn verify_address(&self, public_address: &str) -> Result<PublicAddress, AddressServiceError> {
         Ok(b58_decode_public_address(public_address)?)
     }
@@ -195,15 +379,22 @@ where
 mod tests {
     use super::*;
     use crate::{
-        db::account::AccountModel,
-        service::account::AccountService,
-        test_utils::{get_test_ledger, setup_wallet_service},
+        db::{
+            account::AccountModel, address_book_entry::AddressBookEntryModel,
+            models::AddressBookEntry,
+        },
+        service::{account::AccountService, balance::BalanceService},
+        test_utils::{
+            add_block_to_ledger_db, create_test_received_txo, get_test_ledger,
+            manually_sync_account, setup_wallet_service, MOB,
+        },
         util::b58::b58_encode_public_address,
     };
     use mc_account_keys::{AccountKey, PublicAddress};
     use mc_common::logger::{test_with_logger, Logger};
     use mc_crypto_keys::{RistrettoPrivate, RistrettoPublic};
     use mc_rand::rand_core::RngCore;
+    use mc_transaction_core::{ring_signature::KeyImage, tokens::Mob, Amount, Token};
     use mc_util_from_random::FromRandom;
     use rand::{rngs::StdRng, SeedableRng};
 
@@ -310,4 +501,239 @@ mod tests {
             bs58::encode(mc_util_serial::encode(&public_address)).into_string();
         assert!(service.verify_address(&public_address_b58).is_err());
     }
+
+    #[test_with_logger]
+    fn test_list_unused_subaddresses(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db, None, logger);
+        let wallet_db = service.wallet_db.as_ref().unwrap();
+
+        let account = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+        let account_id = AccountID(account.id);
+
+        // Only the main and change subaddresses exist, so nothing is unused.
+        assert!(service
+            .list_unused_subaddresses(&account_id)
+            .unwrap()
+            .is_empty());
+
+        let address2 = service
+            .assign_address_for_account(&account_id, Some("visit 2"))
+            .unwrap();
+        let address3 = service
+            .assign_address_for_account(&account_id, Some("visit 3"))
+            .unwrap();
+        let address4 = service
+            .assign_address_for_account(&account_id, Some("visit 4"))
+            .unwrap();
+
+        // Fund subaddress 3, which should exclude it from the unused list.
+        create_test_received_txo(
+            &account_key,
+            3,
+            Amount::new(10 * MOB, Mob::ID),
+            13,
+            &mut rng,
+            wallet_db,
+        );
+
+        let unused = service.list_unused_subaddresses(&account_id).unwrap();
+        assert_eq!(unused, vec![address2.clone(), address4.clone()]);
+        assert!(!unused.contains(&address3));
+        assert_eq!(unused[0].comment, "visit 2");
+
+        // Hidden subaddresses are no longer listed, and can be unhidden.
+        let hidden = service
+            .set_address_hidden(&address2.public_address_b58, true)
+            .unwrap();
+        assert!(hidden.hidden);
+        assert_eq!(
+            service.list_unused_subaddresses(&account_id).unwrap(),
+            vec![address4.clone()]
+        );
+
+        service
+            .set_address_hidden(&address2.public_address_b58, false)
+            .unwrap();
+        assert_eq!(
+            service.list_unused_subaddresses(&account_id).unwrap(),
+            vec![address2, address4]
+        );
+    }
+
+    #[test_with_logger]
+    fn test_assign_tracked_address_reports_first_deposit(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db, None, logger);
+        let wallet_db = service.wallet_db.as_ref().unwrap();
+        let pooled_conn = &mut service.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let account = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+        let account_id = AccountID(account.id.clone());
+
+        let tracked = service
+            .assign_tracked_address_for_account(&account_id, "customer-42", None)
+            .unwrap();
+        assert_eq!(tracked.customer_tag, Some("customer-42".to_string()));
+        let untracked = service
+            .assign_address_for_account(&account_id, None)
+            .unwrap();
+        assert_eq!(untracked.customer_tag, None);
+
+        // Nothing is reported before a deposit arrives.
+        assert!(
+            AssignedSubaddress::list_unreported_first_deposits(&account.id, conn)
+                .unwrap()
+                .is_empty()
+        );
+
+        for subaddress_index in [tracked.subaddress_index, untracked.subaddress_index] {
+            create_test_received_txo(
+                &account_key,
+                subaddress_index as u64,
+                Amount::new(10 * MOB, Mob::ID),
+                13,
+                &mut rng,
+                wallet_db,
+            );
+        }
+
+        // Only the tagged subaddress is reported, and only until it is marked.
+        let unreported =
+            AssignedSubaddress::list_unreported_first_deposits(&account.id, conn).unwrap();
+        assert_eq!(unreported.len(), 1);
+        assert_eq!(unreported[0].public_address_b58, tracked.public_address_b58);
+
+        AssignedSubaddress::set_first_deposit_notified(&tracked.public_address_b58, conn).unwrap();
+        assert!(
+            AssignedSubaddress::list_unreported_first_deposits(&account.id, conn)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test_with_logger]
+    fn test_assign_address_for_contact(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db, None, logger);
+        let pooled_conn = &mut service.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let account = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        let account_id = AccountID(account.id.clone());
+
+        let contact_b58 =
+            b58_encode_public_address(&AccountKey::random(&mut rng).default_subaddress()).unwrap();
+        let contact = AddressBookEntry::create(&contact_b58, "Alice", conn).unwrap();
+
+        let address = service
+            .assign_address_for_contact(&account_id, contact.id, Some("From Alice"))
+            .unwrap();
+        assert_eq!(address.address_book_entry_id, Some(contact.id));
+        assert_eq!(address.comment, "From Alice");
+
+        let untagged = service
+            .assign_address_for_account(&account_id, None)
+            .unwrap();
+        assert_eq!(untagged.address_book_entry_id, None);
+
+        // Unknown entries are rejected, and no address is assigned for them.
+        assert!(service
+            .assign_address_for_contact(&account_id, contact.id + 1, None)
+            .is_err());
+        assert_eq!(
+            account.next_subaddress_index(conn).unwrap(),
+            (untagged.subaddress_index + 1) as u64
+        );
+
+        // An existing address can be attached to and detached from a contact.
+        let untagged = service
+            .set_address_book_entry(&untagged.public_address_b58, Some(contact.id))
+            .unwrap();
+        assert_eq!(untagged.address_book_entry_id, Some(contact.id));
+        let untagged = service
+            .set_address_book_entry(&untagged.public_address_b58, None)
+            .unwrap();
+        assert_eq!(untagged.address_book_entry_id, None);
+        assert!(service
+            .set_address_book_entry(&untagged.public_address_b58, Some(contact.id + 1))
+            .is_err());
+
+        // Deleting the entry detaches it from the address.
+        contact.delete(conn).unwrap();
+        let address = service.get_address(&address.public_address_b58).unwrap();
+        assert_eq!(address.address_book_entry_id, None);
+    }
+
+    #[test_with_logger]
+    fn test_assigning_addresses_repairs_orphaned_txos(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        let wallet_db = service.wallet_db.as_ref().unwrap();
+
+        let account = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+        let account_id = AccountID(account.id.clone());
+
+        // Receive at a subaddress which has not been assigned yet.
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &[account_key.subaddress(5)],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(&ledger_db, wallet_db, &account_id, &logger);
+
+        let balance = service.get_balance_for_account(&account_id).unwrap();
+        let balance_pmob = balance.get(&Mob::ID).unwrap();
+        assert_eq!(balance_pmob.orphaned, (100 * MOB) as u128);
+        assert_eq!(balance_pmob.unspent, 0);
+
+        // Addresses 2 through 4 do not cover the txo.
+        for _ in 2..5 {
+            service
+                .assign_address_for_account(&account_id, None)
+                .unwrap();
+        }
+        let balance = service.get_balance_for_account(&account_id).unwrap();
+        assert_eq!(balance.get(&Mob::ID).unwrap().orphaned, (100 * MOB) as u128);
+
+        let address = service
+            .assign_address_for_account(&account_id, None)
+            .unwrap();
+        assert_eq!(address.subaddress_index, 5);
+
+        let balance = service.get_balance_for_account(&account_id).unwrap();
+        let balance_pmob = balance.get(&Mob::ID).unwrap();
+        assert_eq!(balance_pmob.orphaned, 0);
+        assert_eq!(balance_pmob.unspent, (100 * MOB) as u128);
+    }
 }
@@ -0,0 +1,257 @@
+// Copyright (c) 2020-2024 MobileCoin Inc.
+
+//! Service for managing the address book.
+
+use std::ops::DerefMut;
+
+use crate::{
+    db::{
+        address_book_entry::AddressBookEntryModel, exclusive_transaction, models::AddressBookEntry,
+        WalletDbError,
+    },
+    json_rpc::json_rpc_response::{ServerErrorCode, ServerErrorCodes},
+    service::WalletService,
+};
+use mc_connection::{BlockchainConnection, UserTxConnection};
+use mc_fog_report_validation::FogPubkeyResolver;
+
+use displaydoc::Display;
+
+/// Errors for the Address Book Service.
+#[derive(Display, Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum AddressBookServiceError {
+    /// Error interacting with the database: {0}
+    Database(WalletDbError),
+
+    /// Diesel Error: {0}
+    Diesel(diesel::result::Error),
+}
+
+impl From<WalletDbError> for AddressBookServiceError {
+    fn from(src: WalletDbError) -> Self {
+        Self::Database(src)
+    }
+}
+
+impl From<diesel::result::Error> for AddressBookServiceError {
+    fn from(src: diesel::result::Error) -> Self {
+        Self::Diesel(src)
+    }
+}
+
+impl ServerErrorCode for AddressBookServiceError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::Database(e) => e.code(),
+            Self::Diesel(diesel::result::Error::NotFound) => Some(ServerErrorCodes::NotFound),
+            _ => None,
+        }
+    }
+}
+
+/// Trait defining the ways in which the wallet can interact with and manage
+/// the address book.
+#[rustfmt::skip]
+pub trait AddressBookService {
+    /// Adds a contact to the address book.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                 | Purpose                                        | Notes                                      |
+    ///|----------------------|------------------------------------------------|--------------------------------------------|
+    ///| `public_address_b58` | The b58-encoded public address of the contact. | Must be unique among address book entries. |
+    ///| `name`               | The name of the contact.                       |                                            |
+    ///
+    fn create_address_book_entry(
+        &self,
+        public_address_b58: &str,
+        name: &str,
+    ) -> Result<AddressBookEntry, AddressBookServiceError>;
+
+    /// Get an address book entry.
+    ///
+    /// # Arguments
+    ///
+    ///| Name | Purpose                           | Notes                               |
+    ///|------|-----------------------------------|-------------------------------------|
+    ///| `id` | The id of the address book entry. | The entry must exist in the wallet. |
+    ///
+    fn get_address_book_entry(
+        &self,
+        id: i32,
+    ) -> Result<AddressBookEntry, AddressBookServiceError>;
+
+    /// List the entries in the address book, ordered by id.
+    ///
+    /// # Arguments
+    ///
+    ///| Name     | Purpose                                                   | Notes                    |
+    ///|----------|-----------------------------------------------------------|--------------------------|
+    ///| `offset` | The pagination offset. Results start at the offset index. | Optional, defaults to 0. |
+    ///| `limit`  | Limit for the number of results.                          | Optional                 |
+    ///
+    fn list_address_book_entries(
+        &self,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<AddressBookEntry>, AddressBookServiceError>;
+
+    /// Update the public address and name of an address book entry.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                 | Purpose                                            | Notes                               |
+    ///|----------------------|----------------------------------------------------|-------------------------------------|
+    ///| `id`                 | The id of the address book entry.                  | The entry must exist in the wallet. |
+    ///| `public_address_b58` | The new b58-encoded public address of the contact. |                                     |
+    ///| `name`               | The new name of the contact.                       |                                     |
+    ///
+    fn update_address_book_entry(
+        &self,
+        id: i32,
+        public_address_b58: &str,
+        name: &str,
+    ) -> Result<AddressBookEntry, AddressBookServiceError>;
+
+    /// Remove an address book entry, detaching it from any addresses that
+    /// were assigned to the contact.
+    ///
+    /// # Arguments
+    ///
+    ///| Name | Purpose                           | Notes                               |
+    ///|------|-----------------------------------|-------------------------------------|
+    ///| `id` | The id of the address book entry. | The entry must exist in the wallet. |
+    ///
+    fn remove_address_book_entry(
+        &self,
+        id: i32,
+    ) -> Result<bool, AddressBookServiceError>;
+}
+
+impl<T, FPR> AddressBookService for WalletService<T, FPR>
+where
+    T: BlockchainConnection + UserTxConnection + 'static,
+    FPR: FogPubkeyResolver + Send + Sync + 'static,
+{
+    fn create_address_book_entry(
+        &self,
+        public_address_b58: &str,
+        name: &str,
+    ) -> Result<AddressBookEntry, AddressBookServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        exclusive_transaction(conn, |conn| {
+            Ok(AddressBookEntry::create(public_address_b58, name, conn)?)
+        })
+    }
+
+    fn get_address_book_entry(&self, id: i32) -> Result<AddressBookEntry, AddressBookServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        Ok(AddressBookEntry::get(id, conn)?)
+    }
+
+    fn list_address_book_entries(
+        &self,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<AddressBookEntry>, AddressBookServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        Ok(AddressBookEntry::list_all(conn, offset, limit)?)
+    }
+
+    fn update_address_book_entry(
+        &self,
+        id: i32,
+        public_address_b58: &str,
+        name: &str,
+    ) -> Result<AddressBookEntry, AddressBookServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        exclusive_transaction(conn, |conn| {
+            let address_book_entry = AddressBookEntry::get(id, conn)?;
+            address_book_entry.update(public_address_b58, name, conn)?;
+            Ok(AddressBookEntry::get(id, conn)?)
+        })
+    }
+
+    fn remove_address_book_entry(&self, id: i32) -> Result<bool, AddressBookServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        exclusive_transaction(conn, |conn| {
+            AddressBookEntry::get(id, conn)?.delete(conn)?;
+            Ok(true)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::account::AccountID,
+        service::{account::AccountService, address::AddressService},
+        test_utils::{get_test_ledger, setup_wallet_service},
+        util::b58::b58_encode_public_address,
+    };
+    use mc_account_keys::{AccountKey, PublicAddress};
+    use mc_common::logger::{test_with_logger, Logger};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test_with_logger]
+    fn test_address_book_service(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+        let service = setup_wallet_service(ledger_db, None, logger);
+
+        let alice_b58 =
+            b58_encode_public_address(&AccountKey::random(&mut rng).default_subaddress()).unwrap();
+        let bob_b58 =
+            b58_encode_public_address(&AccountKey::random(&mut rng).default_subaddress()).unwrap();
+
+        let alice = service
+            .create_address_book_entry(&alice_b58, "Alice")
+            .unwrap();
+        let bob = service.create_address_book_entry(&bob_b58, "Bob").unwrap();
+        assert_eq!(service.get_address_book_entry(alice.id).unwrap(), alice);
+
+        // Invalid and duplicate addresses are rejected.
+        assert!(service
+            .create_address_book_entry("not an address", "Carol")
+            .is_err());
+        assert!(service
+            .create_address_book_entry(&alice_b58, "Alice again")
+            .is_err());
+
+        let entries = service.list_address_book_entries(None, None).unwrap();
+        assert_eq!(entries, vec![alice.clone(), bob.clone()]);
+        let entries = service.list_address_book_entries(Some(1), Some(1)).unwrap();
+        assert_eq!(entries, vec![bob.clone()]);
+
+        let updated = service
+            .update_address_book_entry(bob.id, &bob_b58, "Robert")
+            .unwrap();
+        assert_eq!(updated.name, "Robert");
+        assert_eq!(updated.public_address_b58, bob_b58);
+
+        // Removing an entry detaches it from the addresses assigned to it.
+        let account = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        let address = service
+            .assign_address_for_contact(&AccountID(account.id), alice.id, None)
+            .unwrap();
+        assert!(service.remove_address_book_entry(alice.id).unwrap());
+        assert!(service.get_address_book_entry(alice.id).is_err());
+        assert!(service.remove_address_book_entry(alice.id).is_err());
+        let address = service.get_address(&address.public_address_b58).unwrap();
+        assert_eq!(address.address_book_entry_id, None);
+
+        let entries = service.list_address_book_entries(None, None).unwrap();
+        assert_eq!(entries, vec![updated]);
+    }
+}
@@ -12,13 +12,16 @@ use crate::{
         txo::TxoModel,
         Conn, WalletDbError,
     },
+    json_rpc::json_rpc_response::{ServerErrorCode, ServerErrorCodes},
     service::{
         account::{AccountService, AccountServiceError},
         ledger::{LedgerService, LedgerServiceError},
         WalletService,
     },
+    util::constants::LEGACY_CHANGE_SUBADDRESS_INDEX,
 };
 use displaydoc::Display;
+use mc_account_keys::CHANGE_SUBADDRESS_INDEX;
 use mc_blockchain_types::BlockVersion;
 use mc_common::HashMap;
 use mc_connection::{BlockchainConnection, UserTxConnection};
@@ -88,6 +91,18 @@ impl From<FeeMapError> for BalanceServiceError {
     }
 }
 
+impl ServerErrorCode for BalanceServiceError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::Database(e) => e.code(),
+            Self::NetworkBlockHeight(e) => e.code(),
+            Self::AccountServiceError(e) => e.code(),
+            Self::Diesel(diesel::result::Error::NotFound) => Some(ServerErrorCodes::NotFound),
+            _ => None,
+        }
+    }
+}
+
 /// The balance object returned by balance services.
 ///
 /// This must be a service object because there is no "Balance" table in our
@@ -97,6 +112,10 @@ pub struct Balance {
     pub max_spendable: u128,
     pub unverified: u128,
     pub unspent: u128,
+    /// The part of `unspent` held on the subaddresses reserved for change.
+    pub change_unspent: u128,
+    /// The part of `unspent` held on every other subaddress.
+    pub received_unspent: u128,
     pub pending: u128,
     pub spent: u128,
     pub secreted: u128,
@@ -109,6 +128,8 @@ impl Default for &Balance {
             max_spendable: 0,
             unverified: 0,
             unspent: 0,
+            change_unspent: 0,
+            received_unspent: 0,
             pending: 0,
             spent: 0,
             secreted: 0,
@@ -126,6 +147,12 @@ pub struct NetworkStatus {
     pub fees: FeeMap,
     pub block_version: u32,
     pub network_info: NetworkConfig,
+    /// The block height last reported by each consensus node, keyed by
+    /// responder id.
+    pub peer_block_heights: BTreeMap<String, u64>,
+    /// Whether the local ledger has caught up with the network, or None if the
+    /// network block height is unknown because the wallet is offline.
+    pub is_synced: Option<bool>,
 }
 
 /// The Wallet Status object returned by balance services.
@@ -142,6 +169,9 @@ pub struct WalletStatus {
     pub min_synced_block_index: u64,
     pub account_ids: Vec<AccountID>,
     pub account_map: HashMap<AccountID, Account>,
+    pub validator_blocks_synced: Option<u64>,
+    pub validator_last_synced_block_index: Option<u64>,
+    pub reorg_detected: bool,
 }
 
 impl WalletStatus {
@@ -200,21 +230,22 @@ where
         let conn = pooled_conn.deref_mut();
         let account = self.get_account(account_id)?;
         let distinct_token_ids = account.get_token_ids(conn)?;
+        let change_subaddress_indices =
+            AssignedSubaddress::list_change_indices_for_account(&account.id, conn)?;
 
         let network_status = self.get_network_status()?;
 
         let balances = distinct_token_ids
             .into_iter()
             .map(|token_id| {
-                let default_token_fee = network_status
-                    .fees
-                    .get_fee_for_token(&token_id)
-                    .unwrap_or(0);
+                let default_token_fee =
+                    default_fee_for_token(&account, &network_status.fees, token_id);
                 let balance = Self::get_balance_inner(
                     Some(&account_id.to_string()),
                     None,
                     token_id,
                     &default_token_fee,
+                    &change_subaddress_indices,
                     conn,
                 )?;
                 Ok((token_id, balance))
@@ -234,20 +265,21 @@ where
         let account_id = AccountID::from(assigned_address.account_id);
         let account = self.get_account(&account_id)?;
         let distinct_token_ids = account.get_token_ids(conn)?;
+        let change_subaddress_indices =
+            AssignedSubaddress::list_change_indices_for_account(&account.id, conn)?;
         let network_status = self.get_network_status()?;
 
         let balances = distinct_token_ids
             .into_iter()
             .map(|token_id| {
-                let default_token_fee = network_status
-                    .fees
-                    .get_fee_for_token(&token_id)
-                    .unwrap_or(0);
+                let default_token_fee =
+                    default_fee_for_token(&account, &network_status.fees, token_id);
                 let balance = Self::get_balance_inner(
                     None,
                     Some(address),
                     token_id,
                     &default_token_fee,
+                    &change_subaddress_indices,
                     conn,
                 )?;
                 Ok((token_id, balance))
@@ -276,13 +308,25 @@ where
             }
         };
 
+        let peer_block_heights = self
+            .network_state
+            .read()
+            .expect("lock poisoned")
+            .peer_to_current_block_index()
+            .iter()
+            .map(|(responder_id, block_index)| (responder_id.to_string(), block_index + 1))
+            .collect();
+        let local_block_height = self.ledger_db.num_blocks()?;
+
         Ok(NetworkStatus {
             network_block_height,
-            local_block_height: self.ledger_db.num_blocks()?,
+            local_block_height,
             local_num_txos: self.ledger_db.num_txos()?,
             fees: fee_map,
             block_version,
             network_info: self.network_setup_config.clone(),
+            peer_block_heights,
+            is_synced: (!self.offline).then_some(local_block_height >= network_block_height),
         })
     }
 
@@ -302,18 +346,19 @@ where
 
         for account in accounts {
             let account_id = AccountID(account.id.clone());
-            let token_ids = account.clone().get_token_ids(conn)?;
+            let token_ids = account.get_token_ids(conn)?;
+            let change_subaddress_indices =
+                AssignedSubaddress::list_change_indices_for_account(&account.id, conn)?;
 
             for token_id in token_ids {
-                let default_token_fee = network_status
-                    .fees
-                    .get_fee_for_token(&token_id)
-                    .unwrap_or(0);
+                let default_token_fee =
+                    default_fee_for_token(&account, &network_status.fees, token_id);
                 let balance = Self::get_balance_inner(
                     Some(&account_id.to_string()),
                     None,
                     token_id,
                     &default_token_fee,
+                    &change_subaddress_indices,
                     conn,
                 )?;
                 balance_per_token
@@ -321,6 +366,8 @@ where
                     .and_modify(|b: &mut Balance| {
                         b.unverified += balance.unverified;
                         b.unspent += balance.unspent;
+                        b.change_unspent += balance.change_unspent;
+                        b.received_unspent += balance.received_unspent;
                         b.pending += balance.pending;
                         b.spent += balance.spent;
                         b.secreted += balance.secreted;
@@ -345,10 +392,44 @@ where
             min_synced_block_index,
             account_ids,
             account_map,
+            validator_blocks_synced: self
+                .validator_sync_stats
+                .as_ref()
+                .map(|stats| stats.blocks_synced()),
+            validator_last_synced_block_index: self
+                .validator_sync_stats
+                .as_ref()
+                .and_then(|stats| stats.last_synced_block_index()),
+            reorg_detected: self
+                .validator_sync_stats
+                .as_ref()
+                .map_or(false, |stats| stats.reorg_detected()),
         })
     }
 }
 
+/// The fee a transaction from this account pays when none is given, which is
+/// set aside from the max spendable balance.
+fn default_fee_for_token(account: &Account, fees: &FeeMap, token_id: TokenId) -> u64 {
+    account
+        .default_fee_for_token(token_id)
+        .or_else(|| fees.get_fee_for_token(&token_id))
+        .unwrap_or(0)
+}
+
+/// Whether the txo was received on one of the subaddresses reserved for change,
+/// or on any subaddress the account's change has been rotated to.
+fn is_change_txo(txo: &Txo, change_subaddress_indices: &[i64]) -> bool {
+    match txo.subaddress_index {
+        Some(i) if change_subaddress_indices.contains(&i) => true,
+        Some(i) => matches!(
+            i as u64,
+            CHANGE_SUBADDRESS_INDEX | LEGACY_CHANGE_SUBADDRESS_INDEX
+        ),
+        None => false,
+    }
+}
+
 fn sum_query_result(txos: Vec<Txo>) -> u128 {
     txos.iter().map(|t| (t.value as u64) as u128).sum::<u128>()
 }
@@ -364,9 +445,10 @@ where
         public_address_b58: Option<&str>,
         token_id: TokenId,
         default_token_fee: &u64,
+        change_subaddress_indices: &[i64],
         conn: Conn,
     ) -> Result<Balance, BalanceServiceError> {
-        let unspent = sum_query_result(Txo::list_unspent(
+        let (change_unspent_txos, received_unspent_txos): (Vec<Txo>, Vec<Txo>) = Txo::list_unspent(
             account_id_hex,
             public_address_b58,
             Some(*token_id),
@@ -374,8 +456,15 @@ where
             None,
             None,
             None,
+            None,
+            None,
             conn,
-        )?);
+        )?
+        .into_iter()
+        .partition(|txo| is_change_txo(txo, change_subaddress_indices));
+        let change_unspent = sum_query_result(change_unspent_txos);
+        let received_unspent = sum_query_result(received_unspent_txos);
+        let unspent = change_unspent + received_unspent;
 
         let spent = sum_query_result(Txo::list_spent(
             account_id_hex,
@@ -385,6 +474,8 @@ where
             None,
             None,
             None,
+            None,
+            None,
             conn,
         )?);
 
@@ -396,6 +487,8 @@ where
             None,
             None,
             None,
+            None,
+            None,
             conn,
         )?);
 
@@ -407,6 +500,8 @@ where
             None,
             None,
             None,
+            None,
+            None,
             conn,
         )?);
 
@@ -422,6 +517,8 @@ where
                 None,
                 None,
                 None,
+                None,
+                None,
                 conn,
             )?)
         };
@@ -439,6 +536,8 @@ where
             max_spendable: spendable_txos_result.max_spendable_in_wallet,
             unverified,
             unspent,
+            change_unspent,
+            received_unspent,
             pending,
             spent,
             secreted,
@@ -452,12 +551,17 @@ mod tests {
     use super::*;
     use crate::{
         service::{account::AccountService, address::AddressService},
-        test_utils::{get_test_ledger, manually_sync_account, setup_wallet_service, MOB},
+        test_utils::{
+            add_block_to_ledger_db, create_test_received_txo, get_test_ledger,
+            manually_sync_account, setup_mock_peer, setup_wallet_service,
+            setup_wallet_service_offline, setup_wallet_service_with_peers, MOB,
+        },
         util::b58::b58_encode_public_address,
     };
     use mc_account_keys::{AccountKey, PublicAddress, RootEntropy, RootIdentity};
     use mc_common::logger::{test_with_logger, Logger};
-    use mc_transaction_core::{tokens::Mob, Token};
+    use mc_rand::rand_core::RngCore;
+    use mc_transaction_core::{ring_signature::KeyImage, tokens::Mob, Amount, Token};
     use mc_util_from_random::FromRandom;
     use rand::{rngs::StdRng, SeedableRng};
 
@@ -569,4 +673,287 @@ mod tests {
             Err(e) => panic!("Unexpected error {:?}", e),
         }
     }
+
+    // Balances for different tokens in the same account are summed separately.
+    #[test_with_logger]
+    fn test_account_balance_per_token(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db, None, logger);
+        let wallet_db = service.wallet_db.as_ref().unwrap();
+
+        let account = service
+            .create_account(Some("A".to_string()), "".to_string(), "".to_string(), false)
+            .unwrap();
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+
+        let other_token_id = TokenId::from(1);
+        create_test_received_txo(
+            &account_key,
+            0,
+            Amount::new(100 * MOB, Mob::ID),
+            13,
+            &mut rng,
+            wallet_db,
+        );
+        create_test_received_txo(
+            &account_key,
+            0,
+            Amount::new(20 * MOB, Mob::ID),
+            14,
+            &mut rng,
+            wallet_db,
+        );
+        create_test_received_txo(
+            &account_key,
+            1,
+            Amount::new(250, other_token_id),
+            14,
+            &mut rng,
+            wallet_db,
+        );
+
+        let account_balance = service
+            .get_balance_for_account(&AccountID(account.id))
+            .expect("Could not get balance for account");
+        assert_eq!(account_balance.len(), 2);
+
+        let balance_pmob = account_balance.get(&Mob::ID).unwrap();
+        assert_eq!(balance_pmob.unspent, 120 * MOB as u128);
+        assert_eq!(balance_pmob.pending, 0);
+        assert_eq!(balance_pmob.spent, 0);
+
+        let balance_other = account_balance.get(&other_token_id).unwrap();
+        assert_eq!(balance_other.unspent, 250);
+        assert_eq!(balance_other.pending, 0);
+        assert_eq!(balance_other.spent, 0);
+    }
+
+    // Change received on a subaddress the account's change was rotated to is
+    // still counted as change after the change subaddress is rotated again.
+    #[test_with_logger]
+    fn test_change_balance_after_rotating_change_subaddress(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db, None, logger);
+        let wallet_db = service.wallet_db.as_ref().unwrap();
+
+        let account = service
+            .create_account(Some("A".to_string()), "".to_string(), "".to_string(), false)
+            .unwrap();
+        let account_id = AccountID(account.id.clone());
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+
+        let first_change = service.rotate_change_subaddress(&account_id).unwrap();
+        let second_change = service.rotate_change_subaddress(&account_id).unwrap();
+        assert_ne!(
+            first_change.subaddress_index,
+            second_change.subaddress_index
+        );
+
+        create_test_received_txo(
+            &account_key,
+            0,
+            Amount::new(100 * MOB, Mob::ID),
+            13,
+            &mut rng,
+            wallet_db,
+        );
+        create_test_received_txo(
+            &account_key,
+            first_change.subaddress_index as u64,
+            Amount::new(20 * MOB, Mob::ID),
+            13,
+            &mut rng,
+            wallet_db,
+        );
+        create_test_received_txo(
+            &account_key,
+            second_change.subaddress_index as u64,
+            Amount::new(3 * MOB, Mob::ID),
+            14,
+            &mut rng,
+            wallet_db,
+        );
+
+        let account_balance = service.get_balance_for_account(&account_id).unwrap();
+        let balance_pmob = account_balance.get(&Mob::ID).unwrap();
+        assert_eq!(balance_pmob.unspent, 123 * MOB as u128);
+        assert_eq!(balance_pmob.change_unspent, 23 * MOB as u128);
+        assert_eq!(balance_pmob.received_unspent, 100 * MOB as u128);
+    }
+
+    // The max spendable balance is limited by the number of inputs a
+    // transaction can have, and sets aside the account's default fee.
+    #[test_with_logger]
+    fn test_max_spendable_for_fragmented_balance(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db, None, logger);
+        let wallet_db = service.wallet_db.as_ref().unwrap();
+
+        let account = service
+            .create_account(Some("A".to_string()), "".to_string(), "".to_string(), false)
+            .unwrap();
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+        let account_id = AccountID(account.id.clone());
+
+        // More txos than fit in a single transaction, smallest last.
+        for (i, value) in (1..=20).rev().enumerate() {
+            create_test_received_txo(
+                &account_key,
+                0,
+                Amount::new(value * MOB, Mob::ID),
+                13 + i as u64,
+                &mut rng,
+                wallet_db,
+            );
+        }
+
+        let balance = service.get_balance_for_account(&account_id).unwrap();
+        let balance_pmob = balance.get(&Mob::ID).unwrap();
+        assert_eq!(balance_pmob.unspent, (210 * MOB) as u128);
+        // The 16 largest txos, 20 down to 5 MOB, less the fee.
+        assert_eq!(
+            balance_pmob.max_spendable,
+            (200 * MOB - Mob::MINIMUM_FEE) as u128
+        );
+
+        service
+            .set_account_default_fee(&account_id, Some(4 * Mob::MINIMUM_FEE))
+            .unwrap();
+        let balance = service.get_balance_for_account(&account_id).unwrap();
+        let balance_pmob = balance.get(&Mob::ID).unwrap();
+        assert_eq!(
+            balance_pmob.max_spendable,
+            (200 * MOB - 4 * Mob::MINIMUM_FEE) as u128
+        );
+    }
+
+    #[test_with_logger]
+    fn test_network_status_per_node_heights(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ahead_ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+        let local_ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        // Move one node three blocks ahead of the local ledger.
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        for _ in 0..3 {
+            add_block_to_ledger_db(
+                &mut ahead_ledger_db,
+                &vec![recipient.clone()],
+                100 * MOB,
+                &[KeyImage::from(rng.next_u64())],
+                &mut rng,
+            );
+        }
+
+        let ahead_peer = setup_mock_peer(1, ahead_ledger_db);
+        let behind_peer = setup_mock_peer(2, local_ledger_db.clone());
+        let ahead_id = ahead_peer.uri().host_and_port_responder_id().unwrap();
+        let behind_id = behind_peer.uri().host_and_port_responder_id().unwrap();
+
+        let service =
+            setup_wallet_service_with_peers(local_ledger_db, vec![ahead_peer, behind_peer], logger);
+
+        let network_status = service.get_network_status().unwrap();
+        assert_eq!(network_status.network_block_height, 15);
+        assert_eq!(network_status.local_block_height, 12);
+        assert_eq!(network_status.peer_block_heights.len(), 2);
+        assert_eq!(network_status.peer_block_heights[&ahead_id.to_string()], 15);
+        assert_eq!(
+            network_status.peer_block_heights[&behind_id.to_string()],
+            12
+        );
+        assert_eq!(network_status.is_synced, Some(false));
+    }
+
+    #[test_with_logger]
+    fn test_network_status_offline_is_synced_unknown(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        // With no peers there is nothing to compare the local ledger against.
+        let service = setup_wallet_service_offline(ledger_db, logger);
+
+        let network_status = service.get_network_status().unwrap();
+        assert_eq!(network_status.local_block_height, 12);
+        assert_eq!(network_status.is_synced, None);
+    }
+
+    #[test_with_logger]
+    fn test_wallet_status_across_accounts(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let alice_entropy = RootEntropy::from_random(&mut rng);
+        let alice_key = AccountKey::from(&RootIdentity::from(&alice_entropy));
+        let bob_entropy = RootEntropy::from_random(&mut rng);
+        let bob_key = AccountKey::from(&RootIdentity::from(&bob_entropy));
+
+        let known_recipients: Vec<PublicAddress> =
+            vec![alice_key.default_subaddress(), bob_key.default_subaddress()];
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        let wallet_db = service.wallet_db.as_ref().unwrap();
+
+        let mut account_ids = Vec::new();
+        for entropy in [alice_entropy, bob_entropy] {
+            let account = service
+                .import_account_from_legacy_root_entropy(
+                    hex::encode(entropy.bytes),
+                    None,
+                    None,
+                    None,
+                    "".to_string(),
+                    "".to_string(),
+                    false,
+                )
+                .unwrap();
+            account_ids.push(AccountID(account.id));
+        }
+
+        // Only Alice has synced, so the wallet as a whole is behind.
+        manually_sync_account(&ledger_db, wallet_db, &account_ids[0], &logger);
+        let wallet_status = service.get_wallet_status().unwrap();
+        assert_eq!(wallet_status.account_map.len(), 2);
+        for account_id in &account_ids {
+            assert!(wallet_status.account_ids.contains(account_id));
+            assert!(wallet_status.account_map.contains_key(account_id));
+        }
+        assert!(wallet_status.min_synced_block_index + 1 < wallet_status.network_block_height);
+
+        manually_sync_account(&ledger_db, wallet_db, &account_ids[1], &logger);
+        let wallet_status = service.get_wallet_status().unwrap();
+        assert_eq!(
+            wallet_status.min_synced_block_index + 1,
+            wallet_status.network_block_height
+        );
+
+        // The wallet totals are the sum of each account's balance.
+        let account_unspent: u128 = account_ids
+            .iter()
+            .map(|account_id| {
+                service.get_balance_for_account(account_id).unwrap()[&Mob::ID].unspent
+            })
+            .sum();
+        assert!(account_unspent > 0);
+        assert_eq!(
+            wallet_status.balance_per_token[&Mob::ID].unspent,
+            account_unspent
+        );
+        assert_eq!(wallet_status.balance_per_token[&Mob::ID].pending, 0);
+    }
 }
@@ -2,15 +2,16 @@
 
 //! Service for managing confirmation numbers.
 
-use std::ops::DerefMut;
+use std::{convert::TryFrom, ops::DerefMut};
 
 use crate::{
     db::{
-        account::AccountID,
-        models::Txo,
+        account::{AccountID, AccountModel},
+        models::{Account, Txo},
         txo::{TxoID, TxoModel},
         WalletDbError,
     },
+    json_rpc::json_rpc_response::{ServerErrorCode, ServerErrorCodes},
     service::{
         transaction_log::{TransactionLogService, TransactionLogServiceError},
         txo::{TxoService, TxoServiceError},
@@ -19,7 +20,7 @@ use crate::{
 };
 use displaydoc::Display;
 use mc_connection::{BlockchainConnection, UserTxConnection};
-use mc_crypto_keys::CompressedRistrettoPublic;
+use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPublic};
 use mc_fog_report_validation::FogPubkeyResolver;
 use mc_ledger_db::Ledger;
 use mc_transaction_extra::TxOutConfirmationNumber;
@@ -43,6 +44,12 @@ pub enum ConfirmationServiceError {
     /// Error decoding from hex: {0}
     HexDecode(hex::FromHexError),
 
+    /// Error with crypto keys: {0}
+    CryptoKey(mc_crypto_keys::KeyError),
+
+    /// Txo with public key {0} is not in the ledger
+    TxoNotInLedger(String),
+
     /// Minted Txo should contain confirmation: {0}
     MissingConfirmation(String),
 
@@ -83,6 +90,12 @@ impl From<hex::FromHexError> for ConfirmationServiceError {
     }
 }
 
+impl From<mc_crypto_keys::KeyError> for ConfirmationServiceError {
+    fn from(src: mc_crypto_keys::KeyError) -> Self {
+        Self::CryptoKey(src)
+    }
+}
+
 impl From<TxoServiceError> for ConfirmationServiceError {
     fn from(src: TxoServiceError) -> Self {
         Self::TxoService(src)
@@ -95,6 +108,18 @@ impl From<TransactionLogServiceError> for ConfirmationServiceError {
     }
 }
 
+impl ServerErrorCode for ConfirmationServiceError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::Database(e) => e.code(),
+            Self::TxoService(e) => e.code(),
+            Self::TransactionLogService(e) => e.code(),
+            Self::Diesel(diesel::result::Error::NotFound) => Some(ServerErrorCodes::NotFound),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Confirmation {
     pub txo_id: TxoID,
@@ -136,6 +161,23 @@ pub trait ConfirmationService {
         txo_id: &TxoID,
         confirmation_hex: &str,
     ) -> Result<bool, ConfirmationServiceError>;
+
+    /// Validate the confirmation number for a Txo in the ledger, identified by its public key. The Txo does not need to be stored in the wallet.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                 | Purpose                                                   | Notes                                                                             |
+    ///|----------------------|-----------------------------------------------------------|-----------------------------------------------------------------------------------|
+    ///| `account_id`         | The account which received the Txo.                       | Account must exist in the wallet.                                                 |
+    ///| `txo_public_key_hex` | The public key of the Txo, as received from the sender.   | Txo must be in the ledger.                                                        |
+    ///| `confirmation_hex`   | The confirmation number to validate.                      | The confirmation number should be delivered by the sender of the Txo in question. |
+    ///
+    fn validate_confirmation_for_public_key(
+        &self,
+        account_id: &AccountID,
+        txo_public_key_hex: &str,
+        confirmation_hex: &str,
+    ) -> Result<bool, ConfirmationServiceError>;
 }
 
 impl<T, FPR> ConfirmationService for WalletService<T, FPR>
@@ -189,4 +231,115 @@ where
             conn,
         )?)
     }
+
+    fn validate_confirmation_for_public_key(
+        &self,
+        account_id: &AccountID,
+        txo_public_key_hex: &str,
+        confirmation_hex: &str,
+    ) -> Result<bool, ConfirmationServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        let account = Account::get(account_id, conn)?;
+
+        let public_key: CompressedRistrettoPublic =
+            mc_util_serial::decode(&hex::decode(txo_public_key_hex)?)?;
+        match self.ledger_db.get_tx_out_index_by_public_key(&public_key) {
+            Ok(_) => {}
+            Err(mc_ledger_db::Error::NotFound) => {
+                return Err(ConfirmationServiceError::TxoNotInLedger(
+                    txo_public_key_hex.to_string(),
+                ))
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        let confirmation: TxOutConfirmationNumber =
+            mc_util_serial::decode(&hex::decode(confirmation_hex)?)?;
+        Ok(confirmation.validate(
+            &RistrettoPublic::try_from(&public_key)?,
+            &account.view_private_key()?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        service::account::AccountService,
+        test_utils::{add_block_with_tx_outs, get_test_ledger, setup_wallet_service, MOB},
+    };
+    use mc_account_keys::AccountKey;
+    use mc_common::logger::{test_with_logger, Logger};
+    use mc_crypto_keys::RistrettoPrivate;
+    use mc_transaction_core::{
+        onetime_keys::create_shared_secret, tokens::Mob, tx::TxOut, Amount, Token,
+    };
+    use mc_transaction_types::BlockVersion;
+    use mc_util_from_random::FromRandom;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test_with_logger]
+    fn test_validate_confirmation_for_public_key(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let mut ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+        let service = setup_wallet_service(ledger_db.clone(), None, logger);
+
+        let account = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        let account_id = AccountID(account.id.clone());
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+        let recipient = account_key.default_subaddress();
+
+        // Send a txo to the account out of band, keeping the tx private key so
+        // that we can compute the confirmation number as the sender would.
+        let tx_private_key = RistrettoPrivate::from_random(&mut rng);
+        let tx_out = TxOut::new(
+            BlockVersion::MAX,
+            Amount::new(10 * MOB, Mob::ID),
+            &recipient,
+            &tx_private_key,
+            Default::default(),
+        )
+        .unwrap();
+        let shared_secret = create_shared_secret(recipient.view_public_key(), &tx_private_key);
+        let confirmation = TxOutConfirmationNumber::from(&shared_secret);
+        let confirmation_hex = hex::encode(mc_util_serial::encode(&confirmation));
+        let public_key_hex = hex::encode(mc_util_serial::encode(&tx_out.public_key));
+
+        // The txo is not in the ledger yet.
+        match service.validate_confirmation_for_public_key(
+            &account_id,
+            &public_key_hex,
+            &confirmation_hex,
+        ) {
+            Err(ConfirmationServiceError::TxoNotInLedger(public_key)) => {
+                assert_eq!(public_key, public_key_hex)
+            }
+            result => panic!("Expected TxoNotInLedger, got {result:?}"),
+        }
+
+        add_block_with_tx_outs(&mut ledger_db, &[tx_out], &[], &mut rng);
+
+        // The txo has not been synced to the wallet, but the confirmation
+        // number still validates against the ledger.
+        assert!(service
+            .validate_confirmation_for_public_key(&account_id, &public_key_hex, &confirmation_hex)
+            .unwrap());
+
+        // A confirmation number for a different shared secret does not validate.
+        let wrong_confirmation =
+            TxOutConfirmationNumber::from(&RistrettoPublic::from_random(&mut rng));
+        let wrong_confirmation_hex = hex::encode(mc_util_serial::encode(&wrong_confirmation));
+        assert!(!service
+            .validate_confirmation_for_public_key(
+                &account_id,
+                &public_key_hex,
+                &wrong_confirmation_hex,
+            )
+            .unwrap());
+    }
 }
@@ -16,13 +16,14 @@ use crate::{
         WalletDbError,
     },
     error::WalletTransactionBuilderError,
+    json_rpc::json_rpc_response::{ServerErrorCode, ServerErrorCodes},
     service::{
         account::AccountServiceError,
         address::{AddressService, AddressServiceError},
         ledger::{LedgerService, LedgerServiceError},
         models::tx_proposal::TxProposal,
         transaction::{TransactionMemo, TransactionService, TransactionServiceError},
-        transaction_builder::DEFAULT_NEW_TX_BLOCK_ATTEMPTS,
+        wallet_service::SUBMIT_NODE_MAX_BLOCKS_BEHIND,
         WalletService,
     },
     util::b58::{
@@ -31,7 +32,7 @@ use crate::{
     },
 };
 
-use mc_account_keys::{AccountKey, DEFAULT_SUBADDRESS_INDEX};
+use mc_account_keys::{AccountKey, PublicAddress, DEFAULT_SUBADDRESS_INDEX};
 use mc_common::{logger::log, HashSet};
 use mc_connection::{BlockchainConnection, RetryableUserTxConnection, UserTxConnection};
 use mc_core::slip10::Slip10KeyGenerator;
@@ -57,11 +58,30 @@ use displaydoc::Display;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use tokio::task;
 
 use std::{
-    convert::TryFrom, fmt, iter::empty, ops::DerefMut, str::FromStr, sync::atomic::Ordering,
+    convert::TryFrom,
+    fmt,
+    iter::empty,
+    ops::DerefMut,
+    str::FromStr,
+    sync::atomic::Ordering,
+    time::{Duration, Instant},
 };
 
+/// The largest memo, in bytes, that can be carried in a gift code. Longer
+/// memos make the b58 code unwieldy to share.
+pub const MAX_GIFT_CODE_MEMO_BYTES: usize = 256;
+
+/// The longest `poll_gift_code_status` waits between checks when no blocks are
+/// appended to the ledger.
+pub const GIFT_CODE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The longest `poll_gift_code_status` waits for a gift code to land. Longer
+/// timeouts are clamped to it.
+pub const MAX_GIFT_CODE_POLL_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
 #[derive(Display, Debug)]
 #[allow(clippy::large_enum_variant, clippy::result_large_err)]
 pub enum GiftCodeServiceError {
@@ -174,6 +194,12 @@ pub enum GiftCodeServiceError {
 
     /// Retry Error
     Retry(mc_connection::RetryError<mc_connection::Error>),
+
+    /// Gift code memo is {0} bytes, which exceeds the maximum of {1}
+    MemoTooLong(usize, usize),
+
+    /// Another claim of this gift code is in flight until block {0}
+    GiftCodeClaimInProgress(u64),
 }
 
 impl From<WalletDbError> for GiftCodeServiceError {
@@ -290,6 +316,23 @@ impl From<mc_connection::RetryError<mc_connection::Error>> for GiftCodeServiceEr
     }
 }
 
+impl ServerErrorCode for GiftCodeServiceError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::AccountNotFound => Some(ServerErrorCodes::NotFound),
+            Self::Database(e) => e.code(),
+            Self::TransactionService(e) => e.code(),
+            Self::AccountService(e) => e.code(),
+            Self::AddressService(e) => e.code(),
+            Self::B58(e) => e.code(),
+            Self::WalletTransactionBuilder(e) => e.code(),
+            Self::LedgerService(e) => e.code(),
+            Self::Diesel(diesel::result::Error::NotFound) => Some(ServerErrorCodes::NotFound),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct EncodedGiftCode(pub String);
 
@@ -305,7 +348,8 @@ pub struct DecodedGiftCode {
     pub root_entropy: Option<Vec<u8>>,
     pub bip39_entropy: Option<Vec<u8>>,
     pub txo_public_key: Vec<u8>,
-    pub value: u64,
+    /// None if the gift code was imported before its txo landed in the ledger.
+    pub value: Option<u64>,
     pub memo: String,
 }
 
@@ -321,7 +365,7 @@ impl TryFrom<GiftCode> for DecodedGiftCode {
             root_entropy: transfer_payload.root_entropy.map(|e| e.bytes.to_vec()),
             bip39_entropy: transfer_payload.bip39_entropy,
             txo_public_key: mc_util_serial::encode(&transfer_payload.txo_public_key),
-            value: src.value as u64,
+            value: src.value.map(|value| value as u64),
             memo: transfer_payload.memo,
         })
     }
@@ -334,6 +378,10 @@ pub enum GiftCodeStatus {
     /// The Gift Code has been submitted, but has not yet hit the ledger.
     GiftCodeSubmittedPending,
 
+    /// The Gift Code Txo is in the ledger, but not yet under enough blocks to
+    /// be claimed, or the local ledger is behind the network.
+    GiftCodePendingConfirmation,
+
     /// The Gift Code Txo is in the ledger and has not yet been claimed.
     GiftCodeAvailable,
 
@@ -356,9 +404,10 @@ pub trait GiftCodeService {
     ///  4. Package the required information into a b58-encoded string
     ///
     /// # Returns:
-    /// * JsonSubmitResponse from submitting the gift code transaction to the
-    ///   network
-    /// * Entropy of the gift code account, hex encoded
+    /// * The signed transaction proposal funding the gift code
+    /// * The b58-encoded gift code
+    /// * The gift code as stored in the wallet_db, which is saved at build time
+    ///   so it is listed even before it is submitted
     ///
     /// # Arguments
     ///
@@ -366,7 +415,7 @@ pub trait GiftCodeService {
     ///|-----------------------|--------------------------------------------------------------------|----------------------------------------------|
     ///| `from_account_id`     | The account on which to perform this action.                       | Account must exist in the wallet.            |
     ///| `value`               | The amount of MOB to send in this transaction.                     |                                              |
-    ///| `memo`                | Memo for whoever claims the gift code.                             |                                              |
+    ///| `memo`                | Memo for whoever claims the gift code.                             | At most MAX_GIFT_CODE_MEMO_BYTES bytes.      |
     ///| `input_txo_ids`       | The specific TXOs to use as inputs to this transaction.            | TXO IDs (obtain from get_txos_for_account)   |
     ///| `fee`                 | The fee amount to submit with this transaction.                    | If not provided, uses MINIMUM_FEE = .01 MOB. |
     ///| `tombstone_block`     | The block after which this transaction expires.                    | If not provided, uses current height + 10.   |
@@ -382,7 +431,7 @@ pub trait GiftCodeService {
         fee: Option<u64>,
         tombstone_block: Option<u64>,
         max_spendable_value: Option<u64>,
-    ) -> Result<(TxProposal, EncodedGiftCode), GiftCodeServiceError>;
+    ) -> Result<(TxProposal, EncodedGiftCode, GiftCode), GiftCodeServiceError>;
 
     /// Submit a `tx_proposal` to the ledger that adds the gift code to the wallet_db once the `tx_proposal` has been appended to the ledger.
     ///
@@ -414,7 +463,24 @@ pub trait GiftCodeService {
         gift_code_b58: &EncodedGiftCode,
     ) -> Result<DecodedGiftCode, GiftCodeServiceError>;
 
-    /// List all gift codes in the wallet.
+    /// Get the value of a gift code as stored in the wallet, without reading
+    /// the ledger. Use `check_gift_code_status` to learn whether the gift code
+    /// has landed or been claimed. The value is None if the gift code was
+    /// imported before it landed in the ledger.
+    ///
+    /// # Arguments
+    ///
+    ///| Name            | Purpose                                | Notes                            |
+    ///|-----------------|----------------------------------------|----------------------------------|
+    ///| `gift_code_b58` | The base58-encoded gift code contents. | Gift code must be in the wallet. |
+    ///
+    fn get_gift_code_value(
+        &self,
+        gift_code_b58: &EncodedGiftCode,
+    ) -> Result<Option<u64>, GiftCodeServiceError>;
+
+    /// List all gift codes in the wallet. Values come from the wallet, so
+    /// listing does not read the ledger.
     ///
     /// # Arguments
     ///
@@ -429,8 +495,25 @@ pub trait GiftCodeService {
         limit: Option<u64>,
     ) -> Result<Vec<DecodedGiftCode>, GiftCodeServiceError>;
 
-    /// Check the status of a gift code currently in your wallet. If the gift
-    /// code is not yet in the wallet, add it.
+    /// Add a gift code received from someone else to the wallet, without
+    /// claiming it, so that it is listed by `list_gift_codes`. The value is
+    /// read from the ledger, and left unset if the gift code has not yet
+    /// landed; importing again once it has landed records the value.
+    ///
+    /// # Arguments
+    ///
+    ///| Name            | Purpose                                | Notes                                  |
+    ///|-----------------|----------------------------------------|----------------------------------------|
+    ///| `gift_code_b58` | The base58-encoded gift code contents. | Must be a valid b58-encoded gift code. |
+    ///
+    fn import_gift_code(
+        &self,
+        gift_code_b58: &EncodedGiftCode,
+    ) -> Result<DecodedGiftCode, GiftCodeServiceError>;
+
+    /// Check the status of a gift code by reading the ledger. The gift code
+    /// does not need to be in the wallet, and is not added to it; use
+    /// `import_gift_code` for that.
     ///
     /// # Arguments
     ///
@@ -443,11 +526,34 @@ pub trait GiftCodeService {
         gift_code_b58: &EncodedGiftCode,
     ) -> Result<(GiftCodeStatus, Option<i64>, String), GiftCodeServiceError>;
 
+    /// Check the status of a gift code until it is available or claimed, or
+    /// until `timeout` elapses, and return the last status seen. The status is
+    /// rechecked whenever blocks are appended to the ledger, and at least every
+    /// `GIFT_CODE_POLL_INTERVAL`. Polling stops early if the wallet service is
+    /// shutting down. The timeout is clamped to `MAX_GIFT_CODE_POLL_TIMEOUT`.
+    ///
+    /// # Arguments
+    ///
+    ///| Name            | Purpose                                | Notes                                  |
+    ///|-----------------|----------------------------------------|----------------------------------------|
+    ///| `gift_code_b58` | The base58-encoded gift code contents. | Must be a valid b58-encoded gift code. |
+    ///| `timeout`       | How long to wait for the gift code.    |                                        |
+    ///
+    async fn poll_gift_code_status(
+        &self,
+        gift_code_b58: &EncodedGiftCode,
+        timeout: Duration,
+    ) -> Result<(GiftCodeStatus, Option<i64>, String), GiftCodeServiceError>;
+
     /// Execute a transaction from the gift code account to drain the account to
     /// the destination specified by the `account_id_hex` and
     /// `public_address_b58`. If no `public_address_b58` is provided,
     /// then a new `AssignedSubaddress` will be created to receive the funds.
     ///
+    /// A second claim of the same gift code fails with
+    /// `GiftCodeClaimInProgress` until the first claim's transaction lands or
+    /// passes its tombstone block.
+    ///
     /// # Arguments
     ///
     ///| Name            | Purpose                                      | Notes                                  |
@@ -463,6 +569,22 @@ pub trait GiftCodeService {
         public_address_b58: Option<String>,
     ) -> Result<Tx, GiftCodeServiceError>;
 
+    /// Execute a transaction from the gift code account to drain the account to
+    /// any public address, without needing an account in this wallet.
+    ///
+    /// # Arguments
+    ///
+    ///| Name              | Purpose                                          | Notes                                  |
+    ///|-------------------|--------------------------------------------------|----------------------------------------|
+    ///| `gift_code_b58`   | The base58-encoded gift code contents.           | Must be a valid b58-encoded gift code. |
+    ///| `destination_b58` | The b58-encoded public address to receive funds. | May be a fog-enabled address.          |
+    ///
+    fn claim_gift_code_to_address(
+        &self,
+        gift_code_b58: &EncodedGiftCode,
+        destination_b58: &str,
+    ) -> Result<Tx, GiftCodeServiceError>;
+
     ///Remove a gift code from the database.
     ///
     /// # Arguments
@@ -492,7 +614,10 @@ where
         fee: Option<u64>,
         tombstone_block: Option<u64>,
         max_spendable_value: Option<u64>,
-    ) -> Result<(TxProposal, EncodedGiftCode), GiftCodeServiceError> {
+    ) -> Result<(TxProposal, EncodedGiftCode, GiftCode), GiftCodeServiceError> {
+        let memo = memo.unwrap_or_default();
+        check_gift_code_memo(&memo)?;
+
         // First we need to generate a new random bip39 entropy. The way that
         // gift codes work currently is that the sender creates a
         // middleman account and sends that account the amount of MOB
@@ -536,6 +661,7 @@ where
                 },
             )],
             input_txo_ids,
+            None,
             fee_value,
             None,
             tombstone_block.map(|t| t.to_string()),
@@ -546,9 +672,12 @@ where
             None,
             None, /* NOTE: Assuming for now that we will not support spend_subaddress
                    * in gift_code construction */
+            None,
         )?;
 
-        let tx_proposal = unsigned_tx_proposal.sign(&from_account).await?;
+        let tx_proposal = unsigned_tx_proposal
+            .sign_with_hardware_wallet_config(&from_account, &self.hardware_wallet_config)
+            .await?;
 
         if tx_proposal.payload_txos.len() != 1 {
             return Err(GiftCodeServiceError::UnexpectedTxProposalFormat);
@@ -561,10 +690,16 @@ where
         let gift_code_b58 = b58_encode_transfer_payload(
             gift_code_bip39_entropy_bytes.to_vec(),
             proto_tx_pubkey,
-            memo.unwrap_or_default(),
+            memo,
         )?;
+        let gift_code_b58 = EncodedGiftCode(gift_code_b58);
 
-        Ok((tx_proposal, EncodedGiftCode(gift_code_b58)))
+        let gift_code_value = tx_proposal.payload_txos[0].amount.value as i64;
+        let gift_code = exclusive_transaction(conn, |conn| {
+            GiftCode::create(&gift_code_b58, Some(gift_code_value), conn)
+        })?;
+
+        Ok((tx_proposal, gift_code_b58, gift_code))
     }
 
     fn submit_gift_code(
@@ -582,16 +717,19 @@ where
             value
         );
 
-        // Save the gift code to the database before attempting to send it out.
+        // Save the gift code to the database before attempting to send it out,
+        // unless build_gift_code already saved it.
         let mut pooled_conn = self.get_pooled_conn()?;
         let conn = pooled_conn.deref_mut();
-        let gift_code =
-            exclusive_transaction(conn, |conn| GiftCode::create(gift_code_b58, value, conn))?;
+        let gift_code = exclusive_transaction(conn, |conn| {
+            GiftCode::get_or_create(gift_code_b58, Some(value), conn)
+        })?;
 
         self.submit_transaction(
             tx_proposal,
             Some(json!({"gift_code_memo": transfer_payload.memo}).to_string()),
             Some(from_account_id.clone().0),
+            None,
         )?;
 
         Ok(DecodedGiftCode {
@@ -599,7 +737,7 @@ where
             root_entropy: transfer_payload.root_entropy.map(|e| e.bytes.to_vec()),
             bip39_entropy: transfer_payload.bip39_entropy,
             txo_public_key: mc_util_serial::encode(&transfer_payload.txo_public_key),
-            value: tx_proposal.payload_txos[0].amount.value,
+            value: Some(tx_proposal.payload_txos[0].amount.value),
             memo: transfer_payload.memo,
         })
     }
@@ -614,6 +752,17 @@ where
         DecodedGiftCode::try_from(gift_code)
     }
 
+    fn get_gift_code_value(
+        &self,
+        gift_code_b58: &EncodedGiftCode,
+    ) -> Result<Option<u64>, GiftCodeServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        Ok(GiftCode::get(gift_code_b58, conn)?
+            .value
+            .map(|value| value as u64))
+    }
+
     fn list_gift_codes(
         &self,
         offset: Option<u64>,
@@ -627,6 +776,30 @@ where
             .collect()
     }
 
+    fn import_gift_code(
+        &self,
+        gift_code_b58: &EncodedGiftCode,
+    ) -> Result<DecodedGiftCode, GiftCodeServiceError> {
+        let transfer_payload = decode_transfer_payload(gift_code_b58)?;
+        let value = self
+            .gift_code_txo_in_ledger(&transfer_payload)?
+            .map(|(value, _block_index)| value as i64);
+
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        let gift_code = exclusive_transaction(conn, |conn| {
+            let gift_code = GiftCode::get_or_create(gift_code_b58, value, conn)?;
+            match (gift_code.value, value) {
+                (None, Some(value)) => {
+                    gift_code.set_value(value, conn)?;
+                    GiftCode::get(gift_code_b58, conn)
+                }
+                _ => Ok(gift_code),
+            }
+        })?;
+        DecodedGiftCode::try_from(gift_code)
+    }
+
     fn check_gift_code_status(
         &self,
         gift_code_b58: &EncodedGiftCode,
@@ -634,7 +807,7 @@ where
         log::info!(self.logger, "encoded_gift_code: {:?}", gift_code_b58);
 
         let transfer_payload = decode_transfer_payload(gift_code_b58)?;
-        let gift_account_key = transfer_payload.account_key;
+        let gift_account_key = &transfer_payload.account_key;
 
         log::info!(
             self.logger,
@@ -644,28 +817,18 @@ where
         );
 
         // Check if the GiftCode is in the local ledger.
-        let gift_txo = match self
-            .ledger_db
-            .get_tx_out_index_by_public_key(&transfer_payload.txo_public_key)
-        {
-            Ok(tx_out_index) => self.ledger_db.get_tx_out_by_index(tx_out_index)?,
-            Err(mc_ledger_db::Error::NotFound) => {
+        let gift_txo = self.gift_code_txo_in_ledger(&transfer_payload)?;
+        let (value, gift_txo_block_index) = match gift_txo {
+            Some(value_and_block_index) => value_and_block_index,
+            None => {
                 return Ok((
                     GiftCodeStatus::GiftCodeSubmittedPending,
                     None,
                     transfer_payload.memo,
                 ))
             }
-            Err(e) => return Err(e.into()),
         };
 
-        let shared_secret = get_tx_out_shared_secret(
-            gift_account_key.view_private_key(),
-            &RistrettoPublic::try_from(&gift_txo.public_key)?,
-        );
-
-        let (value, _blinding) = gift_txo.get_masked_amount()?.get_value(&shared_secret)?;
-
         // Check if the Gift Code has been spent - by convention gift codes are always
         // to the main subaddress index and gift accounts should NEVER have MOB stored
         // anywhere else. If they do, that's not good :,)
@@ -681,55 +844,237 @@ where
         if self.ledger_db.contains_key_image(&gift_code_key_image)? {
             return Ok((
                 GiftCodeStatus::GiftCodeClaimed,
-                Some(value.value as i64),
+                Some(value as i64),
+                transfer_payload.memo,
+            ));
+        }
+
+        // A claim built against a local ledger which is behind the network could
+        // use a tombstone block which has already passed, so wait for the local
+        // ledger to catch up, and for the gift code to be buried deep enough. The
+        // local ledger may trail the network by as much as a node used for
+        // submitting, since the claim's tombstone block leaves room for that.
+        let local_block_height = self.ledger_db.num_blocks()?;
+        let network_block_height = if self.offline {
+            local_block_height
+        } else {
+            self.get_network_block_height()?
+        };
+        let confirmations = local_block_height - gift_txo_block_index;
+        if local_block_height + SUBMIT_NODE_MAX_BLOCKS_BEHIND < network_block_height
+            || confirmations < self.gift_code_confirmations
+        {
+            return Ok((
+                GiftCodeStatus::GiftCodePendingConfirmation,
+                Some(value as i64),
                 transfer_payload.memo,
             ));
         }
 
         Ok((
             GiftCodeStatus::GiftCodeAvailable,
-            Some(value.value as i64),
+            Some(value as i64),
             transfer_payload.memo,
         ))
     }
 
+    async fn poll_gift_code_status(
+        &self,
+        gift_code_b58: &EncodedGiftCode,
+        timeout: Duration,
+    ) -> Result<(GiftCodeStatus, Option<i64>, String), GiftCodeServiceError> {
+        let deadline = Instant::now() + timeout.min(MAX_GIFT_CODE_POLL_TIMEOUT);
+        let ledger_updates = self.ledger_updates();
+        loop {
+            // Read the notification count before checking, so that blocks
+            // appended during the check still wake the wait below.
+            let updates = ledger_updates.count();
+            let result = self.check_gift_code_status(gift_code_b58)?;
+            let landed = matches!(
+                result.0,
+                GiftCodeStatus::GiftCodeAvailable | GiftCodeStatus::GiftCodeClaimed
+            );
+            if landed || self.shutdown_requested() {
+                return Ok(result);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(result);
+            }
+            // The wait blocks, so keep it off the async executor.
+            let wait = GIFT_CODE_POLL_INTERVAL.min(deadline - now);
+            let ledger_updates = ledger_updates.clone();
+            task::spawn_blocking(move || ledger_updates.wait(updates, wait))
+                .await
+                .expect("ledger update wait failed");
+        }
+    }
+
     fn claim_gift_code(
         &self,
         gift_code_b58: &EncodedGiftCode,
         account_id: &AccountID,
         public_address_b58: Option<String>,
     ) -> Result<Tx, GiftCodeServiceError> {
-        let (status, gift_value, _memo) = self.check_gift_code_status(gift_code_b58)?;
+        let (gift_value, memo) = self.claimable_gift_code_value(gift_code_b58)?;
+
+        let public_address_b58 = match public_address_b58 {
+            Some(public_address_b58) => public_address_b58,
+            None => {
+                let address = self.assign_address_for_account(
+                    account_id,
+                    Some(&json!({"gift_code_memo": memo}).to_string()),
+                )?;
+                address.public_address_b58
+            }
+        };
+        let recipient_public_address = b58_decode_public_address(&public_address_b58)?;
+
+        self.drain_gift_code(gift_code_b58, gift_value, &recipient_public_address)
+    }
+
+    fn claim_gift_code_to_address(
+        &self,
+        gift_code_b58: &EncodedGiftCode,
+        destination_b58: &str,
+    ) -> Result<Tx, GiftCodeServiceError> {
+        let recipient_public_address = b58_decode_public_address(destination_b58)?;
+        let (gift_value, _memo) = self.claimable_gift_code_value(gift_code_b58)?;
+
+        self.drain_gift_code(gift_code_b58, gift_value, &recipient_public_address)
+    }
+
+    fn remove_gift_code(
+        &self,
+        gift_code_b58: &EncodedGiftCode,
+    ) -> Result<bool, GiftCodeServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        exclusive_transaction(conn, |conn| {
+            GiftCode::get(gift_code_b58, conn)?.delete(conn)
+        })?;
+        Ok(true)
+    }
+}
+
+impl<T, FPR> WalletService<T, FPR>
+where
+    T: BlockchainConnection + UserTxConnection + 'static,
+    FPR: FogPubkeyResolver + Send + Sync + 'static,
+{
+    /// The value of the gift code's txo and the index of the block containing
+    /// it, or None if the txo is not yet in the local ledger.
+    fn gift_code_txo_in_ledger(
+        &self,
+        transfer_payload: &DecodedTransferPayload,
+    ) -> Result<Option<(u64, u64)>, GiftCodeServiceError> {
+        let tx_out_index = match self
+            .ledger_db
+            .get_tx_out_index_by_public_key(&transfer_payload.txo_public_key)
+        {
+            Ok(tx_out_index) => tx_out_index,
+            Err(mc_ledger_db::Error::NotFound) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let gift_txo = self.ledger_db.get_tx_out_by_index(tx_out_index)?;
+        let block_index = self
+            .ledger_db
+            .get_block_index_by_tx_out_index(tx_out_index)?;
+
+        let shared_secret = get_tx_out_shared_secret(
+            transfer_payload.account_key.view_private_key(),
+            &RistrettoPublic::try_from(&gift_txo.public_key)?,
+        );
+        let (value, _blinding) = gift_txo.get_masked_amount()?.get_value(&shared_secret)?;
+
+        Ok(Some((value.value, block_index)))
+    }
+
+    /// The value of a gift code and its memo, if the gift code is in the
+    /// ledger and has not yet been claimed.
+    fn claimable_gift_code_value(
+        &self,
+        gift_code_b58: &EncodedGiftCode,
+    ) -> Result<(i64, String), GiftCodeServiceError> {
+        let (status, gift_value, memo) = self.check_gift_code_status(gift_code_b58)?;
 
         match status {
             GiftCodeStatus::GiftCodeClaimed => return Err(GiftCodeServiceError::GiftCodeClaimed),
-            GiftCodeStatus::GiftCodeSubmittedPending => {
+            GiftCodeStatus::GiftCodeSubmittedPending
+            | GiftCodeStatus::GiftCodePendingConfirmation => {
                 return Err(GiftCodeServiceError::GiftCodeNotYetAvailable)
             }
             GiftCodeStatus::GiftCodeAvailable => {}
         }
 
         let gift_value = gift_value.ok_or(GiftCodeServiceError::GiftCodeNotYetAvailable)?;
+        Ok((gift_value, memo))
+    }
 
-        let transfer_payload = decode_transfer_payload(gift_code_b58)?;
-        let gift_account_key = transfer_payload.account_key;
+    /// Submit a transaction that spends the gift code's txo, less the fee, to
+    /// the recipient.
+    ///
+    /// The gift code is marked as being claimed until the claim transaction's
+    /// tombstone block, so that a concurrent claim is refused rather than
+    /// racing this one to consensus. The mark is cleared if the claim fails.
+    fn drain_gift_code(
+        &self,
+        gift_code_b58: &EncodedGiftCode,
+        gift_value: i64,
+        recipient_public_address: &PublicAddress,
+    ) -> Result<Tx, GiftCodeServiceError> {
+        let num_blocks_in_ledger = self.ledger_db.num_blocks()?;
+        let tombstone_block = num_blocks_in_ledger + self.tombstone_horizon;
 
-        let default_subaddress = if public_address_b58.is_some() {
-            public_address_b58.ok_or(GiftCodeServiceError::AccountNotFound)
-        } else {
-            let address = self.assign_address_for_account(
-                account_id,
-                Some(&json!({"gift_code_memo": transfer_payload.memo}).to_string()),
-            )?;
-            Ok(address.public_address_b58)
-        }?;
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        let gift_code = exclusive_transaction(conn, |conn| {
+            let gift_code = GiftCode::get_or_create(gift_code_b58, Some(gift_value), conn)?;
+            if gift_code.value.is_none() {
+                gift_code.set_value(gift_value, conn)?;
+            }
+            if let Some(claim_tombstone_block) = gift_code.claim_tombstone_block {
+                if claim_tombstone_block as u64 > num_blocks_in_ledger {
+                    return Err(GiftCodeServiceError::GiftCodeClaimInProgress(
+                        claim_tombstone_block as u64,
+                    ));
+                }
+            }
+            gift_code.set_claim_tombstone_block(Some(tombstone_block), conn)?;
+            Ok(gift_code)
+        })?;
+
+        let result = self.propose_gift_code_drain(
+            gift_code_b58,
+            gift_value,
+            recipient_public_address,
+            tombstone_block,
+        );
+        if result.is_err() {
+            exclusive_transaction(conn, |conn| gift_code.set_claim_tombstone_block(None, conn))?;
+        }
+        result
+    }
 
-        let recipient_public_address = b58_decode_public_address(&default_subaddress)?;
+    /// Build the transaction that drains the gift code and propose it to a
+    /// peer. The recipient may be fog-enabled, in which case its fog report is
+    /// resolved before building.
+    fn propose_gift_code_drain(
+        &self,
+        gift_code_b58: &EncodedGiftCode,
+        gift_value: i64,
+        recipient_public_address: &PublicAddress,
+        tombstone_block: u64,
+    ) -> Result<Tx, GiftCodeServiceError> {
+        let transfer_payload = decode_transfer_payload(gift_code_b58)?;
+        let gift_account_key = transfer_payload.account_key;
 
-        // If the gift code value is less than the MINIMUM_FEE, well, then shucks,
+        // If the gift code value is less than the network fee, well, then shucks,
         // someone messed up when they were making it. Welcome to the Lost MOB
         // club :)
-        if (gift_value as u64) < Mob::MINIMUM_FEE {
+        let fee_value = self.get_network_fee(Mob::ID)?;
+        if (gift_value as u64) < fee_value {
             return Err(GiftCodeServiceError::InsufficientValueForFee(
                 gift_value as u64,
             ));
@@ -799,19 +1144,17 @@ where
         memo_builder.set_sender_credential(SenderMemoCredential::from(&gift_account_key));
         memo_builder.enable_destination_memo();
         let block_version = self.get_network_block_version()?;
-        let fee = Amount::new(Mob::MINIMUM_FEE, Mob::ID);
+        let fee = Amount::new(fee_value, Mob::ID);
         let mut transaction_builder =
             TransactionBuilder::new(block_version, fee, fog_resolver, memo_builder)?;
         transaction_builder.add_input(input_credentials);
         transaction_builder.add_output(
-            Amount::new(gift_value as u64 - Mob::MINIMUM_FEE, Mob::ID),
-            &recipient_public_address,
+            Amount::new(gift_value as u64 - fee_value, Mob::ID),
+            recipient_public_address,
             &mut rng,
         )?;
 
-        let num_blocks_in_ledger = self.ledger_db.num_blocks()?;
-        transaction_builder
-            .set_tombstone_block(num_blocks_in_ledger + DEFAULT_NEW_TX_BLOCK_ATTEMPTS);
+        transaction_builder.set_tombstone_block(tombstone_block);
         let tx = transaction_builder.build(&NoKeysRingSigner {}, &mut rng)?;
 
         let responder_ids = self.peer_manager.responder_ids();
@@ -837,18 +1180,6 @@ where
 
         Ok(tx)
     }
-
-    fn remove_gift_code(
-        &self,
-        gift_code_b58: &EncodedGiftCode,
-    ) -> Result<bool, GiftCodeServiceError> {
-        let mut pooled_conn = self.get_pooled_conn()?;
-        let conn = pooled_conn.deref_mut();
-        exclusive_transaction(conn, |conn| {
-            GiftCode::get(gift_code_b58, conn)?.delete(conn)
-        })?;
-        Ok(true)
-    }
 }
 
 /// Decode the gift code from b58 to its component parts.
@@ -856,9 +1187,22 @@ where
 pub fn decode_transfer_payload(
     gift_code_b58: &EncodedGiftCode,
 ) -> Result<DecodedTransferPayload, GiftCodeServiceError> {
+    // The memo is a protobuf string field, so decoding has already rejected
+    // any payload whose memo is not valid UTF-8. Memo length is deliberately
+    // not checked here, so gift codes built before the limit stay claimable.
     Ok(b58_decode_transfer_payload(gift_code_b58.to_string())?)
 }
 
+fn check_gift_code_memo(memo: &str) -> Result<(), GiftCodeServiceError> {
+    if memo.len() > MAX_GIFT_CODE_MEMO_BYTES {
+        return Err(GiftCodeServiceError::MemoTooLong(
+            memo.len(),
+            MAX_GIFT_CODE_MEMO_BYTES,
+        ));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -866,14 +1210,15 @@ mod tests {
         service::{account::AccountService, balance::BalanceService},
         test_utils::{
             add_block_to_ledger_db, add_block_with_tx, get_test_ledger, manually_sync_account,
-            setup_wallet_service, MOB,
+            setup_mock_peer_with_fees, setup_wallet_service, setup_wallet_service_with_peers, MOB,
         },
     };
     use mc_account_keys::PublicAddress;
     use mc_common::logger::{async_test_with_logger, Logger};
     use mc_rand::rand_core::RngCore;
-    use mc_transaction_core::{ring_signature::KeyImage, tokens::Mob, Token};
+    use mc_transaction_core::{ring_signature::KeyImage, tokens::Mob, FeeMap, Token};
     use rand::{rngs::StdRng, SeedableRng};
+    use std::collections::BTreeMap;
 
     #[async_test_with_logger]
     async fn test_gift_code_lifecycle(logger: Logger) {
@@ -921,7 +1266,7 @@ mod tests {
         assert_eq!(balance_pmob.unspent, 100 * MOB as u128);
 
         // Create a gift code for Bob
-        let (tx_proposal, gift_code_b58) = service
+        let (tx_proposal, gift_code_b58, _gift_code) = service
             .build_gift_code(
                 &AccountID(alice.id.clone()),
                 2 * MOB,
@@ -961,6 +1306,11 @@ mod tests {
         assert_eq!(status, GiftCodeStatus::GiftCodeAvailable);
         assert!(gift_code_value_opt.is_some());
 
+        // The value stored in the wallet matches the value read from the ledger
+        let stored_value = service.get_gift_code_value(&gift_code_b58).unwrap();
+        assert_eq!(stored_value.map(|value| value as i64), gift_code_value_opt);
+        assert_eq!(stored_value, Some(2 * MOB));
+
         let decoded = decode_transfer_payload(&gift_code_b58).expect("Could not decode gift code");
         let gift_code_account_key = decoded.account_key;
 
@@ -991,7 +1341,7 @@ mod tests {
         // Verify that we can get the gift_code
         log::info!(logger, "Getting gift code from database");
         let gotten_gift_code = service.get_gift_code(&gift_code_b58).unwrap();
-        assert_eq!(gotten_gift_code.value, value.value);
+        assert_eq!(gotten_gift_code.value, Some(value.value));
         assert_eq!(gotten_gift_code.gift_code_b58, gift_code_b58.to_string());
 
         // Check that we can list all
@@ -1104,7 +1454,7 @@ mod tests {
         assert_eq!(balance_pmob.unspent, 100 * MOB as u128);
 
         // Create a gift code for Bob
-        let (tx_proposal, gift_code_b58) = service
+        let (tx_proposal, gift_code_b58, _gift_code) = service
             .build_gift_code(
                 &AccountID(alice.id.clone()),
                 2 * MOB,
@@ -1160,4 +1510,533 @@ mod tests {
             .expect("Could not list gift codes");
         assert_eq!(gift_codes.len(), 0);
     }
+
+    #[async_test_with_logger]
+    async fn test_claim_gift_code_to_fog_address(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID(alice.id.to_string());
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let (tx_proposal, gift_code_b58, _gift_code) = service
+            .build_gift_code(&alice_account_id, 2 * MOB, None, None, None, None, None)
+            .await
+            .unwrap();
+        service
+            .submit_gift_code(&alice_account_id, &gift_code_b58, &tx_proposal)
+            .unwrap();
+        add_block_with_tx(&mut ledger_db, tx_proposal.tx, &mut rng);
+
+        // The destination is not an account in this wallet, and uses fog
+        let fog_account_key = AccountKey::random_with_fog(&mut rng);
+        let fog_public_address = fog_account_key.default_subaddress();
+        assert!(fog_public_address.fog_report_url().is_some());
+        let fog_address_b58 = b58_encode_public_address(&fog_public_address).unwrap();
+
+        match service.claim_gift_code_to_address(&gift_code_b58, "not a b58 address") {
+            Err(GiftCodeServiceError::B58(_)) => {}
+            Ok(_) => panic!("Should not claim a gift code to an invalid address"),
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        let tx = service
+            .claim_gift_code_to_address(&gift_code_b58, &fog_address_b58)
+            .unwrap();
+
+        // The single output pays the fog address the gift value less the fee
+        assert_eq!(tx.prefix.outputs.len(), 1);
+        let output = &tx.prefix.outputs[0];
+        let shared_secret = get_tx_out_shared_secret(
+            fog_account_key.view_private_key(),
+            &RistrettoPublic::try_from(&output.public_key).unwrap(),
+        );
+        let (amount, _blinding) = output
+            .get_masked_amount()
+            .unwrap()
+            .get_value(&shared_secret)
+            .unwrap();
+        assert_eq!(amount.value, 2 * MOB - Mob::MINIMUM_FEE);
+
+        add_block_with_tx(&mut ledger_db, tx, &mut rng);
+        let (status, _value, _memo) = service.check_gift_code_status(&gift_code_b58).unwrap();
+        assert_eq!(status, GiftCodeStatus::GiftCodeClaimed);
+    }
+
+    #[async_test_with_logger]
+    async fn test_claim_gift_code_uses_network_fee(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        // The network charges more than the hardcoded minimum fee
+        let network_fee = 3 * Mob::MINIMUM_FEE;
+        let fee_map = FeeMap::try_from(BTreeMap::from([(Mob::ID, network_fee)])).unwrap();
+        let peers = vec![
+            setup_mock_peer_with_fees(1, ledger_db.clone(), fee_map.clone()),
+            setup_mock_peer_with_fees(2, ledger_db.clone(), fee_map),
+        ];
+        let service = setup_wallet_service_with_peers(ledger_db.clone(), peers, logger.clone());
+        assert_eq!(service.get_network_fee(Mob::ID).unwrap(), network_fee);
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID(alice.id.to_string());
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let (tx_proposal, gift_code_b58, _gift_code) = service
+            .build_gift_code(&alice_account_id, 2 * MOB, None, None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(tx_proposal.tx.prefix.fee, network_fee);
+        service
+            .submit_gift_code(&alice_account_id, &gift_code_b58, &tx_proposal)
+            .unwrap();
+        add_block_with_tx(&mut ledger_db, tx_proposal.tx, &mut rng);
+
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        let recipient_b58 = b58_encode_public_address(&recipient).unwrap();
+        let tx = service
+            .claim_gift_code_to_address(&gift_code_b58, &recipient_b58)
+            .unwrap();
+        assert_eq!(tx.prefix.fee, network_fee);
+    }
+
+    #[async_test_with_logger]
+    async fn test_concurrent_gift_code_claims(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID(alice.id.to_string());
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let (tx_proposal, gift_code_b58, _gift_code) = service
+            .build_gift_code(&alice_account_id, 2 * MOB, None, None, None, None, None)
+            .await
+            .unwrap();
+        service
+            .submit_gift_code(&alice_account_id, &gift_code_b58, &tx_proposal)
+            .unwrap();
+        add_block_with_tx(&mut ledger_db, tx_proposal.tx, &mut rng);
+
+        let destination = AccountKey::random(&mut rng).default_subaddress();
+        let destination_b58 = b58_encode_public_address(&destination).unwrap();
+
+        // Two claims race each other, and only one of them may go out
+        let (first, second) = std::thread::scope(|scope| {
+            let first = scope
+                .spawn(|| service.claim_gift_code_to_address(&gift_code_b58, &destination_b58));
+            let second = scope
+                .spawn(|| service.claim_gift_code_to_address(&gift_code_b58, &destination_b58));
+            (first.join().unwrap(), second.join().unwrap())
+        });
+        let results = [first, second];
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+        assert!(results
+            .iter()
+            .any(|r| matches!(r, Err(GiftCodeServiceError::GiftCodeClaimInProgress(_)))));
+
+        // The claim never lands, so once it is past its tombstone block the gift
+        // code can be claimed again
+        for _ in 0..service.tombstone_horizon {
+            add_block_to_ledger_db(
+                &mut ledger_db,
+                &vec![AccountKey::random(&mut rng).default_subaddress()],
+                MOB,
+                &[KeyImage::from(rng.next_u64())],
+                &mut rng,
+            );
+        }
+        service
+            .claim_gift_code_to_address(&gift_code_b58, &destination_b58)
+            .unwrap();
+    }
+
+    #[async_test_with_logger]
+    async fn test_build_gift_code_saves_gift_code(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID(alice.id.to_string());
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let (tx_proposal, gift_code_b58, db_gift_code) = service
+            .build_gift_code(
+                &alice_account_id,
+                2 * MOB,
+                Some("Gift code for Bob".to_string()),
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(db_gift_code.gift_code_b58, gift_code_b58.to_string());
+        assert_eq!(db_gift_code.value, Some((2 * MOB) as i64));
+
+        // The gift code is stored as soon as it is built
+        let gift_code = service.get_gift_code(&gift_code_b58).unwrap();
+        assert_eq!(gift_code.gift_code_b58, db_gift_code.gift_code_b58);
+        assert_eq!(
+            gift_code.value.map(|value| value as i64),
+            db_gift_code.value
+        );
+
+        let gift_codes = service.list_gift_codes(None, None).unwrap();
+        assert_eq!(gift_codes.len(), 1);
+        assert_eq!(gift_codes[0].gift_code_b58, db_gift_code.gift_code_b58);
+
+        // Submitting reuses the stored gift code rather than adding another
+        let submitted = service
+            .submit_gift_code(&alice_account_id, &gift_code_b58, &tx_proposal)
+            .unwrap();
+        assert_eq!(submitted.gift_code_b58, db_gift_code.gift_code_b58);
+        assert_eq!(service.list_gift_codes(None, None).unwrap().len(), 1);
+    }
+
+    #[async_test_with_logger]
+    async fn test_build_gift_code_memo_too_long(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db, None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+
+        // The limit is in bytes, so multi-byte characters count more than once.
+        let memo = "\u{e9}".repeat(MAX_GIFT_CODE_MEMO_BYTES / 2 + 1);
+        assert!(memo.chars().count() < MAX_GIFT_CODE_MEMO_BYTES);
+
+        match service
+            .build_gift_code(
+                &AccountID(alice.id.clone()),
+                2 * MOB,
+                Some(memo.clone()),
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+        {
+            Err(GiftCodeServiceError::MemoTooLong(len, max)) => {
+                assert_eq!(len, memo.len());
+                assert_eq!(max, MAX_GIFT_CODE_MEMO_BYTES);
+            }
+            Ok(_) => panic!("Should not be able to build a gift code with a long memo"),
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[async_test_with_logger]
+    async fn test_poll_gift_code_status_returns_when_available(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        let alice = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID(alice.id.to_string());
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let (tx_proposal, gift_code_b58, _gift_code) = service
+            .build_gift_code(&alice_account_id, 2 * MOB, None, None, None, None, None)
+            .await
+            .unwrap();
+        service
+            .submit_gift_code(&alice_account_id, &gift_code_b58, &tx_proposal)
+            .unwrap();
+
+        // Nothing lands, so polling waits out the timeout.
+        let (status, _value, _memo) = service
+            .poll_gift_code_status(&gift_code_b58, Duration::from_millis(50))
+            .await
+            .unwrap();
+        assert_eq!(status, GiftCodeStatus::GiftCodeSubmittedPending);
+
+        // The gift code lands while polling, and the poll returns well before
+        // its timeout.
+        let ledger_updates = service.ledger_updates();
+        let tx = tx_proposal.tx.clone();
+        let mut appending_ledger_db = ledger_db.clone();
+        let appender = std::thread::spawn(move || {
+            let mut rng: StdRng = SeedableRng::from_seed([21u8; 32]);
+            std::thread::sleep(Duration::from_millis(200));
+            add_block_with_tx(&mut appending_ledger_db, tx, &mut rng);
+            ledger_updates.notify();
+        });
+
+        let start = Instant::now();
+        let (status, value, _memo) = service
+            .poll_gift_code_status(&gift_code_b58, Duration::from_secs(30))
+            .await
+            .unwrap();
+        appender.join().unwrap();
+        assert_eq!(status, GiftCodeStatus::GiftCodeAvailable);
+        assert_eq!(value, Some(2 * MOB as i64));
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[async_test_with_logger]
+    async fn test_gift_code_pending_confirmation(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let mut service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        service.gift_code_confirmations = 3;
+
+        let alice = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID(alice.id.to_string());
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let (tx_proposal, gift_code_b58, _gift_code) = service
+            .build_gift_code(&alice_account_id, 2 * MOB, None, None, None, None, None)
+            .await
+            .unwrap();
+        service
+            .submit_gift_code(&alice_account_id, &gift_code_b58, &tx_proposal)
+            .unwrap();
+        add_block_with_tx(&mut ledger_db, tx_proposal.tx, &mut rng);
+
+        // The block the gift code landed in is its first confirmation.
+        let bob = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        let bob_account_id = AccountID(bob.id);
+        for _ in 0..2 {
+            let (status, value, _memo) = service.check_gift_code_status(&gift_code_b58).unwrap();
+            assert_eq!(status, GiftCodeStatus::GiftCodePendingConfirmation);
+            assert_eq!(value, Some(2 * MOB as i64));
+            match service.claim_gift_code(&gift_code_b58, &bob_account_id, None) {
+                Err(GiftCodeServiceError::GiftCodeNotYetAvailable) => {}
+                result => panic!("Expected GiftCodeNotYetAvailable, got {result:?}"),
+            }
+
+            add_block_to_ledger_db(
+                &mut ledger_db,
+                &vec![AccountKey::random(&mut rng).default_subaddress()],
+                MOB,
+                &[KeyImage::from(rng.next_u64())],
+                &mut rng,
+            );
+        }
+
+        let (status, _value, _memo) = service.check_gift_code_status(&gift_code_b58).unwrap();
+        assert_eq!(status, GiftCodeStatus::GiftCodeAvailable);
+    }
+
+    #[async_test_with_logger]
+    async fn test_import_gift_code(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        let alice = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID(alice.id.to_string());
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let (tx_proposal, gift_code_b58, _gift_code) = service
+            .build_gift_code(
+                &alice_account_id,
+                2 * MOB,
+                Some("Gift code for Bob".to_string()),
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        // Bob receives the gift code in a wallet of his own
+        let bob_service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        assert!(bob_service.list_gift_codes(None, None).unwrap().is_empty());
+
+        // Before the gift code lands, it is stored without a value
+        let imported = bob_service.import_gift_code(&gift_code_b58).unwrap();
+        assert_eq!(imported.gift_code_b58, gift_code_b58.to_string());
+        assert_eq!(imported.value, None);
+        assert_eq!(imported.memo, "Gift code for Bob");
+
+        service
+            .submit_gift_code(&alice_account_id, &gift_code_b58, &tx_proposal)
+            .unwrap();
+        add_block_with_tx(&mut ledger_db, tx_proposal.tx, &mut rng);
+
+        // Importing again once it has landed records the value
+        let imported = bob_service.import_gift_code(&gift_code_b58).unwrap();
+        assert_eq!(imported.value, Some(2 * MOB));
+
+        let gift_codes = bob_service.list_gift_codes(None, None).unwrap();
+        assert_eq!(gift_codes.len(), 1);
+        assert_eq!(gift_codes[0].gift_code_b58, gift_code_b58.to_string());
+        assert_eq!(gift_codes[0].value, Some(2 * MOB));
+
+        // Importing does not claim the gift code
+        let (status, _value, _memo) = bob_service.check_gift_code_status(&gift_code_b58).unwrap();
+        assert_eq!(status, GiftCodeStatus::GiftCodeAvailable);
+    }
 }
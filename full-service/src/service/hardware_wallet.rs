@@ -2,7 +2,12 @@
 
 //! Service for managing ledger materials and MobileCoin protocol objects.
 
-use std::convert::{TryFrom, TryInto};
+use std::{
+    convert::{TryFrom, TryInto},
+    fmt::Debug,
+    future::Future,
+    time::Duration,
+};
 
 use ledger_mob::{DeviceHandle, Filters, LedgerHandle, LedgerProvider, Transport};
 
@@ -10,11 +15,15 @@ use mc_account_keys::ViewAccountKey;
 use mc_common::logger::global_log;
 use mc_core::account::{ViewAccount, ViewSubaddress};
 use mc_crypto_keys::RistrettoPublic;
-use mc_transaction_core::tx::TxOut;
+use mc_transaction_core::tx::{Tx, TxOut};
 use mc_transaction_signer::types::TxoSynced;
 use strum::Display;
 
-use crate::service::models::tx_proposal::{InputTxo, TxProposal, UnsignedTxProposal};
+use crate::{
+    db::models::Account,
+    json_rpc::json_rpc_response::{ServerErrorCode, ServerErrorCodes},
+    service::models::tx_proposal::{InputTxo, TxProposal, UnsignedTxProposal},
+};
 
 /// Errors for the Address Service.
 #[derive(Display, Debug)]
@@ -27,6 +36,26 @@ pub enum HardwareWalletServiceError {
     RingCT(mc_transaction_core::ring_ct::Error),
     CryptoKeys(mc_crypto_keys::KeyError),
     CredentialMismatch,
+    MemoCountMismatch,
+    HardwareWalletNotFound(usize),
+    DeviceUnavailable(ledger_mob::Error),
+    AllHardwareWalletsFailed(Vec<String>),
+    ApprovalTimeout,
+}
+
+impl HardwareWalletServiceError {
+    /// Whether the operation may succeed on another device, i.e. when the
+    /// device could not be reached, timed out waiting for approval or holds a
+    /// different account. Errors from a device that was reached, such as the
+    /// user rejecting the transaction, are not retried.
+    fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            HardwareWalletServiceError::DeviceUnavailable(_)
+                | HardwareWalletServiceError::ApprovalTimeout
+                | HardwareWalletServiceError::CredentialMismatch
+        )
+    }
 }
 
 impl From<mc_transaction_core::ring_ct::Error> for HardwareWalletServiceError {
@@ -47,37 +76,166 @@ impl From<ledger_mob::Error> for HardwareWalletServiceError {
     }
 }
 
-async fn get_device_handle() -> Result<DeviceHandle<LedgerHandle>, HardwareWalletServiceError> {
+impl ServerErrorCode for HardwareWalletServiceError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::HardwareWalletNotFound(_) => Some(ServerErrorCodes::NotFound),
+            _ => None,
+        }
+    }
+}
+
+/// The SLIP-0010 account index used on the hardware wallet when an account is
+/// imported without one.
+pub const DEFAULT_HARDWARE_ACCOUNT_INDEX: u32 = 0;
+
+/// The time, in seconds, to wait for the user to approve a transaction on the
+/// hardware wallet unless another is configured.
+pub const DEFAULT_APPROVAL_TIMEOUT_SECS: u32 = 60;
+
+/// The hardware wallet account index that holds the spend key of `account`,
+/// as recorded when it was imported.
+pub fn hardware_account_index(account: &Account) -> u32 {
+    account
+        .hardware_wallet_account_index
+        .map_or(DEFAULT_HARDWARE_ACCOUNT_INDEX, |index| index as u32)
+}
+
+/// Extra time given to the device's own approval timeout, so that waiting on
+/// the user surfaces as an approval timeout rather than as a device error.
+const DEVICE_TIMEOUT_GRACE_SECS: u32 = 5;
+
+/// List the connected hardware wallets. The index of a device in this list can
+/// be used to pick it explicitly.
+pub async fn list_hardware_wallets() -> Result<Vec<String>, HardwareWalletServiceError> {
     let mut ledger_provider = LedgerProvider::init().await;
     let devices = ledger_provider
         .list(Filters::Hid)
         .await
         .map_err(ledger_mob::Error::from)?;
 
-    // Get the first device found, or error if none are found.
-    let device = devices
-        .first()
-        .ok_or(HardwareWalletServiceError::NoHardwareWalletsFound)?;
+    Ok(devices
+        .iter()
+        .map(|device| format!("{:04x?}", device))
+        .collect())
+}
+
+/// Run an operation against the connected hardware wallets, moving on to the
+/// next device if connecting fails or the operation fails in a retryable way.
+/// If `device_index` is set, only that device is tried.
+async fn with_each_device<T, F, Fut>(
+    device_index: Option<usize>,
+    mut operation: F,
+) -> Result<T, HardwareWalletServiceError>
+where
+    F: FnMut(DeviceHandle<LedgerHandle>) -> Fut,
+    Fut: Future<Output = Result<T, HardwareWalletServiceError>>,
+{
+    let mut ledger_provider = LedgerProvider::init().await;
+    let mut devices = ledger_provider
+        .list(Filters::Hid)
+        .await
+        .map_err(ledger_mob::Error::from)?;
 
     global_log::info!("Found devices: {:04x?}", devices);
 
-    let handle = ledger_provider
-        .connect(device.clone())
-        .await
-        .map_err(ledger_mob::Error::from)?
-        .into();
+    if let Some(device_index) = device_index {
+        if device_index >= devices.len() {
+            return Err(HardwareWalletServiceError::HardwareWalletNotFound(
+                device_index,
+            ));
+        }
+        devices = vec![devices.swap_remove(device_index)];
+    }
+
+    if devices.is_empty() {
+        return Err(HardwareWalletServiceError::NoHardwareWalletsFound);
+    }
 
-    Ok(handle)
+    let mut failures = vec![];
+    for device in devices {
+        let result = match ledger_provider.connect(device.clone()).await {
+            Ok(handle) => operation(DeviceHandle::from(handle)).await,
+            Err(e) => Err(HardwareWalletServiceError::DeviceUnavailable(
+                ledger_mob::Error::from(e),
+            )),
+        };
+
+        match result {
+            Err(e) => record_device_failure(&mut failures, &device, e)?,
+            result => return result,
+        }
+    }
+
+    Err(HardwareWalletServiceError::AllHardwareWalletsFailed(
+        failures,
+    ))
+}
+
+/// Record that an operation failed on a device so that the next device can be
+/// tried, or return the error if it is not one another device could avoid.
+fn record_device_failure(
+    failures: &mut Vec<String>,
+    device: &impl Debug,
+    error: HardwareWalletServiceError,
+) -> Result<(), HardwareWalletServiceError> {
+    if !error.is_retryable() {
+        return Err(error);
+    }
+
+    global_log::info!(
+        "Device {:04x?} failed, trying the next one: {:?}",
+        device,
+        error
+    );
+    failures.push(format!("{:04x?}: {:?}", device, error));
+    Ok(())
+}
+
+/// Where to find the hardware wallet to use, and how long to wait on it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HardwareWalletConfig {
+    /// The device from [list_hardware_wallets] to use. If not set, each
+    /// connected device is tried in turn.
+    pub device_index: Option<usize>,
+
+    /// The time, in seconds, the user has to approve a transaction on the
+    /// device.
+    pub approval_timeout_secs: u32,
 }
 
+impl Default for HardwareWalletConfig {
+    fn default() -> Self {
+        Self {
+            device_index: None,
+            approval_timeout_secs: DEFAULT_APPROVAL_TIMEOUT_SECS,
+        }
+    }
+}
+
+/// Compute the key images of `unsynced_txos` with the hardware wallet account
+/// at `account_index`, which must hold the keys of `view_account`.
 pub async fn sync_txos(
     unsynced_txos: Vec<(TxOut, u64)>,
     view_account: &ViewAccountKey,
+    account_index: u32,
+    config: &HardwareWalletConfig,
 ) -> Result<Vec<TxoSynced>, HardwareWalletServiceError> {
-    let mut device_handle = get_device_handle().await?;
+    let unsynced_txos = &unsynced_txos;
+    with_each_device(config.device_index, move |device_handle| {
+        sync_txos_with_device(device_handle, unsynced_txos, view_account, account_index)
+    })
+    .await
+}
 
+async fn sync_txos_with_device(
+    mut device_handle: DeviceHandle<LedgerHandle>,
+    unsynced_txos: &[(TxOut, u64)],
+    view_account: &ViewAccountKey,
+    account_index: u32,
+) -> Result<Vec<TxoSynced>, HardwareWalletServiceError> {
     // Check device and requested accounts match
-    let device_keys = device_handle.account_keys(0).await?;
+    let device_keys = device_handle.account_keys(account_index).await?;
     if device_keys.view_private_key() != view_account.view_private_key()
         || device_keys.spend_public_key() != view_account.spend_public_key()
     {
@@ -88,7 +246,7 @@ pub async fn sync_txos(
     for unsynced_txo in unsynced_txos {
         let tx_public_key = (&unsynced_txo.0.public_key).try_into()?;
         let key_image = device_handle
-            .key_image(0, unsynced_txo.1, tx_public_key)
+            .key_image(account_index, unsynced_txo.1, tx_public_key)
             .await?;
 
         synced_txos.push(TxoSynced {
@@ -100,37 +258,95 @@ pub async fn sync_txos(
     Ok(synced_txos)
 }
 
-pub async fn get_view_only_account_keys() -> Result<ViewAccount, HardwareWalletServiceError> {
-    let mut device_handle = get_device_handle().await?;
-    Ok(device_handle.account_keys(0).await?)
+/// Get the view keys of the hardware wallet account at `account_index`.
+pub async fn get_view_only_account_keys(
+    account_index: u32,
+    config: &HardwareWalletConfig,
+) -> Result<ViewAccount, HardwareWalletServiceError> {
+    with_each_device(config.device_index, |mut device_handle| async move {
+        device_handle
+            .account_keys(account_index)
+            .await
+            .map_err(HardwareWalletServiceError::from)
+    })
+    .await
 }
 
+/// Get the view keys of a subaddress of the hardware wallet account at
+/// `account_index`.
 pub async fn get_view_only_subaddress_keys(
+    account_index: u32,
     subaddress_index: u64,
+    config: &HardwareWalletConfig,
 ) -> Result<ViewSubaddress, HardwareWalletServiceError> {
-    let mut device_handle = get_device_handle().await?;
-    Ok(device_handle.subaddress_keys(0, subaddress_index).await?)
+    with_each_device(config.device_index, |mut device_handle| async move {
+        device_handle
+            .subaddress_keys(account_index, subaddress_index)
+            .await
+            .map_err(HardwareWalletServiceError::from)
+    })
+    .await
 }
 
+/// Sign a transaction proposal with the hardware wallet account at
+/// `account_index`, which must hold the keys of `view_account`. The device,
+/// and how long the user has to approve the transaction on it, are taken from
+/// `config`.
 pub async fn sign_tx_proposal(
     unsigned_tx_proposal: UnsignedTxProposal,
     view_account: &ViewAccountKey,
+    account_index: u32,
+    config: &HardwareWalletConfig,
 ) -> Result<TxProposal, HardwareWalletServiceError> {
-    let mut device_handle = get_device_handle().await?;
+    let unsigned_tx_proposal = &unsigned_tx_proposal;
+    with_each_device(config.device_index, move |device_handle| {
+        sign_tx_proposal_with_device(
+            device_handle,
+            unsigned_tx_proposal.clone(),
+            view_account,
+            account_index,
+            config.approval_timeout_secs,
+        )
+    })
+    .await
+}
 
-    // Check device and requested accounts match
-    let device_keys = device_handle.account_keys(0).await?;
+async fn sign_tx_proposal_with_device(
+    mut device_handle: DeviceHandle<LedgerHandle>,
+    unsigned_tx_proposal: UnsignedTxProposal,
+    view_account: &ViewAccountKey,
+    account_index: u32,
+    approval_timeout_secs: u32,
+) -> Result<TxProposal, HardwareWalletServiceError> {
+    // Check the device account at this index holds the keys of the account the
+    // proposal was built for, so we never sign with the wrong account.
+    let device_keys = device_handle.account_keys(account_index).await?;
     if device_keys.view_private_key() != view_account.view_private_key()
         || device_keys.spend_public_key() != view_account.spend_public_key()
     {
         return Err(HardwareWalletServiceError::CredentialMismatch);
     }
 
+    // The device signs the memos of the proposal's outputs along with the
+    // transaction itself. Count them from the proposal rather than from what is
+    // handed to the device, so that a dropped memo shows up in the signed result.
+    let num_memos_to_sign = num_proposal_memos(&unsigned_tx_proposal);
+
     // Sign transaction proposal
-    global_log::debug!("Signing tx proposal with hardware device");
-    let (tx, txos_synced) = device_handle
-        .transaction(0, 60, unsigned_tx_proposal.unsigned_tx)
-        .await?;
+    global_log::debug!(
+        "Signing tx proposal with {} memos with hardware device",
+        num_memos_to_sign
+    );
+    let (tx, txos_synced) = await_approval(
+        approval_timeout_secs,
+        device_handle.transaction(
+            account_index,
+            approval_timeout_secs + DEVICE_TIMEOUT_GRACE_SECS,
+            unsigned_tx_proposal.unsigned_tx,
+        ),
+    )
+    .await?;
+    check_signed_memos(num_memos_to_sign, &tx)?;
 
     let mut input_txos = vec![];
 
@@ -157,3 +373,227 @@ pub async fn sign_tx_proposal(
         change_txos: unsigned_tx_proposal.change_txos,
     })
 }
+
+/// Wait for a device operation that needs the user's approval, giving up with
+/// [HardwareWalletServiceError::ApprovalTimeout] after `approval_timeout_secs`.
+async fn await_approval<T>(
+    approval_timeout_secs: u32,
+    approval: impl Future<Output = Result<T, ledger_mob::Error>>,
+) -> Result<T, HardwareWalletServiceError> {
+    tokio::time::timeout(Duration::from_secs(approval_timeout_secs.into()), approval)
+        .await
+        .map_err(|_| HardwareWalletServiceError::ApprovalTimeout)?
+        .map_err(HardwareWalletServiceError::from)
+}
+
+/// Count the outputs that carry an encrypted memo.
+fn num_memos(outputs: &[TxOut]) -> usize {
+    outputs
+        .iter()
+        .filter(|tx_out| tx_out.e_memo.is_some())
+        .count()
+}
+
+/// Count the payload and change outputs of a proposal that carry an encrypted
+/// memo.
+fn num_proposal_memos(unsigned_tx_proposal: &UnsignedTxProposal) -> usize {
+    unsigned_tx_proposal
+        .payload_txos
+        .iter()
+        .chain(unsigned_tx_proposal.change_txos.iter())
+        .filter(|output_txo| output_txo.tx_out.e_memo.is_some())
+        .count()
+}
+
+/// Check that every memo of the unsigned proposal made it into the signed
+/// transaction.
+fn check_signed_memos(num_memos_to_sign: usize, tx: &Tx) -> Result<(), HardwareWalletServiceError> {
+    if num_memos(&tx.prefix.outputs) != num_memos_to_sign {
+        return Err(HardwareWalletServiceError::MemoCountMismatch);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::DerefMut;
+
+    use super::*;
+    use crate::{
+        db::account::AccountModel,
+        service::transaction::TransactionMemo,
+        test_utils::{
+            builder_for_random_recipient, get_test_ledger, random_account_with_seed_values,
+            WalletDbTestContext, MOB,
+        },
+    };
+    use mc_account_keys::PublicAddress;
+    use mc_common::logger::{async_test_with_logger, test_with_logger, Logger};
+    use mc_crypto_keys::RistrettoPrivate;
+    use mc_transaction_core::{tokens::Mob, Token};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test_with_logger]
+    fn test_check_signed_memos(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let account_key = random_account_with_seed_values(
+            &wallet_db,
+            &mut ledger_db,
+            &[70 * MOB],
+            &mut rng,
+            &logger,
+        );
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let (recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+        builder.add_recipient(recipient, 10 * MOB, Mob::ID).unwrap();
+        builder.select_txos(conn, None).unwrap();
+        builder.set_tombstone(0).unwrap();
+        let unsigned_tx_proposal = builder
+            .build(
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                conn,
+            )
+            .unwrap();
+
+        // Both the payload and the change output carry a memo
+        let num_memos_to_sign = num_proposal_memos(&unsigned_tx_proposal);
+        assert_eq!(num_memos_to_sign, 2);
+
+        let tx = unsigned_tx_proposal
+            .clone()
+            .sign_with_local_signer(&account_key)
+            .unwrap()
+            .tx;
+        check_signed_memos(num_memos_to_sign, &tx).unwrap();
+
+        // A signed transaction that dropped a memo is rejected
+        let mut dropped_memo_tx = tx.clone();
+        dropped_memo_tx.prefix.outputs[0].e_memo = None;
+        assert!(matches!(
+            check_signed_memos(num_memos_to_sign, &dropped_memo_tx),
+            Err(HardwareWalletServiceError::MemoCountMismatch)
+        ));
+
+        // The memos to sign come from the proposal, not from the unsigned
+        // transaction handed to the device, so a memo lost before signing is
+        // still caught.
+        let mut stripped_tx_proposal = unsigned_tx_proposal;
+        for output in stripped_tx_proposal
+            .unsigned_tx
+            .tx_prefix
+            .outputs
+            .iter_mut()
+        {
+            output.e_memo = None;
+        }
+        assert_eq!(num_proposal_memos(&stripped_tx_proposal), 2);
+        let stripped_tx = stripped_tx_proposal
+            .sign_with_local_signer(&account_key)
+            .unwrap()
+            .tx;
+        assert_eq!(num_memos(&stripped_tx.prefix.outputs), 0);
+        assert!(matches!(
+            check_signed_memos(num_memos_to_sign, &stripped_tx),
+            Err(HardwareWalletServiceError::MemoCountMismatch)
+        ));
+    }
+
+    #[test_with_logger]
+    fn test_record_device_failure(_logger: Logger) {
+        let mut failures = vec![];
+
+        // Failures another device could avoid are recorded, so the next
+        // device is tried.
+        record_device_failure(
+            &mut failures,
+            &"device 0",
+            HardwareWalletServiceError::ApprovalTimeout,
+        )
+        .unwrap();
+        record_device_failure(
+            &mut failures,
+            &"device 1",
+            HardwareWalletServiceError::CredentialMismatch,
+        )
+        .unwrap();
+        assert_eq!(failures.len(), 2);
+        assert!(failures[0].contains("device 0") && failures[0].contains("ApprovalTimeout"));
+        assert!(failures[1].contains("device 1") && failures[1].contains("CredentialMismatch"));
+
+        // Other failures end the operation without trying another device.
+        assert!(matches!(
+            record_device_failure(
+                &mut failures,
+                &"device 2",
+                HardwareWalletServiceError::MemoCountMismatch,
+            ),
+            Err(HardwareWalletServiceError::MemoCountMismatch)
+        ));
+        assert!(matches!(
+            record_device_failure(
+                &mut failures,
+                &"device 2",
+                HardwareWalletServiceError::KeyImageNotFoundForSignedInput,
+            ),
+            Err(HardwareWalletServiceError::KeyImageNotFoundForSignedInput)
+        ));
+        assert_eq!(failures.len(), 2);
+    }
+
+    #[test_with_logger]
+    fn test_hardware_account_index(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        // Accounts sync and sign with the index they were imported from.
+        for (account_index, expected_account_index) in
+            [(Some(4), 4), (None, DEFAULT_HARDWARE_ACCOUNT_INDEX)]
+        {
+            let view_account_key = ViewAccountKey::new(
+                RistrettoPrivate::from_random(&mut rng),
+                RistrettoPublic::from_random(&mut rng),
+            );
+            let account = Account::import_view_only(
+                &view_account_key,
+                None,
+                12,
+                None,
+                None,
+                account_index,
+                false,
+                conn,
+            )
+            .unwrap();
+            assert_eq!(hardware_account_index(&account), expected_account_index);
+        }
+    }
+
+    #[async_test_with_logger]
+    async fn test_await_approval_times_out(_logger: Logger) {
+        let approval = std::future::pending::<Result<(), ledger_mob::Error>>();
+        assert!(matches!(
+            await_approval(0, approval).await,
+            Err(HardwareWalletServiceError::ApprovalTimeout)
+        ));
+
+        let approval = std::future::ready(Ok::<_, ledger_mob::Error>(42));
+        assert_eq!(await_approval(1, approval).await.unwrap(), 42);
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright (c) 2020-2024 MobileCoin Inc.
+
+//! Service for checking whether the wallet is ready to serve requests.
+
+use crate::WalletService;
+use diesel::RunQueryDsl;
+use mc_connection::{BlockchainConnection, UserTxConnection};
+use mc_fog_report_validation::FogPubkeyResolver;
+use mc_ledger_db::Ledger;
+use std::ops::DerefMut;
+
+/// The result of checking the wallet's dependencies.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Health {
+    /// Whether a trivial query against the wallet database succeeded. None if
+    /// the wallet runs without a database.
+    pub wallet_db_reachable: Option<bool>,
+
+    /// The number of blocks in the local ledger, if it could be read.
+    pub local_block_height: Option<u64>,
+
+    /// Whether the account sync thread is running. None if the wallet runs
+    /// without a database, and so without a sync thread.
+    pub sync_thread_running: Option<bool>,
+
+    /// The number of consensus nodes or validators the wallet is configured
+    /// to talk to.
+    pub num_peers: usize,
+
+    /// Why the wallet is not ready, empty if it is.
+    pub problems: Vec<String>,
+}
+
+impl Health {
+    /// Whether the wallet is ready to serve requests.
+    pub fn ready(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Trait defining the ways in which the wallet can report its health.
+pub trait HealthService {
+    /// Check that the wallet database and ledger can be read, that the sync
+    /// thread is running, and that there are peers to talk to when online.
+    /// Failed checks are reported in the result rather than as an error, and
+    /// nothing is written.
+    fn get_health(&self) -> Health;
+}
+
+impl<T, FPR> HealthService for WalletService<T, FPR>
+where
+    T: BlockchainConnection + UserTxConnection + 'static,
+    FPR: FogPubkeyResolver + Send + Sync + 'static,
+{
+    fn get_health(&self) -> Health {
+        let mut problems = Vec::new();
+
+        let wallet_db_reachable = self.wallet_db.as_ref().map(|_| {
+            let result =
+                self.get_pooled_conn()
+                    .map_err(|e| e.to_string())
+                    .and_then(|mut pooled_conn| {
+                        diesel::sql_query("SELECT 1")
+                            .execute(pooled_conn.deref_mut())
+                            .map_err(|e| e.to_string())
+                    });
+            match result {
+                Ok(_) => true,
+                Err(e) => {
+                    problems.push(format!("Wallet database is unreachable: {e}"));
+                    false
+                }
+            }
+        });
+
+        let local_block_height = match self.ledger_db.num_blocks() {
+            Ok(0) => {
+                problems.push("Ledger has no blocks".to_string());
+                None
+            }
+            Ok(num_blocks) => Some(num_blocks),
+            Err(e) => {
+                problems.push(format!("Ledger is unreachable: {e}"));
+                None
+            }
+        };
+
+        let sync_thread_running = self.wallet_db.as_ref().map(|_| {
+            let running = self.sync_thread_running();
+            if !running {
+                problems.push("Sync thread is not running".to_string());
+            }
+            running
+        });
+
+        let num_peers = self.peer_manager.responder_ids().len();
+        if num_peers == 0 && !self.offline {
+            problems.push("No peers are configured".to_string());
+        }
+
+        Health {
+            wallet_db_reachable,
+            local_block_height,
+            sync_thread_running,
+            num_peers,
+            problems,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        get_empty_test_ledger, get_test_ledger, setup_wallet_service, setup_wallet_service_offline,
+    };
+    use mc_account_keys::PublicAddress;
+    use mc_common::logger::{test_with_logger, Logger};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test_with_logger]
+    fn test_get_health(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+        let service = setup_wallet_service(ledger_db, None, logger.clone());
+
+        let health = service.get_health();
+        assert!(health.ready(), "{:?}", health.problems);
+        assert_eq!(health.wallet_db_reachable, Some(true));
+        assert_eq!(health.local_block_height, Some(12));
+        assert_eq!(health.sync_thread_running, Some(true));
+        assert!(health.num_peers > 0);
+
+        // Without a ledger to read, the wallet is not ready.
+        let service = setup_wallet_service_offline(get_empty_test_ledger(), logger);
+        let health = service.get_health();
+        assert!(!health.ready());
+        assert_eq!(health.local_block_height, None);
+        assert_eq!(health.problems, vec!["Ledger has no blocks".to_string()]);
+    }
+}
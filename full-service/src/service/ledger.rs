@@ -4,10 +4,12 @@
 
 use crate::{
     db::{
-        models::{TransactionLog, Txo},
+        account::AccountModel,
+        models::{Account, TransactionLog, Txo},
         transaction_log::{TransactionId, TransactionLogModel},
         txo::TxoModel,
     },
+    json_rpc::json_rpc_response::{ServerErrorCode, ServerErrorCodes},
     service::{
         models::ledger::LedgerSearchResult,
         watcher::{WatcherService, WatcherServiceError},
@@ -15,7 +17,7 @@ use crate::{
     WalletService,
 };
 use mc_blockchain_types::{Block, BlockContents, BlockVersion, BlockVersionError};
-use mc_common::HashSet;
+use mc_common::{logger::log, HashSet};
 use mc_connection::{
     BlockInfo, BlockchainConnection, RetryableBlockchainConnection, UserTxConnection,
     _retry::delay::Fibonacci,
@@ -26,8 +28,9 @@ use mc_ledger_db::{Error as LedgerError, Ledger, LedgerDB};
 use mc_ledger_sync::NetworkState;
 use mc_transaction_core::{
     ring_signature::KeyImage,
+    tokens::Mob,
     tx::{Tx, TxOut, TxOutMembershipProof},
-    FeeMap, FeeMapError,
+    FeeMap, FeeMapError, Token, TokenId,
 };
 use mc_watcher::error::WatcherDBError;
 use rand::Rng;
@@ -82,6 +85,9 @@ pub enum LedgerServiceError {
     /// Error with FeeMap: {0}
     FeeMap(FeeMapError),
 
+    /// The network has no fee for token {0}
+    NoFeeForToken(TokenId),
+
     /// Error interacting with watcher database: {0}
     WatcherDb(WatcherDBError),
 }
@@ -136,6 +142,15 @@ impl From<WatcherServiceError> for LedgerServiceError {
     }
 }
 
+impl ServerErrorCode for LedgerServiceError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::Database(e) => e.code(),
+            _ => None,
+        }
+    }
+}
+
 /// Trait defining the ways in which the wallet can interact with and manage
 /// ledger objects and interfaces.
 #[rustfmt::skip]
@@ -226,9 +241,38 @@ pub trait LedgerService {
     /// Get the last block information cross all nodes
     fn get_latest_block_info(&self) -> Result<BlockInfo, LedgerServiceError>;
 
-    /// Get an object for fees in each of the configured token types
+    /// Get an object for fees in each of the configured token types. The
+    /// last fees fetched are used if no node responds.
     fn get_network_fees(&self) -> Result<FeeMap, LedgerServiceError>;
 
+    /// Get the network's minimum fee for a token, falling back to MINIMUM_FEE
+    /// for MOB if the network fees are unavailable.
+    ///
+    /// # Arguments
+    ///
+    ///| Name       | Purpose                       | Notes |
+    ///|------------|-------------------------------|-------|
+    ///| `token_id` | The token the fee is paid in. |       |
+    ///
+    fn get_network_fee(&self, token_id: TokenId) -> Result<u64, LedgerServiceError>;
+
+    /// Get the fee a transaction pays when it is built without one: the
+    /// account's default fee for MOB fees, if set, and otherwise the network
+    /// fee for the token.
+    ///
+    /// # Arguments
+    ///
+    ///| Name       | Purpose                              | Notes                               |
+    ///|------------|--------------------------------------|-------------------------------------|
+    ///| `account`  | The account sending the transaction. | None to always use the network fee. |
+    ///| `token_id` | The token the fee is paid in.        |                                     |
+    ///
+    fn get_default_fee(
+        &self,
+        account: Option<&Account>,
+        token_id: TokenId,
+    ) -> Result<u64, LedgerServiceError>;
+
     /// Get block version info from the latest block
     fn get_network_block_version(&self) -> Result<BlockVersion, LedgerServiceError>;
 
@@ -424,9 +468,46 @@ where
     }
 
     fn get_network_fees(&self) -> Result<FeeMap, LedgerServiceError> {
-        Ok(FeeMap::try_from(
-            self.get_latest_block_info()?.minimum_fees,
-        )?)
+        let fees = self
+            .get_latest_block_info()
+            .and_then(|block_info| Ok(FeeMap::try_from(block_info.minimum_fees)?));
+
+        let mut cached_fees = self.network_fees.lock().expect("lock poisoned");
+        match fees {
+            Ok(fees) => {
+                *cached_fees = Some(fees.clone());
+                Ok(fees)
+            }
+            Err(err) => cached_fees.clone().ok_or(err),
+        }
+    }
+
+    fn get_network_fee(&self, token_id: TokenId) -> Result<u64, LedgerServiceError> {
+        match self.get_network_fees() {
+            Ok(fees) => fees
+                .get_fee_for_token(&token_id)
+                .ok_or(LedgerServiceError::NoFeeForToken(token_id)),
+            Err(err) if token_id == Mob::ID => {
+                log::warn!(
+                    self.logger,
+                    "Network fees unavailable, using the minimum MOB fee: {:?}",
+                    err
+                );
+                Ok(Mob::MINIMUM_FEE)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn get_default_fee(
+        &self,
+        account: Option<&Account>,
+        token_id: TokenId,
+    ) -> Result<u64, LedgerServiceError> {
+        match account.and_then(|account| account.default_fee_for_token(token_id)) {
+            Some(default_fee) => Ok(default_fee),
+            None => self.get_network_fee(token_id),
+        }
     }
 
     fn get_network_block_version(&self) -> Result<BlockVersion, LedgerServiceError> {
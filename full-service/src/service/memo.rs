@@ -1,5 +1,6 @@
 use crate::{
     db::{account::AccountModel, models::Txo, txo::TxoModel, WalletDbError},
+    json_rpc::json_rpc_response::{ServerErrorCode, ServerErrorCodes},
     service::ledger::{LedgerService, LedgerServiceError},
     util::b58::{b58_decode_public_address, B58Error},
     WalletService,
@@ -75,6 +76,17 @@ impl From<MemoDecodingError> for MemoServiceError {
     }
 }
 
+impl ServerErrorCode for MemoServiceError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::WalletDb(e) => e.code(),
+            Self::B58(e) => e.code(),
+            Self::LedgerService(e) => e.code(),
+            _ => None,
+        }
+    }
+}
+
 pub trait MemoService {
     fn validate_sender_memo(
         &self,
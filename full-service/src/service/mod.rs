@@ -4,10 +4,12 @@
 
 pub mod account;
 pub mod address;
+pub mod address_book;
 pub mod balance;
 pub mod confirmation_number;
 pub mod gift_code;
 pub mod hardware_wallet;
+pub mod health;
 pub mod ledger;
 pub mod memo;
 pub mod models;
@@ -4,6 +4,7 @@
 
 use crate::service::models::watcher::WatcherBlockInfo;
 use mc_blockchain_types::{Block, BlockContents};
+use mc_transaction_core::tx::{TxOut, TxOutMembershipProof};
 use serde_derive::{Deserialize, Serialize};
 
 /// A single search result from the ledger.
@@ -42,3 +43,19 @@ pub enum LedgerSearchResult {
         watcher_info: Option<WatcherBlockInfo>,
     },
 }
+
+/// The subset of the ledger needed to build a transaction, so that a machine
+/// without a full LedgerDB (e.g. an air-gapped one) can construct an unsigned
+/// proposal.
+#[derive(Clone, Debug)]
+pub struct LedgerSnapshot {
+    /// The number of blocks in the ledger when the snapshot was taken.
+    pub num_blocks: u64,
+
+    /// The TxOuts being spent, with their membership proofs.
+    pub inputs: Vec<(TxOut, TxOutMembershipProof)>,
+
+    /// The mixins for each ring, with their membership proofs. There is one
+    /// ring per input.
+    pub rings: Vec<Vec<(TxOut, TxOutMembershipProof)>>,
+}
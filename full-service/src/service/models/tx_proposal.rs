@@ -18,7 +18,10 @@ use protobuf::Message;
 
 use crate::{
     db::{account::AccountModel, models::Account},
-    service::{hardware_wallet, transaction::TransactionServiceError},
+    service::{
+        hardware_wallet::{self, HardwareWalletConfig},
+        transaction::TransactionServiceError,
+    },
     util::b58::b58_decode_public_address,
 };
 
@@ -64,10 +67,28 @@ pub struct UnsignedTxProposal {
 
 impl UnsignedTxProposal {
     pub async fn sign(self, account: &Account) -> Result<TxProposal, TransactionServiceError> {
+        self.sign_with_hardware_wallet_config(account, &HardwareWalletConfig::default())
+            .await
+    }
+
+    /// Sign the proposal, using the hardware wallet picked by `config` if the
+    /// account is view only.
+    pub async fn sign_with_hardware_wallet_config(
+        self,
+        account: &Account,
+        config: &HardwareWalletConfig,
+    ) -> Result<TxProposal, TransactionServiceError> {
         match account.view_only {
             true => {
                 global_log::debug!("signing tx proposal with hardware wallet");
-                Ok(hardware_wallet::sign_tx_proposal(self, &account.view_account_key()?).await?)
+                let view_account_key = account.view_account_key()?;
+                Ok(hardware_wallet::sign_tx_proposal(
+                    self,
+                    &view_account_key,
+                    hardware_wallet::hardware_account_index(account),
+                    config,
+                )
+                .await?)
             }
             false => {
                 global_log::debug!("signing tx proposal with local signer");
@@ -468,7 +489,7 @@ mod tests {
         },
     };
 
-    use mc_common::logger::{test_with_logger, Logger};
+    use mc_common::logger::{async_test_with_logger, test_with_logger, Logger};
     use mc_rand::RngCore;
     use rand::{rngs::StdRng, SeedableRng};
 
@@ -545,11 +566,13 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
                 TransactionMemo::RTH {
                     subaddress_index: Some(alice_address_from_bob.subaddress_index as u64),
                 },
                 None,
                 None,
+                None,
             )
             .unwrap();
 
@@ -567,4 +590,286 @@ mod tests {
             unsigned_tx_proposal_converted_from_v2_json_model
         );
     }
+
+    #[async_test_with_logger]
+    async fn test_compact_tx_proposal_is_smaller_than_verbose(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        let alice_public_address = alice_account_key.default_subaddress();
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_public_address.clone()],
+            100 * MOB,
+            &vec![KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        manually_sync_account(
+            &ledger_db,
+            &service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let bob = service
+            .create_account(
+                Some("Bob's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let bob_address = service
+            .assign_address_for_account(&AccountID(bob.id.clone()), None)
+            .unwrap();
+
+        let tx_proposal = service
+            .build_and_sign_transaction(
+                &alice.id,
+                &[(
+                    bob_address.public_address_b58,
+                    AmountJSON::new(42 * MOB, Mob::ID),
+                )],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let verbose =
+            crate::json_rpc::v1::models::tx_proposal::TxProposal::try_from(&tx_proposal).unwrap();
+        let compact =
+            crate::json_rpc::v2::models::tx_proposal::TxProposal::try_from(&tx_proposal).unwrap();
+
+        let verbose_size = serde_json::to_string(&verbose).unwrap().len();
+        let compact_size = serde_json::to_string(&compact).unwrap().len();
+        assert!(compact_size < verbose_size);
+
+        // The compact form round trips exactly.
+        let from_compact = TxProposal::try_from(&compact).unwrap();
+        assert_eq!(from_compact, tx_proposal);
+
+        // The verbose form does not carry change outputs or shared secrets, so
+        // compare the parts it does carry.
+        let from_verbose = TxProposal::try_from(&verbose).unwrap();
+        assert_eq!(from_verbose.tx, tx_proposal.tx);
+        assert_eq!(from_verbose.input_txos, tx_proposal.input_txos);
+        assert_eq!(
+            from_verbose
+                .payload_txos
+                .iter()
+                .map(|txo| &txo.tx_out)
+                .collect::<Vec<_>>(),
+            tx_proposal
+                .payload_txos
+                .iter()
+                .map(|txo| &txo.tx_out)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[async_test_with_logger]
+    async fn test_v1_tx_proposal_round_trips_through_v2(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_account_key.default_subaddress()],
+            100 * MOB,
+            &vec![KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        manually_sync_account(
+            &ledger_db,
+            &service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let bob = service
+            .create_account(
+                Some("Bob's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let bob_address = service
+            .assign_address_for_account(&AccountID(bob.id.clone()), None)
+            .unwrap();
+
+        let tx_proposal = service
+            .build_and_sign_transaction(
+                &alice.id,
+                &[(
+                    bob_address.public_address_b58,
+                    AmountJSON::new(42 * MOB, Mob::ID),
+                )],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        // v1 JSON -> internal -> v2 JSON -> internal
+        let v1 =
+            crate::json_rpc::v1::models::tx_proposal::TxProposal::try_from(&tx_proposal).unwrap();
+        let from_v1 = TxProposal::try_from(&v1).unwrap();
+        let v2 = crate::json_rpc::v2::models::tx_proposal::TxProposal::try_from(&from_v1).unwrap();
+        let from_v2 = TxProposal::try_from(&v2).unwrap();
+        assert_eq!(from_v2, from_v1);
+
+        // Upgrading v1 JSON directly gives the same v2 JSON
+        let upgraded = crate::json_rpc::v2::models::tx_proposal::TxProposal::try_from(&v1).unwrap();
+        assert_eq!(upgraded, v2);
+        assert!(upgraded.change_txos.is_empty());
+        assert_eq!(TxProposal::try_from(&upgraded).unwrap().tx, tx_proposal.tx);
+    }
+
+    #[test_with_logger]
+    fn test_v2_confirmation_numbers_read_from_their_own_field(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_account_key.default_subaddress()],
+            100 * MOB,
+            &vec![KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        manually_sync_account(
+            &ledger_db,
+            &service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let bob_address = AccountKey::random(&mut rng).default_subaddress();
+        let unsigned_tx_proposal = service
+            .build_transaction(
+                &alice.id,
+                &[(
+                    crate::util::b58::b58_encode_public_address(&bob_address).unwrap(),
+                    AmountJSON::new(42 * MOB, Mob::ID),
+                )],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+
+        let mut json_model =
+            crate::json_rpc::v2::models::tx_proposal::UnsignedTxProposal::try_from(
+                &unsigned_tx_proposal,
+            )
+            .unwrap();
+
+        // Give each output a confirmation number unrelated to its tx_out
+        json_model.payload_txos[0].confirmation_number = hex::encode([7u8; 32]);
+        json_model.change_txos[0].confirmation_number = hex::encode([9u8; 32]);
+
+        let converted = UnsignedTxProposal::try_from(&json_model).unwrap();
+        assert_eq!(
+            converted.payload_txos[0].confirmation_number,
+            TxOutConfirmationNumber::from([7u8; 32])
+        );
+        assert_eq!(
+            converted.payload_txos[0].tx_out,
+            unsigned_tx_proposal.payload_txos[0].tx_out
+        );
+        assert_eq!(
+            converted.change_txos[0].confirmation_number,
+            TxOutConfirmationNumber::from([9u8; 32])
+        );
+        assert_eq!(
+            converted.change_txos[0].tx_out,
+            unsigned_tx_proposal.change_txos[0].tx_out
+        );
+
+        // A confirmation number of the wrong length is rejected rather than
+        // truncated or padded
+        json_model.payload_txos[0].confirmation_number = hex::encode([7u8; 31]);
+        assert!(UnsignedTxProposal::try_from(&json_model).is_err());
+        json_model.payload_txos[0].confirmation_number = hex::encode([7u8; 33]);
+        assert!(UnsignedTxProposal::try_from(&json_model).is_err());
+    }
 }
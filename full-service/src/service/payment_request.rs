@@ -6,6 +6,7 @@ use std::ops::DerefMut;
 
 use crate::{
     db::{assigned_subaddress::AssignedSubaddressModel, models::AssignedSubaddress, WalletDbError},
+    json_rpc::json_rpc_response::{ServerErrorCode, ServerErrorCodes},
     service::WalletService,
     util::b58::{b58_decode_public_address, b58_encode_payment_request, B58Error},
 };
@@ -79,6 +80,18 @@ impl From<LedgerServiceError> for PaymentRequestServiceError {
     }
 }
 
+impl ServerErrorCode for PaymentRequestServiceError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::B58(e) => e.code(),
+            Self::Database(e) => e.code(),
+            Self::LedgerService(e) => e.code(),
+            Self::Diesel(diesel::result::Error::NotFound) => Some(ServerErrorCodes::NotFound),
+            _ => None,
+        }
+    }
+}
+
 #[rustfmt::skip]
 pub trait PaymentRequestService {
     /// Create a payment request b58 code to give to someone else.
@@ -16,6 +16,7 @@ use crate::{
         txo::{TxoInfo, TxoModel, TxoStatus},
         WalletDbError,
     },
+    json_rpc::json_rpc_response::{ServerErrorCode, ServerErrorCodes},
     service::models::tx_proposal::TxProposal,
     WalletService,
 };
@@ -103,6 +104,16 @@ impl From<mc_transaction_core::TxOutConversionError> for ReceiptServiceError {
     }
 }
 
+impl ServerErrorCode for ReceiptServiceError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::Database(e) => e.code(),
+            Self::Diesel(diesel::result::Error::NotFound) => Some(ServerErrorCodes::NotFound),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ReceiverReceipt {
     /// The public key of the Txo sent to the recipient.
@@ -229,8 +240,14 @@ where
         let txo = txos[0].clone();
         let status = txo.status(conn)?;
         let memo = txo.memo(conn)?;
+        let spent_in_transaction_id = txo.spent_in_transaction_log_id(conn)?;
 
-        let txo_info = TxoInfo { txo, memo, status };
+        let txo_info = TxoInfo {
+            txo,
+            memo,
+            status,
+            spent_in_transaction_id,
+        };
 
         if (txo_info.status == TxoStatus::Pending) || (txo_info.status == TxoStatus::Created) {
             return Ok((ReceiptTransactionStatus::TransactionPending, Some(txo_info)));
@@ -431,11 +448,13 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
                 TransactionMemo::RTH {
                     subaddress_index: None,
                 },
                 None,
                 None,
+                None,
             )
             .await
             .expect("Could not build transaction");
@@ -475,7 +494,18 @@ mod tests {
 
         // Get corresponding Txo for Bob
         let txos_and_statuses = service
-            .list_txos(Some(bob.id), None, None, None, None, None, None, None)
+            .list_txos(
+                Some(bob.id),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
             .expect("Could not get Bob Txos");
         assert_eq!(txos_and_statuses.len(), 1);
 
@@ -566,11 +596,13 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
                 TransactionMemo::RTH {
                     subaddress_index: None,
                 },
                 None,
                 None,
+                None,
             )
             .await
             .expect("Could not build transaction");
@@ -694,11 +726,13 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
                 TransactionMemo::RTH {
                     subaddress_index: None,
                 },
                 None,
                 None,
+                None,
             )
             .await
             .expect("Could not build transaction");
@@ -842,11 +876,13 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
                 TransactionMemo::RTH {
                     subaddress_index: None,
                 },
                 None,
                 None,
+                None,
             )
             .await
             .expect("Could not build transaction");
@@ -13,6 +13,7 @@ use crate::{
         Conn, WalletDb,
     },
     error::SyncError,
+    util::thread::join_with_timeout,
 };
 use mc_account_keys::{AccountKey, ViewAccountKey};
 use mc_common::{
@@ -28,20 +29,106 @@ use mc_transaction_core::{
     tx::TxOut,
     Amount,
 };
+use mc_watcher::watcher_db::WatcherDB;
+use mc_watcher_api::TimestampResultCode;
 use rayon::prelude::*;
 
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     convert::TryFrom,
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc, Mutex,
+        Arc, Condvar, Mutex,
     },
     thread,
     time::{Duration, Instant},
 };
 
-const BLOCKS_CHUNK_SIZE: u64 = 1_000;
+/// The default number of blocks loaded into memory and scanned at a time for
+/// each account.
+pub const DEFAULT_BLOCKS_CHUNK_SIZE: u64 = 1_000;
+
+/// How long the sync thread waits for new blocks once every account has caught
+/// up with the ledger, if it is not notified of them sooner. Without a
+/// notifier, the thread checks for new blocks every 10ms instead.
+pub const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How far an account has synced, reported after each chunk of blocks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyncProgress {
+    pub account_id: AccountID,
+
+    /// The number of blocks scanned for this account so far.
+    pub blocks_synced: u64,
+
+    /// The number of blocks in the local ledger.
+    pub total_blocks: u64,
+}
+
+/// Callbacks notified with the progress of each account after every chunk that
+/// is synced. Progress is only computed when at least one is registered.
+pub type SyncProgressListeners = Arc<Mutex<Vec<Box<dyn Fn(&SyncProgress) + Send>>>>;
+
+/// Signalled when blocks are appended to the ledger, so that the sync thread
+/// can scan them right away instead of waiting out its poll interval.
+#[derive(Clone, Debug, Default)]
+pub struct LedgerUpdateNotifier {
+    /// The number of notifications so far, and the condvar waiters block on.
+    updates: Arc<(Mutex<u64>, Condvar)>,
+}
+
+impl LedgerUpdateNotifier {
+    /// Wake everything waiting for new blocks.
+    pub fn notify(&self) {
+        let (count, condvar) = &*self.updates;
+        *count.lock().expect("ledger update lock poisoned") += 1;
+        condvar.notify_all();
+    }
+
+    /// The number of notifications so far, to pass to `wait`.
+    pub fn count(&self) -> u64 {
+        *self.updates.0.lock().expect("ledger update lock poisoned")
+    }
+
+    /// Wait until there has been a notification since `count` was read, or
+    /// until `timeout` elapses. Returns whether there was a notification.
+    pub fn wait(&self, count: u64, timeout: Duration) -> bool {
+        let (updates, condvar) = &*self.updates;
+        let guard = updates.lock().expect("ledger update lock poisoned");
+        let (guard, _) = condvar
+            .wait_timeout_while(guard, timeout, |updates| *updates == count)
+            .expect("ledger update lock poisoned");
+        *guard != count
+    }
+}
+
+/// Settings for the sync thread.
+#[derive(Clone)]
+pub struct SyncThreadConfig {
+    /// When given, the block timestamps of received txos are stored from it.
+    pub watcher_db: Option<WatcherDB>,
+
+    /// Notified with the progress of each account after every chunk.
+    pub progress_listeners: SyncProgressListeners,
+
+    /// When given, the thread waits on it for new blocks once every account
+    /// has caught up, instead of polling.
+    pub ledger_updates: Option<LedgerUpdateNotifier>,
+
+    /// The number of blocks synced at a time for each account.
+    pub blocks_chunk_size: u64,
+}
+
+impl Default for SyncThreadConfig {
+    fn default() -> Self {
+        Self {
+            watcher_db: None,
+            progress_listeners: SyncProgressListeners::default(),
+            ledger_updates: None,
+            blocks_chunk_size: DEFAULT_BLOCKS_CHUNK_SIZE,
+        }
+    }
+}
 
 /// Sync thread - holds objects needed to cleanly terminate the sync thread.
 pub struct SyncThread {
@@ -59,6 +146,39 @@ impl SyncThread {
         accounts_with_deposits: Arc<Mutex<HashMap<AccountID, bool>>>,
         logger: Logger,
     ) -> Self {
+        Self::start_with_config(
+            ledger_db,
+            wallet_db,
+            accounts_with_deposits,
+            SyncThreadConfig::default(),
+            logger,
+        )
+    }
+
+    /// Start the sync thread, also storing the block timestamps of received
+    /// txos from the watcher db when one is given. Each account is synced
+    /// `blocks_chunk_size` blocks at a time. Once all accounts have caught up,
+    /// the thread waits for `ledger_updates`, if given, to be notified of new
+    /// blocks, or for `IDLE_POLL_INTERVAL`, before checking again.
+    pub fn start_with_config(
+        ledger_db: LedgerDB,
+        wallet_db: WalletDb,
+        accounts_with_deposits: Arc<Mutex<HashMap<AccountID, bool>>>,
+        config: SyncThreadConfig,
+        logger: Logger,
+    ) -> Self {
+        let SyncThreadConfig {
+            watcher_db,
+            progress_listeners,
+            ledger_updates,
+            blocks_chunk_size,
+        } = config;
+
+        assert!(
+            blocks_chunk_size >= 1,
+            "blocks_chunk_size must be at least 1"
+        );
+
         // Start the sync thread.
 
         let stop_requested = Arc::new(AtomicBool::new(false));
@@ -75,25 +195,70 @@ impl SyncThread {
                         .get_pooled_conn()
                         .expect("failed getting wallet db connection");
 
+                    if let Err(e) = check_sync_consistency(&ledger_db, conn, &logger) {
+                        log::error!(&logger, "Error checking account sync positions: {:?}", e);
+                    }
+
+                    // The number of blocks in the ledger when block timestamps were last
+                    // synced.
+                    let mut timestamps_synced_num_blocks = None;
+
                     loop {
                         if thread_stop_requested.load(Ordering::SeqCst) {
                             log::debug!(logger, "SyncThread stop requested.");
                             break;
                         }
 
-                        match sync_all_accounts(
+                        // Read before syncing, so that blocks appended during the
+                        // sync are not missed.
+                        let ledger_update_count = ledger_updates
+                            .as_ref()
+                            .map_or(0, LedgerUpdateNotifier::count);
+
+                        let caught_up = match sync_all_accounts(
                             &ledger_db,
                             conn,
                             thread_accounts_with_deposits.clone(),
+                            &progress_listeners,
+                            blocks_chunk_size,
+                            &thread_stop_requested,
                             &logger,
                         ) {
-                            Ok(()) => (),
-                            Err(e) => log::error!(&logger, "Error during account sync:\n{:?}", e),
+                            Ok(caught_up) => caught_up,
+                            Err(e) => {
+                                log::error!(&logger, "Error during account sync:\n{:?}", e);
+                                false
+                            }
+                        };
+                        // Timestamps are only looked up again once blocks have been appended,
+                        // so blocks the watcher has not synced yet are retried once per new
+                        // block rather than on every pass.
+                        if let Some(watcher_db) = &watcher_db {
+                            let num_blocks = ledger_db.num_blocks().ok();
+                            if num_blocks.is_some() && num_blocks != timestamps_synced_num_blocks {
+                                match sync_block_timestamps(watcher_db, conn) {
+                                    Ok(()) => timestamps_synced_num_blocks = num_blocks,
+                                    Err(e) => log::error!(
+                                        &logger,
+                                        "Error syncing block timestamps: {:?}",
+                                        e
+                                    ),
+                                }
+                            }
+                        }
+
+                        match &ledger_updates {
+                            Some(ledger_updates) if caught_up => {
+                                ledger_updates.wait(ledger_update_count, IDLE_POLL_INTERVAL);
+                            }
+                            _ => {
+                                // This sleep is to allow other API calls that need access to the
+                                // database a chance to execute, because the sync process requires
+                                // a write lock on the database. Without a notifier, it is also how
+                                // often the ledger is checked for new blocks.
+                                thread::sleep(Duration::from_millis(10));
+                            }
                         }
-                        // This sleep is to allow other API calls that need access to the database a
-                        // chance to execute, because the sync process requires a write lock on the
-                        // database.
-                        thread::sleep(Duration::from_millis(10));
                     }
                     log::debug!(logger, "SyncThread stopped.");
                 })
@@ -112,6 +277,23 @@ impl SyncThread {
             join_handle.join().expect("SyncThread join failed");
         }
     }
+
+    /// Whether the thread has been started and has not yet exited.
+    pub fn is_running(&self) -> bool {
+        self.join_handle
+            .as_ref()
+            .map_or(false, |join_handle| !join_handle.is_finished())
+    }
+
+    /// Signal the thread to stop and wait at most `timeout` for it to finish.
+    /// Returns false if the thread was still running when the timeout elapsed.
+    pub fn stop_with_timeout(&mut self, timeout: Duration) -> bool {
+        self.stop_requested.store(true, Ordering::SeqCst);
+        match self.join_handle.take() {
+            Some(join_handle) => join_with_timeout(join_handle, timeout),
+            None => true,
+        }
+    }
 }
 
 impl Drop for SyncThread {
@@ -120,26 +302,37 @@ impl Drop for SyncThread {
     }
 }
 
+/// Sync the next chunk of blocks for each account that is behind the ledger,
+/// stopping early once `stop_requested` is set. Returns true if every account
+/// had already caught up.
 pub fn sync_all_accounts(
     ledger_db: &LedgerDB,
     conn: Conn,
     accounts_with_deposits: Arc<Mutex<HashMap<AccountID, bool>>>,
+    progress_listeners: &SyncProgressListeners,
+    blocks_chunk_size: u64,
+    stop_requested: &AtomicBool,
     logger: &Logger,
-) -> Result<(), SyncError> {
+) -> Result<bool, SyncError> {
     // Get the current number of blocks in ledger.
     let num_blocks = ledger_db
         .num_blocks()
         .expect("failed getting number of blocks");
     if num_blocks == 0 {
-        return Ok(()); // FIXME: we want it to fire in this case with empty
-                       // accounts
+        return Ok(true); // FIXME: we want it to fire in this case with empty
+                         // accounts
     }
 
     // Go over our list of accounts and see which ones need to process more blocks.
     let accounts: Vec<Account> =
         { Account::list_all(conn, None, None).expect("Failed getting accounts from database") };
 
+    let mut caught_up = true;
     for account in accounts {
+        if stop_requested.load(Ordering::SeqCst) {
+            return Ok(false);
+        }
+
         // If there are no new blocks for this account, don't do anything.
         //
         // If the account is currently resyncing, we need to set it to false
@@ -159,10 +352,20 @@ pub fn sync_all_accounts(
 
             continue;
         }
-        let found_txos = sync_account_next_chunk(ledger_db, conn, &account.id, logger)?;
-        if found_txos > 0 && !account.resyncing {
+        caught_up = false;
+        let found_txos = sync_account_next_chunk(
+            ledger_db,
+            conn,
+            &account.id,
+            blocks_chunk_size,
+            stop_requested,
+            logger,
+        )?;
+        report_sync_progress(progress_listeners, &account.id, num_blocks, conn)?;
+        if found_txos > 0 && !account.resyncing && account.webhook_enabled {
             // Start tracking the accounts with deposits, but do not fire the webhook
-            // until they are fully synced.
+            // until they are fully synced. Accounts with the webhook disabled are
+            // never tracked.
             accounts_with_deposits
                 .lock()
                 .unwrap()
@@ -170,13 +373,122 @@ pub fn sync_all_accounts(
         }
     }
 
+    Ok(caught_up)
+}
+
+/// Check that each account's next block index agrees with the txos stored for
+/// it and with the ledger, and resync any account where it does not. This runs
+/// when the sync thread starts, since a crash or a restored database or ledger
+/// can leave them out of step.
+///
+/// An account is out of step if it has txos received or spent at or after its
+/// next block index, or if its next block index is past the end of the ledger.
+/// Txos found by `sync_account_block_range` ahead of the next block index are
+/// expected, so blocks up to the account's scanned block index do not count,
+/// as long as they are still in the ledger.
+///
+/// Returns the ids of the accounts that were reset to resync.
+pub fn check_sync_consistency(
+    ledger_db: &LedgerDB,
+    conn: Conn,
+    logger: &Logger,
+) -> Result<Vec<AccountID>, SyncError> {
+    let num_blocks = ledger_db.num_blocks()?;
+
+    let mut resynced = Vec::new();
+    for account in Account::list_all(conn, None, None)? {
+        let next_block_index = account.next_block_index as u64;
+        let scanned_block_index = account
+            .scanned_block_index
+            .map_or(0, |i| i as u64)
+            .min(num_blocks);
+        let max_txo_block_index = Txo::max_block_index_for_account(&account.id, conn)?;
+
+        let txos_after_next_block =
+            max_txo_block_index.map_or(false, |i| i >= next_block_index.max(scanned_block_index));
+        if !txos_after_next_block && next_block_index <= num_blocks {
+            continue;
+        }
+
+        log::warn!(
+            logger,
+            "Account {} has next block index {} but txos up to block {:?} and a ledger of {} \
+             blocks, resyncing",
+            account.id,
+            next_block_index,
+            max_txo_block_index,
+            num_blocks,
+        );
+        account.update_next_block_index(account.first_block_index as u64, conn)?;
+        resynced.push(AccountID(account.id));
+    }
+
+    Ok(resynced)
+}
+
+/// Notify the progress listeners, if there are any, of how far the account has
+/// synced.
+fn report_sync_progress(
+    progress_listeners: &SyncProgressListeners,
+    account_id_hex: &str,
+    total_blocks: u64,
+    conn: Conn,
+) -> Result<(), SyncError> {
+    let progress_listeners = progress_listeners.lock().unwrap();
+    if progress_listeners.is_empty() {
+        return Ok(());
+    }
+
+    let account = Account::get(&AccountID(account_id_hex.to_string()), conn)?;
+    let progress = SyncProgress {
+        account_id: AccountID(account.id),
+        blocks_synced: account.next_block_index as u64,
+        total_blocks,
+    };
+    for listener in progress_listeners.iter() {
+        listener(&progress);
+    }
+
+    Ok(())
+}
+
+/// Store the block timestamps of received txos and finalized transaction logs
+/// that do not have one yet. Blocks the watcher has no timestamp for yet are
+/// tried again on a later call, which the sync thread makes once new blocks are
+/// appended to the ledger.
+pub fn sync_block_timestamps(watcher_db: &WatcherDB, conn: Conn) -> Result<(), SyncError> {
+    let received_block_indices = Txo::list_received_block_indices_without_timestamp(conn)?;
+    let finalized_block_indices =
+        TransactionLog::list_finalized_block_indices_without_timestamp(conn)?;
+
+    for block_index in received_block_indices
+        .iter()
+        .chain(finalized_block_indices.iter())
+        .copied()
+        .collect::<BTreeSet<u64>>()
+    {
+        let (timestamp, result_code) = watcher_db.get_block_timestamp(block_index)?;
+        if result_code == TimestampResultCode::TimestampFound {
+            Txo::update_received_timestamp(block_index, timestamp, conn)?;
+            TransactionLog::update_finalized_timestamp(block_index, timestamp, conn)?;
+        }
+    }
+
     Ok(())
 }
 
+/// Sync the next `blocks_chunk_size` blocks for the account, returning the
+/// number of txos received in them.
+///
+/// `stop_requested` is checked after each block is loaded. Once it is set, the
+/// rest of the chunk is skipped and the account's next block index only moves
+/// past the blocks that were loaded, which are still fully processed.
 pub fn sync_account_next_chunk(
     ledger_db: &LedgerDB,
     conn: Conn,
     account_id_hex: &str,
+    blocks_chunk_size: u64,
+    stop_requested: &AtomicBool,
     logger: &Logger,
 ) -> Result<usize, SyncError> {
     exclusive_transaction(conn, |conn| {
@@ -186,221 +498,280 @@ pub fn sync_account_next_chunk(
 
         let start_time = Instant::now();
         let start_block_index = account.next_block_index as u64;
-        let mut end_block_index: Option<u64> = None;
-
-        // Load transaction outputs and key images for this chunk.
-        let mut tx_outs: Vec<(u64, TxOut)> = Vec::new();
-        let mut key_images: Vec<(u64, KeyImage)> = Vec::new();
-
-        let start = account.next_block_index as u64;
-        let end = start + BLOCKS_CHUNK_SIZE;
-        for block_index in start..end {
-            let block_contents = match ledger_db.get_block_contents(block_index) {
-                Ok(block_contents) => block_contents,
-                Err(mc_ledger_db::Error::NotFound) => {
-                    break;
-                }
-                Err(err) => {
-                    return Err(err.into());
-                }
-            };
-            end_block_index = Some(block_index);
-
-            for tx_out in block_contents.outputs {
-                tx_outs.push((block_index, tx_out));
-            }
 
-            for key_image in block_contents.key_images {
-                key_images.push((block_index, key_image));
-            }
-        }
+        let scan = match scan_block_range(
+            ledger_db,
+            &account,
+            start_block_index,
+            start_block_index + blocks_chunk_size,
+            Some(stop_requested),
+            conn,
+        )? {
+            Some(scan) => scan,
+            // If no blocks were found, exit.
+            None => return Ok(0),
+        };
 
-        // If no blocks were found, exit.
-        if end_block_index.is_none() {
-            return Ok(0);
-        }
-        let end_block_index = end_block_index.unwrap();
+        TransactionLog::update_pending_exceeding_tombstone_block_index_to_failed(
+            scan.end_block_index + 1,
+            conn,
+        )?;
 
-        let num_received_txos = if account.view_only {
-            let view_account_key: ViewAccountKey = mc_util_serial::decode(&account.account_key)?;
+        // Done syncing this chunk. Mark these blocks as synced for this account.
+        account.update_next_block_index(scan.end_block_index + 1, conn)?;
 
-            // Attempt to decode each transaction as received by this account.
-            let received_txos: Vec<_> = tx_outs
-                .into_par_iter()
-                .filter_map(|(block_index, tx_out)| {
-                    let amount = decode_amount(&tx_out, view_account_key.view_private_key())?;
-                    Some((block_index, tx_out, amount))
-                })
-                .collect();
-
-            let mut received_txos_with_subaddresses = Vec::new();
-            for (block_index, tx_out, amount) in received_txos {
-                let subaddress_index =
-                    decode_subaddress_index(&tx_out, view_account_key.view_private_key(), conn);
-
-                received_txos_with_subaddresses.push((
-                    block_index,
-                    tx_out,
-                    amount,
-                    subaddress_index,
-                ));
-            }
+        let num_blocks_synced = scan.end_block_index - start_block_index + 1;
 
-            let num_received_txos = received_txos_with_subaddresses.len();
-
-            // Write received transactions to the database.
-            for (block_index, tx_out, amount, subaddress_index) in received_txos_with_subaddresses {
-                Txo::create_received(
-                    tx_out.clone(),
-                    subaddress_index,
-                    None,
-                    amount,
-                    block_index,
-                    account_id_hex,
-                    conn,
-                )?;
-            }
+        let duration = start_time.elapsed();
 
-            // Match key images to mark existing unspent transactions as spent.
-            let unspent_key_images: MCHashMap<KeyImage, String> =
-                Txo::list_unspent_or_pending_key_images(account_id_hex, None, conn)?;
-            let spent_txos: Vec<(u64, String)> = key_images
-                .into_par_iter()
-                .filter_map(|(block_index, key_image)| {
-                    unspent_key_images
-                        .get(&key_image)
-                        .map(|txo_id_hex| (block_index, txo_id_hex.clone()))
-                })
-                .collect();
-            let num_spent_txos = spent_txos.len();
-
-            for (block_index, txo_id_hex) in &spent_txos {
-                Txo::update_spent_block_index(txo_id_hex, *block_index, conn)?;
-                TransactionLog::update_pending_associated_with_txo_to_succeeded(
-                    txo_id_hex,
-                    *block_index,
-                    conn,
-                )?;
-            }
+        log::debug!(
+            logger,
+            "Synced {} blocks ({}-{}) for account {} in {:?}. {} txos received, {}/{} txos spent.",
+            num_blocks_synced,
+            start_block_index,
+            scan.end_block_index,
+            account_id_hex.chars().take(6).collect::<String>(),
+            duration,
+            scan.num_received_txos,
+            scan.num_spent_txos,
+            scan.num_unspent_key_images,
+        );
 
-            TransactionLog::update_pending_exceeding_tombstone_block_index_to_failed(
-                end_block_index + 1,
-                conn,
-            )?;
+        Ok(scan.num_received_txos)
+    })
+}
 
-            // Done syncing this chunk. Mark these blocks as synced for this account.
-            account.update_next_block_index(end_block_index + 1, conn)?;
+/// Scan the blocks from `start_block_index` up to but not including
+/// `end_block_index` for the account, recording the txos it received and
+/// marking its txos spent in them, without moving its next block index. This
+/// lets a known range be recovered ahead of the full sync.
+///
+/// Txos found here are updated rather than inserted again when a later sync
+/// covers the same blocks. The end of the range is stored as the account's
+/// scanned block index, so that `check_sync_consistency` does not mistake the
+/// txos past its next block index for an out of step account.
+///
+/// Returns the number of txos received in the range.
+pub fn sync_account_block_range(
+    ledger_db: &LedgerDB,
+    conn: Conn,
+    account_id_hex: &str,
+    start_block_index: u64,
+    end_block_index: u64,
+    logger: &Logger,
+) -> Result<usize, SyncError> {
+    exclusive_transaction(conn, |conn| {
+        let account = Account::get(&AccountID(account_id_hex.to_string()), conn)?;
 
-            let num_blocks_synced = end_block_index - start_block_index + 1;
+        let start_time = Instant::now();
 
-            let duration = start_time.elapsed();
+        let scan = match scan_block_range(
+            ledger_db,
+            &account,
+            start_block_index,
+            end_block_index,
+            None,
+            conn,
+        )? {
+            Some(scan) => scan,
+            None => return Ok(0),
+        };
+
+        let scanned_block_index = scan.end_block_index + 1;
+        if account
+            .scanned_block_index
+            .map_or(true, |i| scanned_block_index > i as u64)
+        {
+            account.update_scanned_block_index(scanned_block_index, conn)?;
+        }
 
-            log::debug!(
+        log::debug!(
             logger,
-            "Synced {} blocks ({}-{}) for account {} in {:?}. {} txos received, {}/{} txos spent.",
-            num_blocks_synced,
+            "Scanned blocks {}-{} for account {} in {:?}. {} txos received, {}/{} txos spent.",
             start_block_index,
-            end_block_index,
+            scan.end_block_index,
             account_id_hex.chars().take(6).collect::<String>(),
-            duration,
-            num_received_txos,
-            num_spent_txos,
-            unspent_key_images.len()
+            start_time.elapsed(),
+            scan.num_received_txos,
+            scan.num_spent_txos,
+            scan.num_unspent_key_images,
         );
-            num_received_txos
-        } else {
-            let account_key: AccountKey = mc_util_serial::decode(&account.account_key)?;
-
-            // Attempt to decode each transaction as received by this account.
-            let received_txos: Vec<_> = tx_outs
-                .into_par_iter()
-                .filter_map(|(block_index, tx_out)| {
-                    let amount = decode_amount(&tx_out, account_key.view_private_key())?;
-                    Some((block_index, tx_out, amount))
-                })
-                .collect();
-
-            let mut received_txos_with_subaddresses_and_key_images = Vec::new();
-            for (block_index, tx_out, amount) in received_txos {
-                let (subaddress_index, key_image) =
-                    decode_subaddress_and_key_image(&tx_out, &account_key, conn);
-
-                received_txos_with_subaddresses_and_key_images.push((
-                    block_index,
-                    tx_out,
-                    amount,
-                    subaddress_index,
-                    key_image,
-                ));
-            }
 
-            let num_received_txos = received_txos_with_subaddresses_and_key_images.len();
-
-            // Write received transactions to the database.
-            for (block_index, tx_out, amount, subaddress_index, key_image) in
-                received_txos_with_subaddresses_and_key_images
-            {
-                Txo::create_received(
-                    tx_out.clone(),
-                    subaddress_index,
-                    key_image,
-                    amount,
-                    block_index,
-                    account_id_hex,
-                    conn,
-                )?;
-            }
+        Ok(scan.num_received_txos)
+    })
+}
 
-            // Match key images to mark existing unspent transactions as spent.
-            let unspent_key_images: MCHashMap<KeyImage, String> =
-                Txo::list_unspent_or_pending_key_images(account_id_hex, None, conn)?;
-            let spent_txos: Vec<(u64, String)> = key_images
-                .into_par_iter()
-                .filter_map(|(block_index, key_image)| {
-                    unspent_key_images
-                        .get(&key_image)
-                        .map(|txo_id_hex| (block_index, txo_id_hex.clone()))
-                })
-                .collect();
-            let num_spent_txos = spent_txos.len();
-            for (block_index, txo_id_hex) in &spent_txos {
-                Txo::update_spent_block_index(txo_id_hex, *block_index, conn)?;
-                TransactionLog::update_pending_associated_with_txo_to_succeeded(
-                    txo_id_hex,
-                    *block_index,
-                    conn,
-                )?;
+/// What scanning a range of blocks for an account found.
+struct BlockRangeScan {
+    /// The index of the last block in the range that was in the ledger.
+    end_block_index: u64,
+    num_received_txos: usize,
+    num_spent_txos: usize,
+    num_unspent_key_images: usize,
+}
+
+/// Decode the txos and key images in the blocks from `start_block_index` up to
+/// but not including `end_block_index`, writing the account's received and
+/// spent txos to the database. Loading stops early after any block once
+/// `stop_requested` is set. Returns None if the ledger has none of these
+/// blocks.
+fn scan_block_range(
+    ledger_db: &LedgerDB,
+    account: &Account,
+    start_block_index: u64,
+    end_block_index: u64,
+    stop_requested: Option<&AtomicBool>,
+    conn: Conn,
+) -> Result<Option<BlockRangeScan>, SyncError> {
+    let account_id_hex = account.id.as_str();
+    let mut last_block_index: Option<u64> = None;
+
+    // Load transaction outputs and key images for this range.
+    let mut tx_outs: Vec<(u64, TxOut)> = Vec::new();
+    let mut key_images: Vec<(u64, KeyImage)> = Vec::new();
+
+    for block_index in start_block_index..end_block_index {
+        let block_contents = match ledger_db.get_block_contents(block_index) {
+            Ok(block_contents) => block_contents,
+            Err(mc_ledger_db::Error::NotFound) => {
+                break;
+            }
+            Err(err) => {
+                return Err(err.into());
             }
+        };
+        last_block_index = Some(block_index);
+
+        for tx_out in block_contents.outputs {
+            tx_outs.push((block_index, tx_out));
+        }
 
-            TransactionLog::update_pending_exceeding_tombstone_block_index_to_failed(
-                end_block_index + 1,
+        for key_image in block_contents.key_images {
+            key_images.push((block_index, key_image));
+        }
+
+        if stop_requested.map_or(false, |stop_requested| {
+            stop_requested.load(Ordering::SeqCst)
+        }) {
+            break;
+        }
+    }
+
+    let end_block_index = match last_block_index {
+        Some(block_index) => block_index,
+        None => return Ok(None),
+    };
+
+    let num_received_txos = if account.view_only {
+        let view_account_key: ViewAccountKey = mc_util_serial::decode(&account.account_key)?;
+
+        // Attempt to decode each transaction as received by this account.
+        let received_txos: Vec<_> = tx_outs
+            .into_par_iter()
+            .filter_map(|(block_index, tx_out)| {
+                let amount = decode_amount(&tx_out, view_account_key.view_private_key())?;
+                Some((block_index, tx_out, amount))
+            })
+            .collect();
+
+        let mut received_txos_with_subaddresses = Vec::new();
+        for (block_index, tx_out, amount) in received_txos {
+            let subaddress_index =
+                decode_subaddress_index(&tx_out, view_account_key.view_private_key(), conn);
+
+            received_txos_with_subaddresses.push((block_index, tx_out, amount, subaddress_index));
+        }
+
+        let num_received_txos = received_txos_with_subaddresses.len();
+
+        // Write received transactions to the database.
+        for (block_index, tx_out, amount, subaddress_index) in received_txos_with_subaddresses {
+            Txo::create_received(
+                tx_out.clone(),
+                subaddress_index,
+                None,
+                amount,
+                block_index,
+                account_id_hex,
                 conn,
             )?;
+        }
 
-            // Done syncing this chunk. Mark these blocks as synced for this account.
-            account.update_next_block_index(end_block_index + 1, conn)?;
+        num_received_txos
+    } else {
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key)?;
+
+        // Attempt to decode each transaction as received by this account.
+        let received_txos: Vec<_> = tx_outs
+            .into_par_iter()
+            .filter_map(|(block_index, tx_out)| {
+                let amount = decode_amount(&tx_out, account_key.view_private_key())?;
+                Some((block_index, tx_out, amount))
+            })
+            .collect();
+
+        let mut received_txos_with_subaddresses_and_key_images = Vec::new();
+        for (block_index, tx_out, amount) in received_txos {
+            let (subaddress_index, key_image) =
+                decode_subaddress_and_key_image(&tx_out, &account_key, conn);
+
+            received_txos_with_subaddresses_and_key_images.push((
+                block_index,
+                tx_out,
+                amount,
+                subaddress_index,
+                key_image,
+            ));
+        }
 
-            let num_blocks_synced = end_block_index - start_block_index + 1;
+        let num_received_txos = received_txos_with_subaddresses_and_key_images.len();
+
+        // Write received transactions to the database.
+        for (block_index, tx_out, amount, subaddress_index, key_image) in
+            received_txos_with_subaddresses_and_key_images
+        {
+            Txo::create_received(
+                tx_out.clone(),
+                subaddress_index,
+                key_image,
+                amount,
+                block_index,
+                account_id_hex,
+                conn,
+            )?;
+        }
 
-            let duration = start_time.elapsed();
+        num_received_txos
+    };
 
-            log::debug!(
-            logger,
-            "Synced {} blocks ({}-{}) for account {} in {:?}. {} txos received, {}/{} txos spent.",
-            num_blocks_synced,
-            start_block_index,
-            end_block_index,
-            account_id_hex.chars().take(6).collect::<String>(),
-            duration,
-            num_received_txos,
-            num_spent_txos,
-            unspent_key_images.len()
-        );
-            num_received_txos
-        };
+    // Match key images to mark existing unspent transactions as spent.
+    let unspent_key_images: MCHashMap<KeyImage, String> =
+        Txo::list_unspent_or_pending_key_images(account_id_hex, None, conn)?;
+    let spent_txos: Vec<(u64, String)> = key_images
+        .into_par_iter()
+        .filter_map(|(block_index, key_image)| {
+            unspent_key_images
+                .get(&key_image)
+                .map(|txo_id_hex| (block_index, txo_id_hex.clone()))
+        })
+        .collect();
+    let num_spent_txos = spent_txos.len();
+
+    for (block_index, txo_id_hex) in &spent_txos {
+        Txo::update_spent_block_index(txo_id_hex, *block_index, conn)?;
+        TransactionLog::update_pending_associated_with_txo_to_succeeded(
+            txo_id_hex,
+            *block_index,
+            conn,
+        )?;
+    }
 
-        Ok(num_received_txos)
-    })
+    Ok(Some(BlockRangeScan {
+        end_block_index,
+        num_received_txos,
+        num_spent_txos,
+        num_unspent_key_images: unspent_key_images.len(),
+    }))
 }
 
 /// Attempt to decode the transaction amount. If we can't, then this transaction
@@ -473,17 +844,24 @@ pub fn decode_subaddress_and_key_image(
 mod tests {
     use super::*;
     use crate::{
+        db::{models::NewTransactionLog, schema::transaction_logs, transaction_log::TransactionId},
         service::{account::AccountService, balance::BalanceService, txo::TxoService},
         test_utils::{
-            add_block_to_ledger_db, get_test_ledger, manually_sync_account, setup_wallet_service,
-            MOB,
+            add_block_to_ledger_db, get_test_ledger, manually_sync_account,
+            random_account_with_seed_values, setup_wallet_service, WalletDbTestContext, MOB,
         },
     };
+    use diesel::RunQueryDsl;
     use mc_account_keys::{AccountKey, RootEntropy, RootIdentity};
+    use mc_blockchain_types::BlockSignature;
     use mc_common::logger::{test_with_logger, Logger};
+    use mc_crypto_keys::Ed25519Pair;
     use mc_transaction_core::{tokens::Mob, Token};
     use mc_util_from_random::FromRandom;
-    use rand::{rngs::StdRng, SeedableRng};
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+    use std::ops::DerefMut;
+    use tempdir::TempDir;
+    use url::Url;
 
     #[test_with_logger]
     fn test_process_txo_bigint_in_origin(logger: Logger) {
@@ -558,6 +936,8 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .unwrap();
 
@@ -572,4 +952,729 @@ mod tests {
         let balance_pmob = balance.get(&Mob::ID).unwrap();
         assert_eq!(balance_pmob.unspent, 250_000_000 * MOB as u128);
     }
+
+    #[test_with_logger]
+    fn test_sync_view_only_account_records_txos_without_key_images(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let mut ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        let wallet_db = &service.wallet_db.as_ref().unwrap();
+
+        let account_key = AccountKey::random(&mut rng);
+        let view_account_key = ViewAccountKey::from(&account_key);
+
+        let account = service
+            .import_view_only_account(
+                &(*view_account_key.view_private_key()).into(),
+                &(*view_account_key.spend_public_key()).into(),
+                None,
+                None,
+                None,
+                false,
+            )
+            .unwrap();
+        let account_id = AccountID(account.id);
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &[view_account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &[view_account_key.default_subaddress()],
+            50 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        manually_sync_account(&ledger_db, wallet_db, &account_id, &logger);
+
+        let txos = Txo::list_for_account(
+            &account_id.to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            wallet_db.get_pooled_conn().unwrap().deref_mut(),
+        )
+        .unwrap();
+
+        // Both txos are found, but the view key alone cannot derive their key
+        // images, so neither can be matched against the spent key images.
+        assert_eq!(txos.len(), 2);
+        for txo in &txos {
+            assert_eq!(txo.key_image, None);
+            assert_eq!(txo.spent_block_index, None);
+            assert_eq!(txo.subaddress_index, Some(0));
+        }
+
+        let balance = service.get_balance_for_account(&account_id).unwrap();
+        let balance_pmob = balance.get(&Mob::ID).unwrap();
+        assert_eq!(balance_pmob.unverified, 150 * MOB as u128);
+        assert_eq!(balance_pmob.spent, 0);
+    }
+
+    #[test_with_logger]
+    fn test_sync_spends_only_own_txos_for_shared_key_image_block(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let mut ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        let wallet_db = &service.wallet_db.as_ref().unwrap();
+
+        let mut account_ids = Vec::new();
+        let mut public_addresses = Vec::new();
+        for name in ["Alice", "Bob"] {
+            let account = service
+                .create_account(
+                    Some(name.to_string()),
+                    "".to_string(),
+                    "".to_string(),
+                    false,
+                )
+                .unwrap();
+            let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+            public_addresses.push(account_key.default_subaddress());
+            account_ids.push(AccountID(account.id));
+        }
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &public_addresses,
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        for account_id in &account_ids {
+            manually_sync_account(&ledger_db, wallet_db, account_id, &logger);
+        }
+
+        // Spend both accounts' txos in the same block.
+        let mut key_images = Vec::new();
+        for account_id in &account_ids {
+            let txos = Txo::list_for_account(
+                &account_id.to_string(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                wallet_db.get_pooled_conn().unwrap().deref_mut(),
+            )
+            .unwrap();
+            assert_eq!(txos.len(), 1);
+            let key_image: KeyImage =
+                mc_util_serial::decode(txos[0].key_image.as_ref().unwrap()).unwrap();
+            key_images.push(key_image);
+        }
+        let spent_block_index = add_block_to_ledger_db(
+            &mut ledger_db,
+            &[AccountKey::random(&mut rng).default_subaddress()],
+            100 * MOB,
+            &key_images,
+            &mut rng,
+        );
+
+        // Each account only marks its own txo as spent.
+        for account_id in &account_ids {
+            manually_sync_account(&ledger_db, wallet_db, account_id, &logger);
+
+            let txos = Txo::list_for_account(
+                &account_id.to_string(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                wallet_db.get_pooled_conn().unwrap().deref_mut(),
+            )
+            .unwrap();
+            assert_eq!(txos.len(), 1);
+            assert_eq!(txos[0].spent_block_index, Some(spent_block_index as i64));
+
+            let balance = service.get_balance_for_account(account_id).unwrap();
+            let balance_pmob = balance.get(&Mob::ID).unwrap();
+            assert_eq!(balance_pmob.unspent, 0);
+            assert_eq!(balance_pmob.spent, 100 * MOB as u128);
+        }
+    }
+
+    #[test_with_logger]
+    fn test_webhook_disabled_account_not_tracked(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+
+        let enabled_root_id = RootIdentity::from_random(&mut rng);
+        let disabled_root_id = RootIdentity::from_random(&mut rng);
+        let recipients = [
+            AccountKey::from(&enabled_root_id).default_subaddress(),
+            AccountKey::from(&disabled_root_id).default_subaddress(),
+        ];
+        let ledger_db = get_test_ledger(1, &recipients, 5, &mut rng);
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+        let mut account_ids = Vec::new();
+        for (name, root_id) in [("Hot", &enabled_root_id), ("Cold", &disabled_root_id)] {
+            let (account_id, _) = Account::create_from_root_entropy(
+                &root_id.root_entropy,
+                Some(0),
+                None,
+                None,
+                name,
+                "".to_string(),
+                "".to_string(),
+                false,
+                conn,
+            )
+            .unwrap();
+            account_ids.push(account_id);
+        }
+
+        // Accounts send webhooks by default
+        let cold_account = Account::get(&account_ids[1], conn).unwrap();
+        assert!(cold_account.webhook_enabled);
+        cold_account.update_webhook_enabled(false, conn).unwrap();
+        assert!(!Account::get(&account_ids[1], conn).unwrap().webhook_enabled);
+
+        let accounts_with_deposits = Arc::new(Mutex::new(HashMap::new()));
+        for _ in 0..2 {
+            sync_all_accounts(
+                &ledger_db,
+                conn,
+                accounts_with_deposits.clone(),
+                &SyncProgressListeners::default(),
+                DEFAULT_BLOCKS_CHUNK_SIZE,
+                &AtomicBool::new(false),
+                &logger,
+            )
+            .unwrap();
+        }
+
+        // Only the enabled account is marked for the webhook once synced
+        let accounts_with_deposits = accounts_with_deposits.lock().unwrap();
+        assert_eq!(accounts_with_deposits.get(&account_ids[0]), Some(&true));
+        assert!(!accounts_with_deposits.contains_key(&account_ids[1]));
+    }
+
+    #[test_with_logger]
+    fn test_sync_with_small_chunk_size(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+
+        let root_id = RootIdentity::from_random(&mut rng);
+        let account_key = AccountKey::from(&root_id);
+        let ledger_db = get_test_ledger(1, &[account_key.default_subaddress()], 5, &mut rng);
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+        let (account_id, _) = Account::create_from_root_entropy(
+            &root_id.root_entropy,
+            Some(0),
+            None,
+            None,
+            "Alice",
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+
+        // Each pass scans at most two blocks
+        let mut next_block_indices = Vec::new();
+        for _ in 0..4 {
+            sync_account_next_chunk(
+                &ledger_db,
+                conn,
+                &account_id.to_string(),
+                2,
+                &AtomicBool::new(false),
+                &logger,
+            )
+            .unwrap();
+            let account = Account::get(&account_id, conn).unwrap();
+            next_block_indices.push(account.next_block_index as u64);
+        }
+        assert_eq!(next_block_indices, vec![2, 4, 5, 5]);
+
+        let txos = Txo::list_for_account(
+            &account_id.to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            conn,
+        )
+        .unwrap();
+        assert_eq!(txos.len(), 5);
+    }
+
+    #[test_with_logger]
+    fn test_sync_stops_mid_chunk_when_requested(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+
+        let root_id = RootIdentity::from_random(&mut rng);
+        let account_key = AccountKey::from(&root_id);
+        let ledger_db = get_test_ledger(1, &[account_key.default_subaddress()], 5, &mut rng);
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+        let (account_id, _) = Account::create_from_root_entropy(
+            &root_id.root_entropy,
+            Some(0),
+            None,
+            None,
+            "Alice",
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+
+        // With a stop requested, the chunk ends after the first block, which is
+        // still recorded
+        let stop_requested = AtomicBool::new(true);
+        let num_received = sync_account_next_chunk(
+            &ledger_db,
+            conn,
+            &account_id.to_string(),
+            10,
+            &stop_requested,
+            &logger,
+        )
+        .unwrap();
+        assert_eq!(num_received, 1);
+        let account = Account::get(&account_id, conn).unwrap();
+        assert_eq!(account.next_block_index, 1);
+
+        // Syncing all accounts does not start another chunk
+        let caught_up = sync_all_accounts(
+            &ledger_db,
+            conn,
+            Arc::new(Mutex::new(HashMap::new())),
+            &SyncProgressListeners::default(),
+            10,
+            &stop_requested,
+            &logger,
+        )
+        .unwrap();
+        assert!(!caught_up);
+        let account = Account::get(&account_id, conn).unwrap();
+        assert_eq!(account.next_block_index, 1);
+
+        // Once the stop is cleared, the rest of the blocks are synced
+        stop_requested.store(false, Ordering::SeqCst);
+        sync_account_next_chunk(
+            &ledger_db,
+            conn,
+            &account_id.to_string(),
+            10,
+            &stop_requested,
+            &logger,
+        )
+        .unwrap();
+        let account = Account::get(&account_id, conn).unwrap();
+        assert_eq!(account.next_block_index, 5);
+
+        let txos = Txo::list_for_account(
+            &account_id.to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            conn,
+        )
+        .unwrap();
+        assert_eq!(txos.len(), 5);
+    }
+
+    #[test_with_logger]
+    fn test_sync_account_block_range(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+
+        let root_id = RootIdentity::from_random(&mut rng);
+        let account_key = AccountKey::from(&root_id);
+        let ledger_db = get_test_ledger(1, &[account_key.default_subaddress()], 5, &mut rng);
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+        let (account_id, _) = Account::create_from_root_entropy(
+            &root_id.root_entropy,
+            Some(0),
+            None,
+            None,
+            "Alice",
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+
+        let list_txos = |conn: Conn| {
+            Txo::list_for_account(
+                &account_id.to_string(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                conn,
+            )
+            .unwrap()
+        };
+
+        // Scanning blocks 2 and 3 finds the txos received in them, without
+        // moving the account's next block index
+        let num_received =
+            sync_account_block_range(&ledger_db, conn, &account_id.to_string(), 2, 4, &logger)
+                .unwrap();
+        assert_eq!(num_received, 2);
+        let mut received_block_indices: Vec<_> = list_txos(conn)
+            .iter()
+            .map(|txo| txo.received_block_index)
+            .collect();
+        received_block_indices.sort();
+        assert_eq!(received_block_indices, vec![Some(2), Some(3)]);
+        let account = Account::get(&account_id, conn).unwrap();
+        assert_eq!(account.next_block_index, 0);
+        assert_eq!(account.scanned_block_index, Some(4));
+
+        // The txos ahead of the next block index were found by the range scan,
+        // so the account is not reset
+        assert!(check_sync_consistency(&ledger_db, conn, &logger)
+            .unwrap()
+            .is_empty());
+
+        // Scanning an earlier range does not move the scanned block index back
+        sync_account_block_range(&ledger_db, conn, &account_id.to_string(), 0, 1, &logger).unwrap();
+        let account = Account::get(&account_id, conn).unwrap();
+        assert_eq!(account.scanned_block_index, Some(4));
+
+        // A range past the end of the ledger finds nothing
+        let num_received =
+            sync_account_block_range(&ledger_db, conn, &account_id.to_string(), 5, 10, &logger)
+                .unwrap();
+        assert_eq!(num_received, 0);
+
+        // A full sync over the same blocks does not insert the txos again
+        sync_account_next_chunk(
+            &ledger_db,
+            conn,
+            &account_id.to_string(),
+            10,
+            &AtomicBool::new(false),
+            &logger,
+        )
+        .unwrap();
+        let account = Account::get(&account_id, conn).unwrap();
+        assert_eq!(account.next_block_index, 5);
+        assert_eq!(list_txos(conn).len(), 5);
+    }
+
+    #[test_with_logger]
+    fn test_check_sync_consistency(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+
+        let root_id = RootIdentity::from_random(&mut rng);
+        let account_key = AccountKey::from(&root_id);
+        let ledger_db = get_test_ledger(1, &[account_key.default_subaddress()], 5, &mut rng);
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+        let (account_id, _) = Account::create_from_root_entropy(
+            &root_id.root_entropy,
+            Some(0),
+            None,
+            None,
+            "Alice",
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+
+        sync_account_next_chunk(
+            &ledger_db,
+            conn,
+            &account_id.to_string(),
+            10,
+            &AtomicBool::new(false),
+            &logger,
+        )
+        .unwrap();
+        let account = Account::get(&account_id, conn).unwrap();
+        assert_eq!(account.next_block_index, 5);
+        assert!(check_sync_consistency(&ledger_db, conn, &logger)
+            .unwrap()
+            .is_empty());
+
+        // Move the account back behind txos it has already received
+        account.update_next_block_index(2, conn).unwrap();
+        assert_eq!(
+            check_sync_consistency(&ledger_db, conn, &logger).unwrap(),
+            vec![account_id.clone()]
+        );
+        let account = Account::get(&account_id, conn).unwrap();
+        assert_eq!(account.next_block_index, 0);
+
+        // Move the account past the end of the ledger
+        sync_account_next_chunk(
+            &ledger_db,
+            conn,
+            &account_id.to_string(),
+            10,
+            &AtomicBool::new(false),
+            &logger,
+        )
+        .unwrap();
+        let account = Account::get(&account_id, conn).unwrap();
+        account.update_next_block_index(9, conn).unwrap();
+        assert_eq!(
+            check_sync_consistency(&ledger_db, conn, &logger).unwrap(),
+            vec![account_id.clone()]
+        );
+        let account = Account::get(&account_id, conn).unwrap();
+        assert_eq!(account.next_block_index, 0);
+    }
+
+    #[test_with_logger]
+    fn test_sync_progress_reported_per_chunk(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+        let num_blocks = 2 * DEFAULT_BLOCKS_CHUNK_SIZE + 12;
+        let ledger_db = get_test_ledger(1, &[], num_blocks as usize, &mut rng);
+
+        let root_id = RootIdentity::from_random(&mut rng);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+        let (account_id, _) = Account::create_from_root_entropy(
+            &root_id.root_entropy,
+            Some(0),
+            None,
+            None,
+            "Alice",
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let progress_listeners = SyncProgressListeners::default();
+        let thread_reported = reported.clone();
+        progress_listeners
+            .lock()
+            .unwrap()
+            .push(Box::new(move |progress: &SyncProgress| {
+                thread_reported.lock().unwrap().push(progress.clone())
+            }));
+
+        let accounts_with_deposits = Arc::new(Mutex::new(HashMap::new()));
+        for _ in 0..4 {
+            sync_all_accounts(
+                &ledger_db,
+                conn,
+                accounts_with_deposits.clone(),
+                &progress_listeners,
+                DEFAULT_BLOCKS_CHUNK_SIZE,
+                &AtomicBool::new(false),
+                &logger,
+            )
+            .unwrap();
+        }
+
+        // One report per chunk, and none once the account is synced
+        let blocks_synced: Vec<u64> = reported
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|progress| {
+                assert_eq!(progress.account_id, account_id);
+                assert_eq!(progress.total_blocks, num_blocks);
+                progress.blocks_synced
+            })
+            .collect();
+        assert_eq!(
+            blocks_synced,
+            vec![
+                DEFAULT_BLOCKS_CHUNK_SIZE,
+                2 * DEFAULT_BLOCKS_CHUNK_SIZE,
+                num_blocks,
+            ]
+        );
+    }
+
+    #[test_with_logger]
+    fn test_sync_block_timestamps(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+        let mut ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+
+        let account_key = random_account_with_seed_values(
+            &wallet_db,
+            &mut ledger_db,
+            &[100 * MOB],
+            &mut rng,
+            &logger,
+        );
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+        assert_eq!(
+            Txo::list_received_block_indices_without_timestamp(conn).unwrap(),
+            vec![12]
+        );
+
+        // A transaction log finalized in the same block.
+        diesel::insert_into(transaction_logs::table)
+            .values(&NewTransactionLog {
+                id: "tx_log_id",
+                account_id: &AccountID::from(&account_key).to_string(),
+                fee_value: Mob::MINIMUM_FEE as i64,
+                fee_token_id: *Mob::ID as i64,
+                submitted_block_index: Some(11),
+                tombstone_block_index: Some(20),
+                finalized_block_index: Some(12),
+                comment: "",
+                tx: &[],
+                failed: false,
+                created_at: 0,
+                updated_at: 0,
+                idempotency_key: None,
+            })
+            .execute(conn)
+            .unwrap();
+        assert_eq!(
+            TransactionLog::list_finalized_block_indices_without_timestamp(conn).unwrap(),
+            vec![12]
+        );
+
+        let url = Url::parse("http://www.my_url1.com").unwrap();
+        let watcher_db_tmp = TempDir::new("watcher").unwrap();
+        WatcherDB::create(watcher_db_tmp.path()).unwrap();
+        let watcher_db =
+            WatcherDB::open_rw(watcher_db_tmp.path(), &[url.clone()], logger.clone()).unwrap();
+
+        // The watcher has not seen the block yet, so it is left for a later pass
+        sync_block_timestamps(&watcher_db, conn).unwrap();
+        assert_eq!(
+            Txo::list_received_block_indices_without_timestamp(conn).unwrap(),
+            vec![12]
+        );
+
+        let block = ledger_db.get_block(12).unwrap();
+        let mut signature =
+            BlockSignature::from_block_and_keypair(&block, &Ed25519Pair::from_random(&mut rng))
+                .unwrap();
+        signature.set_signed_at(1_700_000_000);
+        watcher_db
+            .add_block_signature(&url, 12, signature, "00/00".to_string())
+            .unwrap();
+
+        sync_block_timestamps(&watcher_db, conn).unwrap();
+        assert!(Txo::list_received_block_indices_without_timestamp(conn)
+            .unwrap()
+            .is_empty());
+        assert!(
+            TransactionLog::list_finalized_block_indices_without_timestamp(conn)
+                .unwrap()
+                .is_empty()
+        );
+
+        let txos = Txo::list_for_account(
+            &AccountID::from(&account_key).to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(0),
+            conn,
+        )
+        .unwrap();
+        assert_eq!(txos.len(), 1);
+        assert_eq!(txos[0].received_timestamp, Some(1_700_000_000));
+
+        let tx_log = TransactionLog::get(&TransactionId("tx_log_id".to_string()), conn).unwrap();
+        assert_eq!(tx_log.finalized_timestamp, Some(1_700_000_000));
+    }
+
+    #[test_with_logger]
+    fn test_sync_thread_stop_with_timeout(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+        let ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+
+        let mut sync_thread = SyncThread::start_with_config(
+            ledger_db,
+            wallet_db,
+            Arc::new(Mutex::new(HashMap::new())),
+            SyncThreadConfig {
+                ledger_updates: Some(LedgerUpdateNotifier::default()),
+                blocks_chunk_size: 10,
+                ..Default::default()
+            },
+            logger,
+        );
+        assert!(sync_thread.is_running());
+
+        assert!(sync_thread.stop_with_timeout(Duration::from_secs(5)));
+        assert!(!sync_thread.is_running());
+
+        // Stopping an already stopped thread succeeds immediately.
+        assert!(sync_thread.stop_with_timeout(Duration::ZERO));
+    }
 }
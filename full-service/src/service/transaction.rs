@@ -6,12 +6,16 @@ use crate::{
     db::{
         account::{AccountID, AccountModel},
         exclusive_transaction,
-        models::{Account, TransactionLog},
-        transaction_log::{AssociatedTxos, TransactionLogModel, ValueMap},
-        WalletDbError,
+        models::{Account, TransactionLog, Txo},
+        transaction_log::{AssociatedTxos, TransactionId, TransactionLogModel, ValueMap},
+        txo::{TxoID, TxoModel, TxoStatus},
+        Conn, WalletDbError,
     },
     error::WalletTransactionBuilderError,
-    json_rpc::v2::models::amount::Amount as AmountJSON,
+    json_rpc::{
+        json_rpc_response::{ServerErrorCode, ServerErrorCodes},
+        v2::models::amount::Amount as AmountJSON,
+    },
     service::{
         address::{AddressService, AddressServiceError},
         ledger::{LedgerService, LedgerServiceError},
@@ -29,6 +33,7 @@ use mc_connection::{
     BlockchainConnection, RetryableUserTxConnection, UserTxConnection, _retry::delay::Fibonacci,
 };
 use mc_fog_report_validation::FogPubkeyResolver;
+use mc_ledger_db::Ledger;
 use mc_transaction_builder::{
     BurnRedemptionMemoBuilder, EmptyMemoBuilder, MemoBuilder, RTHMemoBuilder,
 };
@@ -40,10 +45,12 @@ use mc_transaction_core::{
 use mc_transaction_extra::{BurnRedemptionMemo, SenderMemoCredential};
 
 use crate::db::{assigned_subaddress::AssignedSubaddressModel, models::AssignedSubaddress};
+use diesel::result::DatabaseErrorKind;
 use displaydoc::Display;
+use grpcio::RpcStatusCode;
 use serde::{Deserialize, Serialize};
 use serde_big_array::BigArray;
-use std::{convert::TryFrom, ops::DerefMut, sync::atomic::Ordering};
+use std::{collections::HashSet, convert::TryFrom, ops::DerefMut};
 
 /// Errors for the Transaction Service.
 #[derive(Display, Debug)]
@@ -72,6 +79,9 @@ pub enum TransactionServiceError {
     /// No peers configured.
     NoPeersConfigured,
 
+    /// No peer is in sync with the network and reachable.
+    NoHealthyPeers,
+
     /// Error converting to/from API protos: {0}
     ProtoConversion(mc_api::ConversionError),
 
@@ -128,6 +138,24 @@ pub enum TransactionServiceError {
 
     /// Hardware Wallet Service Error: {0}
     HardwareWalletService(crate::service::hardware_wallet::HardwareWalletServiceError),
+
+    /// An idempotency key requires an account to log the transaction to
+    IdempotencyKeyWithoutAccount,
+
+    /// Idempotency key {0} was already used for a different transaction
+    IdempotencyKeyConflict(String),
+
+    /// Txo {0} does not belong to account {1}
+    InputTxoNotOwnedByAccount(String, String),
+
+    /// Txo {0} cannot be used as an input, its status is {1}
+    InputTxoNotSpendable(String, String),
+
+    /// Txo {0} has token id {1}, but the transaction sends token id {2}
+    InputTxoTokenMismatch(String, u64, u64),
+
+    /// Txo {0} is listed more than once in the inputs
+    DuplicateInputTxo(String),
 }
 
 impl From<WalletDbError> for TransactionServiceError {
@@ -219,12 +247,28 @@ impl From<mc_transaction_core::ring_ct::Error> for TransactionServiceError {
         Self::RingCT(src)
     }
 }
+
 impl From<crate::service::hardware_wallet::HardwareWalletServiceError> for TransactionServiceError {
     fn from(src: crate::service::hardware_wallet::HardwareWalletServiceError) -> Self {
         Self::HardwareWalletService(src)
     }
 }
 
+impl ServerErrorCode for TransactionServiceError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::B58(e) => e.code(),
+            Self::Database(e) => e.code(),
+            Self::TransactionBuilder(e) => e.code(),
+            Self::AddressService(e) => e.code(),
+            Self::LedgerService(e) => e.code(),
+            Self::HardwareWalletService(e) => e.code(),
+            Self::Diesel(diesel::result::Error::NotFound) => Some(ServerErrorCodes::NotFound),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 /// This represents the different types of Transaction Memos that can be used in
 /// a given transaction
@@ -303,6 +347,50 @@ impl TransactionMemo {
     }
 }
 
+/// The fee a transaction would be charged, along with the shape of the
+/// transaction coin selection would produce.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeeEstimate {
+    /// The fee that would be applied to the transaction.
+    pub fee: Amount,
+
+    /// The number of inputs selected to cover the value and fee.
+    pub num_inputs: u64,
+
+    /// The number of outputs, including change.
+    pub num_outputs: u64,
+
+    /// Whether the account has enough funds to cover the value and fee.
+    pub sufficient_funds: bool,
+}
+
+/// A reason a transaction proposal would be rejected if it were submitted.
+#[derive(Display, Clone, Debug, PartialEq)]
+pub enum TransactionValidationProblem {
+    /// Tombstone block {tombstone_block} has passed, the ledger has
+    /// {num_blocks} blocks
+    TombstoneExpired {
+        tombstone_block: u64,
+        num_blocks: u64,
+    },
+
+    /// Fee {fee} is below the minimum fee {minimum_fee} for token {token_id}
+    FeeTooLow {
+        fee: u64,
+        minimum_fee: u64,
+        token_id: TokenId,
+    },
+
+    /// Input txo {0} has already been spent in the ledger
+    InputSpent(String),
+
+    /// Input txo {txo_id} is not spendable, its status is {status}
+    InputNotUnspent { txo_id: String, status: String },
+
+    /// Input txo {0} is not in the wallet
+    InputNotFound(String),
+}
+
 fn generate_rth_memo_builder(
     subaddress_index: &Option<u64>,
     account_key: &AccountKey,
@@ -336,6 +424,7 @@ pub trait TransactionService {
     ///| `account_id_hex`        | The account on which to perform this action                       | Account must exist in the wallet                                                                  |
     ///| `addresses_and_amounts` | An array of public addresses and Amounts as a tuple               | addresses are b58-encoded public addresses                                                        |
     ///| `input_txo_ids`         | Specific TXOs to use as inputs to this transaction                | TXO IDs (obtain from get_txos_for_account)                                                        |
+    ///| `exclude_txo_ids`       | TXOs which must not be chosen as inputs to this transaction       | Only used when `input_txo_ids` is not provided                                                    |
     ///| `fee_value`             | The fee value to submit with this transaction                     | If not provided, uses MINIMUM_FEE of the first outputs token_id, if available, or defaults to MOB |
     ///| `fee_token_id`          | The fee token_id to submit with this transaction                  | If not provided, uses token_id of first output, if available, or defaults to MOB                  |
     ///| `tombstone_block`       | The block after which this transaction expires                    | If not provided, uses current height + 10                                                         |
@@ -343,6 +432,7 @@ pub trait TransactionService {
     ///| `memo`                  | Memo for the transaction                                          |                                                                                                   |
     ///| `block_version`         | The block version to build this transaction for.                  | Defaults to the network block version                                                             |
     ///| `spend_subaddress` | The subaddress index to spend from.                            | (optional) ONLY use this parameter if you will ALWAYS use this parameter when spending, or else you may get unexpected balances because normal spending can pull any account txos no matter which subaddress they were received at |
+    ///| `change_subaddress_index` | The subaddress index to send change to.                 | Must be assigned for the account. Defaults to the subaddress being spent from, or else the reserved change subaddress |
     ///
     #[allow(clippy::too_many_arguments)]
     fn build_transaction(
@@ -350,6 +440,7 @@ pub trait TransactionService {
         account_id_hex: &str,
         addresses_and_amounts: &[(String, AmountJSON)],
         input_txo_ids: Option<&Vec<String>>,
+        exclude_txo_ids: Option<&Vec<String>>,
         fee_value: Option<String>,
         fee_token_id: Option<String>,
         tombstone_block: Option<String>,
@@ -357,6 +448,7 @@ pub trait TransactionService {
         memo: TransactionMemo,
         block_version: Option<BlockVersion>,
         spend_subaddress: Option<String>,
+        change_subaddress_index: Option<String>,
     ) -> Result<UnsignedTxProposal, TransactionServiceError>;
 
     /// Build a transaction and sign it before submitting it to the network.
@@ -368,6 +460,7 @@ pub trait TransactionService {
     ///| `account_id_hex`        | The account on which to perform this action                       | Account must exist in the wallet                                                                  |
     ///| `addresses_and_amounts` | An array of public addresses and Amounts as a tuple               | addresses are b58-encoded public addresses                                                        |
     ///| `input_txo_ids`         | Specific TXOs to use as inputs to this transaction                | TXO IDs (obtain from get_txos_for_account)                                                        |
+    ///| `exclude_txo_ids`       | TXOs which must not be chosen as inputs to this transaction       | Only used when `input_txo_ids` is not provided                                                    |
     ///| `fee_value`             | The fee value to submit with this transaction                     | If not provided, uses MINIMUM_FEE of the first outputs token_id, if available, or defaults to MOB |
     ///| `fee_token_id`          | The fee token_id to submit with this transaction                  | If not provided, uses token_id of first output, if available, or defaults to MOB                  |
     ///| `tombstone_block`       | The block after which this transaction expires                    | If not provided, uses current height + 10                                                         |
@@ -375,6 +468,7 @@ pub trait TransactionService {
     ///| `memo`                  | Memo for the transaction                                          |                                                                                                   |
     ///| `block_version`         | The block version to build this transaction for.                  | Defaults to the network block version                                                             |
     ///| `spend_subaddress` | The subaddress index to spend from.                               |                                                                                                   |
+    ///| `change_subaddress_index` | The subaddress index to send change to.                 | Must be assigned for the account. Defaults to the subaddress being spent from, or else the reserved change subaddress |
     ///
     #[allow(clippy::too_many_arguments)]
     async fn build_and_sign_transaction(
@@ -382,6 +476,7 @@ pub trait TransactionService {
         account_id_hex: &str,
         addresses_and_amounts: &[(String, AmountJSON)],
         input_txo_ids: Option<&Vec<String>>,
+        exclude_txo_ids: Option<&Vec<String>>,
         fee_value: Option<String>,
         fee_token_id: Option<String>,
         tombstone_block: Option<String>,
@@ -389,23 +484,26 @@ pub trait TransactionService {
         memo: TransactionMemo,
         block_version: Option<BlockVersion>,
         spend_subaddress: Option<String>,
+        change_subaddress_index: Option<String>,
     ) -> Result<TxProposal, TransactionServiceError>;
 
     /// Submits a pre-built TxProposal to the MobileCoin Consensus Network.
     ///
     /// # Arguments
     ///
-    ///| Name             | Purpose                                                     | Notes                                                                                                                                                                                                     |
-    ///|------------------|-------------------------------------------------------------|-----------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------|
-    ///| `tx_proposal`    | Transaction proposal to submit                              | Created with build_transaction                                                                                                                                                                            |
-    ///| `comment`        | Comment to annotate this transaction in the transaction log |                                                                                                                                                                                                           |
-    ///| `account_id_hex` | Account ID for which to log the transaction.                | If omitted, the transaction is not logged and therefor the txos used will not be set to pending, if they exist. This could inadvertently cause an attempt to spend the same txo in multiple transactions. |
+    ///| Name              | Purpose                                                     | Notes                                                                                                                                                                                                     |
+    ///|-------------------|-------------------------------------------------------------|-----------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------|
+    ///| `tx_proposal`     | Transaction proposal to submit                              | Created with build_transaction                                                                                                                                                                            |
+    ///| `comment`         | Comment to annotate this transaction in the transaction log |                                                                                                                                                                                                           |
+    ///| `account_id_hex`  | Account ID for which to log the transaction.                | If omitted, the transaction is not logged and therefor the txos used will not be set to pending, if they exist. This could inadvertently cause an attempt to spend the same txo in multiple transactions. |
+    ///| `idempotency_key` | Client-supplied key stored with the transaction log.        | A repeat submission with the same key returns the original transaction log without resubmitting. Requires `account_id_hex`.                                                                               |
     ///
     fn submit_transaction(
         &self,
         tx_proposal: &TxProposal,
         comment: Option<String>,
         account_id_hex: Option<String>,
+        idempotency_key: Option<String>,
     ) -> Result<Option<(TransactionLog, AssociatedTxos, ValueMap)>, TransactionServiceError>;
 
     /// Build and sign a transaction and submit it to the network.
@@ -417,6 +515,7 @@ pub trait TransactionService {
     ///| `account_id_hex`        | The account on which to perform this action                       | Account must exist in the wallet                                                                  |
     ///| `addresses_and_amounts` | An array of public addresses and Amounts as a tuple               | addresses are b58-encoded public addresses                                                        |
     ///| `input_txo_ids`         | Specific TXOs to use as inputs to this transaction                | TXO IDs (obtain from get_txos_for_account)                                                        |
+    ///| `exclude_txo_ids`       | TXOs which must not be chosen as inputs to this transaction       | Only used when `input_txo_ids` is not provided                                                    |
     ///| `fee_value`             | The fee value to submit with this transaction                     | If not provided, uses MINIMUM_FEE of the first outputs token_id, if available, or defaults to MOB |
     ///| `fee_token_id`          | The fee token_id to submit with this transaction                  | If not provided, uses token_id of first output, if available, or defaults to MOB                  |
     ///| `tombstone_block`       | The block after which this transaction expires                    | If not provided, uses current height + 10                                                         |
@@ -424,6 +523,7 @@ pub trait TransactionService {
     ///| `memo`                  | Memo for the transaction                                          |                                                                                                   |
     ///| `block_version`         | The block version to build this transaction for.                  | Defaults to the network block version                                                             |
     ///| `spend_subaddress` | The subaddress index to spend from.                               |                                                                                                   |
+    ///| `change_subaddress_index` | The subaddress index to send change to.                 | Must be assigned for the account. Defaults to the subaddress being spent from, or else the reserved change subaddress |
     ///
     #[allow(clippy::too_many_arguments)]
     async fn build_sign_and_submit_transaction(
@@ -431,6 +531,7 @@ pub trait TransactionService {
         account_id_hex: &str,
         addresses_and_amounts: &[(String, AmountJSON)],
         input_txo_ids: Option<&Vec<String>>,
+        exclude_txo_ids: Option<&Vec<String>>,
         fee_value: Option<String>,
         fee_token_id: Option<String>,
         tombstone_block: Option<String>,
@@ -439,7 +540,45 @@ pub trait TransactionService {
         memo: TransactionMemo,
         block_version: Option<BlockVersion>,
         spend_subaddress: Option<String>,
+        change_subaddress_index: Option<String>,
     ) -> Result<(TransactionLog, AssociatedTxos, ValueMap, TxProposal), TransactionServiceError>;
+
+    /// Estimate the fee for sending a value, without building the transaction.
+    ///
+    /// # Arguments
+    ///
+    ///| Name             | Purpose                                            | Notes                                               |
+    ///|------------------|----------------------------------------------------|-----------------------------------------------------|
+    ///| `account_id_hex` | The account on which to perform this action        | Account must exist in the wallet                    |
+    ///| `amount`         | The value and token_id to send                     | The fee is paid in the same token, as in a build    |
+    ///| `input_txo_ids`  | Specific TXOs to use as inputs to this transaction | If not provided, inputs are chosen by coin selection |
+    ///
+    fn estimate_fee(
+        &self,
+        account_id_hex: &str,
+        amount: &AmountJSON,
+        input_txo_ids: Option<&Vec<String>>,
+    ) -> Result<FeeEstimate, TransactionServiceError>;
+
+    /// Check whether a transaction proposal would be accepted, without submitting it.
+    ///
+    /// The tombstone block is checked against the local ledger, the fee against the
+    /// network minimum, and each input against the ledger and the wallet. Inputs that
+    /// are pending are rejected, since they are already inputs to a submitted
+    /// transaction.
+    ///
+    /// # Arguments
+    ///
+    ///| Name          | Purpose                              | Notes                                   |
+    ///|---------------|--------------------------------------|-----------------------------------------|
+    ///| `tx_proposal` | The transaction proposal to validate | The transaction proposal must be signed |
+    ///
+    /// # Returns:
+    /// * Every problem found with the proposal, empty if it is valid
+    fn validate_transaction(
+        &self,
+        tx_proposal: &TxProposal,
+    ) -> Result<Vec<TransactionValidationProblem>, TransactionServiceError>;
 }
 
 #[async_trait]
@@ -453,6 +592,7 @@ where
         account_id_hex: &str,
         addresses_and_amounts: &[(String, AmountJSON)],
         input_txo_ids: Option<&Vec<String>>,
+        exclude_txo_ids: Option<&Vec<String>>,
         fee_value: Option<String>,
         fee_token_id: Option<String>,
         tombstone_block: Option<String>,
@@ -460,6 +600,7 @@ where
         memo: TransactionMemo,
         block_version: Option<BlockVersion>,
         spend_subaddress: Option<String>,
+        change_subaddress_index: Option<String>,
     ) -> Result<UnsignedTxProposal, TransactionServiceError> {
         validate_number_inputs(input_txo_ids.unwrap_or(&Vec::new()).len() as u64)?;
         validate_number_outputs(addresses_and_amounts.len() as u64)?;
@@ -468,8 +609,8 @@ where
         let conn = pooled_conn.deref_mut();
 
         exclusive_transaction(conn, |conn| {
-            if Account::get(&AccountID(account_id_hex.to_string()), conn)?.require_spend_subaddress
-            {
+            let account = Account::get(&AccountID(account_id_hex.to_string()), conn)?;
+            if account.require_spend_subaddress {
                 if spend_subaddress.is_none() {
                     return Err(TransactionServiceError::TransactionBuilder(WalletTransactionBuilderError::NullSubaddress(
                         "This account requires subaddresses be specified when spending. Please provide a subaddress to spend from.".to_string()
@@ -498,6 +639,7 @@ where
                 default_fee_token_id = amount.token_id;
             }
 
+            builder.set_tombstone_horizon(self.tombstone_horizon);
             if let Some(tombstone) = tombstone_block {
                 builder.set_tombstone(tombstone.parse::<u64>()?)?;
             } else {
@@ -511,12 +653,7 @@ where
 
             let fee_value = match fee_value {
                 Some(f) => f.parse::<u64>()?,
-                None => self
-                    .get_network_fees()?
-                    .get_fee_for_token(&fee_token_id)
-                    .ok_or(TransactionServiceError::DefaultFeeNotFoundForToken(
-                        fee_token_id,
-                    ))?,
+                None => self.get_default_fee(Some(&account), fee_token_id)?,
             };
 
             builder.set_fee(fee_value, fee_token_id)?;
@@ -540,10 +677,25 @@ where
                 } else {
                     None
                 };
+                if let Some(exclude_txo_ids) = exclude_txo_ids {
+                    builder.exclude_txos(exclude_txo_ids);
+                }
                 builder.select_txos(conn, max_spendable)?;
             }
 
+            if let Some(change_subaddress_index) = change_subaddress_index {
+                let change_subaddress_index = change_subaddress_index.parse::<u64>()?;
+                // Ensure the change subaddress has been assigned for this account
+                AssignedSubaddress::get_for_account_by_index(
+                    account_id_hex,
+                    change_subaddress_index as i64,
+                    conn,
+                )?;
+                builder.set_change_subaddress(change_subaddress_index);
+            }
+
             let unsigned_tx_proposal = builder.build(memo, conn)?;
+            builder.reserve_inputs(conn)?;
 
             Ok(unsigned_tx_proposal)
         })
@@ -554,6 +706,7 @@ where
         account_id_hex: &str,
         addresses_and_amounts: &[(String, AmountJSON)],
         input_txo_ids: Option<&Vec<String>>,
+        exclude_txo_ids: Option<&Vec<String>>,
         fee_value: Option<String>,
         fee_token_id: Option<String>,
         tombstone_block: Option<String>,
@@ -561,11 +714,13 @@ where
         memo: TransactionMemo,
         block_version: Option<BlockVersion>,
         spend_subaddress: Option<String>,
+        change_subaddress_index: Option<String>,
     ) -> Result<TxProposal, TransactionServiceError> {
         let unsigned_tx_proposal = self.build_transaction(
             account_id_hex,
             addresses_and_amounts,
             input_txo_ids,
+            exclude_txo_ids,
             fee_value,
             fee_token_id,
             tombstone_block,
@@ -573,6 +728,7 @@ where
             memo,
             block_version,
             spend_subaddress,
+            change_subaddress_index,
         )?;
 
         let mut pooled_conn = self.get_pooled_conn()?;
@@ -580,7 +736,9 @@ where
 
         let account = Account::get(&AccountID(account_id_hex.to_string()), conn)?;
 
-        let tx_proposal = unsigned_tx_proposal.sign(&account).await?;
+        let tx_proposal = unsigned_tx_proposal
+            .sign_with_hardware_wallet_config(&account, &self.hardware_wallet_config)
+            .await?;
 
         exclusive_transaction(conn, |conn| {
             TransactionLog::log_signed(tx_proposal.clone(), "".to_string(), account_id_hex, conn)?;
@@ -593,26 +751,87 @@ where
         tx_proposal: &TxProposal,
         comment: Option<String>,
         account_id_hex: Option<String>,
+        idempotency_key: Option<String>,
     ) -> Result<Option<(TransactionLog, AssociatedTxos, ValueMap)>, TransactionServiceError> {
         if self.offline {
             return Err(TransactionServiceError::Offline);
         }
 
-        // Pick a peer to submit to.
-        let responder_ids = self.peer_manager.responder_ids();
-        if responder_ids.is_empty() {
+        if self.peer_manager.responder_ids().is_empty() {
             return Err(TransactionServiceError::NoPeersConfigured);
         }
 
-        let idx = self.submit_node_offset.fetch_add(1, Ordering::SeqCst);
-        let responder_id = &responder_ids[idx % responder_ids.len()];
+        // A transaction that was already submitted with this key is not submitted
+        // again. Otherwise the key is reserved on this transaction's log before it is
+        // sent to consensus, so that a concurrent submission of a different
+        // transaction with the same key fails before it is sent.
+        if let Some(idempotency_key) = &idempotency_key {
+            let account_id_hex = account_id_hex
+                .as_ref()
+                .ok_or(TransactionServiceError::IdempotencyKeyWithoutAccount)?;
+
+            let mut pooled_conn = self.get_pooled_conn()?;
+            let conn = pooled_conn.deref_mut();
+            let submitted = exclusive_transaction(conn, |conn| {
+                if let Some(submitted) =
+                    get_idempotent_submission(idempotency_key, tx_proposal, account_id_hex, conn)?
+                {
+                    return Ok(Some(submitted));
+                }
+
+                let transaction_log = TransactionLog::log_signed(
+                    tx_proposal.clone(),
+                    comment.clone().unwrap_or_default(),
+                    account_id_hex,
+                    conn,
+                )?;
+                transaction_log
+                    .update_idempotency_key(idempotency_key, conn)
+                    .map_err(|err| match err {
+                        WalletDbError::Diesel(diesel::result::Error::DatabaseError(
+                            DatabaseErrorKind::UniqueViolation,
+                            _,
+                        )) => TransactionServiceError::IdempotencyKeyConflict(
+                            idempotency_key.to_string(),
+                        ),
+                        err => err.into(),
+                    })?;
+                Ok::<_, TransactionServiceError>(None)
+            })?;
+
+            if let Some(submitted) = submitted {
+                return Ok(Some(submitted));
+            }
+        }
 
-        let block_index = self
-            .peer_manager
-            .conn(responder_id)
-            .ok_or(TransactionServiceError::NodeNotFound)?
-            .propose_tx(&tx_proposal.tx, Fibonacci::from_millis(10).take(5))
-            .map_err(TransactionServiceError::from)?;
+        // Submit to the first healthy peer that can be reached.
+        let mut block_index = None;
+        for responder_id in self.submit_node_candidates() {
+            let result = self
+                .peer_manager
+                .conn(&responder_id)
+                .ok_or(TransactionServiceError::NodeNotFound)?
+                .propose_tx(&tx_proposal.tx, Fibonacci::from_millis(10).take(5));
+
+            match result {
+                Ok(index) => {
+                    self.record_submit_node_result(&responder_id, true);
+                    block_index = Some(index);
+                    break;
+                }
+                Err(err) if is_connection_error(&err.error) => {
+                    log::warn!(
+                        self.logger,
+                        "Could not submit tx to {}, trying the next node: {}",
+                        responder_id,
+                        err
+                    );
+                    self.record_submit_node_result(&responder_id, false);
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        let block_index = block_index.ok_or(TransactionServiceError::NoHealthyPeers)?;
 
         log::trace!(
             self.logger,
@@ -627,18 +846,22 @@ where
             let account_id = AccountID(account_id_hex.to_string());
 
             if Account::get(&account_id, conn).is_ok() {
-                let transaction_log = TransactionLog::log_submitted(
-                    tx_proposal,
-                    block_index,
-                    comment.unwrap_or_default(),
-                    &account_id_hex,
-                    conn,
-                )?;
-
-                let associated_txos = transaction_log.get_associated_txos(conn)?;
-                let value_map = transaction_log.value_map(conn)?;
-
-                Ok(Some((transaction_log, associated_txos, value_map)))
+                let submitted = exclusive_transaction(conn, |conn| {
+                    // Any idempotency key was reserved on this log before submitting.
+                    let transaction_log = TransactionLog::log_submitted(
+                        tx_proposal,
+                        block_index,
+                        comment.unwrap_or_default(),
+                        &account_id_hex,
+                        conn,
+                    )?;
+
+                    let associated_txos = transaction_log.get_associated_txos(conn)?;
+                    let value_map = transaction_log.value_map(conn)?;
+                    Ok::<_, TransactionServiceError>((transaction_log, associated_txos, value_map))
+                })?;
+
+                Ok(Some(submitted))
             } else {
                 Err(TransactionServiceError::Database(
                     WalletDbError::AccountNotFound(account_id_hex),
@@ -654,6 +877,7 @@ where
         account_id_hex: &str,
         addresses_and_amounts: &[(String, AmountJSON)],
         input_txo_ids: Option<&Vec<String>>,
+        exclude_txo_ids: Option<&Vec<String>>,
         fee_value: Option<String>,
         fee_token_id: Option<String>,
         tombstone_block: Option<String>,
@@ -662,6 +886,7 @@ where
         memo: TransactionMemo,
         block_version: Option<BlockVersion>,
         spend_subaddress: Option<String>,
+        change_subaddress_index: Option<String>,
     ) -> Result<(TransactionLog, AssociatedTxos, ValueMap, TxProposal), TransactionServiceError>
     {
         let tx_proposal = self
@@ -669,6 +894,7 @@ where
                 account_id_hex,
                 addresses_and_amounts,
                 input_txo_ids,
+                exclude_txo_ids,
                 fee_value,
                 fee_token_id,
                 tombstone_block,
@@ -676,12 +902,16 @@ where
                 memo,
                 block_version,
                 spend_subaddress,
+                change_subaddress_index,
             )
             .await?;
 
-        if let Some(transaction_log_and_associated_txos) =
-            self.submit_transaction(&tx_proposal, comment, Some(account_id_hex.to_string()))?
-        {
+        if let Some(transaction_log_and_associated_txos) = self.submit_transaction(
+            &tx_proposal,
+            comment,
+            Some(account_id_hex.to_string()),
+            None,
+        )? {
             Ok((
                 transaction_log_and_associated_txos.0,
                 transaction_log_and_associated_txos.1,
@@ -692,6 +922,198 @@ where
             Err(TransactionServiceError::MissingAccountOnSubmit)
         }
     }
+
+    fn estimate_fee(
+        &self,
+        account_id_hex: &str,
+        amount: &AmountJSON,
+        input_txo_ids: Option<&Vec<String>>,
+    ) -> Result<FeeEstimate, TransactionServiceError> {
+        // Txos are only selected once, so a repeated id would be counted as an
+        // input that the transaction does not have.
+        if let Some(input_txo_ids) = input_txo_ids {
+            let mut seen_txo_ids = HashSet::new();
+            if let Some(txo_id) = input_txo_ids
+                .iter()
+                .find(|txo_id| !seen_txo_ids.insert(*txo_id))
+            {
+                return Err(TransactionServiceError::DuplicateInputTxo(txo_id.clone()));
+            }
+        }
+        validate_number_inputs(input_txo_ids.unwrap_or(&Vec::new()).len() as u64)?;
+
+        let amount = Amount::try_from(amount).map_err(TransactionServiceError::InvalidAmount)?;
+
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+
+        let account = Account::get(&AccountID(account_id_hex.to_string()), conn)?;
+
+        // As in build_transaction, the fee defaults to the account's default fee or
+        // the network fee for the token being sent.
+        let fee_value = self.get_default_fee(Some(&account), amount.token_id)?;
+        let target_value = amount.value as u128 + fee_value as u128;
+
+        let (num_inputs, input_value) = match input_txo_ids {
+            Some(input_txo_ids) => {
+                let inputs = Txo::select_by_id(input_txo_ids, conn)?;
+                if let Some(missing_txo_id) = input_txo_ids
+                    .iter()
+                    .find(|txo_id| !inputs.iter().any(|txo| &txo.id == *txo_id))
+                {
+                    return Err(WalletDbError::TxoNotFound(missing_txo_id.clone()).into());
+                }
+                for txo in inputs.iter() {
+                    if txo.account_id.as_deref() != Some(account_id_hex) {
+                        return Err(TransactionServiceError::InputTxoNotOwnedByAccount(
+                            txo.id.clone(),
+                            account_id_hex.to_string(),
+                        ));
+                    }
+                    let status = txo.status(conn)?;
+                    if status != TxoStatus::Unspent {
+                        return Err(TransactionServiceError::InputTxoNotSpendable(
+                            txo.id.clone(),
+                            status.to_string(),
+                        ));
+                    }
+                    if txo.token_id as u64 != *amount.token_id {
+                        return Err(TransactionServiceError::InputTxoTokenMismatch(
+                            txo.id.clone(),
+                            txo.token_id as u64,
+                            *amount.token_id,
+                        ));
+                    }
+                }
+                let input_value: u128 = inputs.iter().map(|txo| txo.value as u64 as u128).sum();
+                (inputs.len() as u64, input_value)
+            }
+            None => match Txo::select_spendable_txos_for_value(
+                account_id_hex,
+                target_value,
+                None,
+                None,
+                &[],
+                *amount.token_id,
+                fee_value,
+                conn,
+            ) {
+                Ok(inputs) => {
+                    let input_value: u128 = inputs.iter().map(|txo| txo.value as u64 as u128).sum();
+                    (inputs.len() as u64, input_value)
+                }
+                Err(WalletDbError::NoSpendableTxos(_))
+                | Err(WalletDbError::InsufficientFunds(_))
+                | Err(WalletDbError::InsufficientFundsFragmentedTxos(..))
+                | Err(WalletDbError::InsufficientFundsUnderMaxSpendable(_)) => (0, 0),
+                Err(e) => return Err(e.into()),
+            },
+        };
+
+        Ok(FeeEstimate {
+            fee: Amount::new(fee_value, amount.token_id),
+            num_inputs,
+            // The single recipient, and the change output the builder adds for the
+            // token being sent, even when the change is zero.
+            num_outputs: 2,
+            sufficient_funds: num_inputs > 0 && input_value >= target_value,
+        })
+    }
+
+    fn validate_transaction(
+        &self,
+        tx_proposal: &TxProposal,
+    ) -> Result<Vec<TransactionValidationProblem>, TransactionServiceError> {
+        let mut problems = Vec::new();
+
+        // The transaction can only be included in a block whose index is less than
+        // its tombstone block, and the next block index is the current block count.
+        let num_blocks = self.ledger_db.num_blocks()?;
+        let tombstone_block = tx_proposal.tx.prefix.tombstone_block;
+        if tombstone_block <= num_blocks {
+            problems.push(TransactionValidationProblem::TombstoneExpired {
+                tombstone_block,
+                num_blocks,
+            });
+        }
+
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+
+        // The fee must meet the network minimum. An account's default fee only
+        // applies when a transaction is built without a fee, so a lower explicit fee
+        // is still valid.
+        let fee = tx_proposal.tx.prefix.fee;
+        let token_id = TokenId::from(tx_proposal.tx.prefix.fee_token_id);
+        let minimum_fee = self.get_network_fee(token_id)?;
+        if fee < minimum_fee {
+            problems.push(TransactionValidationProblem::FeeTooLow {
+                fee,
+                minimum_fee,
+                token_id,
+            });
+        }
+
+        for input_txo in tx_proposal.input_txos.iter() {
+            let txo_id = TxoID::from(&input_txo.tx_out).to_string();
+
+            if self.ledger_db.contains_key_image(&input_txo.key_image)? {
+                problems.push(TransactionValidationProblem::InputSpent(txo_id));
+                continue;
+            }
+
+            match Txo::get(&txo_id, conn) {
+                Ok(txo) => match txo.status(conn)? {
+                    TxoStatus::Unspent => {}
+                    status => problems.push(TransactionValidationProblem::InputNotUnspent {
+                        txo_id,
+                        status: status.to_string(),
+                    }),
+                },
+                Err(WalletDbError::TxoNotFound(_)) => {
+                    problems.push(TransactionValidationProblem::InputNotFound(txo_id));
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(problems)
+    }
+}
+
+/// The transaction log already submitted with an idempotency key, if any. The
+/// key may only be reused to submit the same transaction from the same account.
+/// A log that holds the key but was never submitted, because its submission is
+/// still in progress or failed, is not returned, so that the transaction is
+/// submitted again.
+fn get_idempotent_submission(
+    idempotency_key: &str,
+    tx_proposal: &TxProposal,
+    account_id_hex: &str,
+    conn: Conn,
+) -> Result<Option<(TransactionLog, AssociatedTxos, ValueMap)>, TransactionServiceError> {
+    let transaction_log = match TransactionLog::get_by_idempotency_key(idempotency_key, conn)? {
+        Some(transaction_log) => transaction_log,
+        None => return Ok(None),
+    };
+
+    let transaction_id = TransactionId::try_from(tx_proposal)
+        .map_err(|e| WalletDbError::InvalidArgument(e.to_string()))?;
+    if transaction_log.id != transaction_id.to_string()
+        || transaction_log.account_id != account_id_hex
+    {
+        return Err(TransactionServiceError::IdempotencyKeyConflict(
+            idempotency_key.to_string(),
+        ));
+    }
+
+    if transaction_log.submitted_block_index.is_none() {
+        return Ok(None);
+    }
+
+    let associated_txos = transaction_log.get_associated_txos(conn)?;
+    let value_map = transaction_log.value_map(conn)?;
+    Ok(Some((transaction_log, associated_txos, value_map)))
 }
 
 fn validate_number_inputs(num_inputs: u64) -> Result<(), TransactionServiceError> {
@@ -714,6 +1136,21 @@ fn validate_number_outputs(num_outputs: u64) -> Result<(), TransactionServiceErr
     Ok(())
 }
 
+/// Whether a submission failed because the node couldn't be reached, rather
+/// than because the node rejected the transaction.
+fn is_connection_error(err: &mc_connection::Error) -> bool {
+    match err {
+        // A status from the node is a rejection, unless it says the node is
+        // unavailable or didn't answer in time.
+        mc_connection::Error::Grpc(grpcio::Error::RpcFailure(status)) => {
+            status.code() == RpcStatusCode::UNAVAILABLE
+                || status.code() == RpcStatusCode::DEADLINE_EXCEEDED
+        }
+        mc_connection::Error::Grpc(_) | mc_connection::Error::Attestation(_) => true,
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -724,16 +1161,17 @@ mod tests {
         },
         service::{
             account::AccountService, address::AddressService, balance::BalanceService,
-            transaction_log::TransactionLogService,
+            transaction_log::TransactionLogService, txo::TxoService,
         },
         test_utils::{
             add_block_to_ledger_db, add_block_with_tx_outs, get_test_ledger, manually_sync_account,
-            setup_wallet_service, MOB,
+            setup_mock_peer, setup_wallet_service, setup_wallet_service_with_peers, MOB,
         },
         util::b58::b58_encode_public_address,
     };
-    use mc_account_keys::{AccountKey, PublicAddress};
-    use mc_common::logger::{async_test_with_logger, Logger};
+    use mc_account_keys::{AccountKey, PublicAddress, ViewAccountKey};
+    use mc_common::logger::{async_test_with_logger, test_with_logger, Logger};
+    use mc_connection::Connection;
     use mc_core::account::ShortAddressHash;
     use mc_crypto_keys::RistrettoPublic;
     use mc_rand::rand_core::RngCore;
@@ -776,9 +1214,14 @@ mod tests {
 
         assert_eq!(0, tx_logs.len());
 
+        // Each built proposal reserves its inputs, so fund one txo per proposal
         add_block_to_ledger_db(
             &mut ledger_db,
-            &vec![alice_public_address],
+            &vec![
+                alice_public_address.clone(),
+                alice_public_address.clone(),
+                alice_public_address,
+            ],
             100 * MOB,
             &[KeyImage::from(rng.next_u64())],
             &mut rng,
@@ -802,7 +1245,7 @@ mod tests {
             .get_balance_for_account(&AccountID(alice.id.clone()))
             .unwrap();
         let balance_pmob = balance.get(&Mob::ID).unwrap();
-        assert_eq!(balance_pmob.unspent, 100 * MOB as u128);
+        assert_eq!(balance_pmob.unspent, 300 * MOB as u128);
 
         // Add an account for Bob
         let bob = service
@@ -834,11 +1277,13 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
                 TransactionMemo::RTH {
                     subaddress_index: None,
                 },
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -867,11 +1312,13 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
                 TransactionMemo::RTH {
                     subaddress_index: None,
                 },
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -900,11 +1347,13 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
                 TransactionMemo::RTH {
                     subaddress_index: None,
                 },
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -995,11 +1444,13 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
                 TransactionMemo::RTH {
                     subaddress_index: None,
                 },
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -1097,11 +1548,13 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
                 TransactionMemo::RTH {
                     subaddress_index: None,
                 },
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -1161,9 +1614,10 @@ mod tests {
         );
     }
 
-    // Building a transaction for an invalid public address should fail.
+    // Sending to one of our own addresses should leave the payment spendable once
+    // it lands, so the balance only drops by the fee.
     #[async_test_with_logger]
-    async fn test_invalid_public_address_fails(logger: Logger) {
+    async fn test_send_transaction_to_self(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
 
         let known_recipients: Vec<PublicAddress> = Vec::new();
@@ -1171,7 +1625,6 @@ mod tests {
 
         let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
 
-        // Create our main account for the wallet
         let alice = service
             .create_account(
                 Some("Alice's Main Account".to_string()),
@@ -1180,19 +1633,15 @@ mod tests {
                 false,
             )
             .unwrap();
-
-        // Add a block with a transaction for Alice
         let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
         let alice_account_id = AccountID::from(&alice_account_key);
-        let alice_public_address = alice_account_key.default_subaddress();
         add_block_to_ledger_db(
             &mut ledger_db,
-            &vec![alice_public_address],
+            &[alice_account_key.default_subaddress()],
             100 * MOB,
             &[KeyImage::from(rng.next_u64())],
             &mut rng,
         );
-
         manually_sync_account(
             &ledger_db,
             service.wallet_db.as_ref().unwrap(),
@@ -1200,10 +1649,19 @@ mod tests {
             &logger,
         );
 
-        match service
-            .build_and_sign_transaction(
+        let alice_address = service
+            .assign_address_for_account(&alice_account_id, Some("To Self"))
+            .unwrap();
+
+        let (_transaction_log, _associated_txos, _value_map, tx_proposal) = service
+            .build_sign_and_submit_transaction(
                 &alice.id,
-                &[("NOTB58".to_string(), AmountJSON::new(42 * MOB, Mob::ID))],
+                &[(
+                    alice_address.public_address_b58,
+                    AmountJSON::new(42 * MOB, Mob::ID),
+                )],
+                None,
+                None,
                 None,
                 None,
                 None,
@@ -1214,19 +1672,60 @@ mod tests {
                 },
                 None,
                 None,
+                None,
             )
             .await
-        {
-            Ok(_) => {
-                panic!("Should not be able to build transaction to invalid b58 public address")
-            }
-            Err(TransactionServiceError::InvalidPublicAddress(_)) => {}
-            Err(e) => panic!("Unexpected error {:?}", e),
-        };
+            .unwrap();
+        let fee = tx_proposal.tx.prefix.fee;
+
+        // Until the transaction lands, the inputs are pending.
+        let balance = service.get_balance_for_account(&alice_account_id).unwrap();
+        let balance_pmob = balance.get(&Mob::ID).unwrap();
+        assert_eq!(balance_pmob.unspent, 0);
+        assert_eq!(balance_pmob.pending, 100 * MOB as u128);
+
+        let key_images: Vec<KeyImage> = tx_proposal
+            .input_txos
+            .iter()
+            .map(|txo| txo.key_image)
+            .collect();
+        add_block_with_tx_outs(
+            &mut ledger_db,
+            &[
+                tx_proposal.change_txos[0].tx_out.clone(),
+                tx_proposal.payload_txos[0].tx_out.clone(),
+            ],
+            &key_images,
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        // The payment and the change are both received and unspent.
+        let balance = service.get_balance_for_account(&alice_account_id).unwrap();
+        let balance_pmob = balance.get(&Mob::ID).unwrap();
+        assert_eq!(balance_pmob.unspent, (100 * MOB - fee) as u128);
+        assert_eq!(balance_pmob.pending, 0);
+        assert_eq!(balance_pmob.secreted, 0);
+        assert_eq!(balance_pmob.spent, 100 * MOB as u128);
+
+        let payload_txo_id = TxoID::from(&tx_proposal.payload_txos[0].tx_out);
+        let payload_txo = service.get_txo(&payload_txo_id).unwrap();
+        assert_eq!(
+            payload_txo.txo.account_id,
+            Some(alice_account_id.to_string())
+        );
+        assert_eq!(payload_txo.txo.subaddress_index, Some(2));
+        assert_eq!(payload_txo.status, TxoStatus::Unspent);
     }
 
+    // Building a transaction for an invalid public address should fail.
     #[async_test_with_logger]
-    async fn test_maximum_inputs_and_outputs(logger: Logger) {
+    async fn test_invalid_public_address_fails(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
 
         let known_recipients: Vec<PublicAddress> = Vec::new();
@@ -1250,7 +1749,7 @@ mod tests {
         let alice_public_address = alice_account_key.default_subaddress();
         add_block_to_ledger_db(
             &mut ledger_db,
-            &vec![alice_public_address.clone()],
+            &vec![alice_public_address],
             100 * MOB,
             &[KeyImage::from(rng.next_u64())],
             &mut rng,
@@ -1263,57 +1762,12 @@ mod tests {
             &logger,
         );
 
-        // test ouputs
-        let mut outputs = Vec::new();
-        for _ in 0..17 {
-            outputs.push((
-                b58_encode_public_address(&alice_public_address).unwrap(),
-                AmountJSON::new(42 * MOB, Mob::ID),
-            ));
-        }
         match service
             .build_and_sign_transaction(
                 &alice.id,
-                &outputs,
-                None,
-                None,
-                None,
-                None,
-                None,
-                TransactionMemo::RTH {
-                    subaddress_index: None,
-                },
+                &[("NOTB58".to_string(), AmountJSON::new(42 * MOB, Mob::ID))],
                 None,
                 None,
-            )
-            .await
-        {
-            Ok(_) => {
-                panic!("Should not be able to build transaction with too many ouputs")
-            }
-            Err(TransactionServiceError::TransactionBuilder(
-                WalletTransactionBuilderError::InvalidArgument(_),
-            )) => {}
-            Err(e) => panic!("Unexpected error {:?}", e),
-        };
-
-        // test inputs
-        let mut outputs = Vec::new();
-        for _ in 0..2 {
-            outputs.push((
-                b58_encode_public_address(&alice_public_address).unwrap(),
-                AmountJSON::new(42 * MOB, Mob::ID),
-            ));
-        }
-        let mut inputs = Vec::new();
-        for _ in 0..17 {
-            inputs.push("fake txo id".to_string());
-        }
-        match service
-            .build_and_sign_transaction(
-                &alice.id,
-                &outputs,
-                Some(&inputs),
                 None,
                 None,
                 None,
@@ -1323,22 +1777,22 @@ mod tests {
                 },
                 None,
                 None,
+                None,
             )
             .await
         {
             Ok(_) => {
-                panic!("Should not be able to build transaction with too many inputs")
+                panic!("Should not be able to build transaction to invalid b58 public address")
             }
-            Err(TransactionServiceError::TransactionBuilder(
-                WalletTransactionBuilderError::InvalidArgument(_),
-            )) => {}
+            Err(TransactionServiceError::InvalidPublicAddress(_)) => {}
             Err(e) => panic!("Unexpected error {:?}", e),
         };
     }
 
-    // Test sending a transaction from Alice -> Bob, and then from Bob -> Alice
+    // Test building an unsigned transaction for a view-only account, signing it
+    // offline with the spend key and submitting the signed proposal.
     #[async_test_with_logger]
-    async fn test_send_transaction_with_sender_memo_cred_subaddress_index(logger: Logger) {
+    async fn test_offline_signing_round_trip(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
 
         let known_recipients: Vec<PublicAddress> = Vec::new();
@@ -1346,12 +1800,247 @@ mod tests {
 
         let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
 
-        // Create our main account for the wallet
+        // The spend key is held outside the wallet, which only knows the view key.
+        let alice_account_key = AccountKey::random(&mut rng);
+        let alice_view_account_key = ViewAccountKey::from(&alice_account_key);
         let alice = service
-            .create_account(
-                Some("Alice's Main Account".to_string()),
-                "".to_string(),
-                "".to_string(),
+            .import_view_only_account(
+                &(*alice_view_account_key.view_private_key()).into(),
+                &(*alice_view_account_key.spend_public_key()).into(),
+                None,
+                None,
+                None,
+                false,
+            )
+            .unwrap();
+        let alice_account_id = AccountID(alice.id.clone());
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &[alice_account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let bob = service
+            .create_account(
+                Some("Bob's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let bob_account_key: AccountKey = mc_util_serial::decode(&bob.account_key).unwrap();
+        let bob_account_id = AccountID::from(&bob_account_key);
+        let bob_address = b58_encode_public_address(&bob_account_key.default_subaddress()).unwrap();
+
+        let unsigned_tx_proposal = service
+            .build_transaction(
+                &alice.id,
+                &[(bob_address, AmountJSON::new(42 * MOB, Mob::ID))],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::Empty,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+
+        // Export the unsigned proposal as JSON and read it back on the signing side.
+        let exported = serde_json::to_string(
+            &crate::json_rpc::v2::models::tx_proposal::UnsignedTxProposal::try_from(
+                &unsigned_tx_proposal,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let imported: crate::json_rpc::v2::models::tx_proposal::UnsignedTxProposal =
+            serde_json::from_str(&exported).unwrap();
+        let imported = UnsignedTxProposal::try_from(&imported).unwrap();
+        assert_eq!(imported, unsigned_tx_proposal);
+
+        let tx_proposal = imported.sign_with_local_signer(&alice_account_key).unwrap();
+
+        let (transaction_log, _, _) = service
+            .submit_transaction(&tx_proposal, None, Some(alice.id.clone()), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(transaction_log.account_id, alice.id);
+
+        {
+            let key_images: Vec<KeyImage> = tx_proposal
+                .input_txos
+                .iter()
+                .map(|txo| txo.key_image)
+                .collect();
+
+            add_block_with_tx_outs(
+                &mut ledger_db,
+                &[
+                    tx_proposal.change_txos[0].tx_out.clone(),
+                    tx_proposal.payload_txos[0].tx_out.clone(),
+                ],
+                &key_images,
+                &mut rng,
+            );
+        }
+
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &bob_account_id,
+            &logger,
+        );
+
+        let bob_balance = service.get_balance_for_account(&bob_account_id).unwrap();
+        let bob_balance_pmob = bob_balance.get(&Mob::ID).unwrap();
+        assert_eq!(bob_balance_pmob.unspent, 42 * MOB as u128);
+    }
+
+    #[async_test_with_logger]
+    async fn test_maximum_inputs_and_outputs(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        // Create our main account for the wallet
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+
+        // Add a block with a transaction for Alice
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        let alice_public_address = alice_account_key.default_subaddress();
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_public_address.clone()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        // test ouputs
+        let mut outputs = Vec::new();
+        for _ in 0..17 {
+            outputs.push((
+                b58_encode_public_address(&alice_public_address).unwrap(),
+                AmountJSON::new(42 * MOB, Mob::ID),
+            ));
+        }
+        match service
+            .build_and_sign_transaction(
+                &alice.id,
+                &outputs,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+        {
+            Ok(_) => {
+                panic!("Should not be able to build transaction with too many ouputs")
+            }
+            Err(TransactionServiceError::TransactionBuilder(
+                WalletTransactionBuilderError::InvalidArgument(_),
+            )) => {}
+            Err(e) => panic!("Unexpected error {:?}", e),
+        };
+
+        // test inputs
+        let mut outputs = Vec::new();
+        for _ in 0..2 {
+            outputs.push((
+                b58_encode_public_address(&alice_public_address).unwrap(),
+                AmountJSON::new(42 * MOB, Mob::ID),
+            ));
+        }
+        let mut inputs = Vec::new();
+        for _ in 0..17 {
+            inputs.push("fake txo id".to_string());
+        }
+        match service
+            .build_and_sign_transaction(
+                &alice.id,
+                &outputs,
+                Some(&inputs),
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+        {
+            Ok(_) => {
+                panic!("Should not be able to build transaction with too many inputs")
+            }
+            Err(TransactionServiceError::TransactionBuilder(
+                WalletTransactionBuilderError::InvalidArgument(_),
+            )) => {}
+            Err(e) => panic!("Unexpected error {:?}", e),
+        };
+    }
+
+    // Test sending a transaction from Alice -> Bob, and then from Bob -> Alice
+    #[async_test_with_logger]
+    async fn test_send_transaction_with_sender_memo_cred_subaddress_index(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        // Create our main account for the wallet
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
                 false,
             )
             .unwrap();
@@ -1420,11 +2109,13 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
                 TransactionMemo::RTH {
                     subaddress_index: Some(alice_address_from_bob.subaddress_index as u64),
                 },
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -1612,12 +2303,14 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
                 TransactionMemo::RTHWithPaymentRequestId {
                     subaddress_index: Some(alice_address_from_bob.subaddress_index as u64),
                     payment_request_id,
                 },
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -1824,11 +2517,13 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
                 TransactionMemo::RTH {
                     subaddress_index: Some(alice_subaddress.subaddress_index as u64),
                 },
                 None,
                 Some(alice_subaddress.public_address_b58.clone()),
+                None,
             )
             .await
             .unwrap();
@@ -1987,11 +2682,13 @@ mod tests {
                     None,
                     None,
                     None,
+                    None,
                     TransactionMemo::RTH {
                         subaddress_index: Some(alice_subaddress.subaddress_index as u64),
                     },
                     None,
                     Some(alice_subaddress.public_address_b58.clone()),
+                    None,
                 )
                 .await;
             match res {
@@ -2024,4 +2721,1318 @@ mod tests {
         let balance_pmob = balance.get(&Mob::ID).unwrap();
         assert_eq!(balance_pmob.unspent, (242 * MOB) as u128);
     }
+
+    #[async_test_with_logger]
+    async fn test_send_transaction_with_change_subaddress(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        let mut pooled_conn = service.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let account = service
+            .create_account(
+                Some("Segregated Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+        let account_id = AccountID::from(&account_key);
+
+        // Fund the main address, and keep a separate subaddress for change
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &account_id,
+            &logger,
+        );
+        let change_subaddress = service
+            .assign_address_for_account(&account_id, Some("Change for segregated funds"))
+            .unwrap();
+
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        let recipient_b58 = b58_encode_public_address(&recipient).unwrap();
+
+        // A subaddress that has not been assigned cannot receive the change
+        assert!(service
+            .build_transaction(
+                &account.id,
+                &[(recipient_b58.clone(), AmountJSON::new(42 * MOB, Mob::ID))],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::Empty,
+                None,
+                None,
+                Some("100".to_string()),
+            )
+            .is_err());
+
+        let (transaction_log, _associated_txos, _value_map, tx_proposal) = service
+            .build_sign_and_submit_transaction(
+                &account.id,
+                &[(recipient_b58, AmountJSON::new(42 * MOB, Mob::ID))],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::Empty,
+                None,
+                None,
+                Some(change_subaddress.subaddress_index.to_string()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            tx_proposal.change_txos[0].recipient_public_address,
+            change_subaddress.clone().public_address().unwrap()
+        );
+
+        {
+            let key_images: Vec<KeyImage> = tx_proposal
+                .input_txos
+                .iter()
+                .map(|txo| txo.key_image)
+                .collect();
+
+            add_block_with_tx_outs(
+                &mut ledger_db,
+                &[
+                    tx_proposal.change_txos[0].tx_out.clone(),
+                    tx_proposal.payload_txos[0].tx_out.clone(),
+                ],
+                &key_images,
+                &mut rng,
+            );
+        }
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &account_id,
+            &logger,
+        );
+
+        // The change txo was received at the chosen subaddress
+        let change = transaction_log.get_associated_txos(conn).unwrap().change;
+        assert_eq!(change.len(), 1);
+        let change_txo = Txo::get(&change[0].0.id, conn).unwrap();
+        assert_eq!(
+            change_txo.subaddress_index,
+            Some(change_subaddress.subaddress_index)
+        );
+
+        let balance = service
+            .get_balance_for_address(&change_subaddress.public_address_b58)
+            .unwrap();
+        let balance_pmob = balance.get(&Mob::ID).unwrap();
+        assert_eq!(balance_pmob.unspent, (58 * MOB - Mob::MINIMUM_FEE) as u128);
+    }
+
+    #[async_test_with_logger]
+    async fn test_balance_separates_change_from_received(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let account = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+        let account_id = AccountID::from(&account_key);
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &account_id,
+            &logger,
+        );
+
+        // Everything held so far was received from others
+        let balance = service.get_balance_for_account(&account_id).unwrap();
+        let balance_pmob = balance.get(&Mob::ID).unwrap();
+        assert_eq!(balance_pmob.change_unspent, 0);
+        assert_eq!(balance_pmob.received_unspent, 100 * MOB as u128);
+
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        let recipient_b58 = b58_encode_public_address(&recipient).unwrap();
+        let (_transaction_log, _associated_txos, _value_map, tx_proposal) = service
+            .build_sign_and_submit_transaction(
+                &account.id,
+                &[(recipient_b58, AmountJSON::new(42 * MOB, Mob::ID))],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::Empty,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        {
+            let key_images: Vec<KeyImage> = tx_proposal
+                .input_txos
+                .iter()
+                .map(|txo| txo.key_image)
+                .collect();
+
+            add_block_with_tx_outs(
+                &mut ledger_db,
+                &[
+                    tx_proposal.change_txos[0].tx_out.clone(),
+                    tx_proposal.payload_txos[0].tx_out.clone(),
+                ],
+                &key_images,
+                &mut rng,
+            );
+        }
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &account_id,
+            &logger,
+        );
+
+        // The change from the transaction is all that is left, and it is held on
+        // the change subaddress
+        let balance = service.get_balance_for_account(&account_id).unwrap();
+        let balance_pmob = balance.get(&Mob::ID).unwrap();
+        let change_value = (58 * MOB - Mob::MINIMUM_FEE) as u128;
+        assert_eq!(balance_pmob.unspent, change_value);
+        assert_eq!(balance_pmob.change_unspent, change_value);
+        assert_eq!(balance_pmob.received_unspent, 0);
+    }
+
+    #[async_test_with_logger]
+    async fn test_send_transaction_after_rotating_change_subaddress(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        let mut pooled_conn = service.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let account = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+        let account_id = AccountID::from(&account_key);
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &account_id,
+            &logger,
+        );
+
+        let change_subaddress = service.rotate_change_subaddress(&account_id).unwrap();
+        assert_eq!(
+            service
+                .get_account(&account_id)
+                .unwrap()
+                .change_subaddress_index,
+            Some(change_subaddress.subaddress_index)
+        );
+        assert!(change_subaddress.is_change);
+
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        let recipient_b58 = b58_encode_public_address(&recipient).unwrap();
+        let (transaction_log, _associated_txos, _value_map, tx_proposal) = service
+            .build_sign_and_submit_transaction(
+                &account.id,
+                &[(recipient_b58, AmountJSON::new(42 * MOB, Mob::ID))],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::Empty,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            tx_proposal.change_txos[0].recipient_public_address,
+            change_subaddress.clone().public_address().unwrap()
+        );
+
+        {
+            let key_images: Vec<KeyImage> = tx_proposal
+                .input_txos
+                .iter()
+                .map(|txo| txo.key_image)
+                .collect();
+
+            add_block_with_tx_outs(
+                &mut ledger_db,
+                &[
+                    tx_proposal.change_txos[0].tx_out.clone(),
+                    tx_proposal.payload_txos[0].tx_out.clone(),
+                ],
+                &key_images,
+                &mut rng,
+            );
+        }
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &account_id,
+            &logger,
+        );
+
+        // The change was received at the new change subaddress, and is counted as
+        // change in the balance
+        let change = transaction_log.get_associated_txos(conn).unwrap().change;
+        assert_eq!(change.len(), 1);
+        let change_txo = Txo::get(&change[0].0.id, conn).unwrap();
+        assert_eq!(
+            change_txo.subaddress_index,
+            Some(change_subaddress.subaddress_index)
+        );
+
+        let balance = service.get_balance_for_account(&account_id).unwrap();
+        let balance_pmob = balance.get(&Mob::ID).unwrap();
+        assert_eq!(
+            balance_pmob.change_unspent,
+            (58 * MOB - Mob::MINIMUM_FEE) as u128
+        );
+    }
+
+    #[async_test_with_logger]
+    async fn test_estimate_fee_matches_built_transaction(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        let alice_public_address = alice_account_key.default_subaddress();
+
+        // Two deposits, so that sending more than either one requires two inputs.
+        for _ in 0..2 {
+            add_block_to_ledger_db(
+                &mut ledger_db,
+                &vec![alice_public_address.clone()],
+                100 * MOB,
+                &[KeyImage::from(rng.next_u64())],
+                &mut rng,
+            );
+        }
+
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let bob = service
+            .create_account(
+                Some("Bob's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let bob_address = service
+            .assign_address_for_account(&AccountID(bob.id.clone()), Some("From Alice"))
+            .unwrap();
+
+        let amount = AmountJSON::new(150 * MOB, Mob::ID);
+        let estimate = service.estimate_fee(&alice.id, &amount, None).unwrap();
+        assert_eq!(estimate.fee, Amount::new(Mob::MINIMUM_FEE, Mob::ID));
+        assert_eq!(estimate.num_inputs, 2);
+        assert_eq!(estimate.num_outputs, 2);
+        assert!(estimate.sufficient_funds);
+
+        // The estimate matches what a subsequent build actually charges.
+        let tx_proposal = service
+            .build_and_sign_transaction(
+                &alice.id,
+                &[(bob_address.public_address_b58, amount)],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(tx_proposal.tx.prefix.fee, estimate.fee.value);
+        assert_eq!(tx_proposal.tx.prefix.fee_token_id, *estimate.fee.token_id);
+        assert_eq!(
+            tx_proposal.tx.prefix.inputs.len() as u64,
+            estimate.num_inputs
+        );
+        assert_eq!(
+            tx_proposal.tx.prefix.outputs.len() as u64,
+            estimate.num_outputs
+        );
+
+        // More than the account holds cannot be covered.
+        let estimate = service
+            .estimate_fee(&alice.id, &AmountJSON::new(500 * MOB, Mob::ID), None)
+            .unwrap();
+        assert_eq!(estimate.fee, Amount::new(Mob::MINIMUM_FEE, Mob::ID));
+        assert!(!estimate.sufficient_funds);
+    }
+
+    #[test_with_logger]
+    fn test_estimate_fee_rejects_unusable_inputs(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let mut txo_ids = Vec::new();
+        let mut account_ids = Vec::new();
+        for name in ["Alice", "Bob"] {
+            let account = service
+                .create_account(
+                    Some(name.to_string()),
+                    "".to_string(),
+                    "".to_string(),
+                    false,
+                )
+                .unwrap();
+            let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+            for _ in 0..2 {
+                add_block_to_ledger_db(
+                    &mut ledger_db,
+                    &[account_key.default_subaddress()],
+                    100 * MOB,
+                    &[KeyImage::from(rng.next_u64())],
+                    &mut rng,
+                );
+            }
+            let account_id = AccountID::from(&account_key);
+            manually_sync_account(
+                &ledger_db,
+                service.wallet_db.as_ref().unwrap(),
+                &account_id,
+                &logger,
+            );
+            let txos = service
+                .list_txos(
+                    Some(account_id.to_string()),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+            txo_ids.push(
+                txos.into_iter()
+                    .map(|txo_info| txo_info.txo.id)
+                    .collect::<Vec<_>>(),
+            );
+            account_ids.push(account_id.to_string());
+        }
+        let (alice_id, alice_txo_ids, bob_txo_ids) = (&account_ids[0], &txo_ids[0], &txo_ids[1]);
+        let amount = AmountJSON::new(10 * MOB, Mob::ID);
+
+        // The account's own unspent Txos can be used.
+        let estimate = service
+            .estimate_fee(alice_id, &amount, Some(&vec![alice_txo_ids[0].clone()]))
+            .unwrap();
+        assert_eq!(estimate.num_inputs, 1);
+        assert_eq!(estimate.num_outputs, 2);
+        assert!(estimate.sufficient_funds);
+
+        // Another account's Txo cannot.
+        match service.estimate_fee(alice_id, &amount, Some(&vec![bob_txo_ids[0].clone()])) {
+            Err(TransactionServiceError::InputTxoNotOwnedByAccount(txo_id, account_id)) => {
+                assert_eq!(&txo_id, &bob_txo_ids[0]);
+                assert_eq!(&account_id, alice_id);
+            }
+            result => panic!("Expected InputTxoNotOwnedByAccount, got {result:?}"),
+        }
+
+        // Nor can a spent one.
+        Txo::update_spent_block_index(
+            &alice_txo_ids[1],
+            ledger_db.num_blocks().unwrap(),
+            service.get_pooled_conn().unwrap().deref_mut(),
+        )
+        .unwrap();
+        match service.estimate_fee(alice_id, &amount, Some(&vec![alice_txo_ids[1].clone()])) {
+            Err(TransactionServiceError::InputTxoNotSpendable(txo_id, status)) => {
+                assert_eq!(&txo_id, &alice_txo_ids[1]);
+                assert_eq!(status, TxoStatus::Spent.to_string());
+            }
+            result => panic!("Expected InputTxoNotSpendable, got {result:?}"),
+        }
+
+        // Inputs must be of the token being sent.
+        let other_token = AmountJSON::new(10 * MOB, TokenId::from(1));
+        match service.estimate_fee(
+            alice_id,
+            &other_token,
+            Some(&vec![alice_txo_ids[0].clone()]),
+        ) {
+            Err(TransactionServiceError::InputTxoTokenMismatch(txo_id, 0, 1)) => {
+                assert_eq!(&txo_id, &alice_txo_ids[0]);
+            }
+            result => panic!("Expected InputTxoTokenMismatch, got {result:?}"),
+        }
+
+        // Each input can only be listed once.
+        match service.estimate_fee(
+            alice_id,
+            &amount,
+            Some(&vec![alice_txo_ids[0].clone(), alice_txo_ids[0].clone()]),
+        ) {
+            Err(TransactionServiceError::DuplicateInputTxo(txo_id)) => {
+                assert_eq!(&txo_id, &alice_txo_ids[0]);
+            }
+            result => panic!("Expected DuplicateInputTxo, got {result:?}"),
+        }
+    }
+
+    #[async_test_with_logger]
+    async fn test_build_transaction_uses_account_default_fee(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        let alice_public_address = alice_account_key.default_subaddress();
+
+        // One deposit per build, since each build reserves its inputs.
+        for _ in 0..2 {
+            add_block_to_ledger_db(
+                &mut ledger_db,
+                &vec![alice_public_address.clone()],
+                100 * MOB,
+                &[KeyImage::from(rng.next_u64())],
+                &mut rng,
+            );
+        }
+
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let default_fee = 3 * Mob::MINIMUM_FEE;
+        let alice = service
+            .set_account_default_fee(&alice_account_id, Some(default_fee))
+            .unwrap();
+        assert_eq!(alice.default_fee, Some(default_fee as i64));
+
+        let bob_address =
+            b58_encode_public_address(&AccountKey::random(&mut rng).default_subaddress()).unwrap();
+
+        let unsigned_tx_proposal = service
+            .build_transaction(
+                &alice.id,
+                &[(bob_address.clone(), AmountJSON::new(42 * MOB, Mob::ID))],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::Empty,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(unsigned_tx_proposal.unsigned_tx.tx_prefix.fee, default_fee);
+
+        let estimate = service
+            .estimate_fee(&alice.id, &AmountJSON::new(42 * MOB, Mob::ID), None)
+            .unwrap();
+        assert_eq!(estimate.fee, Amount::new(default_fee, Mob::ID));
+
+        // An explicit fee takes precedence over the account's default fee.
+        let unsigned_tx_proposal = service
+            .build_transaction(
+                &alice.id,
+                &[(bob_address, AmountJSON::new(42 * MOB, Mob::ID))],
+                None,
+                None,
+                Some((2 * Mob::MINIMUM_FEE).to_string()),
+                None,
+                None,
+                None,
+                TransactionMemo::Empty,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            unsigned_tx_proposal.unsigned_tx.tx_prefix.fee,
+            2 * Mob::MINIMUM_FEE
+        );
+    }
+
+    // A single transaction can pay several recipients at once.
+    #[async_test_with_logger]
+    async fn test_build_transaction_multiple_recipients(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        let alice_public_address = alice_account_key.default_subaddress();
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_public_address],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let recipients: Vec<(String, AmountJSON)> = (0..3)
+            .map(|i| {
+                let public_address = AccountKey::random(&mut rng).default_subaddress();
+                (
+                    b58_encode_public_address(&public_address).unwrap(),
+                    AmountJSON::new((10 + i) * MOB, Mob::ID),
+                )
+            })
+            .collect();
+
+        let tx_proposal = service
+            .build_and_sign_transaction(
+                &alice.id,
+                &recipients,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        // One payload txo per recipient, plus change.
+        assert_eq!(tx_proposal.payload_txos.len(), 3);
+        assert_eq!(tx_proposal.change_txos.len(), 1);
+        assert_eq!(tx_proposal.tx.prefix.outputs.len(), 4);
+
+        for ((address, amount), payload_txo) in
+            recipients.iter().zip(tx_proposal.payload_txos.iter())
+        {
+            assert_eq!(
+                &b58_encode_public_address(&payload_txo.recipient_public_address).unwrap(),
+                address
+            );
+            assert_eq!(payload_txo.amount, Amount::try_from(amount).unwrap());
+        }
+
+        // The account is debited the sum of the outlays plus the fee.
+        let input_value: u64 = tx_proposal
+            .input_txos
+            .iter()
+            .map(|txo| txo.amount.value)
+            .sum();
+        let payload_value: u64 = tx_proposal
+            .payload_txos
+            .iter()
+            .map(|txo| txo.amount.value)
+            .sum();
+        let change_value = tx_proposal.change_txos[0].amount.value;
+        assert_eq!(input_value, 100 * MOB);
+        assert_eq!(payload_value, 33 * MOB);
+        assert_eq!(
+            input_value - change_value,
+            payload_value + tx_proposal.tx.prefix.fee
+        );
+        assert_eq!(tx_proposal.tx.prefix.fee, Mob::MINIMUM_FEE);
+    }
+
+    #[async_test_with_logger]
+    async fn test_validate_transaction_expired_tombstone(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        let alice_public_address = alice_account_key.default_subaddress();
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_public_address.clone()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        let tx_proposal = service
+            .build_and_sign_transaction(
+                &alice.id,
+                &[(
+                    b58_encode_public_address(&recipient).unwrap(),
+                    AmountJSON::new(42 * MOB, Mob::ID),
+                )],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        // A freshly built proposal is valid, since building does not make its inputs
+        // pending.
+        assert_eq!(service.validate_transaction(&tx_proposal).unwrap(), vec![]);
+
+        // Let the ledger grow past the tombstone block.
+        let tombstone_block = tx_proposal.tx.prefix.tombstone_block;
+        while ledger_db.num_blocks().unwrap() < tombstone_block {
+            add_block_to_ledger_db(
+                &mut ledger_db,
+                &vec![recipient.clone()],
+                MOB,
+                &[KeyImage::from(rng.next_u64())],
+                &mut rng,
+            );
+        }
+
+        assert_eq!(
+            service.validate_transaction(&tx_proposal).unwrap(),
+            vec![TransactionValidationProblem::TombstoneExpired {
+                tombstone_block,
+                num_blocks: tombstone_block,
+            }]
+        );
+    }
+
+    #[async_test_with_logger]
+    async fn test_validate_transaction_spent_input(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        let alice_public_address = alice_account_key.default_subaddress();
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_public_address.clone()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        let tx_proposal = service
+            .build_and_sign_transaction(
+                &alice.id,
+                &[(
+                    b58_encode_public_address(&recipient).unwrap(),
+                    AmountJSON::new(42 * MOB, Mob::ID),
+                )],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(tx_proposal.input_txos.len(), 1);
+
+        // Spend the input in another transaction before this one is submitted.
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![recipient],
+            MOB,
+            &[tx_proposal.input_txos[0].key_image],
+            &mut rng,
+        );
+
+        let input_txo_id = TxoID::from(&tx_proposal.input_txos[0].tx_out).to_string();
+        assert_eq!(
+            service.validate_transaction(&tx_proposal).unwrap(),
+            vec![TransactionValidationProblem::InputSpent(input_txo_id)]
+        );
+    }
+
+    #[async_test_with_logger]
+    async fn test_validate_transaction_checks_network_fee(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        // Build paying the network fee, then raise the account's default fee.
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        let tx_proposal = service
+            .build_and_sign_transaction(
+                &alice.id,
+                &[(
+                    b58_encode_public_address(&recipient).unwrap(),
+                    AmountJSON::new(42 * MOB, Mob::ID),
+                )],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(service.validate_transaction(&tx_proposal).unwrap(), vec![]);
+
+        // A fee below the account's default fee is still valid, as long as it meets
+        // the network minimum.
+        service
+            .set_account_default_fee(&alice_account_id, Some(2 * Mob::MINIMUM_FEE))
+            .unwrap();
+        assert_eq!(service.validate_transaction(&tx_proposal).unwrap(), vec![]);
+
+        let mut low_fee_tx_proposal = tx_proposal.clone();
+        low_fee_tx_proposal.tx.prefix.fee = Mob::MINIMUM_FEE - 1;
+        assert_eq!(
+            service.validate_transaction(&low_fee_tx_proposal).unwrap(),
+            vec![TransactionValidationProblem::FeeTooLow {
+                fee: Mob::MINIMUM_FEE - 1,
+                minimum_fee: Mob::MINIMUM_FEE,
+                token_id: Mob::ID,
+            }]
+        );
+    }
+
+    #[async_test_with_logger]
+    async fn test_validate_transaction_pending_input(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        let tx_proposal = service
+            .build_and_sign_transaction(
+                &alice.id,
+                &[(
+                    b58_encode_public_address(&recipient).unwrap(),
+                    AmountJSON::new(42 * MOB, Mob::ID),
+                )],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(tx_proposal.input_txos.len(), 1);
+
+        // Once the transaction is submitted, its input is committed to it.
+        TransactionLog::log_submitted(
+            &tx_proposal,
+            ledger_db.num_blocks().unwrap(),
+            "".to_string(),
+            &alice.id,
+            service.get_pooled_conn().unwrap().deref_mut(),
+        )
+        .unwrap();
+
+        let input_txo_id = TxoID::from(&tx_proposal.input_txos[0].tx_out).to_string();
+        assert_eq!(
+            service.validate_transaction(&tx_proposal).unwrap(),
+            vec![TransactionValidationProblem::InputNotUnspent {
+                txo_id: input_txo_id,
+                status: TxoStatus::Pending.to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_is_connection_error() {
+        let status_error = |code: RpcStatusCode| {
+            mc_connection::Error::Grpc(grpcio::Error::RpcFailure(grpcio::RpcStatus::new(code)))
+        };
+
+        assert!(is_connection_error(&status_error(
+            RpcStatusCode::UNAVAILABLE
+        )));
+        assert!(is_connection_error(&status_error(
+            RpcStatusCode::DEADLINE_EXCEEDED
+        )));
+        assert!(is_connection_error(&mc_connection::Error::Grpc(
+            grpcio::Error::RemoteStopped
+        )));
+
+        // The node answered, so trying another node would not help.
+        assert!(!is_connection_error(&status_error(
+            RpcStatusCode::INVALID_ARGUMENT
+        )));
+        assert!(!is_connection_error(&status_error(RpcStatusCode::INTERNAL)));
+        assert!(!is_connection_error(&mc_connection::Error::Other(
+            "rejected".to_string()
+        )));
+    }
+
+    #[test_with_logger]
+    fn test_submit_node_candidates_skip_lagging_node(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+        let lagging_ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        // Move the healthy node several blocks ahead of the lagging one.
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        for _ in 0..3 {
+            add_block_to_ledger_db(
+                &mut ledger_db,
+                &vec![recipient.clone()],
+                100 * MOB,
+                &[KeyImage::from(rng.next_u64())],
+                &mut rng,
+            );
+        }
+
+        let healthy_peer = setup_mock_peer(1, ledger_db.clone());
+        let lagging_peer = setup_mock_peer(2, lagging_ledger_db);
+        let healthy_id = healthy_peer.uri().host_and_port_responder_id().unwrap();
+
+        let service = setup_wallet_service_with_peers(
+            ledger_db.clone(),
+            vec![healthy_peer, lagging_peer],
+            logger.clone(),
+        );
+
+        // Only the healthy node is chosen, wherever the round-robin starts.
+        for _ in 0..4 {
+            assert_eq!(service.submit_node_candidates(), vec![healthy_id.clone()]);
+        }
+
+        // With two healthy nodes, one that could not be reached is tried last until
+        // it accepts a submission again.
+        let peer1 = setup_mock_peer(1, ledger_db.clone());
+        let peer2 = setup_mock_peer(2, ledger_db.clone());
+        let peer1_id = peer1.uri().host_and_port_responder_id().unwrap();
+        let peer2_id = peer2.uri().host_and_port_responder_id().unwrap();
+        let service = setup_wallet_service_with_peers(ledger_db, vec![peer1, peer2], logger);
+
+        service.record_submit_node_result(&peer1_id, false);
+        for _ in 0..4 {
+            assert_eq!(
+                service.submit_node_candidates(),
+                vec![peer2_id.clone(), peer1_id.clone()]
+            );
+        }
+
+        service.record_submit_node_result(&peer1_id, true);
+        let first_choices: Vec<_> = (0..2)
+            .map(|_| service.submit_node_candidates()[0].clone())
+            .collect();
+        assert!(first_choices.contains(&peer1_id));
+        assert!(first_choices.contains(&peer2_id));
+    }
+
+    #[async_test_with_logger]
+    async fn test_submit_transaction_with_idempotency_key(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+
+        // Fund a txo for each of the two transactions, so that they spend
+        // different inputs.
+        for _ in 0..2 {
+            add_block_to_ledger_db(
+                &mut ledger_db,
+                &[alice_account_key.default_subaddress()],
+                100 * MOB,
+                &[KeyImage::from(rng.next_u64())],
+                &mut rng,
+            );
+        }
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let bob_address =
+            b58_encode_public_address(&AccountKey::random(&mut rng).default_subaddress()).unwrap();
+        let mut tx_proposals = Vec::new();
+        for value in [42 * MOB, 43 * MOB] {
+            let tx_proposal = service
+                .build_and_sign_transaction(
+                    &alice.id,
+                    &[(bob_address.clone(), AmountJSON::new(value, Mob::ID))],
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    TransactionMemo::Empty,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap();
+            tx_proposals.push(tx_proposal);
+        }
+
+        let idempotency_key = Some("payment-1".to_string());
+        let (first_log, first_associated_txos, first_value_map) = service
+            .submit_transaction(
+                &tx_proposals[0],
+                None,
+                Some(alice.id.clone()),
+                idempotency_key.clone(),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(first_log.idempotency_key, idempotency_key);
+
+        // A retry after the network has moved on returns the original log untouched.
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &[AccountKey::random(&mut rng).default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        let (second_log, second_associated_txos, second_value_map) = service
+            .submit_transaction(
+                &tx_proposals[0],
+                None,
+                Some(alice.id.clone()),
+                idempotency_key.clone(),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(second_log, first_log);
+        assert_eq!(second_associated_txos, first_associated_txos);
+        assert_eq!(second_value_map, first_value_map);
+
+        let mut pooled_conn = service.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+        let transaction_logs =
+            TransactionLog::list_all(Some(alice.id.clone()), None, None, None, None, conn).unwrap();
+        assert_eq!(transaction_logs.len(), 1);
+
+        // The key cannot be reused for a different transaction.
+        match service.submit_transaction(
+            &tx_proposals[1],
+            None,
+            Some(alice.id.clone()),
+            idempotency_key.clone(),
+        ) {
+            Err(TransactionServiceError::IdempotencyKeyConflict(key)) => {
+                assert_eq!(Some(key), idempotency_key)
+            }
+            result => panic!("Expected IdempotencyKeyConflict, got {result:?}"),
+        }
+        let second_transaction_log = TransactionLog::get(
+            &TransactionId::try_from(&tx_proposals[1]).unwrap(),
+            service.get_pooled_conn().unwrap().deref_mut(),
+        )
+        .unwrap();
+        assert_eq!(second_transaction_log.submitted_block_index, None);
+        assert_eq!(second_transaction_log.idempotency_key, None);
+
+        // A key held by a transaction whose submission has not completed is
+        // reserved against other transactions, and a retry of the same
+        // transaction submits it.
+        let reserved_key = "payment-2".to_string();
+        second_transaction_log
+            .update_idempotency_key(
+                &reserved_key,
+                service.get_pooled_conn().unwrap().deref_mut(),
+            )
+            .unwrap();
+        match service.submit_transaction(
+            &tx_proposals[0],
+            None,
+            Some(alice.id.clone()),
+            Some(reserved_key.clone()),
+        ) {
+            Err(TransactionServiceError::IdempotencyKeyConflict(key)) => {
+                assert_eq!(key, reserved_key)
+            }
+            result => panic!("Expected IdempotencyKeyConflict, got {result:?}"),
+        }
+        let (retried_log, _, _) = service
+            .submit_transaction(
+                &tx_proposals[1],
+                None,
+                Some(alice.id.clone()),
+                Some(reserved_key.clone()),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(retried_log.id, second_transaction_log.id);
+        assert!(retried_log.submitted_block_index.is_some());
+        assert_eq!(retried_log.idempotency_key, Some(reserved_key));
+
+        // Without an account the key cannot be stored.
+        match service.submit_transaction(&tx_proposals[1], None, None, idempotency_key) {
+            Err(TransactionServiceError::IdempotencyKeyWithoutAccount) => {}
+            result => panic!("Expected IdempotencyKeyWithoutAccount, got {result:?}"),
+        }
+    }
 }
@@ -8,7 +8,10 @@
 //! This module, on the other hand, builds a transaction within the context of
 //! the wallet.
 
-use super::models::tx_proposal::{OutputTxo, UnsignedInputTxo, UnsignedTxProposal};
+use super::models::{
+    ledger::LedgerSnapshot,
+    tx_proposal::{OutputTxo, UnsignedInputTxo, UnsignedTxProposal},
+};
 use crate::{
     db::{
         account::{AccountID, AccountModel},
@@ -42,16 +45,25 @@ use std::{collections::BTreeMap, str::FromStr, sync::Arc};
 
 /// Default number of blocks used for calculating transaction tombstone block
 /// number.
-// TODO support for making this configurable
 pub const DEFAULT_NEW_TX_BLOCK_ATTEMPTS: u64 = 10;
 
+/// Where the builder gets the ledger data (block height, input membership
+/// proofs and ring mixins) it needs.
+enum LedgerSource {
+    /// A full, live ledger.
+    LedgerDB(LedgerDB),
+
+    /// A snapshot of just the parts of the ledger needed for this transaction.
+    Snapshot(LedgerSnapshot),
+}
+
 /// A builder of transactions constructed from this wallet.
 pub struct WalletTransactionBuilder<FPR: FogPubkeyResolver + 'static> {
     /// Account ID (hex-encoded) from which to construct a transaction.
     account_id_hex: String,
 
-    /// The ledger DB.
-    ledger_db: LedgerDB,
+    /// The ledger DB, or a snapshot of the parts of it that are needed.
+    ledger: LedgerSource,
 
     /// Optional inputs specified to use to construct the transaction.
     inputs: Vec<Txo>,
@@ -63,6 +75,10 @@ pub struct WalletTransactionBuilder<FPR: FogPubkeyResolver + 'static> {
     /// The block after which this transaction is invalid.
     tombstone: u64,
 
+    /// How many blocks past the current block height the default tombstone
+    /// block is set to.
+    tombstone_horizon: u64,
+
     /// The fee for the transaction.
     fee: Option<(u64, TokenId)>,
 
@@ -78,6 +94,13 @@ pub struct WalletTransactionBuilder<FPR: FogPubkeyResolver + 'static> {
     /// Subaddress (index) from which to restrict TXOs for spending
     /// (optional).
     subaddress_index_to_spend_from: Option<u64>,
+
+    /// Txos which `select_txos` must not choose.
+    excluded_txo_ids: Vec<String>,
+
+    /// Subaddress (index) to send change to, overriding the default
+    /// (optional).
+    change_subaddress_index: Option<u64>,
 }
 
 impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
@@ -86,17 +109,48 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
         account_id_hex: String,
         ledger_db: LedgerDB,
         fog_resolver_factory: Arc<dyn Fn(&[FogUri]) -> Result<FPR, String> + Send + Sync + 'static>,
+    ) -> Self {
+        Self::new_with_ledger_source(
+            account_id_hex,
+            LedgerSource::LedgerDB(ledger_db),
+            fog_resolver_factory,
+        )
+    }
+
+    /// Create a builder that uses a ledger snapshot instead of a full LedgerDB,
+    /// for building a transaction on a machine without a live ledger.
+    #[allow(clippy::type_complexity)]
+    pub fn new_from_ledger_snapshot(
+        account_id_hex: String,
+        ledger_snapshot: LedgerSnapshot,
+        fog_resolver_factory: Arc<dyn Fn(&[FogUri]) -> Result<FPR, String> + Send + Sync + 'static>,
+    ) -> Self {
+        Self::new_with_ledger_source(
+            account_id_hex,
+            LedgerSource::Snapshot(ledger_snapshot),
+            fog_resolver_factory,
+        )
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn new_with_ledger_source(
+        account_id_hex: String,
+        ledger: LedgerSource,
+        fog_resolver_factory: Arc<dyn Fn(&[FogUri]) -> Result<FPR, String> + Send + Sync + 'static>,
     ) -> Self {
         WalletTransactionBuilder {
             account_id_hex,
-            ledger_db,
+            ledger,
             inputs: vec![],
             outlays: vec![],
             tombstone: 0,
+            tombstone_horizon: DEFAULT_NEW_TX_BLOCK_ATTEMPTS,
             fee: None,
             block_version: None,
             fog_resolver_factory,
             subaddress_index_to_spend_from: None,
+            excluded_txo_ids: vec![],
+            change_subaddress_index: None,
         }
     }
 
@@ -109,6 +163,18 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
         Ok(())
     }
 
+    /// Sets the subaddress to send change to. Without it, change goes back to
+    /// the spend subaddress if one is set, or else to the reserved change
+    /// subaddress.
+    pub fn set_change_subaddress(&mut self, subaddress_index: u64) {
+        self.change_subaddress_index = Some(subaddress_index);
+    }
+
+    /// Prevents `select_txos` from choosing the txos with the given txo_ids.
+    pub fn exclude_txos(&mut self, txo_ids: &[String]) {
+        self.excluded_txo_ids = txo_ids.to_vec();
+    }
+
     /// Sets inputs to the txos associated with the given txo_ids. Only unspent
     /// txos are included.
     pub fn set_txos(
@@ -151,6 +217,8 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
             .and_modify(|v| *v += fee_value as u128)
             .or_insert(fee_value as u128);
 
+        // Each token is funded only by inputs of that token.
+        let mut inputs = Vec::new();
         for (token_id, target_value) in outlay_value_sum_map {
             let fee_value = if token_id == fee_token_id {
                 fee_value
@@ -168,20 +236,30 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
                     None
                 };
 
-            self.inputs = Txo::select_spendable_txos_for_value(
+            inputs.extend(Txo::select_spendable_txos_for_value(
                 &self.account_id_hex,
                 target_value,
                 max_spendable_value,
                 spend_subaddress.as_deref(),
+                &self.excluded_txo_ids,
                 *token_id,
                 fee_value,
                 conn,
-            )?;
+            )?);
         }
+        self.inputs = inputs;
 
         Ok(())
     }
 
+    /// Reserves the inputs, so that they are not selected for another
+    /// transaction before this one is submitted.
+    pub fn reserve_inputs(&self, conn: Conn) -> Result<(), WalletTransactionBuilderError> {
+        let input_txo_ids: Vec<String> = self.inputs.iter().map(|txo| txo.id.clone()).collect();
+        Txo::reserve(&input_txo_ids, conn)?;
+        Ok(())
+    }
+
     pub fn add_recipient(
         &mut self,
         recipient: PublicAddress,
@@ -210,12 +288,31 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
         self.block_version = Some(block_version);
     }
 
+    /// Set the number of blocks past the current block height that
+    /// `set_tombstone(0)` picks as the tombstone block.
+    pub fn set_tombstone_horizon(&mut self, tombstone_horizon: u64) {
+        self.tombstone_horizon = tombstone_horizon;
+    }
+
+    /// Set the block after which this transaction is invalid. Setting to 0
+    /// picks the current block height plus the tombstone horizon.
     pub fn set_tombstone(&mut self, tombstone: u64) -> Result<(), WalletTransactionBuilderError> {
+        let num_blocks_in_ledger = match &self.ledger {
+            LedgerSource::LedgerDB(ledger_db) => ledger_db.num_blocks()?,
+            LedgerSource::Snapshot(ledger_snapshot) => ledger_snapshot.num_blocks,
+        };
         let tombstone_block = if tombstone > 0 {
+            // The next block to be added has index num_blocks_in_ledger, so a
+            // tombstone at or below that could never be included in the ledger.
+            if tombstone <= num_blocks_in_ledger {
+                return Err(WalletTransactionBuilderError::TombstoneInPast(
+                    tombstone,
+                    num_blocks_in_ledger,
+                ));
+            }
             tombstone
         } else {
-            let num_blocks_in_ledger = self.ledger_db.num_blocks()?;
-            num_blocks_in_ledger + DEFAULT_NEW_TX_BLOCK_ATTEMPTS
+            num_blocks_in_ledger + self.tombstone_horizon
         };
         self.tombstone = tombstone_block;
         Ok(())
@@ -278,37 +375,32 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
             return Err(WalletTransactionBuilderError::NoInputs);
         }
 
-        // Get membership proofs for our inputs
-        let indexes = self
-            .inputs
+        let required_token_ids = self
+            .outlays
             .iter()
-            .map(|utxo| {
-                let txo = self.ledger_db.get_tx_out_by_index(
-                    self.ledger_db
-                        .get_tx_out_index_by_public_key(&utxo.public_key()?)?,
-                )?;
-                self.ledger_db.get_tx_out_index_by_hash(&txo.hash())
-            })
-            .collect::<Result<Vec<u64>, mc_ledger_db::Error>>()?;
-        let proofs = self.ledger_db.get_tx_out_proof_of_memberships(&indexes)?;
-
-        let inputs_and_proofs: Vec<(Txo, TxOutMembershipProof)> =
-            self.inputs.clone().into_iter().zip(proofs).collect();
+            .map(|(_, _, token_id)| *token_id)
+            .chain(std::iter::once(fee_token_id));
+        for token_id in required_token_ids {
+            if !self
+                .inputs
+                .iter()
+                .any(|txo| txo.token_id as u64 == *token_id)
+            {
+                return Err(WalletTransactionBuilderError::InsufficientFundsForToken(
+                    token_id.to_string(),
+                ));
+            }
+        }
 
-        let excluded_tx_out_indices: Vec<u64> = inputs_and_proofs
-            .iter()
-            .map(|(utxo, _membership_proof)| {
-                let txo = self.ledger_db.get_tx_out_by_index(
-                    self.ledger_db
-                        .get_tx_out_index_by_public_key(&utxo.public_key()?)?,
-                )?;
-                self.ledger_db
-                    .get_tx_out_index_by_hash(&txo.hash())
-                    .map_err(WalletTransactionBuilderError::LedgerDB)
-            })
-            .collect::<Result<Vec<u64>, WalletTransactionBuilderError>>()?;
+        let LedgerSnapshot { inputs, rings, .. } = self.ledger_snapshot()?;
 
-        let rings = self.get_rings(inputs_and_proofs.len(), &excluded_tx_out_indices)?;
+        let inputs_and_proofs: Vec<(Txo, TxOut, TxOutMembershipProof)> = self
+            .inputs
+            .clone()
+            .into_iter()
+            .zip(inputs)
+            .map(|(utxo, (tx_out, proof))| (utxo, tx_out, proof))
+            .collect();
 
         if rings.len() != inputs_and_proofs.len() {
             return Err(WalletTransactionBuilderError::RingSizeMismatch);
@@ -325,14 +417,10 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
             .collect();
 
         let mut unsigned_input_txos = Vec::new();
-        for (utxo, proof) in inputs_and_proofs.iter() {
+        for (utxo, db_tx_out, proof) in inputs_and_proofs.iter() {
             let subaddress_index = utxo.subaddress_index.ok_or_else(|| {
                 WalletTransactionBuilderError::CannotUseOrphanedTxoAsInput(utxo.id.clone())
             })?;
-            let db_tx_out = self.ledger_db.get_tx_out_by_index(
-                self.ledger_db
-                    .get_tx_out_index_by_public_key(&utxo.public_key()?)?,
-            )?;
 
             let (mut ring, mut membership_proofs) = rings_and_proofs
                 .pop()
@@ -342,7 +430,7 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
             }
 
             // Add the input to the ring.
-            let position_opt = ring.iter().position(|txo| *txo == db_tx_out);
+            let position_opt = ring.iter().position(|txo| txo == db_tx_out);
             let real_index = match position_opt {
                 Some(position) => {
                     // The input is already present in the ring.
@@ -375,7 +463,7 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
                 OneTimeKeyDeriveData::SubaddressIndex(subaddress_index as u64);
 
             let unsigned_input_txo = UnsignedInputTxo {
-                tx_out: db_tx_out,
+                tx_out: db_tx_out.clone(),
                 subaddress_index: subaddress_index as u64,
                 amount: Amount::new(utxo.value as u64, TokenId::from(utxo.token_id as u64)),
             };
@@ -418,7 +506,7 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
         let input_value_per_token =
             inputs_and_proofs
                 .iter()
-                .fold(BTreeMap::new(), |mut acc, (utxo, _proof)| {
+                .fold(BTreeMap::new(), |mut acc, (utxo, _tx_out, _proof)| {
                     acc.entry(TokenId::from(utxo.token_id as u64))
                         .and_modify(|value| {
                             global_log::debug!(
@@ -456,22 +544,26 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
             }
 
             let change_amount = Amount::new(change_value as u64, token_id);
-            if let Some(subaddress_index_to_spend_from) = self.subaddress_index_to_spend_from {
-                // Send the change back to the subaddress that is spending the inputs.
-                // In the future, we may want to allow this to be a bit more configurable
-                let change_address = account.public_address(subaddress_index_to_spend_from)?;
-                let reserved_subaddresses_for_spend_subaddress_mode =
-                    ReservedSubaddresses::from_subaddress_index(
-                        &account.account_key()?,
-                        self.subaddress_index_to_spend_from,
-                        None,
-                    );
-
-                // NOTE: This sets the change to return to the subaddress that is spending the
-                // inputs, with the DestinationMemo properly constructed as a Change Output
+            // Send the change to the chosen change subaddress, or else back to the
+            // subaddress that is spending the inputs, or else to the account's
+            // change subaddress if it has been rotated.
+            let change_subaddress_index = self
+                .change_subaddress_index
+                .or(self.subaddress_index_to_spend_from)
+                .or(account.change_subaddress_index.map(|i| i as u64));
+            if let Some(change_subaddress_index) = change_subaddress_index {
+                let change_address = account.public_address(change_subaddress_index)?;
+                let reserved_subaddresses_for_change = ReservedSubaddresses::from_subaddress_index(
+                    &account.account_key()?,
+                    self.subaddress_index_to_spend_from,
+                    Some(change_subaddress_index),
+                );
+
+                // NOTE: This sets the change to return to the chosen subaddress, with the
+                // DestinationMemo properly constructed as a Change Output
                 let tx_out_context = transaction_builder.add_change_output(
                     change_amount,
-                    &reserved_subaddresses_for_spend_subaddress_mode,
+                    &reserved_subaddresses_for_change,
                     &mut rng,
                 )?;
 
@@ -512,14 +604,77 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
         })
     }
 
+    /// Get the ledger data needed to build a transaction from the selected
+    /// inputs: the block height, the input TxOuts with their membership
+    /// proofs, and one ring of mixins per input.
+    ///
+    /// When the builder has a LedgerDB, the returned snapshot can be handed to
+    /// a builder created with `new_from_ledger_snapshot` on a machine without a
+    /// ledger.
+    pub fn ledger_snapshot(&self) -> Result<LedgerSnapshot, WalletTransactionBuilderError> {
+        match &self.ledger {
+            LedgerSource::LedgerDB(ledger_db) => {
+                // Get membership proofs for our inputs
+                let indexes = self
+                    .inputs
+                    .iter()
+                    .map(|utxo| ledger_db.get_tx_out_index_by_public_key(&utxo.public_key()?))
+                    .collect::<Result<Vec<u64>, mc_ledger_db::Error>>()?;
+                let proofs = ledger_db.get_tx_out_proof_of_memberships(&indexes)?;
+                let tx_outs = indexes
+                    .iter()
+                    .map(|index| ledger_db.get_tx_out_by_index(*index))
+                    .collect::<Result<Vec<TxOut>, mc_ledger_db::Error>>()?;
+
+                let rings = Self::get_rings(ledger_db, self.inputs.len(), &indexes)?;
+
+                Ok(LedgerSnapshot {
+                    num_blocks: ledger_db.num_blocks()?,
+                    inputs: tx_outs.into_iter().zip(proofs).collect(),
+                    rings,
+                })
+            }
+            LedgerSource::Snapshot(ledger_snapshot) => {
+                let inputs = self
+                    .inputs
+                    .iter()
+                    .map(|utxo| {
+                        let public_key = utxo.public_key()?;
+                        ledger_snapshot
+                            .inputs
+                            .iter()
+                            .find(|(tx_out, _proof)| tx_out.public_key == public_key)
+                            .cloned()
+                            .ok_or_else(|| {
+                                WalletTransactionBuilderError::InvalidArgument(format!(
+                                    "Ledger snapshot is missing input txo {}",
+                                    utxo.id
+                                ))
+                            })
+                    })
+                    .collect::<Result<Vec<_>, WalletTransactionBuilderError>>()?;
+
+                if ledger_snapshot.rings.len() < inputs.len() {
+                    return Err(WalletTransactionBuilderError::InsufficientTxOuts);
+                }
+
+                Ok(LedgerSnapshot {
+                    num_blocks: ledger_snapshot.num_blocks,
+                    rings: ledger_snapshot.rings[..inputs.len()].to_vec(),
+                    inputs,
+                })
+            }
+        }
+    }
+
     /// Get rings.
     fn get_rings(
-        &self,
+        ledger_db: &LedgerDB,
         num_rings: usize,
         excluded_tx_out_indices: &[u64],
     ) -> Result<Vec<Vec<(TxOut, TxOutMembershipProof)>>, WalletTransactionBuilderError> {
         let num_requested = RING_SIZE * num_rings;
-        let num_txos = self.ledger_db.num_txos()?;
+        let num_txos = ledger_db.num_txos()?;
 
         // Check that the ledger contains enough tx outs.
         if excluded_tx_out_indices.len() as u64 > num_txos {
@@ -546,9 +701,7 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
         let sampled_indices_vec: Vec<u64> = sampled_indices.into_iter().collect();
 
         // Get proofs for all of those indexes.
-        let proofs = self
-            .ledger_db
-            .get_tx_out_proof_of_memberships(&sampled_indices_vec)?;
+        let proofs = ledger_db.get_tx_out_proof_of_memberships(&sampled_indices_vec)?;
 
         // Create an iterator that returns (index, proof) elements.
         let mut indexes_and_proofs_iterator = sampled_indices_vec.into_iter().zip(proofs);
@@ -560,7 +713,7 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
             let mut ring = Vec::new();
             for _ in 0..RING_SIZE {
                 let (index, proof) = indexes_and_proofs_iterator.next().unwrap();
-                let tx_out = self.ledger_db.get_tx_out_by_index(index)?;
+                let tx_out = ledger_db.get_tx_out_by_index(index)?;
 
                 ring.push((tx_out, proof));
             }
@@ -583,19 +736,24 @@ fn extract_fog_uri(addr: &PublicAddress) -> Result<Option<FogUri>, WalletTransac
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashMap, ops::DerefMut, sync::Mutex};
+    use std::{
+        collections::{BTreeSet, HashMap},
+        ops::DerefMut,
+        sync::Mutex,
+    };
 
     use super::*;
     use crate::{
-        db::WalletDbError,
+        db::{models::AssignedSubaddress, WalletDbError},
         service::sync::SyncThread,
         test_utils::{
-            builder_for_random_recipient, get_test_ledger, random_account_with_seed_values,
-            WalletDbTestContext, MOB,
+            builder_for_random_recipient, create_test_received_txo, get_resolver_factory,
+            get_test_ledger, random_account_with_seed_values, WalletDbTestContext, MOB,
         },
     };
     use mc_account_keys::AccountKey;
     use mc_common::logger::{async_test_with_logger, test_with_logger, Logger};
+    use mc_fog_report_validation::MockFogPubkeyResolver;
     use rand::{rngs::StdRng, SeedableRng};
 
     #[async_test_with_logger]
@@ -660,6 +818,186 @@ mod tests {
         assert_eq!(proposal.tx.prefix.outputs.len(), 2);
     }
 
+    // Inputs reserved by one build should not be selected by the next, until the
+    // reservation is released.
+    #[async_test_with_logger]
+    async fn test_sequential_builds_select_disjoint_inputs(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let account_key = random_account_with_seed_values(
+            &wallet_db,
+            &mut ledger_db,
+            &[70 * MOB, 70 * MOB],
+            &mut rng,
+            &logger,
+        );
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let mut first_input_ids = Vec::new();
+        for _ in 0..2 {
+            let (recipient, mut builder) =
+                builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+            builder.add_recipient(recipient, 10 * MOB, Mob::ID).unwrap();
+            builder.select_txos(conn, None).unwrap();
+            builder.set_tombstone(0).unwrap();
+            builder.build(TransactionMemo::Empty, conn).unwrap();
+            builder.reserve_inputs(conn).unwrap();
+
+            let input_ids: Vec<String> = builder.inputs.iter().map(|txo| txo.id.clone()).collect();
+            assert_eq!(input_ids.len(), 1);
+            assert!(!first_input_ids.contains(&input_ids[0]));
+            first_input_ids.extend(input_ids);
+        }
+
+        // Both txos are reserved, so a third build cannot select any inputs
+        let (recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+        builder.add_recipient(recipient, 10 * MOB, Mob::ID).unwrap();
+        assert!(builder.select_txos(conn, None).is_err());
+
+        // Once the reservations are released the txos can be selected again
+        Txo::release_reservations(&first_input_ids, conn).unwrap();
+        builder.select_txos(conn, None).unwrap();
+        assert_eq!(builder.inputs.len(), 1);
+    }
+
+    // Building from a ledger snapshot, without access to the LedgerDB, should
+    // produce the same transaction structure as building online.
+    #[async_test_with_logger]
+    async fn test_build_from_ledger_snapshot(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        // Start sync thread
+        let _sync_thread = SyncThread::start(
+            ledger_db.clone(),
+            wallet_db.clone(),
+            Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            logger.clone(),
+        );
+
+        let account_key = random_account_with_seed_values(
+            &wallet_db,
+            &mut ledger_db,
+            &[11 * MOB, 11 * MOB, 11 * MOB, 111111 * MOB],
+            &mut rng,
+            &logger,
+        );
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let account = Account::get(&AccountID::from(&account_key), conn).unwrap();
+
+        // Build online, and take a snapshot of the ledger data used.
+        let (recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+        let value = 11 * MOB;
+        builder
+            .add_recipient(recipient.clone(), value, Mob::ID)
+            .unwrap();
+        builder.select_txos(conn, None).unwrap();
+        builder.set_tombstone(0).unwrap();
+
+        let ledger_snapshot = builder.ledger_snapshot().unwrap();
+        assert_eq!(ledger_snapshot.num_blocks, ledger_db.num_blocks().unwrap());
+        assert_eq!(ledger_snapshot.inputs.len(), 2);
+        assert_eq!(ledger_snapshot.rings.len(), 2);
+        for ring in ledger_snapshot.rings.iter() {
+            assert_eq!(ring.len(), RING_SIZE);
+        }
+
+        let input_txo_ids: Vec<String> = builder.inputs.iter().map(|txo| txo.id.clone()).collect();
+        let online_proposal = builder
+            .build(
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                conn,
+            )
+            .unwrap()
+            .sign(&account)
+            .await
+            .unwrap();
+
+        // Build again from the snapshot only.
+        let mut offline_builder =
+            WalletTransactionBuilder::<MockFogPubkeyResolver>::new_from_ledger_snapshot(
+                AccountID::from(&account_key).to_string(),
+                ledger_snapshot,
+                get_resolver_factory(&mut rng).unwrap(),
+            );
+        offline_builder
+            .add_recipient(recipient.clone(), value, Mob::ID)
+            .unwrap();
+        offline_builder.set_txos(conn, &input_txo_ids).unwrap();
+        offline_builder.set_tombstone(0).unwrap();
+
+        let offline_proposal = offline_builder
+            .build(
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                conn,
+            )
+            .unwrap()
+            .sign(&account)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            offline_proposal.tx.prefix.tombstone_block,
+            online_proposal.tx.prefix.tombstone_block
+        );
+        assert_eq!(
+            offline_proposal.tx.prefix.fee,
+            online_proposal.tx.prefix.fee
+        );
+        assert_eq!(
+            offline_proposal.tx.prefix.inputs.len(),
+            online_proposal.tx.prefix.inputs.len()
+        );
+        assert_eq!(
+            offline_proposal.tx.prefix.outputs.len(),
+            online_proposal.tx.prefix.outputs.len()
+        );
+        assert_eq!(offline_proposal.payload_txos.len(), 1);
+        assert_eq!(
+            offline_proposal.payload_txos[0].recipient_public_address,
+            recipient
+        );
+        assert_eq!(offline_proposal.payload_txos[0].amount.value, value);
+        assert_eq!(
+            offline_proposal.change_txos[0].amount,
+            online_proposal.change_txos[0].amount
+        );
+
+        let mut online_inputs: Vec<_> = online_proposal
+            .input_txos
+            .iter()
+            .map(|input| input.tx_out.public_key)
+            .collect();
+        let mut offline_inputs: Vec<_> = offline_proposal
+            .input_txos
+            .iter()
+            .map(|input| input.tx_out.public_key)
+            .collect();
+        online_inputs.sort();
+        offline_inputs.sort();
+        assert_eq!(offline_inputs, online_inputs);
+    }
+
     // Test that large values are handled correctly.
     #[test_with_logger]
     fn test_big_input_and_output_values(logger: Logger) {
@@ -708,6 +1046,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             wallet_db.get_pooled_conn().unwrap().deref_mut(),
         )
         .unwrap();
@@ -807,6 +1147,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             Some(0),
             wallet_db.get_pooled_conn().unwrap().deref_mut(),
         )
@@ -908,6 +1250,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             Some(0),
             conn,
         )
@@ -1019,6 +1363,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
             Some(0),
             conn,
         )
@@ -1056,6 +1402,78 @@ mod tests {
         )
     }
 
+    // Test that excluded txos are never selected, and are left out of the max
+    // spendable value
+    #[async_test_with_logger]
+    async fn test_select_txos_excluding_txo_ids(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let account_key = random_account_with_seed_values(
+            &wallet_db,
+            &mut ledger_db,
+            &[100 * MOB, 40 * MOB, 20 * MOB],
+            &mut rng,
+            &logger,
+        );
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let txos = Txo::list_for_account(
+            &AccountID::from(&account_key).to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            conn,
+        )
+        .unwrap();
+        let large_txo_id = txos
+            .iter()
+            .find(|txo| txo.value as u64 == 100 * MOB)
+            .unwrap()
+            .id
+            .clone();
+
+        // The smaller txos don't cover 70 MOB, so the large txo is selected.
+        let (recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+        builder
+            .add_recipient(recipient.clone(), 70 * MOB, Mob::ID)
+            .unwrap();
+        builder.select_txos(conn, None).unwrap();
+        assert!(builder.inputs.iter().any(|txo| txo.id == large_txo_id));
+
+        // Once excluded, it is not selected even though nothing else will do.
+        builder.exclude_txos(&[large_txo_id.clone()]);
+        match builder.select_txos(conn, None) {
+            Ok(_) => panic!("Should not select an excluded txo"),
+            Err(WalletTransactionBuilderError::WalletDb(
+                WalletDbError::InsufficientFundsUnderMaxSpendable(_),
+            )) => {}
+            Err(e) => panic!("Unexpected error {:?}", e),
+        }
+
+        // The remaining txos cover 50 MOB, within the max spendable value.
+        let (recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+        builder.add_recipient(recipient, 50 * MOB, Mob::ID).unwrap();
+        builder.exclude_txos(&[large_txo_id.clone()]);
+        builder.select_txos(conn, Some(100 * MOB)).unwrap();
+        let mut input_values: Vec<u64> =
+            builder.inputs.iter().map(|txo| txo.value as u64).collect();
+        input_values.sort();
+        assert_eq!(input_values, vec![20 * MOB, 40 * MOB]);
+    }
+
     // Test max_spendable correctly filters out txos above max_spendable
     #[async_test_with_logger]
     async fn test_max_spendable(logger: Logger) {
@@ -1137,6 +1555,167 @@ mod tests {
         assert_eq!(proposal.tx.prefix.outputs.len(), 2); // self and change
     }
 
+    // Inputs are only selected from txos of the token they pay for.
+    #[test_with_logger]
+    fn test_select_txos_by_token(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let account_key = random_account_with_seed_values(
+            &wallet_db,
+            &mut ledger_db,
+            &[70 * MOB, 80 * MOB],
+            &mut rng,
+            &logger,
+        );
+        let eusd = TokenId::from(1);
+        for value in [30 * MOB, 40 * MOB] {
+            create_test_received_txo(
+                &account_key,
+                0,
+                Amount::new(value, eusd),
+                12,
+                &mut rng,
+                &wallet_db,
+            );
+        }
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+        let input_token_ids = |builder: &WalletTransactionBuilder<MockFogPubkeyResolver>| {
+            builder
+                .inputs
+                .iter()
+                .map(|txo| TokenId::from(txo.token_id as u64))
+                .collect::<BTreeSet<_>>()
+        };
+
+        // A payment in eUSD with an eUSD fee only spends eUSD.
+        let (recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+        builder.add_recipient(recipient, 50 * MOB, eusd).unwrap();
+        builder.set_fee(1024, eusd).unwrap();
+        builder.select_txos(conn, None).unwrap();
+        assert_eq!(builder.inputs.len(), 2);
+        assert_eq!(input_token_ids(&builder), BTreeSet::from([eusd]));
+
+        // With a MOB fee, inputs of both tokens are kept.
+        let (recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+        builder.add_recipient(recipient, 50 * MOB, eusd).unwrap();
+        builder.select_txos(conn, None).unwrap();
+        assert_eq!(input_token_ids(&builder), BTreeSet::from([Mob::ID, eusd]));
+
+        // More eUSD than the account holds cannot be selected.
+        let (recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+        builder.add_recipient(recipient, 80 * MOB, eusd).unwrap();
+        builder.set_fee(1024, eusd).unwrap();
+        assert!(builder.select_txos(conn, None).is_err());
+
+        // Inputs of another token cannot pay for an eUSD payment.
+        let mob_txo_ids: Vec<String> = Txo::list_for_account(
+            &AccountID::from(&account_key).to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(*Mob::ID),
+            conn,
+        )
+        .unwrap()
+        .into_iter()
+        .map(|txo| txo.id)
+        .collect();
+        let (recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+        builder.add_recipient(recipient, 50 * MOB, eusd).unwrap();
+        builder.set_txos(conn, &mob_txo_ids).unwrap();
+        builder.set_tombstone(0).unwrap();
+        match builder.build(TransactionMemo::Empty, conn) {
+            Err(WalletTransactionBuilderError::InsufficientFundsForToken(token_id)) => {
+                assert_eq!(token_id, eusd.to_string());
+            }
+            result => panic!("Expected InsufficientFundsForToken, got {result:?}"),
+        }
+    }
+
+    #[test_with_logger]
+    fn test_select_txos_from_spend_subaddress(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        // The main subaddress holds 70 and 80 MOB.
+        let account_key = random_account_with_seed_values(
+            &wallet_db,
+            &mut ledger_db,
+            &[70 * MOB, 80 * MOB],
+            &mut rng,
+            &logger,
+        );
+
+        // A second subaddress holds 30 and 40 MOB.
+        let (_public_address_b58, deposit_subaddress_index) =
+            AssignedSubaddress::create_next_for_account(
+                &AccountID::from(&account_key).to_string(),
+                "Deposits",
+                &ledger_db,
+                wallet_db.get_pooled_conn().unwrap().deref_mut(),
+            )
+            .unwrap();
+        let deposit_subaddress_index = deposit_subaddress_index as u64;
+        for value in [30 * MOB, 40 * MOB] {
+            create_test_received_txo(
+                &account_key,
+                deposit_subaddress_index,
+                Amount::new(value, Mob::ID),
+                12,
+                &mut rng,
+                &wallet_db,
+            );
+        }
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        // A restricted build only spends the chosen subaddress's txos, even
+        // though the main subaddress could fund it with fewer inputs.
+        let (recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+        builder.add_recipient(recipient, 60 * MOB, Mob::ID).unwrap();
+        builder
+            .set_spend_subaddress(deposit_subaddress_index)
+            .unwrap();
+        builder.select_txos(conn, None).unwrap();
+        assert_eq!(builder.inputs.len(), 2);
+        for input in builder.inputs.iter() {
+            assert_eq!(
+                input.subaddress_index,
+                Some(deposit_subaddress_index as i64)
+            );
+        }
+
+        // The chosen subaddress cannot cover more than its own balance.
+        let (recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+        builder.add_recipient(recipient, 70 * MOB, Mob::ID).unwrap();
+        builder
+            .set_spend_subaddress(deposit_subaddress_index)
+            .unwrap();
+        assert!(builder.select_txos(conn, None).is_err());
+    }
+
     // Test setting and not setting tombstone block
     #[async_test_with_logger]
     async fn test_tombstone(logger: Logger) {
@@ -1233,6 +1812,38 @@ mod tests {
         assert_eq!(proposal.tx.prefix.tombstone_block, 20);
     }
 
+    // The default tombstone is the current block height plus the horizon, and a
+    // tombstone that has already passed is rejected.
+    #[test_with_logger]
+    fn test_tombstone_horizon(_logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+        let account_key = AccountKey::random(&mut rng);
+        let (_recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+
+        builder.set_tombstone(0).unwrap();
+        assert_eq!(builder.tombstone, 12 + DEFAULT_NEW_TX_BLOCK_ATTEMPTS);
+
+        builder.set_tombstone_horizon(50);
+        builder.set_tombstone(0).unwrap();
+        assert_eq!(builder.tombstone, 62);
+
+        match builder.set_tombstone(12) {
+            Err(WalletTransactionBuilderError::TombstoneInPast(tombstone, num_blocks)) => {
+                assert_eq!(tombstone, 12);
+                assert_eq!(num_blocks, 12);
+            }
+            result => panic!("Expected TombstoneInPast, got {result:?}"),
+        }
+        assert_eq!(builder.tombstone, 62);
+
+        builder.set_tombstone(13).unwrap();
+        assert_eq!(builder.tombstone, 13);
+    }
+
     // Test setting and not setting the fee
     #[async_test_with_logger]
     async fn test_fee(logger: Logger) {
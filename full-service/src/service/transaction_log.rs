@@ -6,16 +6,26 @@ use std::ops::DerefMut;
 
 use crate::{
     db::{
-        models::TransactionLog,
-        transaction_log::{AssociatedTxos, TransactionId, TransactionLogModel, ValueMap},
+        account::{AccountID, AccountModel},
+        exclusive_transaction,
+        models::{Account, TransactionLog, Txo},
+        transaction_log::{AssociatedTxos, TransactionId, TransactionLogModel, TxStatus, ValueMap},
+        txo::{TxoID, TxoModel},
         WalletDbError,
     },
     error::WalletServiceError,
+    json_rpc::json_rpc_response::{ServerErrorCode, ServerErrorCodes},
     WalletService,
 };
 use displaydoc::Display;
 use mc_connection::{BlockchainConnection, UserTxConnection};
 use mc_fog_report_validation::FogPubkeyResolver;
+use mc_ledger_db::Ledger;
+use mc_transaction_core::tx::Tx;
+
+/// The columns of `export_transactions_csv`.
+pub const TRANSACTIONS_CSV_HEADER: &str =
+    "block_index,timestamp,counterparty,value,token_id,fee,fee_token_id,transaction_id";
 
 /// Errors for the Transaction Log Service.
 #[derive(Display, Debug)]
@@ -26,6 +36,22 @@ pub enum TransactionLogServiceError {
 
     /// Diesel Error: {0}
     Diesel(diesel::result::Error),
+
+    /// Transaction {0} is not pending
+    TransactionNotPending(String),
+
+    /// An input of transaction {0} has already been spent
+    InputAlreadySpent(String),
+
+    /// Transaction {0} may still land before its tombstone block {1}; abort it
+    /// with force to do so anyway
+    TombstoneBlockNotReached(String, u64),
+
+    /// Error with LedgerDB: {0}
+    LedgerDB(mc_ledger_db::Error),
+
+    /// Error decoding with mc_util_serial: {0}
+    Decode(mc_util_serial::DecodeError),
 }
 
 impl From<WalletDbError> for TransactionLogServiceError {
@@ -40,6 +66,28 @@ impl From<diesel::result::Error> for TransactionLogServiceError {
     }
 }
 
+impl From<mc_ledger_db::Error> for TransactionLogServiceError {
+    fn from(src: mc_ledger_db::Error) -> Self {
+        Self::LedgerDB(src)
+    }
+}
+
+impl From<mc_util_serial::DecodeError> for TransactionLogServiceError {
+    fn from(src: mc_util_serial::DecodeError) -> Self {
+        Self::Decode(src)
+    }
+}
+
+impl ServerErrorCode for TransactionLogServiceError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::Database(e) => e.code(),
+            Self::Diesel(diesel::result::Error::NotFound) => Some(ServerErrorCodes::NotFound),
+            _ => None,
+        }
+    }
+}
+
 /// Trait defining the ways in which the wallet can interact with and manage
 /// transaction logs.
 #[rustfmt::skip]
@@ -78,6 +126,59 @@ pub trait TransactionLogService {
         &self,
         transaction_id_hex: &str,
     ) -> Result<(TransactionLog, AssociatedTxos, ValueMap), TransactionLogServiceError>;
+
+    /// Trace the transaction which produced a Txo, along with the inputs it consumed.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                   | Notes                                          |
+    ///|--------------|-------------------------------------------|------------------------------------------------|
+    ///| `account_id` | The account which built the transaction.  | Account must exist in the wallet.              |
+    ///| `txo_id`     | The payload or change Txo to trace.       | Txo must exist in the wallet.                  |
+    ///
+    /// # Returns
+    /// * None if the Txo was not produced by a transaction from this account, such as a
+    ///   Txo received from someone else.
+    fn get_txo_provenance(
+        &self,
+        account_id: &AccountID,
+        txo_id: &TxoID,
+    ) -> Result<Option<(TransactionLog, AssociatedTxos, ValueMap)>, TransactionLogServiceError>;
+
+    /// Abort a pending transaction that consensus never accepted, marking it failed and
+    /// releasing its inputs so they can be spent again.
+    ///
+    /// The transaction can still land until the ledger reaches its tombstone block, so
+    /// before then it is only aborted with `force`.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                 | Purpose                           | Notes                                                       |
+    ///|----------------------|-----------------------------------|-------------------------------------------------------------|
+    ///| `transaction_log_id` | The transaction log ID to abort.  | Transaction must be pending, with none of its inputs spent. |
+    ///| `force`              | Abort before the tombstone block. | The transaction may still land.                             |
+    ///
+    fn abort_transaction(
+        &self,
+        transaction_id_hex: &str,
+        force: bool,
+    ) -> Result<(TransactionLog, AssociatedTxos, ValueMap), TransactionLogServiceError>;
+
+    /// Export the transactions sent from an account as CSV, oldest first, with one row per
+    /// outlay. The fee is reported on the first row of each transaction only, so that summing
+    /// the fee column counts each fee once. Failed transactions, and transactions which were
+    /// never submitted, are left out.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                   | Notes                             |
+    ///|--------------|-------------------------------------------|-----------------------------------|
+    ///| `account_id` | The account whose transactions to export. | Account must exist in the wallet. |
+    ///
+    fn export_transactions_csv(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<String, TransactionLogServiceError>;
 }
 
 impl<T, FPR> TransactionLogService for WalletService<T, FPR>
@@ -118,23 +219,149 @@ where
 
         Ok((transaction_log, associated, value_map))
     }
+
+    fn get_txo_provenance(
+        &self,
+        account_id: &AccountID,
+        txo_id: &TxoID,
+    ) -> Result<Option<(TransactionLog, AssociatedTxos, ValueMap)>, TransactionLogServiceError>
+    {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        Account::get(account_id, conn)?;
+        Txo::get(&txo_id.to_string(), conn)?;
+
+        let transaction_log = match TransactionLog::get_for_output_txo(
+            &account_id.to_string(),
+            &txo_id.to_string(),
+            conn,
+        )? {
+            Some(transaction_log) => transaction_log,
+            None => return Ok(None),
+        };
+        let associated = transaction_log.get_associated_txos(conn)?;
+        let value_map = transaction_log.value_map(conn)?;
+
+        Ok(Some((transaction_log, associated, value_map)))
+    }
+
+    fn abort_transaction(
+        &self,
+        transaction_id_hex: &str,
+        force: bool,
+    ) -> Result<(TransactionLog, AssociatedTxos, ValueMap), TransactionLogServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        let transaction_id = TransactionId(transaction_id_hex.to_string());
+
+        exclusive_transaction(conn, |conn| {
+            let transaction_log = TransactionLog::get(&transaction_id, conn)?;
+            if transaction_log.status() != TxStatus::Pending {
+                return Err(TransactionLogServiceError::TransactionNotPending(
+                    transaction_id_hex.to_string(),
+                ));
+            }
+
+            // Once an input is spent the transaction, or a conflicting one, has landed.
+            // The ledger is checked as well as the wallet, since the wallet's sync may
+            // trail the ledger.
+            let tx: Tx = mc_util_serial::decode(&transaction_log.tx)?;
+            let associated = transaction_log.get_associated_txos(conn)?;
+            let mut input_spent = associated
+                .inputs
+                .iter()
+                .any(|txo| txo.spent_block_index.is_some());
+            for key_image in tx.key_images() {
+                input_spent |= self.ledger_db.contains_key_image(&key_image)?;
+            }
+            if input_spent {
+                return Err(TransactionLogServiceError::InputAlreadySpent(
+                    transaction_id_hex.to_string(),
+                ));
+            }
+
+            let tombstone_block = tx.prefix.tombstone_block;
+            if !force && self.ledger_db.num_blocks()? < tombstone_block {
+                return Err(TransactionLogServiceError::TombstoneBlockNotReached(
+                    transaction_id_hex.to_string(),
+                    tombstone_block,
+                ));
+            }
+
+            transaction_log.update_to_failed(conn)?;
+
+            let transaction_log = TransactionLog::get(&transaction_id, conn)?;
+            let associated = transaction_log.get_associated_txos(conn)?;
+            let value_map = transaction_log.value_map(conn)?;
+            Ok((transaction_log, associated, value_map))
+        })
+    }
+
+    fn export_transactions_csv(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<String, TransactionLogServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        Account::get(account_id, conn)?;
+
+        let transaction_logs =
+            TransactionLog::list_all(Some(account_id.to_string()), None, None, None, None, conn)?;
+
+        let mut csv = TRANSACTIONS_CSV_HEADER.to_string();
+        csv.push('\n');
+        for (transaction_log, associated, _value_map) in transaction_logs.iter().rev() {
+            if transaction_log.failed {
+                continue;
+            }
+            let block_index = match transaction_log.submitted_block_index {
+                Some(submitted_block_index) => transaction_log
+                    .finalized_block_index
+                    .unwrap_or(submitted_block_index),
+                None => continue,
+            };
+            for (i, (txo, recipient_b58)) in associated.outputs.iter().enumerate() {
+                let fee = if i == 0 { transaction_log.fee_value } else { 0 };
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{},{},{}\n",
+                    block_index,
+                    transaction_log.created_at,
+                    recipient_b58,
+                    txo.value as u64,
+                    txo.token_id as u64,
+                    fee as u64,
+                    transaction_log.fee_token_id as u64,
+                    transaction_log.id,
+                ));
+            }
+        }
+        Ok(csv)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        db::account::AccountID,
+        db::{
+            account::AccountID,
+            transaction_log::{TransactionLogModel, TxStatus},
+            txo::TxoID,
+        },
         json_rpc::v2::models::amount::Amount,
         service::{
             account::AccountService,
             address::AddressService,
+            balance::BalanceService,
             transaction::{TransactionMemo, TransactionService},
-            transaction_log::TransactionLogService,
+            transaction_log::{
+                TransactionLogService, TransactionLogServiceError, TRANSACTIONS_CSV_HEADER,
+            },
         },
         test_utils::{
             add_block_to_ledger_db, add_block_with_tx_outs, get_test_ledger, manually_sync_account,
             setup_wallet_service, MOB,
         },
+        util::b58::b58_encode_public_address,
     };
     use mc_account_keys::{AccountKey, PublicAddress};
     use mc_common::logger::{async_test_with_logger, Logger};
@@ -207,11 +434,13 @@ mod tests {
                     None,
                     None,
                     None,
+                    None,
                     TransactionMemo::RTH {
                         subaddress_index: None,
                     },
                     None,
                     None,
+                    None,
                 )
                 .await
                 .unwrap();
@@ -285,4 +514,599 @@ mod tests {
 
         assert_eq!(3, tx_logs.len());
     }
+
+    #[async_test_with_logger]
+    async fn test_list_transaction_logs_paginated(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        let alice_public_address = alice_account_key.default_subaddress();
+
+        for _ in 0..5 {
+            add_block_to_ledger_db(
+                &mut ledger_db,
+                &vec![alice_public_address.clone()],
+                100 * MOB,
+                &[KeyImage::from(rng.next_u64())],
+                &mut rng,
+            );
+        }
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let address = service
+            .assign_address_for_account(&alice_account_id, None)
+            .unwrap();
+
+        // Build and land five transactions, each in its own block.
+        for _ in 0..5 {
+            let (_, _, _, tx_proposal) = service
+                .build_sign_and_submit_transaction(
+                    &alice_account_id.to_string(),
+                    &[(
+                        address.public_address_b58.clone(),
+                        Amount::new(50 * MOB, Mob::ID),
+                    )],
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    TransactionMemo::RTH {
+                        subaddress_index: None,
+                    },
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap();
+
+            let key_images: Vec<KeyImage> = tx_proposal
+                .input_txos
+                .iter()
+                .map(|txo| txo.key_image)
+                .collect();
+            add_block_with_tx_outs(
+                &mut ledger_db,
+                &[
+                    tx_proposal.change_txos[0].tx_out.clone(),
+                    tx_proposal.payload_txos[0].tx_out.clone(),
+                ],
+                &key_images,
+                &mut rng,
+            );
+            manually_sync_account(
+                &ledger_db,
+                service.wallet_db.as_ref().unwrap(),
+                &alice_account_id,
+                &logger,
+            );
+        }
+
+        let all_ids: Vec<String> = service
+            .list_transaction_logs(Some(alice_account_id.to_string()), None, None, None, None)
+            .unwrap()
+            .into_iter()
+            .map(|(transaction_log, _, _)| transaction_log.id)
+            .collect();
+        assert_eq!(all_ids.len(), 5);
+
+        // Page through two at a time.
+        let mut paged_logs = Vec::new();
+        let mut page_sizes = Vec::new();
+        for offset in [0, 2, 4] {
+            let page = service
+                .list_transaction_logs(
+                    Some(alice_account_id.to_string()),
+                    Some(offset),
+                    Some(2),
+                    None,
+                    None,
+                )
+                .unwrap();
+            page_sizes.push(page.len());
+            paged_logs.extend(
+                page.into_iter()
+                    .map(|(transaction_log, _, _)| transaction_log),
+            );
+        }
+        assert_eq!(page_sizes, vec![2, 2, 1]);
+
+        // The pages are newest first, and together cover every log exactly once.
+        let submitted_block_indexes: Vec<i64> = paged_logs
+            .iter()
+            .map(|transaction_log| transaction_log.submitted_block_index.unwrap())
+            .collect();
+        let mut sorted = submitted_block_indexes.clone();
+        sorted.sort_by(|a, b| b.cmp(a));
+        assert_eq!(submitted_block_indexes, sorted);
+
+        let paged_ids: Vec<String> = paged_logs
+            .into_iter()
+            .map(|transaction_log| transaction_log.id)
+            .collect();
+        assert_eq!(paged_ids, all_ids);
+    }
+
+    #[async_test_with_logger]
+    async fn test_abort_transaction_restores_balance(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let bob_address =
+            b58_encode_public_address(&AccountKey::random(&mut rng).default_subaddress()).unwrap();
+        let (transaction_log, _, _, _) = service
+            .build_sign_and_submit_transaction(
+                &alice_account_id.to_string(),
+                &[(bob_address, Amount::new(42 * MOB, Mob::ID))],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let balance = service.get_balance_for_account(&alice_account_id).unwrap();
+        let balance_pmob = balance.get(&Mob::ID).unwrap();
+        assert_eq!(balance_pmob.unspent, 0);
+        assert_eq!(balance_pmob.pending, 100 * MOB as u128);
+
+        // The transaction could still land before its tombstone block.
+        match service.abort_transaction(&transaction_log.id, false) {
+            Err(TransactionLogServiceError::TombstoneBlockNotReached(id, _)) => {
+                assert_eq!(id, transaction_log.id)
+            }
+            result => panic!("Expected TombstoneBlockNotReached, got {result:?}"),
+        }
+
+        // Consensus never accepted the transaction, so it is aborted.
+        let (aborted_log, _, _) = service
+            .abort_transaction(&transaction_log.id, true)
+            .unwrap();
+        assert_eq!(aborted_log.status(), TxStatus::Failed);
+
+        let balance = service.get_balance_for_account(&alice_account_id).unwrap();
+        let balance_pmob = balance.get(&Mob::ID).unwrap();
+        assert_eq!(balance_pmob.unspent, 100 * MOB as u128);
+        assert_eq!(balance_pmob.pending, 0);
+        assert_eq!(
+            balance_pmob.max_spendable,
+            (100 * MOB - Mob::MINIMUM_FEE) as u128
+        );
+
+        match service.abort_transaction(&transaction_log.id, true) {
+            Err(TransactionLogServiceError::TransactionNotPending(id)) => {
+                assert_eq!(id, transaction_log.id)
+            }
+            result => panic!("Expected TransactionNotPending, got {result:?}"),
+        }
+    }
+
+    #[async_test_with_logger]
+    async fn test_abort_transaction_landed_but_not_synced(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let bob_address =
+            b58_encode_public_address(&AccountKey::random(&mut rng).default_subaddress()).unwrap();
+        let (transaction_log, _, _, tx_proposal) = service
+            .build_sign_and_submit_transaction(
+                &alice_account_id.to_string(),
+                &[(bob_address, Amount::new(42 * MOB, Mob::ID))],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        // The transaction lands in the ledger, but the account has not synced it yet.
+        let key_images: Vec<KeyImage> = tx_proposal
+            .input_txos
+            .iter()
+            .map(|input_txo| input_txo.key_image)
+            .collect();
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_account_key.default_subaddress()],
+            MOB,
+            &key_images,
+            &mut rng,
+        );
+
+        match service.abort_transaction(&transaction_log.id, true) {
+            Err(TransactionLogServiceError::InputAlreadySpent(id)) => {
+                assert_eq!(id, transaction_log.id)
+            }
+            result => panic!("Expected InputAlreadySpent, got {result:?}"),
+        }
+
+        let balance = service.get_balance_for_account(&alice_account_id).unwrap();
+        let balance_pmob = balance.get(&Mob::ID).unwrap();
+        assert_eq!(balance_pmob.unspent, 0);
+        assert_eq!(balance_pmob.pending, 100 * MOB as u128);
+    }
+
+    #[async_test_with_logger]
+    async fn test_get_txo_provenance(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let bob_address =
+            b58_encode_public_address(&AccountKey::random(&mut rng).default_subaddress()).unwrap();
+        let (transaction_log, associated_txos, _, _) = service
+            .build_sign_and_submit_transaction(
+                &alice_account_id.to_string(),
+                &[(bob_address, Amount::new(42 * MOB, Mob::ID))],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(associated_txos.inputs.len(), 1);
+        assert_eq!(associated_txos.change.len(), 1);
+
+        // The change traces back to the transaction which produced it, and the
+        // input it consumed.
+        let change_txo_id = TxoID(associated_txos.change[0].0.id.clone());
+        let (provenance_log, provenance_txos, _) = service
+            .get_txo_provenance(&alice_account_id, &change_txo_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(provenance_log.id, transaction_log.id);
+        assert_eq!(provenance_txos.inputs, associated_txos.inputs);
+
+        // The received input was not produced by one of Alice's transactions.
+        let input_txo_id = TxoID(associated_txos.inputs[0].id.clone());
+        assert!(service
+            .get_txo_provenance(&alice_account_id, &input_txo_id)
+            .unwrap()
+            .is_none());
+
+        match service.get_txo_provenance(&alice_account_id, &TxoID("missing".to_string())) {
+            Err(TransactionLogServiceError::Database(_)) => {}
+            result => panic!("Expected a database error, got {result:?}"),
+        }
+    }
+
+    #[async_test_with_logger]
+    async fn test_export_transactions_csv(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![
+                alice_account_key.default_subaddress(),
+                alice_account_key.default_subaddress(),
+            ],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let bob_address =
+            b58_encode_public_address(&AccountKey::random(&mut rng).default_subaddress()).unwrap();
+        let carol_address =
+            b58_encode_public_address(&AccountKey::random(&mut rng).default_subaddress()).unwrap();
+        let (transaction_log, _, _, _) = service
+            .build_sign_and_submit_transaction(
+                &alice_account_id.to_string(),
+                &[
+                    (bob_address.clone(), Amount::new(42 * MOB, Mob::ID)),
+                    (carol_address.clone(), Amount::new(8 * MOB, Mob::ID)),
+                ],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        // A transaction which is signed but never submitted is left out.
+        service
+            .build_and_sign_transaction(
+                &alice_account_id.to_string(),
+                &[(bob_address.clone(), Amount::new(10 * MOB, Mob::ID))],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let csv = service.export_transactions_csv(&alice_account_id).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(TRANSACTIONS_CSV_HEADER));
+
+        // One row per outlay, with the fee counted on the first row only.
+        let rows: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+        assert_eq!(rows.len(), 2);
+        let bob_row = rows.iter().find(|row| row[2] == bob_address).unwrap();
+        let block_index = transaction_log.submitted_block_index.unwrap().to_string();
+        assert_eq!(bob_row[0], block_index);
+        assert_eq!(bob_row[1], transaction_log.created_at.to_string());
+        assert_eq!(bob_row[3], (42 * MOB).to_string());
+        assert_eq!(bob_row[4], "0");
+        assert_eq!(bob_row[6], "0");
+        assert_eq!(bob_row[7], transaction_log.id);
+        let carol_row = rows.iter().find(|row| row[2] == carol_address).unwrap();
+        assert_eq!(carol_row[3], (8 * MOB).to_string());
+
+        let fees: u64 = rows.iter().map(|row| row[5].parse::<u64>().unwrap()).sum();
+        assert_eq!(fees, Mob::MINIMUM_FEE);
+    }
+
+    #[async_test_with_logger]
+    async fn test_list_transaction_logs_across_accounts(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let mut account_ids = Vec::new();
+        for funding in [vec![100 * MOB, 100 * MOB], vec![100 * MOB]] {
+            let account = service
+                .create_account(None, "".to_string(), "".to_string(), false)
+                .unwrap();
+            let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+            for value in funding {
+                add_block_to_ledger_db(
+                    &mut ledger_db,
+                    &vec![account_key.default_subaddress()],
+                    value,
+                    &[KeyImage::from(rng.next_u64())],
+                    &mut rng,
+                );
+            }
+            account_ids.push(AccountID::from(&account_key));
+        }
+        for account_id in &account_ids {
+            manually_sync_account(
+                &ledger_db,
+                service.wallet_db.as_ref().unwrap(),
+                account_id,
+                &logger,
+            );
+        }
+        let (alice_account_id, bob_account_id) = (&account_ids[0], &account_ids[1]);
+
+        // Alice, then Bob, then Alice again send a transaction, each submitted at a
+        // later block index.
+        let mut transaction_log_ids = Vec::new();
+        for account_id in [alice_account_id, bob_account_id, alice_account_id] {
+            let recipient =
+                b58_encode_public_address(&AccountKey::random(&mut rng).default_subaddress())
+                    .unwrap();
+            let (transaction_log, _, _, _) = service
+                .build_sign_and_submit_transaction(
+                    &account_id.to_string(),
+                    &[(recipient, Amount::new(10 * MOB, Mob::ID))],
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    TransactionMemo::RTH {
+                        subaddress_index: None,
+                    },
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap();
+            transaction_log_ids.push(transaction_log.id);
+
+            add_block_to_ledger_db(
+                &mut ledger_db,
+                &vec![AccountKey::random(&mut rng).default_subaddress()],
+                MOB,
+                &[KeyImage::from(rng.next_u64())],
+                &mut rng,
+            );
+        }
+
+        // Without an account, the logs of every account are listed newest first,
+        // each with its account and associated txos.
+        let tx_logs = service
+            .list_transaction_logs(None, None, None, None, None)
+            .unwrap();
+        let listed: Vec<(String, String)> = tx_logs
+            .iter()
+            .map(|(log, _, _)| (log.id.clone(), log.account_id.clone()))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                (transaction_log_ids[2].clone(), alice_account_id.to_string()),
+                (transaction_log_ids[1].clone(), bob_account_id.to_string()),
+                (transaction_log_ids[0].clone(), alice_account_id.to_string()),
+            ]
+        );
+        for (_, associated_txos, _) in &tx_logs {
+            assert_eq!(associated_txos.inputs.len(), 1);
+            assert_eq!(associated_txos.outputs.len(), 1);
+        }
+
+        let page = service
+            .list_transaction_logs(None, Some(1), Some(1), None, None)
+            .unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].0.id, transaction_log_ids[1]);
+    }
 }
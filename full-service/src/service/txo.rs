@@ -8,12 +8,16 @@ use crate::{
     db::{
         account::{AccountID, AccountModel},
         assigned_subaddress::AssignedSubaddressModel,
+        exclusive_transaction,
         models::{Account, AssignedSubaddress, Txo},
         txo::{TxoID, TxoInfo, TxoModel, TxoStatus},
         WalletDbError,
     },
     error::WalletTransactionBuilderError,
-    json_rpc::v2::models::amount::Amount,
+    json_rpc::{
+        json_rpc_response::{ServerErrorCode, ServerErrorCodes},
+        v2::models::amount::Amount,
+    },
     service::{
         ledger::LedgerServiceError,
         models::tx_proposal::TxProposal,
@@ -23,8 +27,18 @@ use crate::{
 };
 use displaydoc::Display;
 use mc_connection::{BlockchainConnection, UserTxConnection};
+use mc_crypto_keys::RistrettoPublic;
 use mc_fog_report_validation::FogPubkeyResolver;
-use mc_transaction_core::FeeMapError;
+use mc_ledger_db::Ledger;
+use mc_transaction_core::{tx::TxOutMembershipProof, FeeMapError};
+
+/// An orphaned Txo, with the spend public key of the subaddress it was sent
+/// to. Assigning that subaddress to the account recovers the Txo.
+#[derive(Debug)]
+pub struct OrphanedTxo {
+    pub txo: Txo,
+    pub subaddress_spend_public_key: RistrettoPublic,
+}
 
 /// Errors for the Txo Service.
 #[derive(Display, Debug)]
@@ -74,6 +88,12 @@ pub enum TxoServiceError {
 
     /// Ledger Service Error: {0}
     LedgerService(LedgerServiceError),
+
+    /// Txo {0} was not received by account {1}
+    TxoNotReceivedByAccount(String, String),
+
+    /// Txo is not yet in the local ledger: {0}
+    TxoNotInLedger(String),
 }
 
 impl From<WalletDbError> for TxoServiceError {
@@ -142,6 +162,19 @@ impl From<LedgerServiceError> for TxoServiceError {
     }
 }
 
+impl ServerErrorCode for TxoServiceError {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        match self {
+            Self::Database(e) => e.code(),
+            Self::TransactionService(e) => e.code(),
+            Self::WalletTransactionBuilder(e) => e.code(),
+            Self::LedgerService(e) => e.code(),
+            Self::Diesel(diesel::result::Error::NotFound) => Some(ServerErrorCodes::NotFound),
+            _ => None,
+        }
+    }
+}
+
 /// Trait defining the ways in which the wallet can interact with and manage
 /// Txos.
 #[rustfmt::skip]
@@ -160,6 +193,8 @@ pub trait TxoService {
     ///| `token_id`                 | The tokenId of this a txo                                                                                |                                   |
     ///| `min_received_block_index` | The minimum block index to query for received txos, inclusive                                            |                                   |
     ///| `max_received_block_index` | The maximum block index to query for received txos, inclusive                                            |                                   |
+    ///| `min_value`                | The minimum value of txos to query for, inclusive                                                        |                                   |
+    ///| `max_value`                | The maximum value of txos to query for, inclusive                                                        |                                   |
     ///| `offset`                   | The pagination offset. Results start at the offset index.                                                | Optional, defaults to 0           |
     ///| `limit`                    | Limit for the number of results.                                                                         | Optional                          |
     ///
@@ -172,6 +207,8 @@ pub trait TxoService {
         token_id: Option<u64>,
         min_received_block_index: Option<u64>,
         max_received_block_index: Option<u64>,
+        min_value: Option<u64>,
+        max_value: Option<u64>,
         offset: Option<u64>,
         limit: Option<u64>,
     ) -> Result<Vec<TxoInfo>, TxoServiceError>;
@@ -211,6 +248,53 @@ pub trait TxoService {
         fee_token_id: Option<String>,
         tombstone_block: Option<String>,
     ) -> Result<TxProposal, TxoServiceError>;
+
+    /// Reconcile the Txos for an account. Orphaned Txos that now match one of the account's
+    /// assigned subaddresses are re-attributed to it, and Txos minted by the account's
+    /// transactions that were superseded by another spend of their inputs are purged so they
+    /// cannot be double-counted.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                      | Notes                             |
+    ///|--------------|----------------------------------------------|-----------------------------------|
+    ///| `account_id` | The account on which to perform this action. | Account must exist in the wallet. |
+    ///
+    /// # Returns
+    /// * Vector of the ids of the Txos that were re-attributed
+    fn reconcile_txos(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<Vec<String>, TxoServiceError>;
+
+    /// List the orphaned Txos for an account, each with the spend public key of the subaddress
+    /// it was sent to, so that the missing subaddresses can be identified.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                  | Notes                             |
+    ///|--------------|------------------------------------------|-----------------------------------|
+    ///| `account_id` | The account whose orphaned Txos to list. | Account must exist in the wallet. |
+    ///
+    fn list_orphaned_txos(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<Vec<OrphanedTxo>, TxoServiceError>;
+
+    /// Get the proof that a Txo received by an account is in the ledger.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                      | Notes                               |
+    ///|--------------|----------------------------------------------|-------------------------------------|
+    ///| `account_id` | The account which received the Txo.          | Account must exist in the wallet.   |
+    ///| `txo_id`     | The Txo for which to get a membership proof. | Txo must be in the local ledger.    |
+    ///
+    fn get_txo_membership_proof(
+        &self,
+        account_id: &AccountID,
+        txo_id: &TxoID,
+    ) -> Result<TxOutMembershipProof, TxoServiceError>;
 }
 
 #[async_trait]
@@ -227,6 +311,8 @@ where
         token_id: Option<u64>,
         min_received_block_index: Option<u64>,
         max_received_block_index: Option<u64>,
+        min_value: Option<u64>,
+        max_value: Option<u64>,
         offset: Option<u64>,
         limit: Option<u64>,
     ) -> Result<Vec<TxoInfo>, TxoServiceError> {
@@ -241,6 +327,8 @@ where
                 status,
                 min_received_block_index,
                 max_received_block_index,
+                min_value,
+                max_value,
                 offset,
                 limit,
                 token_id,
@@ -252,6 +340,8 @@ where
                 status,
                 min_received_block_index,
                 max_received_block_index,
+                min_value,
+                max_value,
                 offset,
                 limit,
                 token_id,
@@ -262,6 +352,8 @@ where
                 status,
                 min_received_block_index,
                 max_received_block_index,
+                min_value,
+                max_value,
                 offset,
                 limit,
                 token_id,
@@ -274,7 +366,13 @@ where
             .map(|txo| {
                 let status = txo.status(conn)?;
                 let memo = txo.memo(conn)?;
-                Ok(TxoInfo { txo, memo, status })
+                let spent_in_transaction_id = txo.spent_in_transaction_log_id(conn)?;
+                Ok(TxoInfo {
+                    txo,
+                    memo,
+                    status,
+                    spent_in_transaction_id,
+                })
             })
             .collect::<Result<Vec<TxoInfo>, TxoServiceError>>()?;
 
@@ -287,7 +385,13 @@ where
         let txo = Txo::get(&txo_id.to_string(), conn)?;
         let status = txo.status(conn)?;
         let memo = txo.memo(conn)?;
-        Ok(TxoInfo { txo, memo, status })
+        let spent_in_transaction_id = txo.spent_in_transaction_log_id(conn)?;
+        Ok(TxoInfo {
+            txo,
+            memo,
+            status,
+            spent_in_transaction_id,
+        })
     }
 
     async fn split_txo(
@@ -331,6 +435,7 @@ where
             &account_id_hex,
             &addresses_and_amounts,
             Some(&[txo_id.to_string()].to_vec()),
+            None,
             fee_value,
             fee_token_id,
             tombstone_block,
@@ -340,11 +445,99 @@ where
             },
             None,
             None,
+            None,
         )?;
 
         let account = Account::get(&AccountID(account_id_hex), conn)?;
 
-        Ok(unsigned_transaction.sign(&account).await?)
+        Ok(unsigned_transaction
+            .sign_with_hardware_wallet_config(&account, &self.hardware_wallet_config)
+            .await?)
+    }
+
+    fn reconcile_txos(&self, account_id: &AccountID) -> Result<Vec<String>, TxoServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+
+        exclusive_transaction(conn, |conn| {
+            let reattributed_txo_ids =
+                Txo::reattribute_orphaned(&account_id.to_string(), &self.ledger_db, conn)?;
+            Txo::delete_superseded_outputs(&account_id.to_string(), conn)?;
+            Ok(reattributed_txo_ids)
+        })
+    }
+
+    fn list_orphaned_txos(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<Vec<OrphanedTxo>, TxoServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+
+        let account = Account::get(account_id, conn)?;
+        let view_private_key = account.view_private_key()?;
+
+        let orphaned_txos = Txo::list_orphaned(
+            Some(&account_id.to_string()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            conn,
+        )?;
+
+        let orphaned_txos = orphaned_txos
+            .into_iter()
+            .map(|txo| {
+                let subaddress_spend_public_key =
+                    txo.recover_subaddress_spend_public_key(&view_private_key)?;
+                Ok(OrphanedTxo {
+                    txo,
+                    subaddress_spend_public_key,
+                })
+            })
+            .collect::<Result<Vec<OrphanedTxo>, TxoServiceError>>()?;
+
+        Ok(orphaned_txos)
+    }
+
+    fn get_txo_membership_proof(
+        &self,
+        account_id: &AccountID,
+        txo_id: &TxoID,
+    ) -> Result<TxOutMembershipProof, TxoServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        Account::get(account_id, conn)?;
+        let txo = Txo::get(&txo_id.to_string(), conn)?;
+
+        // Outputs of a transaction which has not landed are in the wallet but
+        // not in the ledger, and are not yet attributed to any account.
+        let index = match self
+            .ledger_db
+            .get_tx_out_index_by_public_key(&txo.public_key()?)
+        {
+            Ok(index) => index,
+            Err(mc_ledger_db::Error::NotFound) => {
+                return Err(TxoServiceError::TxoNotInLedger(txo.id))
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        if txo.account_id.as_deref() != Some(account_id.to_string().as_str()) {
+            return Err(TxoServiceError::TxoNotReceivedByAccount(
+                txo.id,
+                account_id.to_string(),
+            ));
+        }
+
+        self.ledger_db
+            .get_tx_out_proof_of_memberships(&[index])?
+            .pop()
+            .ok_or(TxoServiceError::TxoNotInLedger(txo.id))
     }
 }
 
@@ -352,21 +545,27 @@ where
 mod tests {
     use super::*;
     use crate::{
-        db::account::AccountID,
+        db::{
+            account::AccountID,
+            models::TransactionLog,
+            transaction_log::{TransactionId, TransactionLogModel},
+        },
         service::{
-            account::AccountService, balance::BalanceService, transaction::TransactionService,
+            account::AccountService, balance::BalanceService,
+            models::tx_proposal::UnsignedTxProposal, transaction::TransactionService,
         },
         test_utils::{
-            add_block_to_ledger_db, get_test_ledger, manually_sync_account, setup_wallet_service,
-            MOB,
+            add_block_to_ledger_db, add_block_with_tx, create_test_txo_for_recipient,
+            get_test_ledger, manually_sync_account, setup_wallet_service, MOB,
         },
         util::b58::b58_encode_public_address,
     };
     use mc_account_keys::{AccountKey, PublicAddress};
-    use mc_common::logger::{async_test_with_logger, Logger};
+    use mc_common::logger::{async_test_with_logger, test_with_logger, Logger};
     use mc_rand::RngCore;
     use mc_transaction_core::{ring_signature::KeyImage, tokens::Mob, Token};
     use rand::{rngs::StdRng, SeedableRng};
+    use std::convert::TryFrom;
 
     #[async_test_with_logger]
     async fn test_txo_lifecycle(logger: Logger) {
@@ -422,6 +621,8 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .unwrap();
         assert_eq!(txos.len(), 1);
@@ -450,16 +651,18 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
                 TransactionMemo::RTH {
                     subaddress_index: None,
                 },
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
         let _submitted = service
-            .submit_transaction(&tx_proposal, None, Some(alice.id.clone()))
+            .submit_transaction(&tx_proposal, None, Some(alice.id.clone()), None)
             .unwrap();
 
         let pending: Vec<TxoInfo> = service
@@ -472,6 +675,8 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .unwrap();
         assert_eq!(pending.len(), 1);
@@ -489,4 +694,415 @@ mod tests {
         assert_eq!(balance_pmob.spent, 0);
         assert_eq!(balance_pmob.orphaned, 0);
     }
+
+    #[async_test_with_logger]
+    async fn test_spent_txo_reports_spending_transaction(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let input_txo_id = service
+            .list_txos(
+                Some(alice.id.clone()),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap()[0]
+            .txo
+            .id
+            .clone();
+
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        let tx_proposal = service
+            .build_and_sign_transaction(
+                &alice.id,
+                &[(
+                    b58_encode_public_address(&recipient).unwrap(),
+                    Amount::new(42 * MOB, Mob::ID),
+                )],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let (transaction_log, _, _) = service
+            .submit_transaction(&tx_proposal, None, Some(alice.id.clone()), None)
+            .unwrap()
+            .unwrap();
+
+        // Not yet spent while the transaction is pending
+        let txo_id = TxoID(input_txo_id);
+        let input_txo = service.get_txo(&txo_id).unwrap();
+        assert_eq!(input_txo.status, TxoStatus::Pending);
+        assert_eq!(input_txo.spent_in_transaction_id, None);
+
+        add_block_with_tx(&mut ledger_db, tx_proposal.tx, &mut rng);
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let input_txo = service.get_txo(&txo_id).unwrap();
+        assert_eq!(input_txo.status, TxoStatus::Spent);
+        assert_eq!(input_txo.spent_in_transaction_id, Some(transaction_log.id));
+    }
+
+    #[test_with_logger]
+    fn test_reconcile_txos_reattributes_orphaned(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db, None, logger);
+        let wallet_db = service.wallet_db.as_ref().unwrap();
+        let alice = service
+            .create_account(
+                Some("Alice".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_id = AccountID(alice.id.clone());
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+
+        // Receive a txo at a subaddress the wallet is not tracking yet, so it is
+        // orphaned.
+        let amount = mc_transaction_core::Amount::new(50 * MOB, Mob::ID);
+        let (tx_out, _key_image) =
+            create_test_txo_for_recipient(&alice_account_key, 4, amount, &mut rng);
+        let txo_id = Txo::create_received(
+            tx_out,
+            None,
+            None,
+            amount,
+            13,
+            &alice.id,
+            wallet_db.get_pooled_conn().unwrap().deref_mut(),
+        )
+        .unwrap();
+
+        let balance = service.get_balance_for_account(&alice_account_id).unwrap();
+        let balance_pmob = balance.get(&Mob::ID).unwrap();
+        assert_eq!(balance_pmob.unspent, 0);
+        assert_eq!(balance_pmob.orphaned, 50 * MOB as u128);
+
+        // Track the subaddress without going through the usual orphan repair.
+        AssignedSubaddress::create(
+            &alice_account_key,
+            4,
+            "",
+            wallet_db.get_pooled_conn().unwrap().deref_mut(),
+        )
+        .unwrap();
+
+        let reattributed = service.reconcile_txos(&alice_account_id).unwrap();
+        assert_eq!(reattributed, vec![txo_id.clone()]);
+
+        let txo = Txo::get(&txo_id, wallet_db.get_pooled_conn().unwrap().deref_mut()).unwrap();
+        assert_eq!(txo.subaddress_index, Some(4));
+        assert!(txo.key_image.is_some());
+
+        // The value is counted exactly once, as unspent.
+        let balance = service.get_balance_for_account(&alice_account_id).unwrap();
+        let balance_pmob = balance.get(&Mob::ID).unwrap();
+        assert_eq!(balance_pmob.unspent, 50 * MOB as u128);
+        assert_eq!(balance_pmob.orphaned, 0);
+        assert_eq!(balance_pmob.secreted, 0);
+
+        // Reconciling again is a no-op.
+        let reattributed = service.reconcile_txos(&alice_account_id).unwrap();
+        assert!(reattributed.is_empty());
+    }
+
+    #[test_with_logger]
+    fn test_reconcile_txos_purges_superseded_outputs(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        let wallet_db = service.wallet_db.as_ref().unwrap();
+        let alice = service
+            .create_account(
+                Some("Alice".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_id = AccountID(alice.id.clone());
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+
+        for _ in 0..2 {
+            add_block_to_ledger_db(
+                &mut ledger_db,
+                &[alice_account_key.default_subaddress()],
+                100 * MOB,
+                &[KeyImage::from(rng.next_u64())],
+                &mut rng,
+            );
+        }
+        manually_sync_account(&ledger_db, wallet_db, &alice_account_id, &logger);
+
+        // Build two transactions, each of which reserves one of the inputs.
+        let recipient = b58_encode_public_address(&alice_account_key.subaddress(4)).unwrap();
+        let mut proposals = Vec::new();
+        for _ in 0..2 {
+            let proposal = service
+                .build_transaction(
+                    &alice.id,
+                    &[(recipient.clone(), Amount::new(10 * MOB, Mob::ID))],
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    TransactionMemo::RTH {
+                        subaddress_index: None,
+                    },
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+            proposals.push(proposal);
+        }
+        let minted_txo_ids = |proposal: &UnsignedTxProposal| -> Vec<String> {
+            proposal
+                .payload_txos
+                .iter()
+                .chain(proposal.change_txos.iter())
+                .map(|output_txo| TxoID::from(&output_txo.tx_out).to_string())
+                .collect()
+        };
+
+        // The input of the first transaction is spent by some other transaction, so
+        // the first transaction can never land.
+        let superseded_input_id = TxoID::from(&proposals[0].unsigned_input_txos[0].tx_out);
+        Txo::update_spent_block_index(
+            &superseded_input_id.to_string(),
+            14,
+            wallet_db.get_pooled_conn().unwrap().deref_mut(),
+        )
+        .unwrap();
+
+        let balance = service.get_balance_for_account(&alice_account_id).unwrap();
+        let balance_before = balance.get(&Mob::ID).unwrap().clone();
+
+        let reattributed = service.reconcile_txos(&alice_account_id).unwrap();
+        assert!(reattributed.is_empty());
+
+        // Only the Txos minted by the superseded transaction are gone.
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+        for txo_id in minted_txo_ids(&proposals[0]) {
+            assert!(Txo::get(&txo_id, conn).is_err());
+        }
+        for txo_id in minted_txo_ids(&proposals[1]) {
+            assert!(Txo::get(&txo_id, conn).is_ok());
+        }
+        let transaction_log_id = TransactionId::try_from(&proposals[0]).unwrap();
+        assert!(TransactionLog::get(&transaction_log_id, conn).is_ok());
+
+        // The account's balance is unchanged and nothing is counted twice.
+        let balance = service.get_balance_for_account(&alice_account_id).unwrap();
+        let balance_pmob = balance.get(&Mob::ID).unwrap();
+        assert_eq!(*balance_pmob, balance_before);
+        assert_eq!(balance_pmob.spent, 100 * MOB as u128);
+        assert_eq!(balance_pmob.unspent, 100 * MOB as u128);
+        assert_eq!(balance_pmob.secreted, 0);
+    }
+
+    #[test_with_logger]
+    fn test_list_orphaned_txos_recovers_spend_key(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db, None, logger);
+        let wallet_db = service.wallet_db.as_ref().unwrap();
+        let alice = service
+            .create_account(
+                Some("Alice".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_id = AccountID(alice.id.clone());
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        assert!(service
+            .list_orphaned_txos(&alice_account_id)
+            .unwrap()
+            .is_empty());
+
+        // Receive a txo at subaddress 6, which has not been generated yet.
+        let amount = mc_transaction_core::Amount::new(50 * MOB, Mob::ID);
+        let (tx_out, _key_image) =
+            create_test_txo_for_recipient(&alice_account_key, 6, amount, &mut rng);
+        let txo_id = Txo::create_received(
+            tx_out,
+            None,
+            None,
+            amount,
+            13,
+            &alice.id,
+            wallet_db.get_pooled_conn().unwrap().deref_mut(),
+        )
+        .unwrap();
+
+        let orphaned = service.list_orphaned_txos(&alice_account_id).unwrap();
+        assert_eq!(orphaned.len(), 1);
+        assert_eq!(orphaned[0].txo.id, txo_id);
+        assert_eq!(
+            orphaned[0].subaddress_spend_public_key,
+            *alice_account_key.subaddress(6).spend_public_key()
+        );
+    }
+
+    #[async_test_with_logger]
+    async fn test_get_txo_membership_proof(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        let alice = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let txos = service
+            .list_txos(
+                Some(alice_account_id.to_string()),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(txos.len(), 1);
+        let txo = &txos[0].txo;
+        let txo_id = TxoID(txo.id.clone());
+
+        // A received txo has a proof for its place in the ledger
+        let proof = service
+            .get_txo_membership_proof(&alice_account_id, &txo_id)
+            .unwrap();
+        let index = ledger_db
+            .get_tx_out_index_by_public_key(&txo.public_key().unwrap())
+            .unwrap();
+        assert_eq!(proof.index, index);
+        assert_eq!(proof.highest_index, ledger_db.num_txos().unwrap() - 1);
+        assert!(!proof.elements.is_empty());
+
+        // Another account cannot get a proof for it
+        let bob = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        let bob_account_key: AccountKey = mc_util_serial::decode(&bob.account_key).unwrap();
+        match service.get_txo_membership_proof(&AccountID(bob.id.clone()), &txo_id) {
+            Err(TxoServiceError::TxoNotReceivedByAccount(..)) => {}
+            result => panic!("Expected TxoNotReceivedByAccount, got {result:?}"),
+        }
+
+        // The change of a transaction which has not landed is not yet in the ledger
+        let (_transaction_log, associated_txos, _value_map, _tx_proposal) = service
+            .build_sign_and_submit_transaction(
+                &alice.id,
+                &[(
+                    b58_encode_public_address(&bob_account_key.default_subaddress()).unwrap(),
+                    Amount::new(42 * MOB, Mob::ID),
+                )],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::Empty,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let change_txo_id = TxoID(associated_txos.change[0].0.id.clone());
+        match service.get_txo_membership_proof(&alice_account_id, &change_txo_id) {
+            Err(TxoServiceError::TxoNotInLedger(_)) => {}
+            result => panic!("Expected TxoNotInLedger, got {result:?}"),
+        }
+    }
 }
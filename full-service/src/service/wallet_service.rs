@@ -5,17 +5,26 @@
 use crate::{
     config::{NetworkConfig, WebhookConfig},
     db::{account::AccountID, WalletDb, WalletDbError},
+    error::WalletServiceError,
     service::{
-        sync::SyncThread,
+        hardware_wallet::HardwareWalletConfig,
+        sync::{
+            LedgerUpdateNotifier, SyncProgress, SyncProgressListeners, SyncThread, SyncThreadConfig,
+        },
         t3_sync::{T3Config, T3SyncThread},
+        transaction_builder::DEFAULT_NEW_TX_BLOCK_ATTEMPTS,
         webhook::WebhookThread,
     },
+    validator_ledger_sync::ValidatorSyncStats,
 };
 use diesel::{
     r2d2::{ConnectionManager, PooledConnection},
     SqliteConnection,
 };
-use mc_common::logger::{log, Logger};
+use mc_common::{
+    logger::{log, Logger},
+    ResponderId,
+};
 use mc_connection::{
     BlockchainConnection, ConnectionManager as McConnectionManager, UserTxConnection,
 };
@@ -23,13 +32,25 @@ use mc_fog_report_validation::FogPubkeyResolver;
 use mc_ledger_db::LedgerDB;
 use mc_ledger_sync::PollingNetworkState;
 use mc_rand::rand_core::RngCore;
+use mc_transaction_core::FeeMap;
 use mc_util_uri::FogUri;
 use mc_watcher::watcher_db::WatcherDB;
 use std::{
-    collections::HashMap,
-    sync::{atomic::AtomicUsize, Arc, Mutex, RwLock},
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::Duration,
 };
 
+/// How many blocks a node may be behind the highest block seen on the network
+/// and still be used for submitting transactions.
+pub const SUBMIT_NODE_MAX_BLOCKS_BEHIND: u64 = 1;
+
+/// By default a gift code is available to claim as soon as it lands.
+pub const DEFAULT_GIFT_CODE_CONFIRMATIONS: u64 = 1;
+
 /// Service for interacting with the wallet
 ///
 /// Note that some fields need to be pub in order to be used in trait
@@ -63,25 +84,92 @@ pub struct WalletService<
     pub fog_resolver_factory: Arc<dyn Fn(&[FogUri]) -> Result<FPR, String> + Send + Sync>,
 
     /// Background ledger sync thread.
-    _sync_thread: Option<SyncThread>,
+    sync_thread: Mutex<Option<SyncThread>>,
 
     /// Background T3 sync thread.
     _t3_sync_thread: Option<T3SyncThread>,
 
     /// Webhook Thread
-    _webhook_thread: Option<WebhookThread>,
+    webhook_thread: Mutex<Option<WebhookThread>>,
+
+    /// Callbacks notified of account sync progress by the sync thread.
+    sync_progress_listeners: SyncProgressListeners,
+
+    /// Notified of blocks appended to the ledger, waking the sync thread.
+    /// Created by the service when the ledger is not synced by something which
+    /// notifies it.
+    ledger_updates: LedgerUpdateNotifier,
+
+    /// Set once `shutdown` is called, so that long-running requests can stop.
+    shutdown_requested: AtomicBool,
 
     /// Monotonically increasing counter. This is used for node round-robin
     /// selection.
     pub submit_node_offset: Arc<AtomicUsize>,
 
+    /// Nodes whose last submission attempt failed with a connection error.
+    pub submit_node_failures: Arc<Mutex<HashSet<ResponderId>>>,
+
+    /// The network fees last fetched from a node, used when none respond.
+    pub network_fees: Arc<Mutex<Option<FeeMap>>>,
+
     /// Whether the service should run in offline mode.
     pub offline: bool,
 
+    /// Progress of syncing the ledger from a validator, when backed by one.
+    pub validator_sync_stats: Option<Arc<ValidatorSyncStats>>,
+
+    /// How many blocks past the current block height a new transaction expires
+    /// at, when no tombstone block is given.
+    pub tombstone_horizon: u64,
+
+    /// How many blocks, counting the one it landed in, must hold a gift code's
+    /// txo before the gift code is available to claim.
+    pub gift_code_confirmations: u64,
+
+    /// Whether account names must be unique across the wallet.
+    pub unique_account_names: bool,
+
+    /// Which hardware wallet view only accounts sign and sync with.
+    pub hardware_wallet_config: HardwareWalletConfig,
+
     /// Logger.
     pub logger: Logger,
 }
 
+/// The background threads of a wallet service that is shutting down.
+pub struct BackgroundThreads {
+    sync_thread: Option<SyncThread>,
+    webhook_thread: Option<WebhookThread>,
+}
+
+impl BackgroundThreads {
+    /// Stop the threads, waiting at most `timeout` for each of them.
+    ///
+    /// The sync thread is stopped first so that no new deposits are recorded
+    /// once the webhook thread has delivered the pending ones.
+    pub fn stop(self, timeout: Duration) -> Result<(), WalletServiceError> {
+        let mut timed_out = Vec::new();
+
+        if let Some(mut sync_thread) = self.sync_thread {
+            if !sync_thread.stop_with_timeout(timeout) {
+                timed_out.push("sync");
+            }
+        }
+
+        if let Some(mut webhook_thread) = self.webhook_thread {
+            if !webhook_thread.stop_with_timeout(timeout) {
+                timed_out.push("webhook");
+            }
+        }
+
+        if !timed_out.is_empty() {
+            return Err(WalletServiceError::ShutdownTimeout(timed_out.join(", ")));
+        }
+        Ok(())
+    }
+}
+
 impl<
         T: BlockchainConnection + UserTxConnection + 'static,
         FPR: FogPubkeyResolver + Send + Sync + 'static,
@@ -99,18 +187,27 @@ impl<
         offline: bool,
         t3_sync_config: T3Config,
         webhook_config: Option<WebhookConfig>,
+        blocks_chunk_size: u64,
+        ledger_updates: Option<LedgerUpdateNotifier>,
         logger: Logger,
     ) -> Self {
+        let sync_progress_listeners = SyncProgressListeners::default();
         let (sync_thread, webhook_thread) = if let Some(wallet_db) = wallet_db.clone() {
             log::info!(logger, "Starting Wallet TXO Sync Task Thread");
 
             let accounts_with_deposits = Arc::new(Mutex::new(HashMap::<AccountID, bool>::new()));
 
             (
-                Some(SyncThread::start(
+                Some(SyncThread::start_with_config(
                     ledger_db.clone(),
-                    wallet_db,
+                    wallet_db.clone(),
                     accounts_with_deposits.clone(),
+                    SyncThreadConfig {
+                        watcher_db: watcher_db.clone(),
+                        progress_listeners: sync_progress_listeners.clone(),
+                        ledger_updates: ledger_updates.clone(),
+                        blocks_chunk_size,
+                    },
                     logger.clone(),
                 )),
                 // As a companion to the account syncing, start the webhook syncing
@@ -118,6 +215,7 @@ impl<
                 if let Some(wh_config) = webhook_config {
                     Some(WebhookThread::start(
                         wh_config,
+                        wallet_db,
                         accounts_with_deposits.clone(),
                         logger.clone(),
                     ))
@@ -154,15 +252,75 @@ impl<
             network_setup_config,
             network_state,
             fog_resolver_factory,
-            _sync_thread: sync_thread,
+            sync_thread: Mutex::new(sync_thread),
             _t3_sync_thread: t3_sync_thread,
-            _webhook_thread: webhook_thread,
+            webhook_thread: Mutex::new(webhook_thread),
+            sync_progress_listeners,
+            ledger_updates: ledger_updates.unwrap_or_default(),
+            shutdown_requested: AtomicBool::new(false),
             submit_node_offset: Arc::new(AtomicUsize::new(rng.next_u64() as usize)),
+            submit_node_failures: Arc::new(Mutex::new(HashSet::new())),
+            network_fees: Arc::new(Mutex::new(None)),
             offline,
+            validator_sync_stats: None,
+            tombstone_horizon: DEFAULT_NEW_TX_BLOCK_ATTEMPTS,
+            gift_code_confirmations: DEFAULT_GIFT_CODE_CONFIRMATIONS,
+            unique_account_names: false,
+            hardware_wallet_config: HardwareWalletConfig::default(),
             logger,
         }
     }
 
+    /// Stop the background sync and webhook threads, waiting at most
+    /// `timeout` for each of them.
+    pub fn shutdown(&self, timeout: Duration) -> Result<(), WalletServiceError> {
+        self.begin_shutdown().stop(timeout)
+    }
+
+    /// Flag the service as shutting down and take ownership of its background
+    /// threads, so that they can be stopped without borrowing the service,
+    /// e.g. from a blocking task.
+    pub fn begin_shutdown(&self) -> BackgroundThreads {
+        log::info!(self.logger, "Stopping wallet service threads");
+        self.shutdown_requested.store(true, Ordering::SeqCst);
+        // Wake anything waiting on new blocks so it sees the shutdown.
+        self.ledger_updates.notify();
+
+        BackgroundThreads {
+            sync_thread: self.sync_thread.lock().expect("lock poisoned").take(),
+            webhook_thread: self.webhook_thread.lock().expect("lock poisoned").take(),
+        }
+    }
+
+    /// Register a callback to be notified each time a chunk of blocks has
+    /// been synced for an account.
+    pub fn add_sync_progress_listener(&self, listener: impl Fn(&SyncProgress) + Send + 'static) {
+        self.sync_progress_listeners
+            .lock()
+            .expect("lock poisoned")
+            .push(Box::new(listener));
+    }
+
+    /// The notifier to signal when blocks are appended to the ledger, so that
+    /// the sync thread scans them without waiting out its poll interval.
+    pub fn ledger_updates(&self) -> LedgerUpdateNotifier {
+        self.ledger_updates.clone()
+    }
+
+    /// Whether `shutdown` has been called.
+    pub fn shutdown_requested(&self) -> bool {
+        self.shutdown_requested.load(Ordering::SeqCst)
+    }
+
+    /// Whether the account sync thread is running.
+    pub fn sync_thread_running(&self) -> bool {
+        self.sync_thread
+            .lock()
+            .expect("lock poisoned")
+            .as_ref()
+            .map_or(false, |sync_thread| sync_thread.is_running())
+    }
+
     pub fn get_pooled_conn(
         &self,
     ) -> Result<PooledConnection<ConnectionManager<SqliteConnection>>, WalletDbError> {
@@ -171,4 +329,56 @@ impl<
             .ok_or(WalletDbError::WalletFunctionsDisabled)?
             .get_pooled_conn()
     }
+
+    /// The nodes to try, in order, when submitting a transaction.
+    ///
+    /// Nodes that are more than `SUBMIT_NODE_MAX_BLOCKS_BEHIND` blocks behind
+    /// the network are left out, and nodes whose last submission failed with a
+    /// connection error are moved to the end. The starting node rotates on
+    /// every call.
+    pub fn submit_node_candidates(&self) -> Vec<ResponderId> {
+        let responder_ids = self.peer_manager.responder_ids();
+
+        let healthy: Vec<ResponderId> = {
+            let network_state = self.network_state.read().expect("lock poisoned");
+            match network_state.highest_block_index_on_network() {
+                Some(highest_block_index) => {
+                    let peer_block_indexes = network_state.peer_to_current_block_index();
+                    responder_ids
+                        .into_iter()
+                        .filter(|responder_id| {
+                            peer_block_indexes.get(responder_id).map_or(false, |index| {
+                                index + SUBMIT_NODE_MAX_BLOCKS_BEHIND >= highest_block_index
+                            })
+                        })
+                        .collect()
+                }
+                // Nothing is known about the network yet, so every node is a candidate.
+                None => responder_ids,
+            }
+        };
+        if healthy.is_empty() {
+            return healthy;
+        }
+
+        let offset = self.submit_node_offset.fetch_add(1, Ordering::SeqCst) % healthy.len();
+        let failures = self.submit_node_failures.lock().expect("lock poisoned");
+        let (mut candidates, failed): (Vec<ResponderId>, Vec<ResponderId>) = healthy[offset..]
+            .iter()
+            .chain(healthy[..offset].iter())
+            .cloned()
+            .partition(|responder_id| !failures.contains(responder_id));
+        candidates.extend(failed);
+        candidates
+    }
+
+    /// Record whether a node could be reached when submitting a transaction.
+    pub fn record_submit_node_result(&self, responder_id: &ResponderId, reachable: bool) {
+        let mut failures = self.submit_node_failures.lock().expect("lock poisoned");
+        if reachable {
+            failures.remove(responder_id);
+        } else {
+            failures.insert(responder_id.clone());
+        }
+    }
 }
@@ -2,7 +2,13 @@
 
 //! Manages sending a webhook for synced accounts that have received deposits
 
-use crate::db::account::AccountID;
+use crate::{
+    db::{
+        account::AccountID, assigned_subaddress::AssignedSubaddressModel,
+        models::AssignedSubaddress, WalletDb, WalletDbError,
+    },
+    util::thread::join_with_timeout,
+};
 use mc_common::logger::{log, Logger};
 
 use crate::config::WebhookConfig;
@@ -10,16 +16,179 @@ use reqwest::{
     blocking::Client,
     header::{HeaderMap, HeaderValue, CONTENT_TYPE},
 };
+use serde_derive::Serialize;
 use serde_json::json;
 use std::{
     collections::HashMap,
+    ops::DerefMut,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, Mutex,
     },
     thread,
+    time::{Duration, Instant},
 };
 
+/// The first deposit to a subaddress that was assigned with a customer tag.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TaggedDeposit {
+    pub account_id: String,
+    pub public_address_b58: String,
+    pub subaddress_index: String,
+    pub customer_tag: String,
+}
+
+impl From<&AssignedSubaddress> for TaggedDeposit {
+    fn from(src: &AssignedSubaddress) -> TaggedDeposit {
+        TaggedDeposit {
+            account_id: src.account_id.clone(),
+            public_address_b58: src.public_address_b58.clone(),
+            subaddress_index: (src.subaddress_index as u64).to_string(),
+            customer_tag: src.customer_tag.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// Get the unreported first deposits to tagged subaddresses for the given
+/// accounts.
+pub fn get_tagged_deposits(
+    wallet_db: &WalletDb,
+    accounts: &[AccountID],
+) -> Result<Vec<TaggedDeposit>, WalletDbError> {
+    let mut pooled_conn = wallet_db.get_pooled_conn()?;
+    let conn = pooled_conn.deref_mut();
+
+    let mut tagged_deposits = Vec::new();
+    for account_id in accounts {
+        for assigned_subaddress in
+            AssignedSubaddress::list_unreported_first_deposits(&account_id.to_string(), conn)?
+        {
+            tagged_deposits.push(TaggedDeposit::from(&assigned_subaddress));
+        }
+    }
+    Ok(tagged_deposits)
+}
+
+/// Mark the given tagged deposits as reported, so they are only sent once.
+pub fn mark_tagged_deposits_reported(
+    wallet_db: &WalletDb,
+    tagged_deposits: &[TaggedDeposit],
+) -> Result<(), WalletDbError> {
+    let mut pooled_conn = wallet_db.get_pooled_conn()?;
+    let conn = pooled_conn.deref_mut();
+
+    for tagged_deposit in tagged_deposits {
+        AssignedSubaddress::set_first_deposit_notified(&tagged_deposit.public_address_b58, conn)?;
+    }
+    Ok(())
+}
+
+/// How often a sleeping webhook thread checks whether it was asked to stop.
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Send a webhook for every account with pending deposits, followed by the
+/// first deposits to tagged subaddresses of those accounts.
+///
+/// `restart` is sent along with the accounts and cleared once a webhook has
+/// been delivered successfully.
+fn send_pending_webhooks(
+    client: &Client,
+    webhook_config: &WebhookConfig,
+    wallet_db: &WalletDb,
+    accounts_with_deposits: &Mutex<HashMap<AccountID, bool>>,
+    restart: &mut bool,
+    logger: &Logger,
+) {
+    // Gather the current accounts_to_send, then wipe the contents
+    let accounts_to_send: Vec<_> = accounts_with_deposits
+        .lock()
+        .unwrap()
+        .clone()
+        .iter()
+        .filter(|&(_k, &v)| v == true)
+        .map(|(k, _v)| k.clone())
+        .collect();
+
+    // Delete the keys that we're alerting on
+    for key in accounts_to_send.iter() {
+        log::trace!(logger, "Account to send: {:?}", key);
+        accounts_with_deposits.lock().unwrap().remove(&key);
+    }
+
+    if accounts_to_send.is_empty() {
+        return;
+    }
+
+    // Question: will this keep the connection open? Or will it
+    // close the connection after this request?
+    match client
+        .post(webhook_config.url.clone())
+        .body(
+            json!(
+                {
+                    "accounts": accounts_to_send,
+                    "restart": *restart,
+                }
+            )
+            .to_string(),
+        )
+        .send()
+    {
+        Ok(response) => match response.error_for_status() {
+            Ok(_) => *restart = false,
+            Err(e) => {
+                log::error!(logger, "Failed getting webhook response: {:?}", e);
+            }
+        },
+        Err(e) => {
+            log::error!(logger, "Failed sending webhook request: {:?}", e);
+        }
+    }
+
+    // Report the first deposit to each subaddress assigned with a
+    // customer tag. These are only marked as reported once the
+    // webhook has accepted them, so a failure is retried on the
+    // next deposit to the account.
+    let tagged_deposits = match get_tagged_deposits(wallet_db, &accounts_to_send) {
+        Ok(tagged_deposits) => tagged_deposits,
+        Err(e) => {
+            log::error!(logger, "Failed getting tagged deposits: {:?}", e);
+            Vec::new()
+        }
+    };
+
+    if !tagged_deposits.is_empty() {
+        match client
+            .post(webhook_config.url.clone())
+            .body(
+                json!(
+                    {
+                        "tagged_deposits": tagged_deposits,
+                    }
+                )
+                .to_string(),
+            )
+            .send()
+            .and_then(|response| response.error_for_status())
+        {
+            Ok(_) => {
+                if let Err(e) = mark_tagged_deposits_reported(wallet_db, &tagged_deposits) {
+                    log::error!(logger, "Failed marking tagged deposits reported: {:?}", e);
+                }
+            }
+            Err(e) => {
+                log::error!(logger, "Failed sending tagged deposits webhook: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Sends deposit notifications for synced accounts.
+///
+/// The first notification after the thread starts carries `"restart": true`,
+/// telling the consumer that deposits may have been missed while the wallet
+/// was down and that it should reconcile with `get_txos`. The flag is cleared
+/// once a notification is delivered, and every later one carries `false`.
 pub struct WebhookThread {
     /// The main sync thread handle.
     join_handle: Option<thread::JoinHandle<()>>,
@@ -31,6 +200,7 @@ pub struct WebhookThread {
 impl WebhookThread {
     pub fn start(
         webhook_config: WebhookConfig,
+        wallet_db: WalletDb,
         accounts_with_deposits: Arc<Mutex<HashMap<AccountID, bool>>>,
         logger: Logger,
     ) -> Self {
@@ -59,60 +229,46 @@ impl WebhookThread {
                     let mut json_headers = HeaderMap::new();
                     json_headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
 
+                    // Set until the first notification since startup is delivered.
+                    let mut restart = true;
+
                     loop {
                         if thread_stop_requested.load(Ordering::SeqCst) {
                             log::debug!(logger, "WebhookThread stop requested.");
+                            // Deliver any deposits recorded before the stop, so they are
+                            // not lost on shutdown.
+                            send_pending_webhooks(
+                                &client,
+                                &webhook_config,
+                                &wallet_db,
+                                &accounts_with_deposits,
+                                &mut restart,
+                                &logger,
+                            );
                             break;
                         }
-                        // Gather the current accounts_to_send, then wipe the contents
-                        let accounts_to_send: Vec<_> = accounts_with_deposits
-                            .lock()
-                            .unwrap()
-                            .clone()
-                            .iter()
-                            .filter(|&(_k, &v)| v == true)
-                            .map(|(k, _v)| k.clone())
-                            .collect();
-
-                        // Delete the keys that we're alerting on
-                        for key in accounts_to_send.iter() {
-                            log::debug!(logger, "Account to send: {:?}", key);
-                            accounts_with_deposits.lock().unwrap().remove(&key);
-                        }
 
-                        if accounts_to_send.len() > 0 {
-                            // Question: will this keep the connection open? Or will it
-                            // close the connection after this request?
-                            match client
-                                .post(webhook_config.url.clone())
-                                .body(
-                                    json!(
-                                        {
-                                            "accounts": accounts_to_send,
-                                        }
-                                    )
-                                    .to_string(),
-                                )
-                                .send()
-                            {
-                                Ok(response) => match response.error_for_status() {
-                                    Ok(_) => (),
-                                    Err(e) => {
-                                        log::error!(
-                                            logger,
-                                            "Failed getting webhook response: {:?}",
-                                            e
-                                        );
-                                    }
-                                },
-                                Err(e) => {
-                                    log::error!(logger, "Failed sending webhook request: {:?}", e);
-                                }
+                        send_pending_webhooks(
+                            &client,
+                            &webhook_config,
+                            &wallet_db,
+                            &accounts_with_deposits,
+                            &mut restart,
+                            &logger,
+                        );
+
+                        // Wait for new blocks from consensus, waking early if a stop
+                        // is requested.
+                        let wake_at = Instant::now() + webhook_config.poll_interval;
+                        while !thread_stop_requested.load(Ordering::SeqCst) {
+                            let now = Instant::now();
+                            if now >= wake_at {
+                                break;
                             }
+                            thread::sleep(STOP_CHECK_INTERVAL.min(wake_at - now));
                         }
-                        // for new blocks from consensus
-                        thread::sleep(webhook_config.poll_interval);
                     }
+                    log::debug!(logger, "WebhookThread stopped.");
                 })
                 .expect("failed starting webhook thread"),
         );
@@ -121,12 +277,24 @@ impl WebhookThread {
             stop_requested,
         }
     }
+
     pub fn stop(&mut self) {
         self.stop_requested.store(true, Ordering::SeqCst);
         if let Some(join_handle) = self.join_handle.take() {
             join_handle.join().expect("WebhookThread join failed");
         }
     }
+
+    /// Signal the thread to stop and wait at most `timeout` for it to send any
+    /// pending webhooks and finish. Returns false if the thread was still
+    /// running when the timeout elapsed.
+    pub fn stop_with_timeout(&mut self, timeout: Duration) -> bool {
+        self.stop_requested.store(true, Ordering::SeqCst);
+        match self.join_handle.take() {
+            Some(join_handle) => join_with_timeout(join_handle, timeout),
+            None => true,
+        }
+    }
 }
 
 impl Drop for WebhookThread {
@@ -134,3 +302,222 @@ impl Drop for WebhookThread {
         self.stop();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        service::{account::AccountService, address::AddressService},
+        test_utils::{
+            create_test_received_txo, get_test_ledger, setup_wallet_service, WalletDbTestContext,
+            MOB,
+        },
+    };
+    use httpmock::{Method::POST, MockServer};
+    use mc_account_keys::{AccountKey, PublicAddress};
+    use mc_common::logger::test_with_logger;
+    use mc_transaction_core::{tokens::Mob, Amount, Token};
+    use rand::{rngs::StdRng, SeedableRng};
+    use reqwest::Url;
+
+    #[test_with_logger]
+    fn test_stop_sends_pending_webhooks(logger: Logger) {
+        let server = MockServer::start();
+        let account_id = AccountID("a".repeat(64));
+        let webhook_mock = server.mock(|when, then| {
+            when.method(POST).path("/received_txos").json_body(json!({
+                "accounts": [account_id.to_string()],
+                "restart": true,
+            }));
+            then.status(200);
+        });
+
+        // A long poll interval keeps the thread asleep, so the deposit below can
+        // only be sent when the thread is stopped.
+        let webhook_config = WebhookConfig {
+            url: Url::parse(&server.url("/received_txos")).unwrap(),
+            poll_interval: Duration::from_secs(600),
+        };
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+        let accounts_with_deposits = Arc::new(Mutex::new(HashMap::new()));
+
+        let mut webhook_thread = WebhookThread::start(
+            webhook_config,
+            wallet_db,
+            accounts_with_deposits.clone(),
+            logger,
+        );
+
+        // Let the thread make its first pass with nothing to send and go to sleep
+        thread::sleep(Duration::from_millis(200));
+        accounts_with_deposits
+            .lock()
+            .unwrap()
+            .insert(account_id, true);
+        assert_eq!(webhook_mock.hits(), 0);
+
+        assert!(webhook_thread.stop_with_timeout(Duration::from_secs(5)));
+        webhook_mock.assert_hits(1);
+        assert!(accounts_with_deposits.lock().unwrap().is_empty());
+    }
+
+    #[test_with_logger]
+    fn test_restart_flag_only_on_first_webhook(logger: Logger) {
+        let server = MockServer::start();
+        let account_id = AccountID("b".repeat(64));
+        let restart_mock = server.mock(|when, then| {
+            when.method(POST).path("/received_txos").json_body(json!({
+                "accounts": [account_id.to_string()],
+                "restart": true,
+            }));
+            then.status(200);
+        });
+        let no_restart_mock = server.mock(|when, then| {
+            when.method(POST).path("/received_txos").json_body(json!({
+                "accounts": [account_id.to_string()],
+                "restart": false,
+            }));
+            then.status(200);
+        });
+
+        let webhook_config = WebhookConfig {
+            url: Url::parse(&server.url("/received_txos")).unwrap(),
+            poll_interval: Duration::from_millis(10),
+        };
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+        let accounts_with_deposits = Arc::new(Mutex::new(HashMap::new()));
+
+        let mut webhook_thread = WebhookThread::start(
+            webhook_config,
+            wallet_db,
+            accounts_with_deposits.clone(),
+            logger,
+        );
+
+        // The first webhook since startup signals the restart
+        accounts_with_deposits
+            .lock()
+            .unwrap()
+            .insert(account_id.clone(), true);
+        for _ in 0..500 {
+            if restart_mock.hits() > 0 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        restart_mock.assert_hits(1);
+        assert_eq!(no_restart_mock.hits(), 0);
+
+        // Later webhooks do not
+        for _ in 0..2 {
+            let hits_before = no_restart_mock.hits();
+            accounts_with_deposits
+                .lock()
+                .unwrap()
+                .insert(account_id.clone(), true);
+            for _ in 0..500 {
+                if no_restart_mock.hits() > hits_before {
+                    break;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+
+        assert!(webhook_thread.stop_with_timeout(Duration::from_secs(5)));
+        restart_mock.assert_hits(1);
+        no_restart_mock.assert_hits(2);
+    }
+
+    #[test_with_logger]
+    fn test_tagged_deposit_webhook_delivered(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+        let service = setup_wallet_service(ledger_db, None, logger.clone());
+        let wallet_db = service.wallet_db.clone().unwrap();
+
+        let account = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+        let account_id = AccountID(account.id.clone());
+        let tracked = service
+            .assign_tracked_address_for_account(&account_id, "customer-42", None)
+            .unwrap();
+        create_test_received_txo(
+            &account_key,
+            tracked.subaddress_index as u64,
+            Amount::new(10 * MOB, Mob::ID),
+            13,
+            &mut rng,
+            &wallet_db,
+        );
+
+        let server = MockServer::start();
+        let accounts_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/received_txos")
+                .json_body_partial(json!({ "accounts": [account_id.to_string()] }).to_string());
+            then.status(200);
+        });
+        let tagged_deposit_mock = server.mock(|when, then| {
+            when.method(POST).path("/received_txos").json_body(json!({
+                "tagged_deposits": [
+                    {
+                        "account_id": account_id.to_string(),
+                        "public_address_b58": tracked.public_address_b58,
+                        "subaddress_index": tracked.subaddress_index.to_string(),
+                        "customer_tag": "customer-42",
+                    }
+                ]
+            }));
+            then.status(200);
+        });
+
+        let webhook_config = WebhookConfig {
+            url: Url::parse(&server.url("/received_txos")).unwrap(),
+            poll_interval: Duration::from_millis(10),
+        };
+        let accounts_with_deposits = Arc::new(Mutex::new(HashMap::new()));
+        let mut webhook_thread = WebhookThread::start(
+            webhook_config,
+            wallet_db.clone(),
+            accounts_with_deposits.clone(),
+            logger,
+        );
+
+        accounts_with_deposits
+            .lock()
+            .unwrap()
+            .insert(account_id.clone(), true);
+        for _ in 0..500 {
+            if tagged_deposit_mock.hits() > 0 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        // The deposit is delivered once and marked as reported, so a later deposit
+        // to the account does not send it again.
+        accounts_with_deposits
+            .lock()
+            .unwrap()
+            .insert(account_id.clone(), true);
+        for _ in 0..500 {
+            if accounts_mock.hits() > 1 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(webhook_thread.stop_with_timeout(Duration::from_secs(5)));
+
+        accounts_mock.assert_hits(2);
+        tagged_deposit_mock.assert_hits(1);
+        assert!(get_tagged_deposits(&wallet_db, &[account_id])
+            .unwrap()
+            .is_empty());
+    }
+}
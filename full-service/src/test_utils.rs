@@ -14,7 +14,7 @@ use crate::{
     error::SyncError,
     service::{
         models::tx_proposal::{TxProposal, UnsignedTxProposal},
-        sync::sync_account_next_chunk,
+        sync::{sync_account_next_chunk, DEFAULT_BLOCKS_CHUNK_SIZE},
         transaction::TransactionMemo,
         transaction_builder::WalletTransactionBuilder,
     },
@@ -51,7 +51,7 @@ use std::{
     env,
     ops::DerefMut,
     path::PathBuf,
-    sync::{Arc, RwLock},
+    sync::{atomic::AtomicBool, Arc, RwLock},
     time::Duration,
 };
 use tempdir::TempDir;
@@ -297,31 +297,50 @@ pub fn setup_peer_manager_and_network_state(
     ConnectionManager<MockBlockchainConnection<LedgerDB>>,
     Arc<RwLock<PollingNetworkState<MockBlockchainConnection<LedgerDB>>>>,
 ) {
-    let (peers, node_ids) = if offline {
-        (vec![], vec![])
+    let peers = if offline {
+        vec![]
     } else {
-        let mut minimum_fees = BTreeMap::new();
-        minimum_fees.insert(Mob::ID, Mob::MINIMUM_FEE);
-        minimum_fees.insert(TokenId::from(1), 1024);
-        let fee_map = FeeMap::try_from(minimum_fees).unwrap();
-
-        let peer1 = MockBlockchainConnection::new(
-            test_client_uri(1),
-            ledger_db.clone(),
-            0,
-            fee_map.clone(),
-        );
-        let peer2 = MockBlockchainConnection::new(test_client_uri(2), ledger_db, 0, fee_map);
-
-        (
-            vec![peer1.clone(), peer2.clone()],
-            vec![
-                peer1.uri().host_and_port_responder_id().unwrap(),
-                peer2.uri().host_and_port_responder_id().unwrap(),
-            ],
-        )
+        vec![
+            setup_mock_peer(1, ledger_db.clone()),
+            setup_mock_peer(2, ledger_db),
+        ]
     };
 
+    setup_peer_manager_and_network_state_for_peers(peers, logger)
+}
+
+/// A mock consensus node that serves blocks from `ledger_db`.
+pub fn setup_mock_peer(id: u32, ledger_db: LedgerDB) -> MockBlockchainConnection<LedgerDB> {
+    let mut minimum_fees = BTreeMap::new();
+    minimum_fees.insert(Mob::ID, Mob::MINIMUM_FEE);
+    minimum_fees.insert(TokenId::from(1), 1024);
+    let fee_map = FeeMap::try_from(minimum_fees).unwrap();
+
+    setup_mock_peer_with_fees(id, ledger_db, fee_map)
+}
+
+/// A mock consensus node that serves blocks from `ledger_db` and reports
+/// `fee_map` as the network fees.
+pub fn setup_mock_peer_with_fees(
+    id: u32,
+    ledger_db: LedgerDB,
+    fee_map: FeeMap,
+) -> MockBlockchainConnection<LedgerDB> {
+    MockBlockchainConnection::new(test_client_uri(id), ledger_db, 0, fee_map)
+}
+
+pub fn setup_peer_manager_and_network_state_for_peers(
+    peers: Vec<MockBlockchainConnection<LedgerDB>>,
+    logger: Logger,
+) -> (
+    ConnectionManager<MockBlockchainConnection<LedgerDB>>,
+    Arc<RwLock<PollingNetworkState<MockBlockchainConnection<LedgerDB>>>>,
+) {
+    let node_ids = peers
+        .iter()
+        .map(|peer| peer.uri().host_and_port_responder_id().unwrap())
+        .collect();
+
     let peer_manager = ConnectionManager::new(peers, logger.clone());
 
     let quorum_set = QuorumSet::new_with_node_ids(2, node_ids);
@@ -352,6 +371,8 @@ pub fn manually_sync_account(
             ledger_db,
             wallet_db.get_pooled_conn().unwrap().deref_mut(),
             &account_id.to_string(),
+            DEFAULT_BLOCKS_CHUNK_SIZE,
+            &AtomicBool::new(false),
             logger,
         ) {
             Ok(_) => {}
@@ -594,6 +615,8 @@ pub fn random_account_with_seed_values(
                 None,
                 None,
                 None,
+                None,
+                None,
                 Some(0),
                 wallet_db.get_pooled_conn().unwrap().deref_mut(),
             )
@@ -654,14 +677,52 @@ pub fn setup_wallet_service(
     webhook_config: Option<WebhookConfig>,
     logger: Logger,
 ) -> WalletService<MockBlockchainConnection<LedgerDB>, MockFogPubkeyResolver> {
-    setup_wallet_service_impl(ledger_db, logger, false, false, webhook_config)
+    let (peer_manager, network_state) =
+        setup_peer_manager_and_network_state(ledger_db.clone(), logger.clone(), false);
+    setup_wallet_service_impl(
+        ledger_db,
+        logger,
+        false,
+        false,
+        webhook_config,
+        peer_manager,
+        network_state,
+    )
 }
 
 pub fn setup_wallet_service_offline(
     ledger_db: LedgerDB,
     logger: Logger,
 ) -> WalletService<MockBlockchainConnection<LedgerDB>, MockFogPubkeyResolver> {
-    setup_wallet_service_impl(ledger_db, logger, true, false, None)
+    let (peer_manager, network_state) =
+        setup_peer_manager_and_network_state(ledger_db.clone(), logger.clone(), true);
+    setup_wallet_service_impl(
+        ledger_db,
+        logger,
+        true,
+        false,
+        None,
+        peer_manager,
+        network_state,
+    )
+}
+
+pub fn setup_wallet_service_with_peers(
+    ledger_db: LedgerDB,
+    peers: Vec<MockBlockchainConnection<LedgerDB>>,
+    logger: Logger,
+) -> WalletService<MockBlockchainConnection<LedgerDB>, MockFogPubkeyResolver> {
+    let (peer_manager, network_state) =
+        setup_peer_manager_and_network_state_for_peers(peers, logger.clone());
+    setup_wallet_service_impl(
+        ledger_db,
+        logger,
+        false,
+        false,
+        None,
+        peer_manager,
+        network_state,
+    )
 }
 
 fn setup_wallet_service_impl(
@@ -670,6 +731,8 @@ fn setup_wallet_service_impl(
     offline: bool,
     no_wallet_db: bool,
     webhook_config: Option<WebhookConfig>,
+    peer_manager: ConnectionManager<MockBlockchainConnection<LedgerDB>>,
+    network_state: Arc<RwLock<PollingNetworkState<MockBlockchainConnection<LedgerDB>>>>,
 ) -> WalletService<MockBlockchainConnection<LedgerDB>, MockFogPubkeyResolver> {
     let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
 
@@ -678,8 +741,6 @@ fn setup_wallet_service_impl(
         true => None,
         false => Some(db_test_context.get_db_instance(logger.clone())),
     };
-    let (peer_manager, network_state) =
-        setup_peer_manager_and_network_state(ledger_db.clone(), logger.clone(), offline);
 
     let network_setup_config = NetworkConfig {
         offline,
@@ -699,6 +760,8 @@ fn setup_wallet_service_impl(
         offline,
         T3Config::default(),
         webhook_config,
+        DEFAULT_BLOCKS_CHUNK_SIZE,
+        None,
         logger,
     )
 }
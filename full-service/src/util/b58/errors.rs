@@ -1,3 +1,4 @@
+use crate::json_rpc::json_rpc_response::{ServerErrorCode, ServerErrorCodes};
 use displaydoc::Display;
 
 #[derive(Display, Debug, PartialEq)]
@@ -38,3 +39,11 @@ impl From<mc_api::display::Error> for B58Error {
         Self::PrintableWrapper(src)
     }
 }
+
+impl ServerErrorCode for B58Error {
+    fn code(&self) -> Option<ServerErrorCodes> {
+        // Every variant describes a value which could not be decoded as the
+        // expected b58 type.
+        Some(ServerErrorCodes::B58Decode)
+    }
+}
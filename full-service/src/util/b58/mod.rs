@@ -180,14 +180,17 @@ pub fn b58_decode_transfer_payload(
         ))));
     }
 
+    // The checks above guarantee exactly one entropy was decoded, so this is
+    // only reachable if they are changed.
+    let account_key = account_key.ok_or(B58Error::TransferPayloadRequiresSingleEntropy)?;
+
     let txo_public_key =
         CompressedRistrettoPublic::try_from(transfer_payload.get_tx_out_public_key())?;
 
     Ok(DecodedTransferPayload {
         root_entropy,
         bip39_entropy,
-        account_key: account_key.unwrap(), /* guaranteed to succeed because the code above either
-                                            * manages to set it or returns an error. */
+        account_key,
         txo_public_key,
         memo: transfer_payload.get_memo().to_string(),
     })
@@ -10,8 +10,10 @@ mod tests {
     };
     use bip39::{Language, Mnemonic};
     use mc_account_keys::{AccountKey, PublicAddress};
+    use mc_api::printable::TransferPayload;
     use mc_core::slip10::Slip10KeyGenerator;
     use mc_transaction_core::{tokens::Mob, Amount, Token};
+    use protobuf::Message;
     use rand::{rngs::StdRng, CryptoRng, RngCore, SeedableRng};
 
     fn get_public_address<T: RngCore + CryptoRng>(rng: &mut T) -> PublicAddress {
@@ -254,4 +256,57 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    /// Malformed user input should be rejected with an Error at every decode
+    /// entry point, never a panic.
+    fn decoding_garbage_strings() {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let public_address_b58 = b58_encode_public_address(&get_public_address(&mut rng)).unwrap();
+
+        let garbage = [
+            "",
+            "NOTVALIDB58",
+            "0OIl",
+            "not b58 at all!",
+            "\u{1F4A5}\u{1F4A5}\u{1F4A5}",
+            "1111111111111111111111111111111111111111111111111111111111111111",
+            &public_address_b58[..public_address_b58.len() - 1],
+        ];
+
+        for input in garbage.iter() {
+            assert!(b58_printable_wrapper_type(input.to_string()).is_err());
+            assert!(b58_decode_public_address(input).is_err());
+            assert!(b58_decode_payment_request(input.to_string()).is_err());
+            assert!(b58_decode_transfer_payload(input.to_string()).is_err());
+        }
+
+        // A valid wrapper of the wrong type is also an Error.
+        assert_eq!(
+            b58_decode_payment_request(public_address_b58.clone()).err(),
+            Some(B58Error::NotPaymentRequest)
+        );
+        assert_eq!(
+            b58_decode_transfer_payload(public_address_b58).err(),
+            Some(B58Error::NotTransferPayload)
+        );
+    }
+
+    #[test]
+    /// A transfer payload whose memo bytes are not valid UTF-8 must fail to
+    /// decode rather than yield a mangled memo.
+    fn decoding_transfer_payload_with_non_utf8_memo_fails() {
+        let (_account_key, bip39_entropy_bytes) = get_account_and_entropy_bytes();
+
+        let mut transfer_payload = TransferPayload::new();
+        transfer_payload.set_bip39_entropy(bip39_entropy_bytes);
+        transfer_payload.set_memo("MEMO".to_string());
+        let mut bytes = transfer_payload.write_to_bytes().unwrap();
+        assert!(TransferPayload::parse_from_bytes(&bytes).is_ok());
+
+        // Swap the memo for bytes of the same length that are not UTF-8.
+        let memo_start = bytes.windows(4).position(|w| w == b"MEMO").unwrap();
+        bytes[memo_start..memo_start + 4].copy_from_slice(&[0xff, 0xfe, 0xfd, 0xfc]);
+        assert!(TransferPayload::parse_from_bytes(&bytes).is_err());
+    }
 }
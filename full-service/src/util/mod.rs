@@ -1,3 +1,4 @@
 pub mod b58;
 pub mod constants;
 pub mod encoding_helpers;
+pub mod thread;
@@ -0,0 +1,47 @@
+// Copyright (c) 2018-2024 MobileCoin Inc.
+
+//! Helpers for managing background threads.
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Wait at most `timeout` for a thread to finish, joining it if it did.
+/// Returns false, leaving the thread detached, if it is still running.
+pub fn join_with_timeout(join_handle: thread::JoinHandle<()>, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while !join_handle.is_finished() {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    join_handle.join().expect("Thread join failed");
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn test_join_with_timeout() {
+        let join_handle = thread::spawn(|| {});
+        assert!(join_with_timeout(join_handle, Duration::from_secs(5)));
+
+        let release = Arc::new(AtomicBool::new(false));
+        let thread_release = release.clone();
+        let join_handle = thread::spawn(move || {
+            while !thread_release.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(10));
+            }
+        });
+        assert!(!join_with_timeout(join_handle, Duration::from_millis(50)));
+        release.store(true, Ordering::SeqCst);
+    }
+}
@@ -2,6 +2,7 @@
 
 //! Ledger syncing via the Validator Service.
 
+use crate::service::sync::LedgerUpdateNotifier;
 use mc_blockchain_types::BlockData;
 use mc_common::logger::{log, Logger};
 use mc_ledger_db::{Ledger, LedgerDB};
@@ -9,45 +10,103 @@ use mc_ledger_sync::{NetworkState, PollingNetworkState};
 use mc_validator_api::ValidatorUri;
 use mc_validator_connection::ValidatorConnection;
 use std::{
+    fmt::Debug,
     sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc, RwLock,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, RwLock,
     },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 /// The maximum number of blocks to try and retrieve in each iteration
 pub const MAX_BLOCKS_PER_SYNC_ITERATION: u32 = 1000;
 
+/// The longest we wait between attempts while the validator keeps failing
+pub const MAX_FAILURE_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How often a waiting sync thread checks whether it has been asked to stop
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Progress of syncing the ledger from the validator.
+#[derive(Debug, Default)]
+pub struct ValidatorSyncStats {
+    blocks_synced: AtomicU64,
+    last_synced_block_index: Mutex<Option<u64>>,
+    reorg_detected: AtomicBool,
+}
+
+impl ValidatorSyncStats {
+    /// The number of blocks appended to the ledger since startup.
+    pub fn blocks_synced(&self) -> u64 {
+        self.blocks_synced.load(Ordering::SeqCst)
+    }
+
+    /// The index of the last block appended to the ledger, if any.
+    pub fn last_synced_block_index(&self) -> Option<u64> {
+        *self
+            .last_synced_block_index
+            .lock()
+            .expect("sync stats lock poisoned")
+    }
+
+    /// Whether the validator served blocks that conflict with the local
+    /// ledger. Syncing halts once this is set, since the wallet cannot
+    /// resolve a reorg itself.
+    pub fn reorg_detected(&self) -> bool {
+        self.reorg_detected.load(Ordering::SeqCst)
+    }
+
+    fn record_block_appended(&self, block_index: u64) {
+        self.blocks_synced.fetch_add(1, Ordering::SeqCst);
+        *self
+            .last_synced_block_index
+            .lock()
+            .expect("sync stats lock poisoned") = Some(block_index);
+    }
+}
+
 pub struct ValidatorLedgerSyncThread {
     join_handle: Option<thread::JoinHandle<()>>,
     stop_requested: Arc<AtomicBool>,
+    sync_stats: Arc<ValidatorSyncStats>,
 }
 
 impl ValidatorLedgerSyncThread {
+    /// Start syncing the ledger from the validator. `ledger_updates` is
+    /// notified each time blocks are appended.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         validator_uri: &ValidatorUri,
         chain_id: String,
+        reconnect_after_failures: u32,
         poll_interval: Duration,
         ledger_db: LedgerDB,
         network_state: Arc<RwLock<PollingNetworkState<ValidatorConnection>>>,
+        ledger_updates: LedgerUpdateNotifier,
         logger: Logger,
     ) -> Self {
         let stop_requested = Arc::new(AtomicBool::new(false));
 
-        let validator_conn = ValidatorConnection::new(validator_uri, chain_id, logger.clone());
+        let sync_stats = Arc::new(ValidatorSyncStats::default());
+
+        let validator_uri = validator_uri.clone();
 
         let thread_stop_requested = stop_requested.clone();
+        let thread_sync_stats = sync_stats.clone();
         let join_handle = Some(
             thread::Builder::new()
                 .name("ValidatorLedgerSync".into())
                 .spawn(move || {
                     Self::thread_entrypoint(
-                        validator_conn,
+                        validator_uri,
+                        chain_id,
+                        reconnect_after_failures,
                         poll_interval,
                         ledger_db,
                         network_state,
+                        thread_sync_stats,
+                        ledger_updates,
                         logger,
                         thread_stop_requested,
                     );
@@ -58,9 +117,15 @@ impl ValidatorLedgerSyncThread {
         Self {
             join_handle,
             stop_requested,
+            sync_stats,
         }
     }
 
+    /// The progress of this thread, e.g. for the wallet status.
+    pub fn sync_stats(&self) -> Arc<ValidatorSyncStats> {
+        self.sync_stats.clone()
+    }
+
     pub fn stop(&mut self) {
         self.stop_requested.store(true, Ordering::SeqCst);
         if let Some(thread) = self.join_handle.take() {
@@ -68,32 +133,84 @@ impl ValidatorLedgerSyncThread {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn thread_entrypoint(
-        validator_conn: ValidatorConnection,
+        validator_uri: ValidatorUri,
+        chain_id: String,
+        reconnect_after_failures: u32,
         poll_interval: Duration,
         mut ledger_db: LedgerDB,
         mut network_state: Arc<RwLock<PollingNetworkState<ValidatorConnection>>>,
+        sync_stats: Arc<ValidatorSyncStats>,
+        ledger_updates: LedgerUpdateNotifier,
         logger: Logger,
         stop_requested: Arc<AtomicBool>,
     ) {
         log::info!(logger, "ValidatorLedgerSync thread started");
 
+        let mut validator_conn =
+            ValidatorConnection::new(&validator_uri, chain_id.clone(), logger.clone());
+        let mut failures = ValidatorFailures::new(reconnect_after_failures);
+
         loop {
             if stop_requested.load(Ordering::SeqCst) {
                 log::debug!(logger, "ValidatorLedgerSyncThread stop requested.");
                 break;
             }
 
-            let block_data =
-                Self::get_next_blocks(&ledger_db, &validator_conn, &mut network_state, &logger);
+            if sync_stats.reorg_detected() {
+                log::error!(
+                    logger,
+                    "Validator ledger sync halted: the validator's chain conflicts with the local \
+                     ledger, which needs to be resolved manually"
+                );
+                break;
+            }
+
+            let result = Self::get_next_blocks(
+                &ledger_db,
+                &validator_conn,
+                &mut network_state,
+                &sync_stats,
+                &logger,
+            );
+            let block_data = match result {
+                Ok(block_data) => {
+                    failures.record_success(&logger);
+                    block_data
+                }
+                Err(err) => {
+                    log::error!(
+                        logger,
+                        "Failed getting blocks data from validator: {:?}",
+                        err
+                    );
+                    if failures.record_failure(&logger) {
+                        log::info!(logger, "Reconnecting to validator {}", validator_uri);
+                        validator_conn = ValidatorConnection::new(
+                            &validator_uri,
+                            chain_id.clone(),
+                            logger.clone(),
+                        );
+                    }
+                    sleep_unless_stopped(failures.backoff(poll_interval), &stop_requested);
+                    continue;
+                }
+            };
             if !block_data.is_empty() {
-                Self::append_safe_blocks(&mut ledger_db, &block_data, &logger);
+                Self::append_safe_blocks(
+                    &mut ledger_db,
+                    &block_data,
+                    &sync_stats,
+                    &ledger_updates,
+                    &logger,
+                );
             }
 
             // If we got no blocks, or less than the amount we asked for, sleep for a bit.
             // Getting less the amount we asked for indicates we are fully synced.
             if block_data.is_empty() || block_data.len() < MAX_BLOCKS_PER_SYNC_ITERATION as usize {
-                thread::sleep(poll_interval);
+                sleep_unless_stopped(poll_interval, &stop_requested);
             }
         }
     }
@@ -102,8 +219,9 @@ impl ValidatorLedgerSyncThread {
         ledger_db: &LedgerDB,
         validator_conn: &ValidatorConnection,
         network_state: &Arc<RwLock<PollingNetworkState<ValidatorConnection>>>,
+        sync_stats: &ValidatorSyncStats,
         logger: &Logger,
-    ) -> Vec<BlockData> {
+    ) -> Result<Vec<BlockData>, mc_validator_connection::Error> {
         let num_blocks = ledger_db
             .num_blocks()
             .expect("Failed getting the number of blocks in ledger");
@@ -127,27 +245,24 @@ impl ValidatorLedgerSyncThread {
             is_behind
         );
         if !is_behind {
-            return Vec::new();
+            return Ok(Vec::new());
         }
 
         log::debug!(logger, "network state is behind, local ledger has {} blocks, network highest block index is {}", num_blocks, highest_block_index_on_network);
-        let blocks_data =
-            match validator_conn.get_blocks_data(num_blocks, MAX_BLOCKS_PER_SYNC_ITERATION) {
-                Ok(blocks_data) => blocks_data,
-                Err(err) => {
-                    log::error!(
-                        logger,
-                        "Failed getting blocks data from validator: {:?}",
-                        err
-                    );
-                    return Vec::new();
-                }
-            };
-
-        mc_ledger_sync::identify_safe_blocks(ledger_db, &blocks_data, logger)
+        fetch_safe_blocks(ledger_db, validator_conn, sync_stats, logger)
     }
 
-    fn append_safe_blocks(ledger_db: &mut LedgerDB, block_data: &[BlockData], logger: &Logger) {
+    /// Append the blocks in order, stopping at the first block that fails to
+    /// append so that the ledger stays contiguous. The remaining blocks are
+    /// fetched again on the next iteration. `ledger_updates` is notified if
+    /// any blocks were appended.
+    fn append_safe_blocks(
+        ledger_db: &mut LedgerDB,
+        block_data: &[BlockData],
+        sync_stats: &ValidatorSyncStats,
+        ledger_updates: &LedgerUpdateNotifier,
+        logger: &Logger,
+    ) {
         log::info!(
             logger,
             "Appending {} blocks to ledger, which currently has {} blocks",
@@ -157,21 +272,29 @@ impl ValidatorLedgerSyncThread {
                 .expect("failed getting number of blocks"),
         );
 
+        let mut appended = false;
         for block_data in block_data {
-            ledger_db
-                .append_block(
-                    block_data.block(),
-                    block_data.contents(),
-                    None,
-                    block_data.metadata(),
-                )
-                .unwrap_or_else(|err| {
-                    panic!(
-                        "Failed appending block #{} to ledger: {}",
-                        block_data.block().index,
-                        err
-                    )
-                });
+            let block_index = block_data.block().index;
+            if let Err(err) = ledger_db.append_block(
+                block_data.block(),
+                block_data.contents(),
+                None,
+                block_data.metadata(),
+            ) {
+                log::error!(
+                    logger,
+                    "Failed appending block #{} to ledger, skipping the rest of this batch: {}",
+                    block_index,
+                    err
+                );
+                break;
+            }
+            sync_stats.record_block_appended(block_index);
+            appended = true;
+        }
+
+        if appended {
+            ledger_updates.notify();
         }
     }
 }
@@ -181,3 +304,383 @@ impl Drop for ValidatorLedgerSyncThread {
         self.stop();
     }
 }
+
+/// A source of blocks to sync the ledger from.
+trait BlocksDataSource {
+    type Error: Debug;
+
+    fn get_blocks_data(&self, offset: u64, limit: u32) -> Result<Vec<BlockData>, Self::Error>;
+}
+
+impl BlocksDataSource for ValidatorConnection {
+    type Error = mc_validator_connection::Error;
+
+    fn get_blocks_data(&self, offset: u64, limit: u32) -> Result<Vec<BlockData>, Self::Error> {
+        ValidatorConnection::get_blocks_data(self, offset, limit)
+    }
+}
+
+/// Fetch the blocks following the local ledger, keeping only those that are
+/// safe to append.
+///
+/// If the blocks conflict with the local ledger, the reorg is recorded in the
+/// sync stats and nothing is returned.
+fn fetch_safe_blocks<S: BlocksDataSource>(
+    ledger_db: &LedgerDB,
+    source: &S,
+    sync_stats: &ValidatorSyncStats,
+    logger: &Logger,
+) -> Result<Vec<BlockData>, S::Error> {
+    let num_blocks = ledger_db
+        .num_blocks()
+        .expect("Failed getting the number of blocks in ledger");
+    let blocks_data = source.get_blocks_data(num_blocks, MAX_BLOCKS_PER_SYNC_ITERATION)?;
+
+    if let Some(block_index) = find_conflicting_block(ledger_db, &blocks_data) {
+        log::error!(
+            logger,
+            "Validator returned block #{} which conflicts with the local ledger",
+            block_index
+        );
+        sync_stats.reorg_detected.store(true, Ordering::SeqCst);
+        return Ok(Vec::new());
+    }
+
+    Ok(mc_ledger_sync::identify_safe_blocks(
+        ledger_db,
+        &blocks_data,
+        logger,
+    ))
+}
+
+/// The index of the first block that conflicts with the local ledger, if any.
+///
+/// A block conflicts when the local ledger has a different block at its index,
+/// or when it directly follows the local ledger but names a different parent.
+fn find_conflicting_block(ledger_db: &LedgerDB, blocks_data: &[BlockData]) -> Option<u64> {
+    let num_blocks = ledger_db
+        .num_blocks()
+        .expect("Failed getting the number of blocks in ledger");
+
+    blocks_data.iter().map(BlockData::block).find_map(|block| {
+        let conflicts = if block.index < num_blocks {
+            let local_block = ledger_db
+                .get_block(block.index)
+                .expect("Failed getting block from ledger");
+            local_block.id != block.id
+        } else if block.index == num_blocks && num_blocks > 0 {
+            let local_block = ledger_db
+                .get_block(num_blocks - 1)
+                .expect("Failed getting block from ledger");
+            local_block.id != block.parent_id
+        } else {
+            false
+        };
+        conflicts.then_some(block.index)
+    })
+}
+
+/// Sleep for `duration`, waking early if a stop is requested so that stopping
+/// the thread does not wait out a long backoff.
+fn sleep_unless_stopped(duration: Duration, stop_requested: &AtomicBool) {
+    let deadline = Instant::now() + duration;
+    while !stop_requested.load(Ordering::SeqCst) {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        thread::sleep(remaining.min(STOP_CHECK_INTERVAL));
+    }
+}
+
+/// Tracks consecutive failures to fetch blocks from the validator, to back off
+/// and reconnect while it is unreachable.
+struct ValidatorFailures {
+    consecutive_failures: u32,
+    reconnect_after_failures: u32,
+}
+
+impl ValidatorFailures {
+    fn new(reconnect_after_failures: u32) -> Self {
+        Self {
+            consecutive_failures: 0,
+            reconnect_after_failures,
+        }
+    }
+
+    fn record_success(&mut self, logger: &Logger) {
+        if self.consecutive_failures > 0 {
+            log::warn!(
+                logger,
+                "Validator recovered after {} consecutive failures, leaving degraded state",
+                self.consecutive_failures
+            );
+        }
+        self.consecutive_failures = 0;
+    }
+
+    /// Record a failure, returning whether to reconnect to the validator.
+    fn record_failure(&mut self, logger: &Logger) -> bool {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        if self.consecutive_failures == 1 {
+            log::warn!(
+                logger,
+                "Failed fetching blocks from validator, entering degraded state"
+            );
+        }
+
+        self.reconnect_after_failures > 0
+            && self.consecutive_failures % self.reconnect_after_failures == 0
+    }
+
+    /// How long to wait before the next attempt, doubling with each
+    /// consecutive failure.
+    fn backoff(&self, poll_interval: Duration) -> Duration {
+        let exponent = self.consecutive_failures.saturating_sub(1).min(16);
+        poll_interval
+            .saturating_mul(1 << exponent)
+            .min(MAX_FAILURE_BACKOFF)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{add_block_to_ledger_db, get_test_ledger};
+    use mc_account_keys::AccountKey;
+    use mc_common::logger::{test_with_logger, Logger};
+    use mc_rand::RngCore;
+    use mc_transaction_core::ring_signature::KeyImage;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::cell::Cell;
+
+    /// Serves the blocks of a ledger, after failing a number of times.
+    struct FlakyBlocksDataSource {
+        ledger_db: LedgerDB,
+        failures_left: Cell<u32>,
+    }
+
+    impl BlocksDataSource for FlakyBlocksDataSource {
+        type Error = String;
+
+        fn get_blocks_data(&self, offset: u64, limit: u32) -> Result<Vec<BlockData>, String> {
+            if self.failures_left.get() > 0 {
+                self.failures_left.set(self.failures_left.get() - 1);
+                return Err("validator unavailable".to_string());
+            }
+
+            let num_blocks = self.ledger_db.num_blocks().unwrap();
+            Ok((offset..num_blocks.min(offset + limit as u64))
+                .map(|index| self.ledger_db.get_block_data(index).unwrap())
+                .collect())
+        }
+    }
+
+    #[test_with_logger]
+    fn test_sync_resumes_after_validator_failures(logger: Logger) {
+        // Both ledgers are built from the same seed, so they share their blocks
+        let mut ledger_db = get_test_ledger(5, &[], 12, &mut StdRng::from_seed([20u8; 32]));
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let mut validator_ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        add_block_to_ledger_db(
+            &mut validator_ledger_db,
+            &[recipient],
+            100,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        let source = FlakyBlocksDataSource {
+            ledger_db: validator_ledger_db,
+            failures_left: Cell::new(3),
+        };
+        let mut failures = ValidatorFailures::new(2);
+        let mut reconnects = 0;
+        let sync_stats = ValidatorSyncStats::default();
+
+        let block_data = loop {
+            match fetch_safe_blocks(&ledger_db, &source, &sync_stats, &logger) {
+                Ok(block_data) => {
+                    failures.record_success(&logger);
+                    break block_data;
+                }
+                Err(_) => {
+                    if failures.record_failure(&logger) {
+                        reconnects += 1;
+                    }
+                }
+            }
+        };
+
+        assert_eq!(reconnects, 1);
+        assert_eq!(failures.consecutive_failures, 0);
+        assert_eq!(block_data.len(), 1);
+        assert!(!sync_stats.reorg_detected());
+
+        ValidatorLedgerSyncThread::append_safe_blocks(
+            &mut ledger_db,
+            &block_data,
+            &sync_stats,
+            &LedgerUpdateNotifier::default(),
+            &logger,
+        );
+        assert_eq!(ledger_db.num_blocks().unwrap(), 13);
+        assert_eq!(sync_stats.blocks_synced(), 1);
+        assert_eq!(sync_stats.last_synced_block_index(), Some(12));
+    }
+
+    #[test_with_logger]
+    fn test_append_wakes_waiting_sync(logger: Logger) {
+        let mut ledger_db = get_test_ledger(5, &[], 12, &mut StdRng::from_seed([20u8; 32]));
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let mut validator_ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        add_block_to_ledger_db(
+            &mut validator_ledger_db,
+            &[recipient],
+            100,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        // Wait the way the sync thread does once it has caught up, but with a
+        // poll interval far longer than the test should take.
+        let poll_interval = Duration::from_secs(30);
+        let ledger_updates = LedgerUpdateNotifier::default();
+        let count = ledger_updates.count();
+        let waiter_updates = ledger_updates.clone();
+        let waiter = thread::spawn(move || {
+            let start = Instant::now();
+            let notified = waiter_updates.wait(count, poll_interval);
+            (notified, start.elapsed())
+        });
+
+        let block_data = vec![validator_ledger_db.get_block_data(12).unwrap()];
+        ValidatorLedgerSyncThread::append_safe_blocks(
+            &mut ledger_db,
+            &block_data,
+            &ValidatorSyncStats::default(),
+            &ledger_updates,
+            &logger,
+        );
+        assert_eq!(ledger_db.num_blocks().unwrap(), 13);
+
+        let (notified, elapsed) = waiter.join().unwrap();
+        assert!(notified);
+        assert!(elapsed < poll_interval);
+        assert_eq!(ledger_updates.count(), count + 1);
+    }
+
+    #[test_with_logger]
+    fn test_append_stops_at_bad_block(logger: Logger) {
+        // Both ledgers are built from the same seed, so they share their blocks
+        let mut ledger_db = get_test_ledger(5, &[], 12, &mut StdRng::from_seed([20u8; 32]));
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let mut validator_ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        for _ in 0..2 {
+            add_block_to_ledger_db(
+                &mut validator_ledger_db,
+                &[recipient.clone()],
+                100,
+                &[KeyImage::from(rng.next_u64())],
+                &mut rng,
+            );
+        }
+
+        // The repeated block 12 cannot follow itself, so block 13 is left for
+        // the next batch.
+        let block_12 = validator_ledger_db.get_block_data(12).unwrap();
+        let block_13 = validator_ledger_db.get_block_data(13).unwrap();
+        let block_data = vec![block_12.clone(), block_12, block_13];
+
+        let sync_stats = ValidatorSyncStats::default();
+        ValidatorLedgerSyncThread::append_safe_blocks(
+            &mut ledger_db,
+            &block_data,
+            &sync_stats,
+            &LedgerUpdateNotifier::default(),
+            &logger,
+        );
+
+        assert_eq!(ledger_db.num_blocks().unwrap(), 13);
+        assert_eq!(
+            ledger_db.get_block(12).unwrap(),
+            *validator_ledger_db.get_block_data(12).unwrap().block()
+        );
+        assert_eq!(sync_stats.blocks_synced(), 1);
+        assert_eq!(sync_stats.last_synced_block_index(), Some(12));
+    }
+
+    #[test_with_logger]
+    fn test_conflicting_blocks_halt_sync(logger: Logger) {
+        // The ledgers are built from different seeds, so their blocks differ
+        let ledger_db = get_test_ledger(5, &[], 12, &mut StdRng::from_seed([20u8; 32]));
+        let validator_ledger_db = get_test_ledger(5, &[], 13, &mut StdRng::from_seed([21u8; 32]));
+
+        // A block at an index the local ledger already has
+        let existing_block = validator_ledger_db.get_block_data(5).unwrap();
+        assert_eq!(
+            find_conflicting_block(&ledger_db, &[existing_block]),
+            Some(5)
+        );
+
+        // The next block, whose parent is not the last local block
+        let source = FlakyBlocksDataSource {
+            ledger_db: validator_ledger_db,
+            failures_left: Cell::new(0),
+        };
+        let sync_stats = ValidatorSyncStats::default();
+        let block_data = fetch_safe_blocks(&ledger_db, &source, &sync_stats, &logger).unwrap();
+
+        assert!(block_data.is_empty());
+        assert!(sync_stats.reorg_detected());
+        assert_eq!(ledger_db.num_blocks().unwrap(), 12);
+        assert_eq!(sync_stats.blocks_synced(), 0);
+    }
+
+    #[test_with_logger]
+    fn test_failure_backoff_doubles_up_to_max(logger: Logger) {
+        let poll_interval = Duration::from_secs(5);
+        let mut failures = ValidatorFailures::new(5);
+
+        failures.record_failure(&logger);
+        assert_eq!(failures.backoff(poll_interval), Duration::from_secs(5));
+        failures.record_failure(&logger);
+        assert_eq!(failures.backoff(poll_interval), Duration::from_secs(10));
+        failures.record_failure(&logger);
+        assert_eq!(failures.backoff(poll_interval), Duration::from_secs(20));
+
+        for _ in 0..10 {
+            failures.record_failure(&logger);
+        }
+        assert_eq!(failures.backoff(poll_interval), MAX_FAILURE_BACKOFF);
+
+        failures.record_success(&logger);
+        assert_eq!(failures.backoff(poll_interval), poll_interval);
+    }
+
+    #[test_with_logger]
+    fn test_sleep_unless_stopped_wakes_on_stop(_logger: Logger) {
+        let stop_requested = Arc::new(AtomicBool::new(false));
+
+        let start = Instant::now();
+        sleep_unless_stopped(Duration::from_millis(50), &stop_requested);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        // A stop requested partway through cuts the longest backoff short.
+        let thread_stop_requested = stop_requested.clone();
+        let stopper = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            thread_stop_requested.store(true, Ordering::SeqCst);
+        });
+        let start = Instant::now();
+        sleep_unless_stopped(MAX_FAILURE_BACKOFF, &stop_requested);
+        assert!(start.elapsed() < Duration::from_secs(5));
+        stopper.join().unwrap();
+    }
+}
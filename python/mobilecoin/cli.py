@@ -1008,6 +1008,7 @@ def _format_balances(balances):
 def _format_gift_code_status(status):
     return {
         'GiftCodeSubmittedPending': 'pending',
+        'GiftCodePendingConfirmation': 'confirming',
         'GiftCodeAvailable': 'available',
         'GiftCodeClaimed': 'claimed',
     }[status]
@@ -86,6 +86,18 @@ class ClientAsync:
         r = await self._req({"method": "get_network_status"})
         return r['network_status']
 
+    async def get_hardware_wallets(self):
+        r = await self._req({"method": "get_hardware_wallets"})
+        return r['hardware_wallets']
+
+    async def get_health(self):
+        r = await self._req({"method": "get_health"})
+        return r['health']
+
+    async def get_network_fees(self):
+        r = await self._req({"method": "get_network_fees"})
+        return r['fees']
+
     async def get_block(self, block_index):
         r = await self._req({
             "method": "get_block",
@@ -109,11 +121,12 @@ class ClientAsync:
         })
         return r['account_map']
 
-    async def get_account_status(self, account_id):
+    async def get_account_status(self, account_id, structured_amounts=None):
         return await self._req({
             "method": "get_account_status",
             "params": {
                 "account_id": account_id,
+                "structured_amounts": structured_amounts,
             },
         })
 
@@ -166,6 +179,13 @@ class ClientAsync:
         })
         return r['account_secrets']
 
+    async def export_transaction_log(self, account_id):
+        r = await self._req({
+            "method": "export_transaction_log",
+            "params": {"account_id": account_id}
+        })
+        return r['csv']
+
     async def update_account_name(self, account_id, name):
         r = await self._req({
             "method": "update_account_name",
@@ -176,10 +196,10 @@ class ClientAsync:
         })
         return r['account']
 
-    async def remove_account(self, account_id):
+    async def remove_account(self, account_id, force=False):
         return await self._req({
             "method": "remove_account",
-            "params": {"account_id": account_id}
+            "params": {"account_id": account_id, "force": force}
         })
 
     async def get_addresses(self, account_id, offset=0, limit=1000):
@@ -214,6 +234,91 @@ class ClientAsync:
         })
         return r['address']
 
+    async def assign_address_for_contact(self, account_id, address_book_entry_id, metadata=None):
+        if metadata is None:
+            metadata = ''
+
+        r = await self._req({
+            "method": "assign_address_for_contact",
+            "params": {
+                "account_id": account_id,
+                "address_book_entry_id": str(address_book_entry_id),
+                "metadata": metadata,
+            },
+        })
+        return r['address']
+
+    async def set_address_book_entry(self, address, address_book_entry_id=None):
+        if address_book_entry_id is not None:
+            address_book_entry_id = str(address_book_entry_id)
+
+        r = await self._req({
+            "method": "set_address_book_entry",
+            "params": {
+                "address": address,
+                "address_book_entry_id": address_book_entry_id,
+            },
+        })
+        return r['address']
+
+    async def create_address_book_entry(self, public_address_b58, name):
+        r = await self._req({
+            "method": "create_address_book_entry",
+            "params": {
+                "public_address_b58": public_address_b58,
+                "name": name,
+            },
+        })
+        return r['address_book_entry']
+
+    async def get_address_book_entry(self, address_book_entry_id):
+        r = await self._req({
+            "method": "get_address_book_entry",
+            "params": {
+                "address_book_entry_id": str(address_book_entry_id),
+            },
+        })
+        return r['address_book_entry']
+
+    async def get_address_book_entries(self, offset=0, limit=1000):
+        r = await self._req({
+            "method": "get_address_book_entries",
+            "params": {
+                "offset": int(offset),
+                "limit": int(limit),
+            },
+        })
+        return r['address_book_entries']
+
+    async def update_address_book_entry(self, address_book_entry_id, public_address_b58, name):
+        r = await self._req({
+            "method": "update_address_book_entry",
+            "params": {
+                "address_book_entry_id": str(address_book_entry_id),
+                "public_address_b58": public_address_b58,
+                "name": name,
+            },
+        })
+        return r['address_book_entry']
+
+    async def remove_address_book_entry(self, address_book_entry_id):
+        r = await self._req({
+            "method": "remove_address_book_entry",
+            "params": {
+                "address_book_entry_id": str(address_book_entry_id),
+            },
+        })
+        return r['removed']
+
+    async def rotate_change_subaddress(self, account_id):
+        r = await self._req({
+            "method": "rotate_change_subaddress",
+            "params": {
+                "account_id": account_id,
+            },
+        })
+        return r['address']
+
     async def get_transaction_logs(
         self,
         account_id,
@@ -221,6 +326,7 @@ class ClientAsync:
         max_block_index=None,
         offset=None,
         limit=None,
+        structured_amounts=None,
     ):
         r = await self._req({
             "method": "get_transaction_logs",
@@ -230,6 +336,7 @@ class ClientAsync:
                 "max_block_index": max_block_index,
                 "offset": offset,
                 "limit": limit,
+                "structured_amounts": structured_amounts,
             },
         })
         return r['transaction_log_map']
@@ -240,6 +347,8 @@ class ClientAsync:
         addresses_and_amounts,
         tombstone_block=None,
         fee=None,
+        exclude_txo_ids=None,
+        change_subaddress_index=None,
     ):
         params = {
             "account_id": account_id,
@@ -256,6 +365,10 @@ class ClientAsync:
             params['fee_token_id'] = str(fee.token.token_id)
         if tombstone_block is not None:
             params['tombstone_block'] = str(int(tombstone_block))
+        if exclude_txo_ids is not None:
+            params['exclude_txo_ids'] = list(exclude_txo_ids)
+        if change_subaddress_index is not None:
+            params['change_subaddress_index'] = str(int(change_subaddress_index))
         return params
 
     async def build_transaction(self, *args, **kwargs):
@@ -332,10 +445,13 @@ class ClientAsync:
         self,
         name=None,
         fog_info=None,
+        account_index=None,
     ):
         params = {"name": name}
         if fog_info is not None:
             params['fog_info'] = fog_info
+        if account_index is not None:
+            params['account_index'] = account_index
 
         r = await self._req({
             "method": "import_view_only_account_from_hardware_wallet",
@@ -352,6 +468,17 @@ class ClientAsync:
         })
         return r
 
+    async def sync_account_block_range(self, account_id, start_block_index, end_block_index):
+        r = await self._req({
+            "method": "sync_account_block_range",
+            "params": {
+                "account_id": account_id,
+                "start_block_index": str(start_block_index),
+                "end_block_index": str(end_block_index),
+            },
+        })
+        return r['num_txos_received']
+
     async def sync_view_only_account(self, params):
         r = await self._req({
             "method": "sync_view_only_account",
@@ -379,10 +506,13 @@ class ClientAsync:
             "params": {"b58_code": b58_code},
         })
 
-    async def get_txos(self, account_id):
+    async def get_txos(self, account_id, structured_amounts=None):
         return await self._req({
             "method": "get_txos",
-            "params": {"account_id": account_id},
+            "params": {
+                "account_id": account_id,
+                "structured_amounts": structured_amounts,
+            },
         })
 
     async def get_mc_protocol_txo(self, txo_id):
@@ -391,6 +521,13 @@ class ClientAsync:
             "params": {"txo_id": txo_id},
         })
 
+    async def get_txo_membership_proof(self, account_id, txo_id):
+        r = await self._req({
+            "method": "get_txo_membership_proof",
+            "params": {"account_id": account_id, "txo_id": txo_id},
+        })
+        return r['membership_proof']
+
     # Polling utility functions.
 
     @staticmethod
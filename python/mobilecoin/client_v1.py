@@ -362,6 +362,15 @@ class Client:
         })
         return r['gift_code']
 
+    def import_gift_code(self, gift_code_b58):
+        r = self._req({
+            "method": "import_gift_code",
+            "params": {
+                "gift_code_b58": gift_code_b58,
+            },
+        })
+        return r['gift_code']
+
     def check_gift_code_status(self, gift_code_b58):
         r = self._req({
             "method": "check_gift_code_status",
@@ -371,6 +380,16 @@ class Client:
         })
         return r
 
+    def poll_gift_code_status(self, gift_code_b58, timeout_seconds):
+        r = self._req({
+            "method": "poll_gift_code_status",
+            "params": {
+                "gift_code_b58": gift_code_b58,
+                "timeout_seconds": str(timeout_seconds),
+            },
+        })
+        return r
+
     def get_all_gift_codes(self):
         r = self._req({
             "method": "get_all_gift_codes",